@@ -1,8 +1,10 @@
 use clap::ValueEnum;
 use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
 
 /// Available color schemes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ColorScheme {
     /// Classic green-yellow-red gradient
     Classic,
@@ -111,17 +113,121 @@ fn gradient(stops: &[(f64, (u8, u8, u8))], t: f64) -> (u8, u8, u8) {
     stops.last().map(|(_, c)| *c).unwrap_or((255, 255, 255))
 }
 
+/// Blend a color towards a tint color by factor `t` (0.0 = unchanged, 1.0 = tint)
+/// Non-RGB colors are returned unchanged
+pub fn tint(color: Color, tint: (u8, u8, u8), t: f64) -> Color {
+    match color {
+        Color::Rgb(r, g, b) => {
+            let (r, g, b) = lerp_rgb((r, g, b), tint, t.clamp(0.0, 1.0));
+            Color::Rgb(r, g, b)
+        }
+        other => other,
+    }
+}
+
+/// Color for a packet loss percentage: red above `crit`, yellow above `warn`, else green
+pub fn loss_color(loss_percent: f64, warn: f64, crit: f64) -> Color {
+    if loss_percent > crit {
+        Color::Red
+    } else if loss_percent > warn {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+/// Badge color for a quality grade, from green (A) to red (F)
+pub fn grade_color(grade: char) -> Color {
+    match grade {
+        'A' => Color::Green,
+        'B' => Color::LightGreen,
+        'C' => Color::Yellow,
+        'D' => Color::LightRed,
+        _ => Color::Red,
+    }
+}
+
+/// Accent colors telling targets apart in multi-target mode, cycled by target index
+const TARGET_ACCENTS: [Color; 8] = [
+    Color::Rgb(80, 200, 255),  // Sky blue
+    Color::Rgb(255, 170, 60),  // Orange
+    Color::Rgb(120, 230, 120), // Green
+    Color::Rgb(255, 110, 180), // Pink
+    Color::Rgb(200, 160, 255), // Lavender
+    Color::Rgb(240, 230, 90),  // Yellow
+    Color::Rgb(60, 220, 200),  // Teal
+    Color::Rgb(255, 120, 100), // Coral
+];
+
+/// Accent color identifying the target at `idx` in multi-target mode
+pub fn target_accent(idx: usize) -> Color {
+    TARGET_ACCENTS[idx % TARGET_ACCENTS.len()]
+}
+
+/// Pick black or white text for legibility on top of a background color
+pub fn contrast_fg(bg: Color) -> Color {
+    match bg {
+        Color::Rgb(r, g, b) => {
+            let luminance = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+            if luminance > 140.0 {
+                Color::Black
+            } else {
+                Color::White
+            }
+        }
+        _ => Color::White,
+    }
+}
+
+/// Whether the terminal advertises 24-bit color, from the `COLORTERM` variable
+/// (and `WT_SESSION`, since Windows Terminal supports it without setting `COLORTERM`)
+pub fn truecolor_supported(colorterm: Option<&str>, windows_terminal: bool) -> bool {
+    windows_terminal
+        || colorterm
+            .is_some_and(|v| v.eq_ignore_ascii_case("truecolor") || v.eq_ignore_ascii_case("24bit"))
+}
+
+/// Nearest color in the 6×6×6 cube of the xterm 256-color palette, for terminals
+/// without 24-bit color
+pub fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| (c as u16 * 5 + 127) / 255;
+    (16 + 36 * level(r) + 6 * level(g) + level(b)) as u8
+}
+
+/// Parse an `RRGGBB` hex color (with or without a leading '#')
+pub fn parse_hex_color(s: &str) -> Result<Color, String> {
+    let hex = s.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("invalid color '{}' (expected RRGGBB)", s));
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0);
+    Ok(Color::Rgb(channel(0), channel(2), channel(4)))
+}
+
 /// Color gradient for RTT visualization using true RGB colors
+#[derive(Clone, PartialEq)]
 pub struct ColorScale {
     /// RTT value (ms) that is considered "bad"
     pub max_rtt: u64,
     /// Color scheme to use
     pub scheme: ColorScheme,
+    /// Scheme being faded out and how far the fade is (0.0 = all old, 1.0 = all new)
+    blend_from: Option<(ColorScheme, f64)>,
 }
 
 impl ColorScale {
     pub fn new(max_rtt: u64, scheme: ColorScheme) -> Self {
-        Self { max_rtt, scheme }
+        Self {
+            max_rtt,
+            scheme,
+            blend_from: None,
+        }
+    }
+
+    /// Blend the colors with those of a previous scheme, `t` of the way to this one
+    pub fn blend_from(mut self, scheme: ColorScheme, t: f64) -> Self {
+        self.blend_from = Some((scheme, t.clamp(0.0, 1.0)));
+        self
     }
 
     /// Get the color stops for the current scheme
@@ -209,48 +315,88 @@ impl ColorScale {
         }
     }
 
+    /// Color for the paused/scroll indicators, picked to stand out against the
+    /// scheme's gradient (yellow for the default scheme)
+    pub fn indicator_color(&self) -> Color {
+        let (r, g, b) = match self.scheme {
+            ColorScheme::Classic | ColorScheme::Fire => (0, 200, 255),
+            ColorScheme::Dark | ColorScheme::Grayscale => (255, 200, 0),
+            ColorScheme::Ocean | ColorScheme::Ice => (255, 150, 0),
+            ColorScheme::Neon => (0, 255, 200),
+            ColorScheme::Matrix => (255, 80, 200),
+            ColorScheme::Plasma => (120, 255, 120),
+            ColorScheme::Thermal => (230, 0, 230),
+        };
+        Color::Rgb(r, g, b)
+    }
+
     /// Get color for a given RTT value (f64 version for sub-ms precision)
     pub fn color_for_rtt_f64(&self, rtt_ms: Option<f64>) -> Color {
         match rtt_ms {
             None => Color::Indexed(240),
             Some(rtt) => {
                 let ratio = (rtt / self.max_rtt as f64).min(1.0);
-                let (r, g, b) = gradient(&self.get_stops(), ratio);
+                let mut rgb = gradient(&self.get_stops(), ratio);
+                if let Some((from, t)) = self.blend_from {
+                    let old = gradient(&ColorScale::new(self.max_rtt, from).get_stops(), ratio);
+                    rgb = lerp_rgb(old, rgb, t);
+                }
+                let (r, g, b) = rgb;
                 Color::Rgb(r, g, b)
             }
         }
     }
 
+    /// A strip of `width` cells running through the gradient from 0 to the scale, as
+    /// ANSI background colors (the nearest 256 colors without `truecolor`)
+    pub fn preview_strip(&self, width: usize, truecolor: bool) -> String {
+        let mut strip = String::new();
+        for i in 0..width {
+            let rtt = i as f64 / width.saturating_sub(1).max(1) as f64 * self.max_rtt as f64;
+            if let Color::Rgb(r, g, b) = self.color_for_rtt_f64(Some(rtt)) {
+                if truecolor {
+                    strip += &format!("\x1b[48;2;{};{};{}m ", r, g, b);
+                } else {
+                    strip += &format!("\x1b[48;5;{}m ", ansi256(r, g, b));
+                }
+            }
+        }
+        strip + "\x1b[0m"
+    }
+
     /// Get legend entries as (color, label) pairs
     pub fn legend_entries(&self) -> Vec<(Color, String)> {
         let num_entries = 11;
         let mut entries = Vec::new();
 
-        // Use floating point display when scale is small to avoid quantization
-        let use_decimals = self.max_rtt < 10;
+        // Boundaries are multiples of a tenth of the scale: show one decimal when
+        // they aren't whole numbers (small scales), otherwise round to whole ms
+        let steps = (num_entries - 1) as u64;
+        let use_decimals = self.max_rtt < 10 * steps && !self.max_rtt.is_multiple_of(steps);
+        let boundary = |i: usize| i as f64 / steps as f64 * self.max_rtt as f64;
+        let format_boundary = |rtt: f64| {
+            if use_decimals {
+                format!("{:.1}", rtt)
+            } else {
+                format!("{}", rtt.round() as u64)
+            }
+        };
 
         for i in 0..num_entries {
-            let ratio = i as f64 / (num_entries - 1) as f64;
-            let rtt_f64 = ratio * self.max_rtt as f64;
+            let rtt_f64 = boundary(i);
             let color = self.color_for_rtt_f64(Some(rtt_f64));
 
-            if i == num_entries - 1 {
-                if use_decimals {
-                    entries.push((color, format!("{:.1}ms+", rtt_f64)));
-                } else {
-                    entries.push((color, format!("{}ms+", rtt_f64 as u64)));
-                }
+            let label = if i == num_entries - 1 {
+                // The top entry starts exactly at the scale's "bad" threshold
+                format!("{}ms+", self.max_rtt)
             } else {
-                let next_rtt_f64 = (i + 1) as f64 / (num_entries - 1) as f64 * self.max_rtt as f64;
-                if use_decimals {
-                    entries.push((color, format!("{:.1} - {:.1}ms", rtt_f64, next_rtt_f64)));
-                } else {
-                    entries.push((
-                        color,
-                        format!("{} - {}ms", rtt_f64 as u64, next_rtt_f64 as u64),
-                    ));
-                }
-            }
+                format!(
+                    "{}-{}ms",
+                    format_boundary(rtt_f64),
+                    format_boundary(boundary(i + 1))
+                )
+            };
+            entries.push((color, label));
         }
 
         // Add timeout entry
@@ -292,6 +438,59 @@ impl ColorScale {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_truecolor_supported() {
+        assert!(truecolor_supported(Some("truecolor"), false));
+        assert!(truecolor_supported(Some("24BIT"), false));
+        assert!(truecolor_supported(None, true));
+        assert!(!truecolor_supported(Some("256color"), false));
+        assert!(!truecolor_supported(None, false));
+    }
+
+    #[test]
+    fn test_preview_strip() {
+        let scale = ColorScale::new(200, ColorScheme::Grayscale);
+        let strip = scale.preview_strip(3, true);
+        assert!(strip.starts_with("\x1b[48;2;20;20;20m "));
+        assert!(strip.ends_with("\x1b[48;2;255;255;255m \x1b[0m"));
+        assert_eq!(strip.matches(' ').count(), 3);
+        assert!(scale.preview_strip(3, false).starts_with("\x1b[48;5;16m "));
+        assert_eq!(ansi256(255, 255, 255), 231);
+        assert_eq!(ansi256(255, 0, 0), 196);
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("1a2b3c"), Ok(Color::Rgb(0x1a, 0x2b, 0x3c)));
+        assert_eq!(parse_hex_color("#FFFFFF"), Ok(Color::Rgb(255, 255, 255)));
+        assert!(parse_hex_color("12345").is_err());
+        assert!(parse_hex_color("zzzzzz").is_err());
+    }
+
+    #[test]
+    fn test_legend_labels_increasing() {
+        let parse = |s: &str| s.parse::<f64>().unwrap();
+        for max_rtt in 1..=10000 {
+            let entries = ColorScale::new(max_rtt, ColorScheme::Dark).legend_entries();
+            let (ranges, rest) = entries.split_at(entries.len() - 2);
+
+            let mut prev_hi = 0.0;
+            for (i, (_, label)) in ranges.iter().enumerate() {
+                let (lo, hi) = label.strip_suffix("ms").unwrap().split_once('-').unwrap();
+                let (lo, hi) = (parse(lo), parse(hi));
+                assert!(lo < hi, "scale {}: degenerate label {}", max_rtt, label);
+                if i > 0 {
+                    assert_eq!(lo, prev_hi, "scale {}: gap before {}", max_rtt, label);
+                }
+                prev_hi = hi;
+            }
+
+            let top = parse(rest[0].1.strip_suffix("ms+").unwrap());
+            assert_eq!(top, prev_hi, "scale {}", max_rtt);
+            assert_eq!(top, max_rtt as f64);
+        }
+    }
+
     #[test]
     fn test_color_scale_rgb() {
         let scale = ColorScale::new(200, ColorScheme::Classic);
@@ -315,6 +514,54 @@ mod tests {
         assert!(b > 120 && b < 135);
     }
 
+    #[test]
+    fn test_blend_from() {
+        let ocean = ColorScale::new(100, ColorScheme::Ocean);
+        let fire = ColorScale::new(100, ColorScheme::Fire);
+        let fading = |t| {
+            ColorScale::new(100, ColorScheme::Fire)
+                .blend_from(ColorScheme::Ocean, t)
+                .color_for_rtt_f64(Some(100.0))
+        };
+        assert_eq!(fading(0.0), ocean.color_for_rtt_f64(Some(100.0)));
+        assert_eq!(fading(1.0), fire.color_for_rtt_f64(Some(100.0)));
+        // Halfway between Ocean's white and Fire's pale yellow (255, 255, 200)
+        assert_eq!(fading(0.5), Color::Rgb(255, 255, 227));
+        assert_eq!(
+            fire.blend_from(ColorScheme::Ocean, 0.5)
+                .color_for_rtt_f64(None),
+            Color::Indexed(240)
+        );
+    }
+
+    #[test]
+    fn test_indicator_color_stands_out() {
+        let mut scheme = ColorScheme::Classic;
+        loop {
+            let scale = ColorScale::new(100, scheme);
+            let Color::Rgb(r, g, b) = scale.indicator_color() else {
+                panic!("{} indicator isn't RGB", scheme);
+            };
+            // Far from every color of the gradient
+            for (_, (sr, sg, sb)) in scale.get_stops() {
+                let distance = ((r as f64 - sr as f64).powi(2)
+                    + (g as f64 - sg as f64).powi(2)
+                    + (b as f64 - sb as f64).powi(2))
+                .sqrt();
+                assert!(
+                    distance > 150.0,
+                    "{} indicator too close to {:?}",
+                    scheme,
+                    (sr, sg, sb)
+                );
+            }
+            scheme = scheme.next();
+            if scheme == ColorScheme::Classic {
+                break;
+            }
+        }
+    }
+
     #[test]
     fn test_all_schemes() {
         // Ensure all schemes work without panicking
@@ -1,5 +1,14 @@
-use crate::color::ColorScheme;
+use crate::color::{ColorScheme, parse_hex_color};
+use crate::ping::socks::Socks5Proxy;
+use crate::ping::spawn::{PingerConfig, Probe};
+use crate::ping::{
+    DEFAULT_PRECISION, IntervalSweep, MissedTicks, PingStats, Reducer, RttUnit, WaitFor,
+};
+use crate::ui::legend::LegendMode;
 use clap::{Parser, ValueEnum};
+use ratatui::style::Color;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum Mode {
@@ -9,6 +18,8 @@ pub enum Mode {
     UdpClient,
     /// UDP server mode - echoes ping packets back to clients
     UdpServer,
+    /// TLS handshake mode - times TCP connect + TLS handshake to an HTTPS endpoint
+    TlsHandshake,
 }
 
 impl std::fmt::Display for Mode {
@@ -17,58 +28,438 @@ impl std::fmt::Display for Mode {
             Mode::Icmp => write!(f, "ICMP"),
             Mode::UdpClient => write!(f, "UDP Client"),
             Mode::UdpServer => write!(f, "UDP Server"),
+            Mode::TlsHandshake => write!(f, "TLS Handshake"),
         }
     }
 }
 
+/// Footer section whose visibility can be forced with --footer-show / --footer-hide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FooterSection {
+    /// Sent and received counts
+    Sent,
+    /// Lost count, loss percentage and longest burst
+    Loss,
+    /// Last RTT ("Recent:")
+    Recent,
+    /// Mini history of recent RTTs
+    Sparkline,
+}
+
+/// Range the footer sparkline's bar heights span, from --spark-scale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SparkScale {
+    /// From 0 to this RTT in ms
+    Fixed(u64),
+    /// From the lowest to the highest RTT shown
+    Auto,
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(name = "rttui")]
 #[command(about = "A visual ping graph with true-color terminal support")]
 #[command(version)]
 pub struct Config {
     /// Target host (IP address or hostname). If not provided, settings dialog opens.
-    #[arg()]
+    #[arg(env = "RTTUI_HOST")]
     pub host: Option<String>,
 
     /// Ping mode
-    #[arg(short, long, value_enum, default_value = "icmp")]
+    #[arg(short, long, value_enum, default_value = "icmp", env = "RTTUI_MODE")]
     pub mode: Mode,
 
-    /// Ping interval in milliseconds
-    #[arg(short, long, default_value = "1000")]
+    /// Ping interval (e.g. 500ms, 2s, 1.5s; plain numbers are milliseconds)
+    #[arg(short, long, default_value = "1000", value_parser = parse_duration_ms, env = "RTTUI_INTERVAL")]
     pub interval: u64,
 
+    /// Sweep the interval from start to end over a duration, e.g. 1s:100ms:5m
+    /// (overrides --interval; setting the interval in the UI stops the sweep)
+    #[arg(long, value_parser = parse_interval_sweep)]
+    pub interval_sweep: Option<IntervalSweep>,
+
+    /// What to do after falling behind the send schedule (e.g. the process was stalled):
+    /// skip the missed pings, burst them back-to-back, or delay the schedule
+    #[arg(long, value_enum, default_value = "skip")]
+    pub missed_ticks: MissedTicks,
+
+    /// Randomize each gap between sends by up to ±PERCENT of the interval, so instances
+    /// probing the same target don't fall into step (the header shows the nominal interval)
+    #[arg(long, default_value = "0", value_name = "PERCENT")]
+    pub jitter_interval: f64,
+
+    /// Graph one cell per N pings, combined with --aggregate-by (1 graphs every ping;
+    /// only the graph is combined, statistics and exports keep every ping)
+    #[arg(long, default_value = "1", value_name = "N")]
+    pub aggregate: usize,
+
+    /// How the pings of one graph cell are combined: hold the last value, average,
+    /// maximum, or show the cell as lost if any ping was
+    #[arg(long, value_enum, default_value = "last")]
+    pub aggregate_by: Reducer,
+
     /// UDP port for client/server mode
-    #[arg(short, long, default_value = "44444")]
+    #[arg(short, long, default_value = "44444", env = "RTTUI_PORT")]
     pub port: u16,
 
+    /// Accept UDP replies that don't echo the packet sent (e.g. a generic echo service on
+    /// port 7), matching them to the oldest unanswered ping; RTTs are best-effort
+    #[arg(long, default_value = "false")]
+    pub udp_lenient: bool,
+
     /// Bind address for UDP server mode (e.g., 0.0.0.0, ::, 192.168.1.1)
     #[arg(long)]
     pub bind: Option<String>,
 
-    /// Ping timeout in milliseconds
-    #[arg(short, long, default_value = "3000")]
+    /// Source address to send pings from in UDP client mode (e.g. to pick an uplink)
+    /// Must be the same IP family as the target; not supported in ICMP mode
+    #[arg(long)]
+    pub source: Option<IpAddr>,
+
+    /// TCP port for TLS handshake mode
+    #[arg(long, default_value = "443")]
+    pub tls_port: u16,
+
+    /// Server name sent (SNI) and checked against the certificate in TLS handshake mode
+    /// Defaults to the target host
+    #[arg(long, value_parser = parse_sni)]
+    pub sni: Option<String>,
+
+    /// SOCKS5 proxy to connect through in TLS handshake mode (socks5://host:port)
+    /// RTTs then include the hop to the proxy
+    #[arg(long, value_parser = Socks5Proxy::parse)]
+    pub proxy: Option<Socks5Proxy>,
+
+    /// DNS server to resolve hosts through instead of the system resolver (ip or ip:port)
+    #[arg(long, value_parser = parse_dns_server, env = "RTTUI_DNS_SERVER")]
+    pub dns_server: Option<SocketAddr>,
+
+    /// Hosts file to look names up in before DNS, instead of the system hosts file
+    #[arg(long, value_name = "PATH", env = "RTTUI_HOSTS")]
+    pub hosts: Option<String>,
+
+    /// Ping timeout (e.g. 500ms, 3s; plain numbers are milliseconds)
+    #[arg(short, long, default_value = "3000", value_parser = parse_duration_ms, env = "RTTUI_TIMEOUT")]
     pub timeout: u64,
 
     /// Color scale - RTT (ms) that is considered "bad"
     /// The gradient scales proportionally from low to this value
-    #[arg(short = 's', long, default_value = "200")]
+    #[arg(short = 's', long, default_value = "200", env = "RTTUI_SCALE")]
     pub scale: u64,
 
     /// Color scheme for the graph
-    #[arg(short = 'c', long, value_enum, default_value = "dark")]
+    #[arg(
+        short = 'c',
+        long,
+        value_enum,
+        default_value = "dark",
+        env = "RTTUI_COLORS"
+    )]
     pub colors: ColorScheme,
 
-    /// Hide the terminal cursor while running
+    /// Print a strip of a color scheme's gradient from 0 to --scale and exit
+    #[arg(long, value_enum, value_name = "SCHEME")]
+    pub preview_scheme: Option<ColorScheme>,
+
+    /// Unit RTTs are shown in (auto uses µs below 1 ms, for LAN latencies)
+    #[arg(long, value_enum, default_value = "ms", env = "RTTUI_UNIT")]
+    pub unit: RttUnit,
+
+    /// Decimals RTTs and jitter in ms are shown with everywhere (µs are always whole)
+    #[arg(long, default_value_t = DEFAULT_PRECISION, value_name = "N")]
+    pub precision: usize,
+
+    /// When the color legend is shown: auto on terminals at least 100 columns wide, or
+    /// always on / off regardless of width (toggle with 'e')
+    #[arg(long, value_enum, default_value = "auto", value_name = "MODE")]
+    pub legend: LegendMode,
+
+    /// Show times in UTC instead of local time (also used for --export timestamps)
+    #[arg(long, default_value = "false")]
+    pub utc: bool,
+
+    /// Background color for the graph area as RRGGBB (e.g. 202020), so dark samples stand out
+    #[arg(long, value_parser = parse_hex_color, env = "RTTUI_GRAPH_BG")]
+    pub graph_bg: Option<Color>,
+
+    /// Color of the live cursor as RRGGBB (default: white)
+    #[arg(long, value_parser = parse_hex_color, env = "RTTUI_CURSOR_COLOR")]
+    pub cursor_color: Option<Color>,
+
+    /// Maximum graph width in columns, so screenshots have the same samples per row everywhere
+    #[arg(long)]
+    pub graph_width: Option<u16>,
+
+    /// Fixed number of samples per graph row, whatever the terminal width (e.g. 60 at a
+    /// 1s interval for a minute per row): a narrower grid is centered, a wider one cut off
+    #[arg(long, value_name = "N")]
+    pub samples_per_row: Option<usize>,
+
+    /// Hide the cursor glyph after the newest sample (the graph still follows live)
     #[arg(long, default_value = "false")]
     pub hide_cursor: bool,
 
+    /// Hide the corner status indicator (PAUSED, the scroll position and "+N merged")
+    #[arg(long, default_value = "false")]
+    pub hide_indicator: bool,
+
+    /// Blink the cursor glyph, as a sign of life while paused or between slow pings
+    #[arg(long, default_value = "false")]
+    pub blink_cursor: bool,
+
+    /// Draw the graph top-down, with the newest row at the top instead of the bottom
+    #[arg(long, default_value = "false")]
+    pub top_down: bool,
+
+    /// Quit right away on q/Esc instead of asking for confirmation
+    #[arg(long, default_value = "false")]
+    pub no_confirm_quit: bool,
+
+    /// Ask before a target change clears the history
+    #[arg(long, default_value = "false")]
+    pub confirm_target_change: bool,
+
     /// History buffer size in megabytes (approximate)
-    #[arg(short = 'b', long, default_value = "10")]
+    #[arg(short = 'b', long, default_value = "10", env = "RTTUI_BUFFER_MB")]
     pub buffer_mb: u64,
+
+    /// Only keep history for this long (e.g. 30m, 1h), in addition to the buffer size limit
+    #[arg(long, value_parser = parse_duration_ms)]
+    pub retain: Option<u64>,
+
+    /// File with additional targets to monitor, one host per line ("-" for stdin)
+    /// Blank lines and lines starting with '#' are ignored
+    #[arg(long)]
+    pub targets_file: Option<String>,
+
+    /// Comma-separated targets to monitor (e.g. 1.1.1.1,8.8.8.8,9.9.9.9), each shown on
+    /// one line of the summary, as a quick alternative to --targets-file
+    #[arg(long, value_name = "HOSTS", value_delimiter = ',')]
+    pub targets: Vec<String>,
+
+    /// Smoothing factor (0-1] for the footer sparkline's moving average
+    /// Lower values smooth more; 1 disables smoothing
+    #[arg(long)]
+    pub spark_smoothing: Option<f64>,
+
+    /// RTT (ms) of the footer sparkline's tallest bar, or "auto" to span the lowest to
+    /// the highest RTT shown (default: the color scale)
+    #[arg(long, value_name = "SCALE", value_parser = parse_spark_scale)]
+    pub spark_scale: Option<SparkScale>,
+
+    /// Keep replies over FACTOR × the median RTT shown from stretching auto-ranged views
+    /// (the line chart's y-axis and --spark-scale auto): they are drawn at the top instead.
+    /// Only the display is clipped; stats, tooltips and exports keep the true RTT
+    #[arg(long, value_name = "FACTOR")]
+    pub clip_outliers: Option<f64>,
+
+    /// Show the scheduling jitter of the sends in the footer: how far the time between
+    /// sends strays from the interval, to tell scheduler-induced jitter from the network's
+    #[arg(long, default_value = "false")]
+    pub sched_jitter: bool,
+
+    /// Show the effective RTT in the footer: the average with every lost ping counted
+    /// as taking --loss-penalty, so loss drags it up the way it feels in interactive use
+    #[arg(long, default_value = "false")]
+    pub effective_rtt: bool,
+
+    /// RTT a lost ping counts as in the effective RTT (default: the timeout)
+    #[arg(long, value_parser = parse_duration_ms, value_name = "DURATION")]
+    pub loss_penalty: Option<u64>,
+
+    /// Show the lowest RTT over this window (e.g. 60s) in the footer: if it stays low
+    /// while many samples are slow, the slow ones take another path rather than the
+    /// whole route degrading
+    #[arg(long, value_parser = parse_duration_ms, value_name = "DURATION")]
+    pub best_window: Option<u64>,
+
+    /// Footer sections to always show, regardless of terminal width (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub footer_show: Vec<FooterSection>,
+
+    /// Footer sections to always hide, regardless of terminal width (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub footer_hide: Vec<FooterSection>,
+
+    /// Baseline RTT in milliseconds to compare samples against.
+    /// Samples above the baseline are tinted on the graph (toggle with 'b')
+    #[arg(long)]
+    pub baseline: Option<f64>,
+
+    /// Loss percentage above which loss is shown in yellow
+    #[arg(long, default_value = "1")]
+    pub loss_warn: f64,
+
+    /// Loss percentage above which loss is shown in red
+    #[arg(long, default_value = "5")]
+    pub loss_crit: f64,
+
+    /// Average RTT in milliseconds graded D in the quality summary (with --loss-crit for loss)
+    #[arg(long, default_value = "100")]
+    pub quality_rtt: f64,
+
+    /// Jitter in milliseconds graded D in the quality summary
+    #[arg(long, default_value = "30")]
+    pub quality_jitter: f64,
+
+    /// How far in percent the average of the last 10 replies must stray from that of the
+    /// last 100 for the footer's trend arrow to show latency rising or falling
+    #[arg(long, default_value = "10", value_name = "PERCENT")]
+    pub trend_sensitivity: f64,
+
+    /// Rolling windows for the recent loss/RTT summary (comma-separated, e.g. 1m,5m,15m)
+    #[arg(long, value_delimiter = ',', default_value = "1m,5m,15m", value_parser = parse_duration_ms)]
+    pub windows: Vec<u64>,
+
+    /// Replies over this multiple of the median of the last 50 replies count as a
+    /// latency spike (a run of them counts once); the footer shows how many there were
+    #[arg(long, default_value = "3")]
+    pub spike_factor: f64,
+
+    /// Mark the start of each latency spike on the graph
+    #[arg(long, default_value = "false")]
+    pub mark_spikes: bool,
+
+    /// Leave the first N samples after a (re)start out of the statistics
+    /// (they are still graphed); the first pings often include ARP/DNS/connection setup
+    #[arg(long, default_value = "0")]
+    pub warmup: u64,
+
+    /// Send this many pings, then exit and print statistics
+    #[arg(short = 'n', long)]
+    pub count: Option<u64>,
+
+    /// Run for this long (e.g. 30s, 5m, 1h), then exit and print statistics
+    #[arg(long, value_parser = parse_duration_ms)]
+    pub duration: Option<u64>,
+
+    /// Exit with status 0 as soon as the target replies, e.g. to wait for a host to come
+    /// back after a reboot (status 1 if --count or --duration runs out first)
+    #[arg(long, default_value = "false", conflicts_with = "wait_down")]
+    pub wait_up: bool,
+
+    /// Exit with status 0 once the target stops replying, after LOSSES pings lost in a
+    /// row (3 if not given; status 1 if --count or --duration runs out first)
+    #[arg(
+        long,
+        value_name = "LOSSES",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "3"
+    )]
+    pub wait_down: Option<u64>,
+
+    /// With --count or --duration, exit with status 2 if more than PERCENT of the pings
+    /// were lost (status 3 if none were sent), for use as a pass/fail check
+    #[arg(long, value_name = "PERCENT")]
+    pub fail_over_loss: Option<f64>,
+
+    /// Don't print statistics on exit or warnings at startup (errors still are), e.g. when
+    /// run from a script that only needs the per-ping lines or the exit status
+    #[arg(short, long, default_value = "false")]
+    pub quiet: bool,
+
+    /// Oscilloscope-style live view: the graph is a fixed window where new samples
+    /// overwrite the oldest in place behind a moving write head, instead of scrolling
+    /// (pausing or scrolling back shows the usual history)
+    #[arg(long, default_value = "false")]
+    pub sweep: bool,
+
+    /// Columns each sample takes in the graph (2 draws chunkier cells with half as many
+    /// samples per row, easier to read on high-DPI terminals)
+    #[arg(long, default_value = "1", value_name = "1|2")]
+    pub cell_width: usize,
+
+    /// Dot density graph for very wide terminals: each cell groups 8 samples, colored by
+    /// their highest RTT, with one braille dot per sample that was high or lost (toggle
+    /// with 'd')
+    #[arg(long, default_value = "false")]
+    pub dots: bool,
+
+    /// Draw timeouts as blank gaps instead of `X` marks (toggle with 'x')
+    #[arg(long, default_value = "false")]
+    pub loss_gaps: bool,
+
+    /// Tint samples close to the lowest RTT on screen, anchoring the floor latency (toggle with 'f')
+    #[arg(long, default_value = "false")]
+    pub floor: bool,
+
+    /// Shade every other quarter of the color scale in the graph and label the RTTs at
+    /// 25/50/75% of the scale, to read RTT from color without the legend (toggle with 'r')
+    #[arg(long, default_value = "false")]
+    pub bands: bool,
+
+    /// Draw faint vertical gridlines every --grid-every samples, to tell about how long
+    /// ago something happened (toggle with 'v')
+    #[arg(long, default_value = "false")]
+    pub grid: bool,
+
+    /// Samples between the gridlines of --grid
+    #[arg(long, default_value = "10", value_name = "SAMPLES")]
+    pub grid_every: usize,
+
+    /// Fade live samples into the background over this many rows back from the newest (0 = off)
+    #[arg(long, default_value = "0", value_name = "ROWS")]
+    pub fade: usize,
+
+    /// Mouse wheel acceleration: 0 scrolls a fixed 3 rows per wheel tick; above 0 a lone
+    /// tick scrolls 1 row and each further tick of a quick run scrolls FACTOR rows more
+    #[arg(long, default_value = "0", value_name = "FACTOR")]
+    pub wheel_accel: f64,
+
+    /// Freeze the view on the first timeout so it's on screen when you come back
+    /// (End resumes and re-arms it)
+    #[arg(long, default_value = "false")]
+    pub pause_on_loss: bool,
+
+    /// Maximum redraw rate in frames per second (lower values save CPU and battery)
+    #[arg(long, default_value = "15")]
+    pub fps: u8,
+
+    /// Write the session (summary, samples and notes) to this JSON file on exit
+    #[arg(long)]
+    pub export: Option<String>,
+
+    /// Compare the session against a file written by a previous run with --export
+    #[arg(long)]
+    pub compare: Option<String>,
+
+    /// Show live RTT and loss in the terminal window title
+    #[arg(long, default_value = "false")]
+    pub set_title: bool,
+
+    /// Start with recording paused (press Space to resume), e.g. for scripted captures
+    #[arg(long, default_value = "false")]
+    pub start_paused: bool,
+
+    /// Graph synthetic data instead of pinging (baseline, spikes and sporadic loss),
+    /// e.g. to try out color schemes or take screenshots without a network
+    /// The data depends only on the target name, so runs are reproducible
+    #[arg(long, default_value = "false")]
+    pub demo: bool,
+
+    /// Don't restore or save the last target, scale, colors, gap mode and quit confirmation between runs
+    #[arg(long, default_value = "false")]
+    pub no_state: bool,
+
+    /// Serve Prometheus metrics over HTTP on this port (at /metrics)
+    #[arg(long)]
+    pub metrics_port: Option<u16>,
+
+    /// Unix socket to answer with a JSON snapshot of the stats on every connect
+    #[arg(long, value_name = "PATH")]
+    pub stats_socket: Option<String>,
 }
 
 impl Config {
+    /// Mode as shown in the header ("Demo" when graphing synthetic data)
+    pub fn mode_label(&self) -> String {
+        if self.demo {
+            "Demo".to_string()
+        } else {
+            self.mode.to_string()
+        }
+    }
+
     /// Validate configuration
     pub fn validate(&self) -> anyhow::Result<()> {
         // For server mode, host is not required
@@ -78,10 +469,48 @@ impl Config {
             anyhow::bail!("Interval must be greater than 0");
         }
 
+        // Durations are only kept to the nanosecond
+        if self.precision > 6 {
+            anyhow::bail!("--precision must be at most 6");
+        }
+
         if self.timeout == 0 {
             anyhow::bail!("Timeout must be greater than 0");
         }
 
+        if self.source.is_some() && self.mode != Mode::UdpClient {
+            anyhow::bail!("--source is only supported in UDP client mode");
+        }
+
+        if self.udp_lenient && self.mode != Mode::UdpClient {
+            anyhow::bail!("--udp-lenient is only supported in UDP client mode");
+        }
+
+        if self.sni.is_some() && self.mode != Mode::TlsHandshake {
+            anyhow::bail!("--sni is only supported in TLS handshake mode");
+        }
+        if self.proxy.is_some() && self.mode != Mode::TlsHandshake {
+            anyhow::bail!("--proxy is only supported in TLS handshake mode");
+        }
+        if self.stats_socket.is_some() && !cfg!(unix) {
+            anyhow::bail!("--stats-socket is only supported on Unix");
+        }
+
+        if let Some(section) = self
+            .footer_show
+            .iter()
+            .find(|section| self.footer_hide.contains(section))
+        {
+            anyhow::bail!(
+                "Footer section '{}' can't be both shown and hidden",
+                section.to_possible_value().unwrap().get_name()
+            );
+        }
+
+        if self.demo && self.mode == Mode::UdpServer {
+            anyhow::bail!("--demo can't be used in UDP server mode");
+        }
+
         if self.scale == 0 {
             anyhow::bail!("Scale must be greater than 0");
         }
@@ -90,9 +519,180 @@ impl Config {
             anyhow::bail!("Buffer size must be greater than 0");
         }
 
+        if self.retain == Some(0) {
+            anyhow::bail!("Retention must be greater than 0");
+        }
+
+        if self
+            .spark_smoothing
+            .is_some_and(|alpha| !(alpha > 0.0 && alpha <= 1.0))
+        {
+            anyhow::bail!("Sparkline smoothing must be between 0 (exclusive) and 1");
+        }
+
+        if !(0.0..=100.0).contains(&self.loss_warn) || !(0.0..=100.0).contains(&self.loss_crit) {
+            anyhow::bail!("Loss thresholds must be between 0 and 100");
+        }
+
+        if self.loss_warn > self.loss_crit {
+            anyhow::bail!("--loss-warn must not be greater than --loss-crit");
+        }
+
+        if !(self.wheel_accel.is_finite() && self.wheel_accel >= 0.0) {
+            anyhow::bail!("--wheel-accel must be 0 or more");
+        }
+
+        if self.wait_down == Some(0) {
+            anyhow::bail!("--wait-down needs at least 1 loss");
+        }
+
+        if let Some(percent) = self.fail_over_loss {
+            if !(0.0..=100.0).contains(&percent) {
+                anyhow::bail!("--fail-over-loss must be between 0 and 100");
+            }
+            if self.count.is_none() && self.duration.is_none() {
+                anyhow::bail!("--fail-over-loss needs --count or --duration");
+            }
+        }
+
+        if !(1..=2).contains(&self.cell_width) {
+            anyhow::bail!("--cell-width must be 1 or 2");
+        }
+
+        if self.aggregate == 0 {
+            anyhow::bail!("--aggregate must be at least 1");
+        }
+
+        if !(0.0..100.0).contains(&self.jitter_interval) {
+            anyhow::bail!("Interval jitter must be at least 0% and less than 100%");
+        }
+
+        if !self.spike_factor.is_finite() || self.spike_factor <= 1.0 {
+            anyhow::bail!("Spike factor must be a number greater than 1");
+        }
+
+        if self.quality_rtt <= 0.0 || self.quality_jitter <= 0.0 {
+            anyhow::bail!("Quality thresholds must be greater than 0");
+        }
+
+        if self.grid_every == 0 {
+            anyhow::bail!("Grid spacing must be greater than 0");
+        }
+
+        if !self.trend_sensitivity.is_finite() || self.trend_sensitivity <= 0.0 {
+            anyhow::bail!("Trend sensitivity must be a number greater than 0");
+        }
+
+        if self
+            .clip_outliers
+            .is_some_and(|factor| factor.is_nan() || factor < 1.0)
+        {
+            anyhow::bail!("--clip-outliers must be a number of at least 1");
+        }
+
+        if self.graph_width == Some(0) {
+            anyhow::bail!("Graph width must be greater than 0");
+        }
+
+        if self.samples_per_row == Some(0) {
+            anyhow::bail!("Samples per row must be greater than 0");
+        }
+
+        if self.windows.contains(&0) {
+            anyhow::bail!("Summary windows must be greater than 0");
+        }
+
+        if self.count == Some(0) {
+            anyhow::bail!("Count must be greater than 0");
+        }
+
+        if self.duration == Some(0) {
+            anyhow::bail!("Duration must be greater than 0");
+        }
+
+        if self.fps == 0 {
+            anyhow::bail!("FPS must be greater than 0");
+        }
+
+        if self.baseline.is_some_and(|b| !b.is_finite() || b <= 0.0) {
+            anyhow::bail!("Baseline must be a number greater than 0");
+        }
+
         Ok(())
     }
 
+    /// Hosts file given with --hosts (None = the system one)
+    pub fn hosts_file(&self) -> Option<&Path> {
+        self.hosts.as_deref().map(Path::new)
+    }
+
+    /// Targets given with --targets, followed by those read from --targets-file
+    pub fn load_targets(&self) -> anyhow::Result<Vec<String>> {
+        let mut targets: Vec<String> = self
+            .targets
+            .iter()
+            .map(|host| host.trim())
+            .filter(|host| !host.is_empty())
+            .map(String::from)
+            .collect();
+        let Some(path) = &self.targets_file else {
+            return Ok(targets);
+        };
+
+        let contents = if path == "-" {
+            std::io::read_to_string(std::io::stdin())?
+        } else {
+            std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read targets file {}: {}", path, e))?
+        };
+
+        targets.extend(parse_targets(&contents));
+        Ok(targets)
+    }
+
+    /// Pinger settings for `host` at `ip` from the mode and probe options
+    /// (the UDP server mode has no pinger; it gets the ICMP defaults)
+    pub fn pinger_config(&self, host: &str, ip: IpAddr) -> PingerConfig {
+        let probe = match self.mode {
+            _ if self.demo => Probe::Demo,
+            Mode::Icmp | Mode::UdpServer => Probe::Icmp,
+            Mode::UdpClient => Probe::Udp(self.port),
+            Mode::TlsHandshake => Probe::Tls(self.tls_port),
+        };
+        PingerConfig::new(host, ip)
+            .probe(probe)
+            .interval(self.interval)
+            .timeout(self.timeout)
+            .source(self.source)
+            .lenient(self.udp_lenient)
+            .sni(self.sni.clone())
+            .proxy(self.proxy.clone())
+            .missed_ticks(self.missed_ticks)
+            .interval_jitter(self.jitter_interval)
+    }
+
+    /// Condition given with --wait-up / --wait-down, if any
+    pub fn wait_for(&self) -> Option<WaitFor> {
+        if self.wait_up {
+            Some(WaitFor::Up)
+        } else {
+            self.wait_down.map(WaitFor::Down)
+        }
+    }
+
+    /// Exit status for --fail-over-loss given the final stats: 2 if the loss is over
+    /// the limit, 3 if no pings were sent to judge by (None = passed or not checked)
+    pub fn loss_exit_code(&self, stats: &PingStats) -> Option<u8> {
+        let limit = self.fail_over_loss?;
+        if stats.total_sent == 0 {
+            Some(3)
+        } else if stats.loss_percent() > limit {
+            Some(2)
+        } else {
+            None
+        }
+    }
+
     /// Calculate max history entries from buffer size in MB
     /// Each PingResult is approximately 48 bytes
     pub fn max_history(&self) -> usize {
@@ -101,11 +701,204 @@ impl Config {
         bytes / BYTES_PER_RESULT
     }
 
+    /// Minimum time between redraws for the configured frame rate
+    pub fn frame_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(1000 / self.fps.max(1) as u64)
+    }
+
     /// Get target as resolved IP address (for display)
-    #[allow(dead_code)]
     pub fn target_display(&self) -> String {
         self.host
             .clone()
             .unwrap_or_else(|| format!("0.0.0.0:{}", self.port))
     }
 }
+
+/// Parse a targets list: one host per line, skipping blank lines and '#' comments
+pub fn parse_targets(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse a human-friendly duration ("500", "500ms", "2s", "1.5s", "5m", "1h") into milliseconds
+pub fn parse_duration_ms(input: &str) -> Result<u64, String> {
+    let s = input.trim();
+    let (number, multiplier) = if let Some(n) = s.strip_suffix("ms") {
+        (n, 1.0)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1000.0)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 60_000.0)
+    } else if let Some(n) = s.strip_suffix('h') {
+        (n, 3_600_000.0)
+    } else {
+        (s, 1.0)
+    };
+
+    let value: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid duration '{}' (try 500ms or 2s)", input))?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(format!("invalid duration '{}' (try 500ms or 2s)", input));
+    }
+
+    let ms = value * multiplier;
+    if ms.fract() != 0.0 {
+        return Err(format!("duration '{}' is not a whole number of ms", input));
+    }
+    Ok(ms as u64)
+}
+
+/// Parse an interval sweep "start:end:duration", each part a duration like "500ms" or "2m"
+pub fn parse_interval_sweep(input: &str) -> Result<IntervalSweep, String> {
+    let parts: Vec<&str> = input.split(':').collect();
+    let [start, end, duration] = parts[..] else {
+        return Err(format!(
+            "invalid interval sweep '{}' (expected start:end:duration)",
+            input
+        ));
+    };
+    let sweep = IntervalSweep {
+        start_ms: parse_duration_ms(start)?,
+        end_ms: parse_duration_ms(end)?,
+        duration_ms: parse_duration_ms(duration)?,
+    };
+    if sweep.start_ms == 0 || sweep.end_ms == 0 || sweep.duration_ms == 0 {
+        return Err("interval sweep values must be greater than 0".to_string());
+    }
+    Ok(sweep)
+}
+
+/// Parse a sparkline scale: "auto" or an RTT like "50" or "50ms"
+pub fn parse_spark_scale(input: &str) -> Result<SparkScale, String> {
+    if input.eq_ignore_ascii_case("auto") {
+        return Ok(SparkScale::Auto);
+    }
+    match parse_duration_ms(input)? {
+        0 => Err("sparkline scale must be greater than 0".to_string()),
+        ms => Ok(SparkScale::Fixed(ms)),
+    }
+}
+
+/// Check a TLS server name given with --sni (hostname or IP address)
+pub fn parse_sni(input: &str) -> Result<String, String> {
+    crate::ping::tls::parse_server_name(input).map_err(|e| e.to_string())?;
+    Ok(input.to_string())
+}
+
+/// Parse a DNS server address: "1.1.1.1", "1.1.1.1:5353", "::1" or "[::1]:5353"
+/// (port 53 unless given)
+pub fn parse_dns_server(input: &str) -> Result<SocketAddr, String> {
+    let s = input.trim();
+    if let Ok(addr) = s.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    s.parse::<IpAddr>()
+        .map(|ip| SocketAddr::new(ip, 53))
+        .map_err(|_| format!("invalid DNS server '{}' (expected ip or ip:port)", input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_targets() {
+        let contents = "# routers\n10.0.0.1\n\n  example.com  # upstream\n   \n8.8.8.8\n";
+        assert_eq!(
+            parse_targets(contents),
+            vec!["10.0.0.1", "example.com", "8.8.8.8"]
+        );
+    }
+
+    #[test]
+    fn test_targets_list() {
+        let config = Config::parse_from(["rttui", "--targets", "1.1.1.1, 8.8.8.8,,9.9.9.9"]);
+        assert_eq!(
+            config.load_targets().unwrap(),
+            vec!["1.1.1.1", "8.8.8.8", "9.9.9.9"]
+        );
+        assert!(config.host.is_none());
+    }
+
+    #[test]
+    fn test_parse_interval_sweep() {
+        assert_eq!(
+            parse_interval_sweep("1s:100ms:5m"),
+            Ok(IntervalSweep {
+                start_ms: 1000,
+                end_ms: 100,
+                duration_ms: 300_000,
+            })
+        );
+        assert!(parse_interval_sweep("1s:100ms").is_err());
+        assert!(parse_interval_sweep("0:100ms:1m").is_err());
+    }
+
+    #[test]
+    fn test_parse_spark_scale() {
+        assert_eq!(parse_spark_scale("auto"), Ok(SparkScale::Auto));
+        assert_eq!(parse_spark_scale("50"), Ok(SparkScale::Fixed(50)));
+        assert_eq!(parse_spark_scale("1s"), Ok(SparkScale::Fixed(1000)));
+        assert!(parse_spark_scale("0").is_err());
+        assert!(parse_spark_scale("fast").is_err());
+    }
+
+    #[test]
+    fn test_loss_exit_code() {
+        use crate::ping::PingResult;
+
+        let config = Config::parse_from(["rttui", "-n", "4", "--fail-over-loss", "25", "host"]);
+        assert!(config.validate().is_ok());
+        let mut stats = PingStats::new();
+        assert_eq!(config.loss_exit_code(&stats), Some(3));
+
+        let now = std::time::Instant::now();
+        let rtt = std::time::Duration::from_millis(10);
+        stats.record(&PingResult::success(1, rtt, now, None));
+        stats.record(&PingResult::success(2, rtt, now, None));
+        stats.record(&PingResult::success(3, rtt, now, None));
+        stats.record(&PingResult::timeout(4, now));
+        // 25% is at the limit, not over it
+        assert_eq!(config.loss_exit_code(&stats), None);
+        stats.record(&PingResult::timeout(5, now));
+        assert_eq!(config.loss_exit_code(&stats), Some(2));
+
+        let unchecked = Config::parse_from(["rttui", "-n", "4", "host"]);
+        assert_eq!(unchecked.loss_exit_code(&stats), None);
+        let unbounded = Config::parse_from(["rttui", "--fail-over-loss", "5", "host"]);
+        assert!(unbounded.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_ms() {
+        assert_eq!(parse_duration_ms("250"), Ok(250));
+        assert_eq!(parse_duration_ms("500ms"), Ok(500));
+        assert_eq!(parse_duration_ms("2s"), Ok(2000));
+        assert_eq!(parse_duration_ms(" 1.5s "), Ok(1500));
+        assert_eq!(parse_duration_ms("5m"), Ok(300_000));
+        assert_eq!(parse_duration_ms("1h"), Ok(3_600_000));
+        assert!(parse_duration_ms("1.5ms").is_err());
+        assert!(parse_duration_ms("fast").is_err());
+        assert!(parse_duration_ms("-1s").is_err());
+    }
+
+    #[test]
+    fn test_parse_dns_server() {
+        assert_eq!(
+            parse_dns_server("1.1.1.1"),
+            Ok("1.1.1.1:53".parse().unwrap())
+        );
+        assert_eq!(
+            parse_dns_server("10.0.0.1:5353"),
+            Ok("10.0.0.1:5353".parse().unwrap())
+        );
+        assert_eq!(parse_dns_server("::1"), Ok("[::1]:53".parse().unwrap()));
+        assert!(parse_dns_server("dns.example").is_err());
+    }
+}
@@ -0,0 +1,235 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::ping::{PingResult, PingStats, mean_jitter, rfc3339};
+
+/// Summary statistics of a session, as stored in an export file
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub sent: u64,
+    pub received: u64,
+    pub lost: u64,
+    pub loss_percent: f64,
+    pub min_ms: Option<f64>,
+    pub avg_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    /// Mean jitter between consecutive replies (missing in exports of older versions)
+    #[serde(default)]
+    pub jitter_ms: Option<f64>,
+}
+
+impl ExportSummary {
+    /// Summarize cumulative stats, with the jitter taken from `results`
+    pub fn new<'a>(stats: &PingStats, results: impl IntoIterator<Item = &'a PingResult>) -> Self {
+        let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+        Self {
+            sent: stats.total_sent,
+            received: stats.total_received,
+            lost: stats.total_lost,
+            loss_percent: stats.loss_percent(),
+            min_ms: stats.min_rtt.map(ms),
+            avg_ms: stats.avg_rtt().map(ms),
+            max_ms: stats.max_rtt.map(ms),
+            p95_ms: stats.rtt_percentile(95.0).map(ms),
+            jitter_ms: mean_jitter(results),
+        }
+    }
+}
+
+/// A single sample in an export file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSample {
+    pub seq: u64,
+    /// Wall-clock send time (RFC 3339, with the UTC offset of the machine that sent it)
+    pub timestamp: String,
+    /// Round-trip time in ms (None = timeout)
+    pub rtt_ms: Option<f64>,
+}
+
+/// A text note added during the session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportNote {
+    /// When the note was added (RFC 3339)
+    pub timestamp: String,
+    /// `seq` of the sample the note is pinned to, the first one sent after it (None if
+    /// that sample never arrived or was dropped from history)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    pub text: String,
+}
+
+/// A whole session written by `--export` and read back by `--compare`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionExport {
+    pub target: String,
+    /// When the export was written (RFC 3339)
+    pub exported_at: String,
+    pub summary: ExportSummary,
+    pub samples: Vec<ExportSample>,
+    /// Notes added with 'a', oldest first (left out when there are none)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<ExportNote>,
+}
+
+impl SessionExport {
+    /// Timestamps are written in local time with its offset, or in UTC with `utc`
+    pub fn new<'a>(
+        target: &str,
+        stats: &PingStats,
+        results: impl IntoIterator<Item = &'a PingResult> + Clone,
+        utc: bool,
+    ) -> Self {
+        Self {
+            target: target.to_string(),
+            exported_at: rfc3339(chrono::Local::now(), utc),
+            summary: ExportSummary::new(stats, results.clone()),
+            samples: results
+                .into_iter()
+                .map(|result| ExportSample {
+                    seq: result.seq,
+                    timestamp: result.timestamp_rfc3339(utc),
+                    rtt_ms: result.rtt_ms_f64(),
+                })
+                .collect(),
+            notes: Vec::new(),
+        }
+    }
+
+    /// Add the notes of the session, with timestamps written like the samples'
+    pub fn with_notes<'a>(
+        mut self,
+        notes: impl IntoIterator<Item = (DateTime<Local>, Option<u64>, &'a str)>,
+        utc: bool,
+    ) -> Self {
+        self.notes = notes
+            .into_iter()
+            .map(|(time, seq, text)| ExportNote {
+                timestamp: rfc3339(time, utc),
+                seq,
+                text: text.to_string(),
+            })
+            .collect();
+        self
+    }
+
+    /// Write the export as pretty-printed JSON
+    pub fn write(&self, path: &str) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+            .map_err(|e| anyhow::anyhow!("Failed to write export file {}: {}", path, e))
+    }
+
+    /// Read an export written by a previous run
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read export file {}: {}", path, e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| anyhow::anyhow!("Invalid export file {}: {}", path, e))
+    }
+}
+
+/// Change of the current session relative to a previous run (negative = lower now)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub avg_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub jitter_ms: Option<f64>,
+    pub loss_percent: f64,
+}
+
+impl Comparison {
+    pub fn new(current: &ExportSummary, previous: &ExportSummary) -> Self {
+        let delta = |now: Option<f64>, before: Option<f64>| Some(now? - before?);
+        Self {
+            avg_ms: delta(current.avg_ms, previous.avg_ms),
+            p95_ms: delta(current.p95_ms, previous.p95_ms),
+            jitter_ms: delta(current.jitter_ms, previous.jitter_ms),
+            loss_percent: current.loss_percent - previous.loss_percent,
+        }
+    }
+
+    /// Deltas as labelled strings with the delta they show, e.g. ("Δavg", "-3.2ms", Some(-3.2))
+    pub fn parts(&self) -> [(&'static str, String, Option<f64>); 4] {
+        let ms = |label, d: Option<f64>| {
+            let text = d
+                .map(|d| format!("{:+.1}ms", d))
+                .unwrap_or_else(|| "-".to_string());
+            (label, text, d)
+        };
+        [
+            ms("Δavg", self.avg_ms),
+            ms("Δp95", self.p95_ms),
+            ms("Δjitter", self.jitter_ms),
+            (
+                "Δloss",
+                format!("{:+.1}%", self.loss_percent),
+                Some(self.loss_percent),
+            ),
+        ]
+    }
+
+    /// One-line summary for printing
+    pub fn format(&self) -> String {
+        let parts: Vec<String> = self
+            .parts()
+            .iter()
+            .map(|(label, value, _)| format!("{} {}", label, value))
+            .collect();
+        format!("vs previous run: {}", parts.join(" | "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_export_round_trip_and_compare() {
+        let now = Instant::now();
+        let results: Vec<PingResult> = (0..20)
+            .map(|seq| match seq {
+                5 => PingResult::timeout(seq, now),
+                _ => PingResult::success(seq, Duration::from_millis(10 + seq), now, None),
+            })
+            .collect();
+        let mut stats = PingStats::new();
+        results.iter().for_each(|r| stats.record(r));
+
+        let export = SessionExport::new("example.com", &stats, &results, false);
+        let json = serde_json::to_string(&export).unwrap();
+        let loaded: SessionExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.summary, export.summary);
+        assert_eq!(loaded.samples.len(), 20);
+        assert_eq!(loaded.samples[5].rtt_ms, None);
+        assert_eq!(loaded.summary.p95_ms, Some(29.0));
+        // No notes key unless there are notes
+        assert!(!json.contains("notes"));
+
+        let time = chrono::DateTime::parse_from_rfc3339("2024-05-01T12:00:00+02:00").unwrap();
+        let noted = export.with_notes(
+            [(time.with_timezone(&Local), Some(7), "rebooted router")],
+            true,
+        );
+        let loaded: SessionExport =
+            serde_json::from_str(&serde_json::to_string(&noted).unwrap()).unwrap();
+        assert_eq!(
+            loaded.notes,
+            vec![ExportNote {
+                timestamp: "2024-05-01T10:00:00Z".to_string(),
+                seq: Some(7),
+                text: "rebooted router".to_string(),
+            }]
+        );
+
+        let mut better = loaded.summary;
+        better.avg_ms = better.avg_ms.map(|avg| avg - 3.2);
+        better.loss_percent = 0.0;
+        let comparison = Comparison::new(&better, &loaded.summary);
+        assert_eq!(
+            comparison.format(),
+            "vs previous run: Δavg -3.2ms | Δp95 +0.0ms | Δjitter - | Δloss -5.0%"
+        );
+    }
+}
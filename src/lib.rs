@@ -1,4 +1,46 @@
+//! Ping engine and terminal UI behind the `rttui` binary
+//!
+//! The [`ping`] module can be used on its own: start a [`Pinger`] and consume the
+//! [`PingResult`] stream it sends, without the TUI. [`spawn_pinger`] does the same from
+//! a [`PingerConfig`] with the options of the CLI.
+//!
+//! ```no_run
+//! use rttui::{IcmpPinger, PingStats, Pinger, resolve_host};
+//! use tokio::sync::mpsc;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let ip = resolve_host("example.com", None, None).await?;
+//! let (tx, mut rx) = mpsc::unbounded_channel();
+//! let handle = Box::new(IcmpPinger::new(ip, 1000, 3000)).start(tx);
+//!
+//! let mut stats = PingStats::new();
+//! while let Some(result) = rx.recv().await {
+//!     stats.record(&result);
+//!     if stats.total_sent == 10 {
+//!         break;
+//!     }
+//! }
+//! handle.abort();
+//! println!("{}", stats.format_stats());
+//! # Ok(())
+//! # }
+//! ```
+
 pub mod color;
 pub mod config;
+pub mod export;
+pub mod metrics;
 pub mod ping;
+pub mod state;
 pub mod ui;
+
+pub use ping::icmp::IcmpPinger;
+pub use ping::socks::Socks5Proxy;
+pub use ping::spawn::{PingerConfig, Probe, spawn_pinger};
+pub use ping::tls::TlsPinger;
+pub use ping::udp::{UdpClientPinger, UdpServer};
+pub use ping::{
+    InFlight, LocalAddr, LossReason, MissedTicks, PingResult, PingStats, Pinger, ProbeSeq,
+    ProbeSwitch, Reducer, Trend, WaitFor, WaitTracker, normalize_host, resolve_host, scope_id,
+    split_port,
+};
@@ -1,24 +1,24 @@
-mod color;
-mod config;
-mod ping;
-mod ui;
-
-use std::io;
-use std::net::{IpAddr, SocketAddr};
-use std::time::Duration;
+use std::io::{self, IsTerminal, Write};
+use std::net::IpAddr;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use crossterm::{
     event::{
         self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
         MouseEventKind,
     },
     execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    terminal::{
+        EnterAlternateScreen, LeaveAlternateScreen, SetTitle, disable_raw_mode, enable_raw_mode,
+    },
 };
 use ratatui::{
-    Terminal,
+    Frame, Terminal,
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -27,149 +27,464 @@ use ratatui::{
 };
 use tokio::sync::mpsc;
 
-use config::{Config, Mode};
-use ping::icmp::IcmpPinger;
-use ping::resolve_host;
-use ping::udp::{UdpClientPinger, UdpServer};
-use ping::{PingResult, Pinger};
-use ui::app::{App, HeaderEditField, PingPopup};
-use ui::footer::Footer;
-use ui::graph::Graph;
-use ui::header::{Header, HeaderField};
-use ui::legend::{LEGEND_WIDTH, Legend, MIN_WIDTH_FOR_LEGEND};
-use ui::settings::SettingsMenu;
-
-/// Start a pinger task for the given configuration
+use rttui::color::{ColorScale, ColorScheme, loss_color, target_accent, truecolor_supported};
+use rttui::config::{Config, Mode};
+use rttui::export::{Comparison, ExportSummary, SessionExport};
+use rttui::metrics::{self, SharedMetrics};
+use rttui::ping::demo::DEMO_ADDR;
+use rttui::ping::icmp;
+use rttui::ping::udp::UdpServer;
+use rttui::ping::{InFlight, LocalAddr, PingResult, PingStats, ProbeSeq, ProbeSwitch, WaitTracker};
+use rttui::ping::{
+    check_source_family, check_target_ip, format_time, normalize_host, resolve_host,
+};
+use rttui::state::SessionState;
+use rttui::ui::app::{App, EventKind, HeaderEditField, PingPopup, SettingsField, TargetState};
+use rttui::ui::footer::Footer;
+use rttui::ui::graph::Graph;
+use rttui::ui::header::{Header, HeaderField};
+use rttui::ui::heatmap::Heatmap;
+use rttui::ui::histogram::Histogram;
+use rttui::ui::input::TextInput;
+use rttui::ui::legend::{LEGEND_HEIGHT, LEGEND_WIDTH, Legend};
+use rttui::ui::linechart::LineChart;
+use rttui::ui::losses::LossTimeline;
+use rttui::ui::settings::SettingsMenu;
+use rttui::ui::targets::TargetList;
+
+/// Smallest terminal the full layout fits in (header, graph, footer)
+const MIN_TERMINAL_WIDTH: u16 = 30;
+const MIN_TERMINAL_HEIGHT: u16 = 10;
+
+/// Start a pinger task for the given configuration, pinging `host` at `resolved_ip`
+/// `switch` stops and resumes sending without restarting the pinger; `seq` numbers the
+/// probes; `in_flight` and `local_addr` are kept up to date by pingers that track them
+/// (UDP client mode)
+#[allow(clippy::too_many_arguments)]
 fn start_pinger(
-    mode: Mode,
+    config: &Config,
+    host: &str,
     resolved_ip: IpAddr,
-    interval: u64,
-    timeout: u64,
-    port: u16,
+    switch: &ProbeSwitch,
+    seq: &ProbeSeq,
+    in_flight: &InFlight,
+    local_addr: &LocalAddr,
+    sweep_started: Instant,
     tx: mpsc::UnboundedSender<PingResult>,
 ) -> tokio::task::JoinHandle<()> {
-    match mode {
-        Mode::Icmp => {
-            let pinger = Box::new(IcmpPinger::new(resolved_ip, interval, timeout));
-            pinger.start(tx)
-        }
-        Mode::UdpClient => {
-            let target = SocketAddr::new(resolved_ip, port);
-            let pinger = Box::new(UdpClientPinger::new(target, interval, timeout));
-            pinger.start(tx)
-        }
-        Mode::UdpServer => unreachable!(),
+    let mut pinger = config
+        .pinger_config(host, resolved_ip)
+        .switch(switch.clone())
+        .seq(seq.clone())
+        .in_flight(in_flight.clone())
+        .local_addr(local_addr.clone());
+    if let Some(sweep) = config.interval_sweep {
+        pinger = pinger.sweep(sweep, sweep_started);
     }
+    pinger.start(tx)
+}
+
+/// Resolve a target host (demo targets all get the same documentation address)
+async fn resolve_target(config: &Config, host: &str) -> Result<IpAddr> {
+    if config.demo {
+        return Ok(DEMO_ADDR);
+    }
+    if config.mode == Mode::Icmp {
+        icmp::check_target(host)?;
+    }
+    resolve_host(host, config.dns_server, config.hosts_file()).await
+}
+
+/// Check that ICMP pings of `ip`'s family can be sent, off the async runtime since the
+/// check waits for a loopback reply
+async fn check_icmp_support(ip: IpAddr) -> Result<()> {
+    tokio::task::spawn_blocking(move || icmp::check_support(ip)).await?
 }
 
+/// Print a color scheme's gradient across the terminal with the RTTs it stands for
+/// underneath, to pick a scheme and scale from the shell
+fn print_scheme_preview(scheme: ColorScheme, scale: u64) {
+    let truecolor = truecolor_supported(
+        std::env::var("COLORTERM").ok().as_deref(),
+        std::env::var_os("WT_SESSION").is_some(),
+    );
+    let width = crossterm::terminal::size().map_or(80, |(width, _)| width as usize);
+    let strip = ColorScale::new(scale, scheme).preview_strip(width, truecolor);
+    let (low, mid, high) = (
+        "0ms".to_string(),
+        format!("{}ms", scale / 2),
+        format!("{}ms", scale),
+    );
+    let gap = width.saturating_sub(low.len() + mid.len() + high.len());
+    println!("{} (scale {}ms)", scheme, scale);
+    println!("{}", strip);
+    println!(
+        "{}{}{}{}{}",
+        low,
+        " ".repeat(gap / 2),
+        mid,
+        " ".repeat(gap - gap / 2),
+        high
+    );
+}
+
+/// Save the current window title on the terminal's title stack (xterm extension)
+fn push_window_title(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(b"\x1b[22;0t")?;
+    out.flush()
+}
+
+/// Restore the window title saved by `push_window_title`
+fn pop_window_title(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(b"\x1b[23;0t")?;
+    out.flush()
+}
+
+// Returns the exit status instead of calling `process::exit`, so destructors (e.g. the
+// one removing the stats socket file) still run
 #[tokio::main]
-async fn main() -> Result<()> {
-    let config = Config::parse();
+async fn main() -> Result<ExitCode> {
+    let matches = Config::command().get_matches();
+    let mut config = Config::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // Remembered preferences from the last run, under any options given on the command line
+    let state = if config.no_state {
+        SessionState::default()
+    } else {
+        SessionState::load()
+    };
+    state.apply(&mut config, &matches);
+    config.validate()?;
+    if let Some(host) = &config.host {
+        config.host = Some(normalize_host(host)?);
+    }
+    // Demo mode doesn't need a real target
+    if config.demo
+        && config.host.is_none()
+        && config.targets_file.is_none()
+        && config.targets.is_empty()
+    {
+        config.host = Some("demo".to_string());
+    }
+
+    if let Some(scheme) = config.preview_scheme {
+        print_scheme_preview(scheme, config.scale);
+        return Ok(ExitCode::SUCCESS);
+    }
 
     // Handle server mode separately (no TUI)
     if config.mode == Mode::UdpServer {
-        config.validate()?;
         let server = UdpServer::new(config.bind.clone(), config.port);
         server.run().await?;
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Previous run to compare against, loaded up front so a bad file fails fast
+    let previous_run = match &config.compare {
+        Some(path) => Some(SessionExport::load(path)?.summary),
+        None => None,
+    };
+
+    // Additional targets for the multi-target summary
+    let target_hosts = config.load_targets()?;
+
+    // Without an explicit host, graph the first listed target
+    let mut primary_target_idx = None;
+    if config.host.is_none() && !target_hosts.is_empty() {
+        config.host = Some(target_hosts[0].clone());
+        primary_target_idx = Some(0);
+    }
+
+    // Resolve summary targets before entering the TUI so errors are visible
+    let mut targets = Vec::with_capacity(target_hosts.len());
+    for host in target_hosts {
+        let ip = match resolve_target(&config, &host).await {
+            Ok(ip) => Some(ip),
+            Err(e) => {
+                // An error, not a warning, so it's printed even with --quiet
+                eprintln!("Failed to resolve {}: {}", host, e);
+                None
+            }
+        };
+        targets.push(TargetState::new(host, ip));
     }
 
     // Check if we have a host - if not, we'll start with settings dialog open
     let has_host = config.host.is_some();
+    let mut target_warning = None;
     let (mut resolved_ip, mut resolved_ip_str) = if has_host {
         let host = config.host.as_ref().unwrap();
-        let ip = resolve_host(host).await?;
+        let ip = resolve_target(&config, host).await?;
+        target_warning = check_target_ip(ip)?;
+        if let Some(source) = config.source {
+            check_source_family(source, ip)?;
+        }
+        if config.mode == Mode::Icmp && !config.demo {
+            check_icmp_support(ip).await?;
+        }
         (Some(ip), ip.to_string())
     } else {
         (None, "not set".to_string())
     };
 
+    // Prometheus endpoint and stats socket, fed from the same stats as the footer
+    let shared_metrics = (config.metrics_port.is_some() || config.stats_socket.is_some())
+        .then(SharedMetrics::default);
+    if let (Some(port), Some(shared)) = (config.metrics_port, &shared_metrics) {
+        let listener = metrics::bind(port).await?;
+        tokio::spawn(metrics::serve(listener, shared.clone()));
+    }
+    #[cfg(unix)]
+    let _stats_socket = match (&config.stats_socket, &shared_metrics) {
+        (Some(path), Some(shared)) => {
+            let (listener, file) = metrics::bind_socket(path)?;
+            tokio::spawn(metrics::serve_socket(listener, shared.clone()));
+            Some(file)
+        }
+        _ => None,
+    };
+
+    // Wall-clock limit from --duration, checked alongside --count
+    let deadline = config
+        .duration
+        .map(|ms| tokio::time::Instant::now() + Duration::from_millis(ms));
+
+    // One-shot measurement without a terminal (e.g. piped into a script)
+    let wait = config.wait_for();
+    if (config.count.is_some() || deadline.is_some() || wait.is_some())
+        && !io::stdout().is_terminal()
+    {
+        let Some(ip) = resolved_ip else {
+            anyhow::bail!("A target host is required when not running in a terminal");
+        };
+        if let Some(warning) = target_warning.filter(|_| !config.quiet) {
+            eprintln!("Warning: {}", warning);
+        }
+        let (stats, results, wait_met) = run_headless(&config, ip, deadline, shared_metrics).await;
+        if !config.quiet {
+            println!("{}", stats.format_stats_in(config.unit, config.precision));
+        }
+        if let Some(previous) = previous_run.as_ref().filter(|_| !config.quiet) {
+            let current = ExportSummary::new(&stats, &results);
+            println!("{}", Comparison::new(&current, previous).format());
+        }
+        if let Some(path) = &config.export {
+            SessionExport::new(&resolved_ip_str, &stats, &results, config.utc).write(path)?;
+        }
+        if wait.is_some() && !wait_met {
+            return Ok(ExitCode::FAILURE);
+        }
+        if let Some(code) = config.loss_exit_code(&stats) {
+            eprintln!("{}", loss_failure(&config, &stats));
+            return Ok(ExitCode::from(code));
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // RGB gradients turn into approximations on terminals without 24-bit color
+    let truecolor = truecolor_supported(
+        std::env::var("COLORTERM").ok().as_deref(),
+        std::env::var_os("WT_SESSION").is_some(),
+    );
+
     // Set up terminal with mouse support
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    if config.set_title {
+        push_window_title(&mut stdout)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
     let mut app = App::new(config.clone());
+    app.deadline = deadline;
+    app.previous_run = previous_run;
+    app.metrics = shared_metrics;
+    app.paused = config.start_paused;
+    app.footer_event = target_warning.map(String::from);
+    app.recent_targets = state.recent_targets.clone();
+    if let (Some(host), Some(_)) = (config.host.as_deref(), resolved_ip) {
+        app.remember_target(host);
+    }
+    if !truecolor {
+        app.banner = Some(
+            "No truecolor support detected (COLORTERM); colors may look off. Esc to dismiss"
+                .to_string(),
+        );
+    }
 
-    // If no host provided, open settings dialog immediately
+    // If no host provided, open settings dialog immediately (offering the last target)
     if !has_host {
         app.open_settings();
+        if let Some(target) = &state.target {
+            app.settings_target = target.clone();
+        }
     }
 
+    // Start one pinger per summary target, tagging results with the target index
+    // (the primary target shares the main pinger instead of pinging twice)
+    let (targets_tx, mut targets_rx) = mpsc::unbounded_channel::<(usize, PingResult)>();
+    for (idx, target) in targets.iter().enumerate() {
+        let Some(ip) = target.ip else {
+            continue;
+        };
+        if Some(idx) == primary_target_idx {
+            continue;
+        }
+        let (target_tx, mut target_rx) = mpsc::unbounded_channel::<PingResult>();
+        start_pinger(
+            &config,
+            &target.host,
+            ip,
+            &app.probe_switch,
+            &ProbeSeq::default(),
+            &InFlight::default(),
+            &LocalAddr::default(),
+            app.sweep_started,
+            target_tx,
+        );
+        let targets_tx = targets_tx.clone();
+        tokio::spawn(async move {
+            while let Some(result) = target_rx.recv().await {
+                if targets_tx.send((idx, result)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    app.targets = targets;
+    app.primary_target_idx = primary_target_idx;
+
     // Create ping channel
     let (mut tx, mut rx) = mpsc::unbounded_channel::<PingResult>();
 
     // Start pinger only if we have a host
-    let mut pinger_handle: Option<tokio::task::JoinHandle<()>> = if let Some(ip) = resolved_ip {
-        Some(start_pinger(
-            config.mode,
+    let mut pinger_handle: Option<tokio::task::JoinHandle<()>> = resolved_ip.map(|ip| {
+        start_pinger(
+            &config,
+            config.host.as_deref().unwrap_or_default(),
             ip,
-            config.interval,
-            config.timeout,
-            config.port,
+            &app.probe_switch,
+            &app.probe_seq,
+            &app.in_flight,
+            &app.local_addr,
+            app.sweep_started,
             tx.clone(),
-        ))
-    } else {
-        None
-    };
+        )
+    });
+
+    // SIGTERM / SIGHUP quit through the normal path so the terminal is restored
+    let terminate = spawn_termination_listener();
+
+    // Host the running pinger targets, to go back to when a new target can't be used
+    let mut active_host = config.host.clone();
 
     // Main event loop with restart support
     loop {
-        let result = run_app(&mut terminal, &mut app, &mut rx, &resolved_ip_str).await;
+        let result = run_app(
+            &mut terminal,
+            &mut app,
+            &mut rx,
+            &mut targets_rx,
+            &resolved_ip_str,
+            &terminate,
+        )
+        .await;
 
         // Check if we need to restart pinger
         if app.needs_pinger_restart {
             app.needs_pinger_restart = false;
 
-            // Abort current pinger if running
-            if let Some(handle) = pinger_handle.take() {
+            // With the same target and mode (e.g. a new interval) the new pinger takes over
+            // without a hole in the graph: it starts before the old one is stopped and on
+            // the same channel, so replies to probes already sent still arrive (in ICMP and
+            // TLS mode, where each probe runs on its own), and it continues the numbering
+            let handoff = app.new_mode.is_none() && app.new_target.is_none();
+            let mut old_handle = pinger_handle.take();
+
+            // Otherwise abort the current pinger first
+            if !handoff && let Some(handle) = old_handle.take() {
                 handle.abort();
                 let _ = handle.await; // Wait for it to finish
             }
 
-            // Clear old results if target changed
-            if app.new_target.is_some() {
+            // Results from another mode aren't comparable
+            if let Some(mode) = app.new_mode.take() {
                 app.clear_all_data();
+                app.footer_event = Some(format!("Switched to {} mode", mode));
+            }
 
-                // Resolve new target
-                let new_host = app.new_target.take().unwrap();
-                match resolve_host(&new_host).await {
-                    Ok(ip) => {
+            // Resolve a new target, clearing the old results only once it's usable
+            if let Some(new_host) = app.new_target.take() {
+                let mut resolved = resolve_target(&app.config, &new_host).await;
+                if let Ok(ip) = resolved
+                    && app.config.mode == Mode::Icmp
+                    && !app.config.demo
+                {
+                    resolved = check_icmp_support(ip).await.map(|_| ip);
+                }
+                let resolved = resolved.and_then(|ip| Ok((ip, check_target_ip(ip)?)));
+                match resolved {
+                    Ok((ip, warning)) => {
+                        app.clear_all_data();
+                        // The main graph no longer follows a summary target
+                        app.primary_target_idx = None;
+                        // A corrected target also clears the notice about a failed one
+                        app.banner = None;
                         resolved_ip = Some(ip);
                         resolved_ip_str = ip.to_string();
+                        app.remember_target(&new_host);
+                        active_host = Some(new_host);
+                        app.footer_event = warning.map(String::from);
                     }
                     Err(e) => {
-                        // Failed to resolve - keep old target, show error
-                        eprintln!("Failed to resolve {}: {}", new_host, e);
-                        // Restore old host in config if we had one
-                        if let Some(old_host) = &config.host {
-                            app.config.host = Some(old_host.clone());
-                        }
+                        // Keep graphing the old target and let the user fix the new one
+                        app.target_change_failed(&new_host, &e.to_string(), active_host.clone());
                     }
                 }
             }
 
-            // Get new interval
-            let new_interval = app.new_interval.take().unwrap_or(app.config.interval);
-
-            // Create new channel
-            let (new_tx, new_rx) = mpsc::unbounded_channel::<PingResult>();
-            tx = new_tx;
-            rx = new_rx;
+            // A new interval is already applied to app.config, which the pinger reads
+            app.new_interval = None;
+            // Mark where the restart happened, unless history was just cleared
+            if !app.results.is_empty() {
+                app.mark_event(EventKind::Restart);
+            }
+            if !handoff {
+                // The new pinger starts cold again
+                app.start_warmup();
+
+                // Create new channel, numbering the probes from 1 again
+                let (new_tx, new_rx) = mpsc::unbounded_channel::<PingResult>();
+                tx = new_tx;
+                rx = new_rx;
+                app.probe_seq = ProbeSeq::default();
+            }
 
             // Start new pinger only if we have a resolved IP
+            // Tasks of the old pinger may linger, so the new one gets its own count
+            app.in_flight = InFlight::default();
+            app.local_addr = LocalAddr::default();
             if let Some(ip) = resolved_ip {
                 pinger_handle = Some(start_pinger(
-                    app.config.mode,
+                    &app.config,
+                    app.config.host.as_deref().unwrap_or_default(),
                     ip,
-                    new_interval,
-                    app.config.timeout,
-                    app.config.port,
+                    &app.probe_switch,
+                    &app.probe_seq,
+                    &app.in_flight,
+                    &app.local_addr,
+                    app.sweep_started,
                     tx.clone(),
                 ));
+                if app.results.is_empty() {
+                    app.awaiting_first_result = Some(Instant::now());
+                }
+            }
+            // Handed off: the old pinger stops only now that the new one is running
+            if let Some(handle) = old_handle {
+                handle.abort();
             }
 
             continue;
@@ -185,6 +500,9 @@ async fn main() -> Result<()> {
                 DisableMouseCapture
             )?;
             terminal.show_cursor()?;
+            if app.config.set_title {
+                pop_window_title(terminal.backend_mut())?;
+            }
             eprintln!("Error: {}", e);
             return Err(e);
         }
@@ -200,426 +518,1168 @@ async fn main() -> Result<()> {
         DisableMouseCapture
     )?;
     terminal.show_cursor()?;
+    if app.config.set_title {
+        pop_window_title(terminal.backend_mut())?;
+    }
+
+    // Remember preferences for next time (keeping the last target if none was set)
+    if !app.config.no_state {
+        let mut new_state = SessionState::from_app(&app);
+        new_state.target = new_state.target.or(state.target);
+        if let Err(e) = new_state.save()
+            && !app.config.quiet
+        {
+            eprintln!("Warning: {}", e);
+        }
+    }
 
     // Print final stats
-    println!("\n{}", app.stats.format_stats());
+    if let Some(wait) = app.wait.as_ref().filter(|_| app.wait_met) {
+        let target = app.config.host.as_deref().unwrap_or(&resolved_ip_str);
+        println!("{} is {}", target, wait.wait);
+    }
+    if !app.config.quiet {
+        println!(
+            "\n{}",
+            app.stats
+                .format_stats_in(app.config.unit, app.config.precision)
+        );
+        for target in &app.targets {
+            println!(
+                "{}: {}",
+                target.host,
+                target
+                    .stats
+                    .format_stats_in(app.config.unit, app.config.precision)
+            );
+        }
+        if let Some(comparison) = app.run_comparison() {
+            println!("{}", comparison.format());
+        }
+    }
+    if let Some(path) = &app.config.export {
+        let target = app.config.host.as_deref().unwrap_or(&resolved_ip_str);
+        let notes = app
+            .notes
+            .iter()
+            .map(|note| (note.timestamp, app.note_probe_seq(note), note.text.as_str()));
+        SessionExport::new(target, &app.stats, &app.results, app.config.utc)
+            .with_notes(notes, app.config.utc)
+            .write(path)?;
+    }
+    // A wait that ended any other way (quit, --count, --duration) failed
+    if app.wait.is_some() && !app.wait_met {
+        return Ok(ExitCode::FAILURE);
+    }
+    if let Some(code) = app.config.loss_exit_code(&app.stats) {
+        eprintln!("{}", loss_failure(&app.config, &app.stats));
+        return Ok(ExitCode::from(code));
+    }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
-async fn run_app(
-    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-    app: &mut App,
-    rx: &mut mpsc::UnboundedReceiver<PingResult>,
-    resolved_ip: &str,
-) -> Result<()> {
-    loop {
-        // Draw UI
-        terminal.draw(|frame| {
-            let size = frame.area();
-
-            // Determine if we have room for legend
-            let show_legend = size.width >= MIN_WIDTH_FOR_LEGEND;
-
-            // Main layout: header, graph (+ optional legend), footer
-            let main_chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(3), // Header
-                    Constraint::Min(5),    // Graph area
-                    Constraint::Length(2), // Footer
-                ])
-                .split(size);
-
-            // Header
-            let header = Header::new(
-                &app.config,
-                Some(resolved_ip),
-                size.width,
-                app.header_selected,
-            );
-            frame.render_widget(header, main_chunks[0]);
-            app.header_area = Some((
-                main_chunks[0].x,
-                main_chunks[0].y,
-                main_chunks[0].width,
-                main_chunks[0].height,
-            ));
+/// Why a --fail-over-loss check failed
+fn loss_failure(config: &Config, stats: &PingStats) -> String {
+    if stats.total_sent == 0 {
+        "No pings were sent".to_string()
+    } else {
+        format!(
+            "Loss {:.1}% is over the {}% limit",
+            stats.loss_percent(),
+            config.fail_over_loss.unwrap_or_default()
+        )
+    }
+}
 
-            // Graph area (with optional legend on right)
-            let graph_width = if show_legend {
-                main_chunks[1].width.saturating_sub(LEGEND_WIDTH) as usize
-            } else {
-                main_chunks[1].width as usize
-            };
-            let total_rows = app.total_rows(graph_width);
-
-            let graph_area = if show_legend {
-                let graph_chunks = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Min(20),              // Graph
-                        Constraint::Length(LEGEND_WIDTH), // Legend
-                    ])
-                    .split(main_chunks[1]);
-
-                let graph = Graph::new(
-                    &app.results,
-                    &app.color_scale,
-                    app.view_end_row,
-                    total_rows,
-                    app.result_base_seq,
-                    app.paused,
-                    app.config.hide_cursor,
-                    app.highlight_rtt_range,
-                );
-                frame.render_widget(graph, graph_chunks[0]);
-
-                let legend = Legend::new(&app.color_scale);
-                frame.render_widget(legend, graph_chunks[1]);
-
-                // Store legend area for mouse calculations
-                app.legend_area = Some((
-                    graph_chunks[1].x,
-                    graph_chunks[1].y,
-                    graph_chunks[1].width,
-                    graph_chunks[1].height,
-                ));
+/// Ping without the TUI, printing one line per result until `--count` pings are
+/// done, the `--duration` deadline passes or the `--wait-up` / `--wait-down`
+/// condition is met (returned as the last value)
+/// Results are kept only when they are needed for `--export` or `--compare`
+/// Stats are also published to `metrics` after every result, if given
+async fn run_headless(
+    config: &Config,
+    ip: IpAddr,
+    deadline: Option<tokio::time::Instant>,
+    metrics: Option<SharedMetrics>,
+) -> (PingStats, Vec<PingResult>, bool) {
+    let keep_results = config.export.is_some() || config.compare.is_some();
+    let mut results = Vec::new();
+    let (tx, mut rx) = mpsc::unbounded_channel::<PingResult>();
+    let handle = start_pinger(
+        config,
+        config.host.as_deref().unwrap_or_default(),
+        ip,
+        &ProbeSwitch::default(),
+        &ProbeSeq::default(),
+        &InFlight::default(),
+        &LocalAddr::default(),
+        Instant::now(),
+        tx,
+    );
+
+    let mut stats = PingStats::new();
+    let mut warmup_remaining = config.warmup;
+    let mut wait = config.wait_for().map(WaitTracker::new);
+    let mut wait_met = false;
+    let target = config.host.clone().unwrap_or_else(|| ip.to_string());
+    while config.count.is_none_or(|count| stats.total_sent < count) {
+        let next = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, rx.recv()).await.ok(),
+            None => Some(rx.recv().await),
+        };
+        let Some(Some(result)) = next else {
+            break;
+        };
+        if warmup_remaining > 0 {
+            warmup_remaining -= 1;
+        } else {
+            stats.record(&result);
+        }
+        if let Some(metrics) = &metrics {
+            metrics.update(&target, &stats, Some(&result));
+        }
+        match result.rtt {
+            Some(rtt) => {
+                let ms = rtt.as_secs_f64() * 1000.0;
+                println!(
+                    "seq={} time={} {}",
+                    result.seq,
+                    config.unit.number(ms, config.precision),
+                    config.unit.resolve(ms).suffix()
+                )
+            }
+            None if result.corrupted => println!("seq={} corrupted reply", result.seq),
+            None => match result.loss_reason {
+                Some(reason) => println!("seq={} {}", result.seq, reason),
+                None => println!("seq={} timeout", result.seq),
+            },
+        }
+        if let Some(wait) = &mut wait
+            && wait.update(&result)
+        {
+            println!("{} is {}", target, wait.wait);
+            wait_met = true;
+        }
+        if keep_results {
+            results.push(result);
+        }
+        if wait_met {
+            break;
+        }
+    }
+    handle.abort();
 
-                graph_chunks[0]
-            } else {
-                let graph = Graph::new(
-                    &app.results,
-                    &app.color_scale,
-                    app.view_end_row,
-                    total_rows,
-                    app.result_base_seq,
-                    app.paused,
-                    app.config.hide_cursor,
-                    app.highlight_rtt_range,
-                );
-                frame.render_widget(graph, main_chunks[1]);
-                app.legend_area = None; // No legend when terminal is narrow
-                main_chunks[1]
-            };
+    (stats, results, wait_met)
+}
 
-            // Store graph area for mouse calculations
-            app.graph_area = Some((
-                graph_area.x,
-                graph_area.y,
-                graph_area.width,
-                graph_area.height,
-            ));
+/// Render a notice instead of the layout when the terminal can't fit it
+/// Clickable areas are forgotten so stale positions don't catch mouse events
+fn draw_too_small(frame: &mut Frame, app: &mut App) {
+    let size = frame.area();
+    app.header_area = None;
+    app.footer_area = None;
+    app.legend_area = None;
+    app.graph_area = None;
+    app.inline_edit_confirm_area = None;
+    app.quit_confirm_yes_area = None;
+    app.quit_confirm_no_area = None;
+    app.target_confirm_yes_area = None;
+    app.target_confirm_no_area = None;
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Terminal too small",
+            Style::default().fg(Color::Yellow),
+        )),
+        Line::from(format!(
+            "{}x{} (need {}x{})",
+            size.width, size.height, MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT
+        )),
+    ];
+    if app.quit_confirm {
+        lines.push(Line::from("Quit? (y/n)"));
+    } else if app.target_confirm.is_some() {
+        lines.push(Line::from("Change target? (y/n)"));
+    }
+    // Vertically centered, as far as it fits
+    let top = size.height.saturating_sub(lines.len() as u16) / 2;
+    let area = Rect {
+        y: size.y + top,
+        height: size.height - top,
+        ..size
+    };
+    frame.render_widget(
+        Paragraph::new(lines).alignment(ratatui::layout::Alignment::Center),
+        area,
+    );
+}
+
+/// Render one frame of the whole UI, recording widget areas for mouse handling
+fn draw_ui(frame: &mut Frame, app: &mut App, resolved_ip: &str) {
+    let size = frame.area();
+
+    if size.width < MIN_TERMINAL_WIDTH || size.height < MIN_TERMINAL_HEIGHT {
+        draw_too_small(frame, app);
+        return;
+    }
+
+    // Zen mode gives the whole terminal to the graph
+    let zen = app.zen_mode;
+
+    // Determine if we have room for legend
+    let show_legend = app.update_legend_visibility(size.width) && !zen;
 
-            // Footer
-            let recent_rtts = app.recent_rtts_slice();
-            let footer = Footer::new(&app.stats, &recent_rtts, &app.color_scale, size.width);
-            frame.render_widget(footer, main_chunks[2]);
-            app.footer_area = Some((
-                main_chunks[2].x,
-                main_chunks[2].y,
-                main_chunks[2].width,
-                main_chunks[2].height,
+    // Main layout: header, graph (+ optional legend), targets summary, footer
+    let targets_height = if app.targets.is_empty() || zen {
+        0
+    } else {
+        TargetList::height(app.targets.len())
+    };
+    let (header_height, footer_height) = if zen { (0, 0) } else { (3, 2) };
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(header_height),  // Header
+            Constraint::Min(5),                 // Graph area
+            Constraint::Length(targets_height), // Targets summary
+            Constraint::Length(footer_height),  // Footer
+        ])
+        .split(size);
+
+    if targets_height > 0 {
+        let target_list = TargetList::new(&app.targets, &app.color_scale)
+            .loss_thresholds(app.config.loss_warn, app.config.loss_crit)
+            .unit(app.config.unit)
+            .precision(app.config.precision);
+        frame.render_widget(target_list, main_chunks[2]);
+    }
+
+    // Header
+    if zen {
+        app.header_area = None;
+    } else {
+        let header = Header::new(
+            &app.config,
+            Some(resolved_ip),
+            size.width,
+            app.header_selected,
+        )
+        .accent(app.primary_target_idx.map(target_accent))
+        .local_addr(app.local_addr.get());
+        frame.render_widget(header, main_chunks[0]);
+        app.header_area = Some((
+            main_chunks[0].x,
+            main_chunks[0].y,
+            main_chunks[0].width,
+            main_chunks[0].height,
+        ));
+    }
+
+    // Graph area (with optional legend and histogram stacked on the right)
+    let show_side_panel = show_legend || (app.histogram_visible && !zen);
+    // --graph-width caps the graph (left-aligned) so samples per row don't depend on the terminal
+    let max_graph_width = app.config.graph_width;
+    let clamp_width = |width: u16| max_graph_width.map_or(width, |max| width.min(max));
+    let graph_width = clamp_width(if show_side_panel {
+        main_chunks[1].width.saturating_sub(LEGEND_WIDTH)
+    } else {
+        main_chunks[1].width
+    }) as usize;
+    let row_len = app.row_len(graph_width);
+    app.sync_view_to_row_len(row_len);
+    let total_rows = app.total_rows(row_len);
+
+    let graph_area = if show_side_panel {
+        let graph_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(20),              // Graph
+                Constraint::Length(LEGEND_WIDTH), // Legend / histogram
+            ])
+            .split(main_chunks[1]);
+
+        let legend_height = if !show_legend {
+            0
+        } else if app.histogram_visible {
+            LEGEND_HEIGHT
+        } else {
+            graph_chunks[1].height
+        };
+        let side_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(legend_height), // Legend
+                Constraint::Min(0),                // Histogram
+            ])
+            .split(graph_chunks[1]);
+
+        if show_legend {
+            let legend = Legend::new(&app.color_scale);
+            frame.render_widget(legend, side_chunks[0]);
+
+            // Store legend area for mouse calculations
+            app.legend_area = Some((
+                side_chunks[0].x,
+                side_chunks[0].y,
+                side_chunks[0].width,
+                side_chunks[0].height,
             ));
+        } else {
+            app.legend_area = None;
+        }
 
-            // Render popup if present
-            if let Some(popup) = &app.popup {
-                // Convert stable sequence number to VecDeque index
-                // If the sample has been evicted from the buffer, skip rendering
-                let result_idx = popup.result_seq.checked_sub(app.result_base_seq);
-                if let Some(idx) = result_idx
-                    && let Some(result) = app.results.get(idx)
-                {
-                    let rtt_str = result
-                        .rtt_ms_f64()
-                        .map(|ms| format!("{:.2}ms", ms))
-                        .unwrap_or_else(|| "TIMEOUT".to_string());
-                    let jitter_str = result
-                        .jitter_ms_f64()
-                        .map(|ms| format!("±{:.2}ms", ms))
-                        .unwrap_or_else(|| "-".to_string());
-                    let time_str = result.timestamp_str();
-
-                    let popup_width = 28u16;
-                    let popup_height = 6u16;
-
-                    // Position popup near click but within bounds
-                    let popup_x = popup
-                        .screen_x
-                        .saturating_sub(popup_width / 2)
-                        .min(size.width.saturating_sub(popup_width));
-                    let popup_y = if popup.screen_y > popup_height + 1 {
-                        popup.screen_y - popup_height - 1
-                    } else {
-                        popup.screen_y + 1
-                    }
-                    .min(size.height.saturating_sub(popup_height));
-
-                    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
-
-                    frame.render_widget(Clear, popup_area);
-
-                    let popup_block = Block::default()
-                        .title(" Ping Info ")
-                        .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::Cyan))
-                        .style(Style::default().bg(Color::Rgb(30, 30, 40)));
-
-                    let popup_text = vec![
-                        Line::from(vec![
-                            Span::styled("Time:   ", Style::default().fg(Color::Gray)),
-                            Span::styled(&time_str, Style::default().fg(Color::White)),
-                        ]),
-                        Line::from(vec![
-                            Span::styled("RTT:    ", Style::default().fg(Color::Gray)),
-                            Span::styled(
-                                &rtt_str,
-                                Style::default()
-                                    .fg(Color::White)
-                                    .add_modifier(Modifier::BOLD),
-                            ),
-                        ]),
-                        Line::from(vec![
-                            Span::styled("Jitter: ", Style::default().fg(Color::Gray)),
-                            Span::styled(&jitter_str, Style::default().fg(Color::Yellow)),
-                        ]),
-                        Line::from(vec![
-                            Span::styled("Seq:    ", Style::default().fg(Color::Gray)),
-                            Span::styled(
-                                format!("{}", result.seq),
-                                Style::default().fg(Color::Cyan),
-                            ),
-                        ]),
-                    ];
-
-                    let popup_para = Paragraph::new(popup_text).block(popup_block);
-                    frame.render_widget(popup_para, popup_area);
+        if app.histogram_visible && !zen {
+            let histogram = Histogram::new(&app.results, &app.color_scale);
+            frame.render_widget(histogram, side_chunks[1]);
+        }
+
+        graph_chunks[0]
+    } else {
+        app.legend_area = None; // No legend when terminal is narrow
+        main_chunks[1]
+    };
+    let graph_area = Rect {
+        width: clamp_width(graph_area.width),
+        ..graph_area
+    };
+
+    if app.settings_open && app.scheme_compare {
+        draw_scheme_compare(frame, app, graph_area, total_rows);
+    } else if app.loss_view {
+        // Loss timeline replaces the graph; there is no graph to click on
+        let events = app.loss_events.as_slice();
+        let visible = LossTimeline::visible_rows(graph_area.height);
+        app.loss_scroll = app.loss_scroll.min(events.len().saturating_sub(visible));
+        frame.render_widget(
+            LossTimeline::new(events, app.loss_scroll).utc(app.display_utc),
+            graph_area,
+        );
+        app.graph_area = None;
+    } else if app.line_view {
+        // The line chart always follows the newest samples and has no clickable cells
+        let chart = LineChart::new(&app.results, &app.color_scale)
+            .background(app.config.graph_bg)
+            .clip_outliers(app.config.clip_outliers);
+        frame.render_widget(chart, graph_area);
+        app.graph_area = None;
+    } else if app.heatmap_view {
+        // Cells are placed by time, so there is no sample sequence to click through
+        let heatmap = Heatmap::new(&app.results, &app.color_scale)
+            .background(app.config.graph_bg)
+            .utc(app.display_utc);
+        frame.render_widget(heatmap, graph_area);
+        app.graph_area = None;
+    } else {
+        // A fixed row length narrower than the space is centered in it
+        let grid_area = match app.config.samples_per_row {
+            Some(row_len) => {
+                let width = graph_area.width.min(app.row_columns(row_len) as u16);
+                Rect {
+                    x: graph_area.x + (graph_area.width - width) / 2,
+                    width,
+                    ..graph_area
                 }
             }
+            None => graph_area,
+        };
+        draw_graph(frame, app, grid_area, total_rows);
+    }
+    if app.startup_notice_visible() {
+        draw_startup_notice(frame, app, graph_area, resolved_ip);
+    }
 
-            // Render settings menu if open
-            if app.settings_open {
-                let settings_menu = SettingsMenu::new(
-                    app.settings_field,
-                    app.settings_target.clone(),
-                    app.settings_interval,
-                    app.settings_scale,
-                    app.settings_colors,
-                    app.settings_hide_cursor,
-                    app.settings_buffer_mb,
-                    app.settings_input_active,
-                    app.settings_input_buffer.clone(),
-                    app.settings_input_cursor,
-                    app.settings_input_selected,
-                );
-                frame.render_widget(settings_menu, size);
-            }
+    // Footer
+    if zen {
+        app.footer_area = None;
+    } else {
+        draw_footer(frame, app, main_chunks[3]);
+    }
 
-            // Render inline edit popup if active
-            if let Some(field) = app.inline_edit {
-                let (popup_x, popup_y) = app.inline_edit_pos;
-
-                let title = match field {
-                    HeaderEditField::Target => " Target ",
-                    HeaderEditField::Interval => " Interval (ms) ",
-                    HeaderEditField::Scale => " Scale (ms) ",
-                    HeaderEditField::Colors => " Color Scheme ",
-                };
-
-                let popup_width = 30u16.max(app.inline_edit_buffer.len() as u16 + 6);
-                let popup_height = 4u16; // Increased for confirm button
-
-                // Position below the clicked item
-                let px = popup_x
-                    .saturating_sub(1)
-                    .min(size.width.saturating_sub(popup_width));
-                let py = (popup_y + 1).min(size.height.saturating_sub(popup_height));
-
-                let popup_area = Rect::new(px, py, popup_width, popup_height);
-                frame.render_widget(Clear, popup_area);
-
-                let popup_block = Block::default()
-                    .title(title)
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Cyan))
-                    .style(Style::default().bg(Color::Rgb(30, 30, 40)));
-
-                // Styles matching settings menu
-                let input_style = Style::default().fg(Color::White).bg(Color::Rgb(60, 60, 80));
-                let selected_text_style = Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::Rgb(150, 180, 255));
-                let value_style = Style::default().fg(Color::Cyan);
-                let selected_style = Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD);
-                let button_style = Style::default().fg(Color::White).bg(Color::Rgb(60, 60, 80));
-                let button_selected_style = Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::Rgb(100, 200, 100));
-
-                let input_focused = !app.inline_edit_confirm_focused;
-
-                // Render content based on field type
-                let input_line = if field == HeaderEditField::Colors {
-                    // Colors is an enum selector - show with arrows
-                    Line::from(vec![
-                        Span::styled(
-                            "◄ ",
-                            if input_focused {
-                                Style::default().fg(Color::White)
-                            } else {
-                                Style::default().fg(Color::DarkGray)
-                            },
-                        ),
-                        Span::styled(
-                            app.inline_edit_buffer.clone(),
-                            if input_focused {
-                                selected_style
-                            } else {
-                                value_style
-                            },
-                        ),
-                        Span::styled(
-                            " ►",
-                            if input_focused {
-                                Style::default().fg(Color::White)
-                            } else {
-                                Style::default().fg(Color::DarkGray)
-                            },
+    // Render popup if present
+    if let Some(popup) = &app.popup {
+        // Convert stable sequence number to VecDeque index
+        // If the sample has been evicted from the buffer, skip rendering
+        let result_idx = popup.result_seq.checked_sub(app.result_base_seq);
+        if let Some(idx) = result_idx
+            && let Some(result) = app.results.get(idx)
+        {
+            let (unit, precision) = (app.config.unit, app.config.precision);
+            let rtt_str = result
+                .rtt_ms_f64()
+                .map(|ms| unit.format(ms, precision))
+                .unwrap_or_else(|| result.loss_label());
+            let jitter_str = unit.format_jitter(result.jitter_ms_f64(), precision);
+            let smoothed_str = unit.format_jitter(result.smoothed_jitter_ms_f64(), precision);
+            // With the UTC offset, so it can be matched against logs from other machines
+            let time_str = format_time(result.timestamp, "%H:%M:%S%.3f %:z", app.display_utc);
+
+            let context = app.sample_context(idx);
+            let note = app.note_at(popup.result_seq);
+
+            let popup_width = 30u16;
+            // Extra lines for the comparison to earlier replies and for a note pinned to the sample
+            let popup_height = 7u16 + u16::from(context.is_some()) + u16::from(note.is_some());
+
+            // Position popup near click but within bounds
+            let popup_x = popup
+                .screen_x
+                .saturating_sub(popup_width / 2)
+                .min(size.width.saturating_sub(popup_width));
+            let popup_y = if popup.screen_y > popup_height + 1 {
+                popup.screen_y - popup_height - 1
+            } else {
+                popup.screen_y + 1
+            }
+            .min(size.height.saturating_sub(popup_height));
+
+            let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+            frame.render_widget(Clear, popup_area);
+
+            let popup_block = Block::default()
+                .title(" Ping Info ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .style(Style::default().bg(Color::Rgb(30, 30, 40)));
+
+            let mut popup_text = vec![
+                Line::from(vec![
+                    Span::styled("Time:   ", Style::default().fg(Color::Gray)),
+                    Span::styled(&time_str, Style::default().fg(Color::White)),
+                ]),
+                Line::from(vec![
+                    Span::styled("RTT:    ", Style::default().fg(Color::Gray)),
+                    Span::styled(
+                        &rtt_str,
+                        Style::default()
+                            .fg(Color::White)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                ]),
+                Line::from(vec![
+                    Span::styled("Jitter: ", Style::default().fg(Color::Gray)),
+                    Span::styled(&jitter_str, Style::default().fg(Color::Yellow)),
+                ]),
+                Line::from(vec![
+                    Span::styled("Smooth: ", Style::default().fg(Color::Gray)),
+                    Span::styled(&smoothed_str, Style::default().fg(Color::Yellow)),
+                ]),
+                Line::from(vec![
+                    Span::styled("Seq:    ", Style::default().fg(Color::Gray)),
+                    Span::styled(format!("{}", result.seq), Style::default().fg(Color::Cyan)),
+                ]),
+            ];
+            // e.g. "vs avg: +40.20ms new max"
+            if let Some(context) = context {
+                let sign = if context.vs_avg_ms < 0.0 { '-' } else { '+' };
+                let mut line = vec![
+                    Span::styled("vs avg: ", Style::default().fg(Color::Gray)),
+                    Span::styled(
+                        format!(
+                            "{}{}",
+                            sign,
+                            unit.format(context.vs_avg_ms.abs(), precision)
                         ),
-                    ])
-                } else if app.inline_edit_selected && input_focused {
-                    // Selected text (select-all state)
-                    Line::from(vec![Span::styled(
-                        app.inline_edit_buffer.clone(),
-                        selected_text_style,
-                    )])
-                } else if app.inline_edit_input_active && input_focused {
-                    // Text input mode - show cursor
-                    let before: String = app
-                        .inline_edit_buffer
-                        .chars()
-                        .take(app.inline_edit_cursor)
-                        .collect();
-                    let after: String = app
-                        .inline_edit_buffer
-                        .chars()
-                        .skip(app.inline_edit_cursor)
-                        .collect();
-                    Line::from(vec![
-                        Span::styled(before, input_style),
-                        Span::styled("▏", Style::default().fg(Color::White)),
-                        Span::styled(after, input_style),
-                    ])
-                } else {
-                    // Navigation mode or unfocused - show value with appropriate style
-                    Line::from(vec![Span::styled(
-                        app.inline_edit_buffer.clone(),
-                        if input_focused {
-                            selected_style
+                        Style::default().fg(if sign == '+' {
+                            Color::LightRed
                         } else {
-                            value_style
-                        },
-                    )])
-                };
-
-                // Confirm button line
-                let button_line = Line::from(vec![Span::styled(
-                    " Confirm ",
-                    if app.inline_edit_confirm_focused {
-                        button_selected_style
+                            Color::LightGreen
+                        }),
+                    ),
+                ];
+                if context.new_max {
+                    line.push(Span::styled(" new max", Style::default().fg(Color::Red)));
+                } else if context.new_min {
+                    line.push(Span::styled(" new min", Style::default().fg(Color::Green)));
+                }
+                popup_text.insert(2, Line::from(line));
+            }
+            if let Some(note) = note {
+                popup_text.push(Line::from(vec![
+                    Span::styled("Note:   ", Style::default().fg(Color::Gray)),
+                    Span::styled(&note.text, Style::default().fg(Color::LightGreen)),
+                ]));
+            }
+
+            let popup_para = Paragraph::new(popup_text).block(popup_block);
+            frame.render_widget(popup_para, popup_area);
+        }
+    }
+
+    // Render settings menu if open (the scheme comparison takes its place)
+    if app.settings_open && !app.scheme_compare {
+        let settings_menu = SettingsMenu::new(
+            app.settings_field,
+            app.settings_target.clone(),
+            app.settings_interval,
+            app.settings_timeout,
+            app.settings_scale,
+            app.settings_colors,
+            app.settings_unit,
+            app.settings_aggregate_by,
+            app.config.aggregate > 1,
+            app.settings_hide_cursor,
+            app.settings_hide_indicator,
+            app.settings_top_down,
+            app.settings_confirm_quit,
+            app.settings_buffer_mb,
+            app.settings_input_active,
+            app.settings_input.clone(),
+        );
+        frame.render_widget(settings_menu, size);
+    }
+
+    // Render inline edit popup if active
+    if let Some(field) = app.inline_edit {
+        let (popup_x, popup_y) = app.inline_edit_pos;
+
+        let title = match field {
+            HeaderEditField::Target => " Target ",
+            HeaderEditField::Mode => " Mode ",
+            HeaderEditField::Interval => " Interval (e.g. 500ms, 2s) ",
+            HeaderEditField::Scale => " Scale (ms) ",
+            HeaderEditField::Colors => " Color Scheme ",
+            HeaderEditField::Time => " Go to time (HH:MM[:SS]) ",
+            HeaderEditField::Note => " Note ",
+        };
+
+        let error_width = app
+            .inline_edit_input
+            .error
+            .as_ref()
+            .map_or(0, |e| e.len() as u16 + 2);
+        let popup_width = 30u16
+            .max(app.inline_edit_input.text.len() as u16 + 6)
+            .max(error_width);
+        // Confirm button, plus a line for the validation error if any
+        let popup_height = if app.inline_edit_input.error.is_some() {
+            5u16
+        } else {
+            4u16
+        };
+
+        // Position below the clicked item
+        let px = popup_x
+            .saturating_sub(1)
+            .min(size.width.saturating_sub(popup_width));
+        let py = (popup_y + 1).min(size.height.saturating_sub(popup_height));
+
+        let popup_area = Rect::new(px, py, popup_width, popup_height);
+        frame.render_widget(Clear, popup_area);
+
+        let popup_block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .style(Style::default().bg(Color::Rgb(30, 30, 40)));
+
+        // Styles matching settings menu
+        let input_style = Style::default().fg(Color::White).bg(Color::Rgb(60, 60, 80));
+        let selected_text_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Rgb(150, 180, 255));
+        let value_style = Style::default().fg(Color::Cyan);
+        let selected_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let button_style = Style::default().fg(Color::White).bg(Color::Rgb(60, 60, 80));
+        let button_selected_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Rgb(100, 200, 100));
+
+        let input_focused = !app.inline_edit_confirm_focused;
+        // Invalid input is shown in red and Confirm is greyed out until it's fixed
+        let invalid = app.inline_edit_input.error.is_some();
+        let (input_style, selected_style, value_style) = if invalid {
+            let red = Style::default().fg(Color::Red);
+            (input_style.fg(Color::Red), red, red)
+        } else {
+            (input_style, selected_style, value_style)
+        };
+
+        // Render content based on field type
+        let input_line = if field == HeaderEditField::Colors {
+            // Colors is an enum selector - show with arrows
+            Line::from(vec![
+                Span::styled(
+                    "◄ ",
+                    if input_focused {
+                        Style::default().fg(Color::White)
                     } else {
-                        button_style
+                        Style::default().fg(Color::DarkGray)
                     },
-                )]);
-
-                let inner = popup_block.inner(popup_area);
-                frame.render_widget(popup_block, popup_area);
-                let para = Paragraph::new(vec![input_line, button_line]);
-                frame.render_widget(para, inner);
-
-                // Store confirm button area for click detection
-                app.inline_edit_confirm_area = Some((
-                    px + 1, // after border
-                    py + 2, // second line inside popup
-                    9,      // " Confirm " width
-                ));
+                ),
+                Span::styled(
+                    app.inline_edit_input.text.clone(),
+                    if input_focused {
+                        selected_style
+                    } else {
+                        value_style
+                    },
+                ),
+                Span::styled(
+                    " ►",
+                    if input_focused {
+                        Style::default().fg(Color::White)
+                    } else {
+                        Style::default().fg(Color::DarkGray)
+                    },
+                ),
+            ])
+        } else if app.inline_edit_input_active && input_focused {
+            // Text input mode - show cursor (or the selected text)
+            Line::from(
+                app.inline_edit_input
+                    .spans(input_style, selected_text_style),
+            )
+        } else {
+            // Navigation mode or unfocused - show value with appropriate style
+            Line::from(vec![Span::styled(
+                app.inline_edit_input.text.clone(),
+                if input_focused {
+                    selected_style
+                } else {
+                    value_style
+                },
+            )])
+        };
+
+        // Confirm button line
+        let button_line = Line::from(vec![Span::styled(
+            " Confirm ",
+            if invalid {
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .bg(Color::Rgb(45, 45, 55))
+            } else if app.inline_edit_confirm_focused {
+                button_selected_style
             } else {
-                app.inline_edit_confirm_area = None;
-            }
+                button_style
+            },
+        )]);
+
+        let inner = popup_block.inner(popup_area);
+        frame.render_widget(popup_block, popup_area);
+        let mut lines = vec![input_line, button_line];
+        if let Some(error) = &app.inline_edit_input.error {
+            lines.push(Line::from(Span::styled(
+                error.clone(),
+                Style::default().fg(Color::Red),
+            )));
+        }
+        let para = Paragraph::new(lines);
+        frame.render_widget(para, inner);
+
+        // Store confirm button area for click detection
+        app.inline_edit_confirm_area = Some((
+            px + 1, // after border
+            py + 2, // second line inside popup
+            9,      // " Confirm " width
+        ));
+    } else {
+        app.inline_edit_confirm_area = None;
+    }
 
-            // Render quit confirmation dialog if active
-            if app.quit_confirm {
-                let popup_width = 32u16;
-                let popup_height = 5u16;
-                let popup_x = size.width.saturating_sub(popup_width) / 2;
-                let popup_y = size.height.saturating_sub(popup_height) / 2;
-
-                let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
-                frame.render_widget(Clear, popup_area);
-
-                let popup_block = Block::default()
-                    .title(" Quit? ")
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Yellow))
-                    .style(Style::default().bg(Color::Rgb(40, 40, 50)));
-
-                // Styles matching settings menu
-                let button_style = Style::default().fg(Color::White).bg(Color::Rgb(60, 60, 80));
-                let yes_selected_style = Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::Rgb(200, 100, 100));
-                let no_selected_style = Style::default()
+    // Render the quick-switch list of recent targets if open
+    if let Some(selected) = app.recent_targets_selected {
+        draw_recent_targets(frame, app, selected);
+    }
+
+    // Render quit confirmation dialog if active
+    if app.quit_confirm {
+        let (yes, no) = draw_confirm_dialog(
+            frame,
+            " Quit? ",
+            "Are you sure you want to quit?",
+            app.quit_confirm_no_focused,
+        );
+        app.quit_confirm_yes_area = Some(yes);
+        app.quit_confirm_no_area = Some(no);
+    } else {
+        app.quit_confirm_yes_area = None;
+        app.quit_confirm_no_area = None;
+    }
+
+    // Render target change confirmation dialog if active
+    if let Some(target) = &app.target_confirm {
+        let (yes, no) = draw_confirm_dialog(
+            frame,
+            " Change target? ",
+            &format!("Clear history and ping {}?", target),
+            app.target_confirm_no_focused,
+        );
+        app.target_confirm_yes_area = Some(yes);
+        app.target_confirm_no_area = Some(no);
+    } else {
+        app.target_confirm_yes_area = None;
+        app.target_confirm_no_area = None;
+    }
+}
+
+/// Render the centered quick-switch list of recent targets, numbered, with the current
+/// target marked and the selected entry highlighted
+fn draw_recent_targets(frame: &mut Frame, app: &App, selected: usize) {
+    let size = frame.area();
+    let hint = " ↑/↓ select │ Enter/1-9 switch │ Esc close ";
+    let entries_width = app
+        .recent_targets
+        .iter()
+        .map(|host| host.chars().count() + 6)
+        .max()
+        .unwrap_or(0);
+    let popup_width = (entries_width.max(hint.chars().count()) as u16 + 2).min(size.width);
+    let popup_height = (app.recent_targets.len() as u16 + 3).min(size.height);
+    let popup_area = Rect::new(
+        size.width.saturating_sub(popup_width) / 2,
+        size.height.saturating_sub(popup_height) / 2,
+        popup_width,
+        popup_height,
+    );
+    frame.render_widget(Clear, popup_area);
+
+    let current = app.config.host.as_deref();
+    let mut lines: Vec<Line> = app
+        .recent_targets
+        .iter()
+        .enumerate()
+        .map(|(idx, host)| {
+            let marker = if Some(host.as_str()) == current {
+                "•"
+            } else {
+                " "
+            };
+            let style = if idx == selected {
+                Style::default()
                     .fg(Color::Black)
-                    .bg(Color::Rgb(100, 200, 100));
+                    .bg(Color::Rgb(150, 180, 255))
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let number = if idx < 9 {
+                format!("{}", idx + 1)
+            } else {
+                " ".to_string()
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!(" {} ", number),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(format!("{}{} ", marker, host), style),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(Span::styled(
+        hint,
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let popup_block = Block::default()
+        .title(" Recent targets ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Rgb(30, 30, 40)));
+    frame.render_widget(Paragraph::new(lines).block(popup_block), popup_area);
+}
+
+/// Render a centered Yes/No dialog, returning the Yes and No button areas (x, y, width)
+fn draw_confirm_dialog(
+    frame: &mut Frame,
+    title: &str,
+    message: &str,
+    no_focused: bool,
+) -> ((u16, u16, u16), (u16, u16, u16)) {
+    let size = frame.area();
+    let popup_width = (message.chars().count() as u16 + 2).max(32).min(size.width);
+    let popup_height = 5u16;
+    let popup_x = size.width.saturating_sub(popup_width) / 2;
+    let popup_y = size.height.saturating_sub(popup_height) / 2;
+
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+    frame.render_widget(Clear, popup_area);
+
+    let popup_block = Block::default()
+        .title(title.to_string())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .style(Style::default().bg(Color::Rgb(40, 40, 50)));
+
+    // Styles matching settings menu
+    let button_style = Style::default().fg(Color::White).bg(Color::Rgb(60, 60, 80));
+    let yes_selected_style = Style::default()
+        .fg(Color::Black)
+        .bg(Color::Rgb(200, 100, 100));
+    let no_selected_style = Style::default()
+        .fg(Color::Black)
+        .bg(Color::Rgb(100, 200, 100));
+
+    let popup_text = vec![
+        Line::from(Span::styled(
+            message.to_string(),
+            Style::default().fg(Color::White),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                " Yes ",
+                if !no_focused {
+                    yes_selected_style
+                } else {
+                    button_style
+                },
+            ),
+            Span::raw("  "),
+            Span::styled(
+                " No ",
+                if no_focused {
+                    no_selected_style
+                } else {
+                    button_style
+                },
+            ),
+        ]),
+    ];
+
+    let para = Paragraph::new(popup_text)
+        .block(popup_block)
+        .alignment(ratatui::layout::Alignment::Center);
+    frame.render_widget(para, popup_area);
+
+    // Buttons are centered: " Yes " (5) + "  " (2) + " No " (4) = 11
+    let buttons_start = popup_x + (popup_width - 11) / 2;
+    (
+        (buttons_start, popup_y + 3, 5),
+        (buttons_start + 7, popup_y + 3, 4),
+    )
+}
+
+/// Render the stats footer, recording its area
+fn draw_footer(frame: &mut Frame, app: &mut App, area: Rect) {
+    let recent_rtts = app.recent_rtts_slice();
+    let windows = app.window_summaries();
+    let split = app.split_comparison();
+    let comparison = app.run_comparison();
+    let footer = Footer::new(&app.stats, &recent_rtts, &app.color_scale, area.width)
+        .windows(&windows)
+        .smoothing(app.spark_alpha)
+        .spark_scale(app.config.spark_scale)
+        .clip_outliers(app.config.clip_outliers)
+        .loss_thresholds(app.config.loss_warn, app.config.loss_crit)
+        .event(app.footer_event.as_deref())
+        .comparison(comparison)
+        .split(split)
+        .quality(app.quality())
+        .trend(app.trend())
+        .unit(app.config.unit)
+        .precision(app.config.precision)
+        .sections(&app.config.footer_show, &app.config.footer_hide)
+        .spikes(app.spike_count)
+        .last_loss_reason(app.results.back().and_then(|result| result.loss_reason))
+        .smoothed_rtt(app.smoothed_rtt)
+        .in_flight(
+            (app.config.mode == Mode::UdpClient && !app.config.demo).then(|| app.in_flight.get()),
+            app.config.timeout / app.config.interval.max(1),
+        )
+        .sched_jitter(app.sched_jitter().filter(|_| app.config.sched_jitter))
+        .effective_rtt(app.effective_rtt())
+        .best(
+            app.config
+                .best_window
+                .map(|window_ms| (window_ms, app.best_rtt(window_ms))),
+        )
+        .send_rate(
+            app.send_rate().filter(|_| !app.probe_switch.is_stopped()),
+            1000.0 / app.config.interval.max(1) as f64,
+        );
+    frame.render_widget(footer, area);
+    app.footer_area = Some((area.x, area.y, area.width, area.height));
+}
 
-                let popup_text = vec![
-                    Line::from(Span::styled(
-                        "Are you sure you want to quit?",
-                        Style::default().fg(Color::White),
+/// The graph in the previous (top) and the selected (bottom) color scheme from settings,
+/// over the same samples, each under a title line
+fn draw_scheme_compare(frame: &mut Frame, app: &mut App, area: Rect, total_rows: usize) {
+    let halves = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+        .split(area);
+    let shown = app.color_scale.clone();
+    let schemes = [
+        (
+            "Previous",
+            app.settings_previous_colors,
+            "Esc: keep previous",
+        ),
+        (
+            "Selected",
+            app.settings_colors,
+            "←/→: change │ Enter: keep selected",
+        ),
+    ];
+    for (half, (label, scheme, hint)) in halves.iter().zip(schemes) {
+        let title = Line::from(vec![
+            Span::styled(format!(" {}: ", label), Style::default().fg(Color::Gray)),
+            Span::styled(scheme.to_string(), Style::default().fg(Color::Cyan)),
+            Span::styled(format!("  {}", hint), Style::default().fg(Color::DarkGray)),
+        ]);
+        frame.render_widget(Paragraph::new(title), Rect { height: 1, ..*half });
+        app.color_scale = ColorScale::new(app.settings_scale, scheme);
+        let graph_area = Rect {
+            y: half.y + 1,
+            height: half.height.saturating_sub(1),
+            ..*half
+        };
+        draw_graph(frame, app, graph_area, total_rows);
+    }
+    app.color_scale = shown;
+    // Nothing to click on while comparing
+    app.graph_area = None;
+}
+
+/// Render the ping graph and the selection summary, recording the graph area
+fn draw_graph(frame: &mut Frame, app: &mut App, graph_area: Rect, total_rows: usize) {
+    let graph = Graph::new(
+        &app.results,
+        &app.color_scale,
+        app.view_end_row,
+        total_rows,
+        app.result_base_seq,
+        app.paused,
+        app.config.hide_cursor,
+        app.highlight_rtt_range,
+    )
+    .baseline(app.baseline_ms)
+    .unit(app.config.unit)
+    .precision(app.config.precision)
+    .numeric(app.numeric_view)
+    .dots(app.dots_view)
+    .aggregate(app.config.aggregate, app.config.aggregate_by)
+    .cell_width(app.config.cell_width)
+    .loss_gaps(app.loss_gaps)
+    .floor(app.floor_visible)
+    .bands(app.bands_visible)
+    .grid(app.grid_visible.then_some(app.config.grid_every))
+    .fade(app.config.fade)
+    .background(app.config.graph_bg)
+    .cursor_color(app.config.cursor_color)
+    .selection(app.selection)
+    .events(&app.events)
+    .sending_stopped(app.probe_switch.is_stopped())
+    .coalesced(app.frame_coalesced)
+    .top_down(app.config.top_down)
+    .sweep(app.config.sweep)
+    .hide_indicator(app.config.hide_indicator)
+    .cursor_lit(app.cursor_lit)
+    .row_len(app.config.samples_per_row)
+    .cache(&mut app.graph_cache);
+    frame.render_widget(graph, graph_area);
+
+    // Summary of the drag-selected range, pinned to the top-left of the graph
+    if let (Some((first, last)), Some(stats)) = (app.selection_seqs(), app.selection_stats()) {
+        let rtt_str = stats.rtt_summary(app.config.unit, app.config.precision);
+        let lines = vec![
+            Line::from(vec![
+                Span::styled("Seq:  ", Style::default().fg(Color::Gray)),
+                Span::styled(
+                    format!("{}-{}", first, last),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("RTT:  ", Style::default().fg(Color::Gray)),
+                Span::styled(
+                    rtt_str,
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("Lost: ", Style::default().fg(Color::Gray)),
+                Span::styled(
+                    format!("{} ({:.1}%)", stats.total_lost, stats.loss_percent()),
+                    Style::default().fg(loss_color(
+                        stats.loss_percent(),
+                        app.config.loss_warn,
+                        app.config.loss_crit,
                     )),
-                    Line::from(""),
-                    Line::from(vec![
-                        Span::styled(
-                            " Yes ",
-                            if !app.quit_confirm_no_focused {
-                                yes_selected_style
-                            } else {
-                                button_style
-                            },
-                        ),
-                        Span::raw("  "),
-                        Span::styled(
-                            " No ",
-                            if app.quit_confirm_no_focused {
-                                no_selected_style
-                            } else {
-                                button_style
-                            },
-                        ),
-                    ]),
-                ];
+                ),
+            ]),
+        ];
+
+        let height = 5u16.min(graph_area.height.saturating_sub(1));
+        // Top-down the newest rows are at the top, so the summary moves to the bottom
+        let y = if app.config.top_down {
+            graph_area
+                .bottom()
+                .saturating_sub(height + 1)
+                .max(graph_area.y)
+        } else {
+            graph_area.y + 1
+        };
+        let popup_area = Rect::new(
+            graph_area.x + 1,
+            y,
+            36u16.min(graph_area.width.saturating_sub(2)),
+            height,
+        );
+        frame.render_widget(Clear, popup_area);
+        let block = Block::default()
+            .title(format!(" Selection: {} samples ", stats.total_sent))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .style(Style::default().bg(Color::Rgb(30, 30, 40)));
+        frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+    }
 
-                let para = Paragraph::new(popup_text)
-                    .block(popup_block)
-                    .alignment(ratatui::layout::Alignment::Center);
-                frame.render_widget(para, popup_area);
-
-                // Store button areas for click detection
-                // Buttons are centered: " Yes " (5) + "  " (2) + " No " (4) = 11
-                // Center offset: (popup_width - 2 - 11) / 2 = (30 - 11) / 2 = 9 (approx)
-                let buttons_start = popup_x + (popup_width - 11) / 2;
-                app.quit_confirm_yes_area = Some((buttons_start, popup_y + 3, 5));
-                app.quit_confirm_no_area = Some((buttons_start + 7, popup_y + 3, 4));
-            } else {
-                app.quit_confirm_yes_area = None;
-                app.quit_confirm_no_area = None;
+    // One-time notice across the top row of the graph (the bottom row top-down)
+    if let Some(banner) = &app.banner {
+        let y = if app.config.top_down {
+            graph_area.bottom().saturating_sub(1)
+        } else {
+            graph_area.y
+        };
+        let banner_area = Rect::new(graph_area.x, y, graph_area.width, 1);
+        frame.render_widget(Clear, banner_area);
+        frame.render_widget(
+            Paragraph::new(format!(" {} ", banner))
+                .style(Style::default().fg(Color::Black).bg(Color::Yellow)),
+            banner_area,
+        );
+    }
+
+    // Store graph area for mouse calculations
+    app.graph_area = Some((
+        graph_area.x,
+        graph_area.y,
+        graph_area.width,
+        graph_area.height,
+    ));
+}
+
+/// Render the notice shown over the blank graph until the first result arrives, so a
+/// slow first reply isn't mistaken for a hang
+fn draw_startup_notice(frame: &mut Frame, app: &App, graph_area: Rect, resolved_ip: &str) {
+    let target = app.config.host.as_deref().unwrap_or_default();
+    let mut pinging = vec![
+        Span::raw("Pinging "),
+        Span::styled(target, Style::default().fg(Color::Cyan)),
+    ];
+    if target != resolved_ip {
+        pinging.push(Span::styled(
+            format!(" ({})", resolved_ip),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    let lines = vec![
+        Line::from(pinging),
+        Line::from(Span::styled(
+            "waiting for first reply…",
+            Style::default().fg(Color::Yellow),
+        )),
+    ];
+    let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4).min(graph_area.width);
+    let height = 4u16.min(graph_area.height);
+    let popup_area = Rect::new(
+        graph_area.x + (graph_area.width - width) / 2,
+        graph_area.y + (graph_area.height - height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(ratatui::layout::Alignment::Center)
+            .block(block),
+        popup_area,
+    );
+}
+
+/// Set a flag when the process is asked to terminate (SIGTERM or SIGHUP, e.g. from a
+/// service manager or a closed tmux session), so the UI can quit and clean up
+fn spawn_termination_listener() -> Arc<AtomicBool> {
+    let terminate = Arc::new(AtomicBool::new(false));
+
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let flag = terminate.clone();
+        tokio::spawn(async move {
+            let (Ok(mut sigterm), Ok(mut sighup)) = (
+                signal(SignalKind::terminate()),
+                signal(SignalKind::hangup()),
+            ) else {
+                return;
+            };
+            tokio::select! {
+                _ = sigterm.recv() => {}
+                _ = sighup.recv() => {}
             }
-        })?;
+            flag.store(true, Ordering::Relaxed);
+        });
+    }
+
+    terminate
+}
+
+async fn run_app(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    rx: &mut mpsc::UnboundedReceiver<PingResult>,
+    targets_rx: &mut mpsc::UnboundedReceiver<(usize, PingResult)>,
+    resolved_ip: &str,
+    terminate: &AtomicBool,
+) -> Result<()> {
+    // Last title written, so the terminal is only updated when it changes
+    let mut window_title = String::new();
+    // Minimum time between redraws, from --fps
+    let frame_interval = app.config.frame_interval();
+    // Draw the first frame right away, then only after input or new results
+    let mut needs_redraw = true;
+    // Input is drawn immediately so a low frame rate doesn't add input latency
+    let mut input_pending = false;
+    let mut last_draw = Instant::now()
+        .checked_sub(frame_interval)
+        .unwrap_or_else(Instant::now);
+
+    loop {
+        if app.config.set_title {
+            let title = app.window_title();
+            if title != window_title {
+                execute!(terminal.backend_mut(), SetTitle(&title))?;
+                window_title = title;
+            }
+        }
 
-        // Handle events with timeout to allow ping updates
-        if event::poll(Duration::from_millis(50))? {
+        // Redraw only when something changed, at most once per frame interval
+        // (or right away after input)
+        if needs_redraw && (input_pending || last_draw.elapsed() >= frame_interval) {
+            app.begin_frame();
+            let frame = terminal.draw(|frame| draw_ui(frame, app, resolved_ip))?;
+            needs_redraw = false;
+            if app.snapshot_requested {
+                app.snapshot_requested = false;
+                app.save_snapshot(frame.buffer);
+                // Show the confirmation
+                needs_redraw = true;
+            }
+            input_pending = false;
+            last_draw = Instant::now();
+        }
+
+        // Handle events, waking up at least once per frame to pick up ping results
+        let poll_timeout = if needs_redraw {
+            frame_interval.saturating_sub(last_draw.elapsed())
+        } else {
+            frame_interval
+        };
+        if event::poll(poll_timeout)? {
+            needs_redraw = true;
+            input_pending = true;
             match event::read()? {
                 Event::Key(key) if key.kind == KeyEventKind::Press => {
                     // Handle quit confirmation dialog first
@@ -646,6 +1706,56 @@ async fn run_app(
                             _ => {}
                         }
                     }
+                    // Then the target change confirmation
+                    else if app.target_confirm.is_some() {
+                        match key.code {
+                            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                                app.confirm_target_change();
+                            }
+                            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                                app.cancel_target_change();
+                            }
+                            KeyCode::Enter => {
+                                // Enter confirms the focused button
+                                if app.target_confirm_no_focused {
+                                    app.cancel_target_change();
+                                } else {
+                                    app.confirm_target_change();
+                                }
+                            }
+                            KeyCode::Left | KeyCode::Right | KeyCode::Tab => {
+                                // Toggle between Yes and No
+                                app.target_confirm_no_focused = !app.target_confirm_no_focused;
+                            }
+                            _ => {}
+                        }
+                    }
+                    // Then the quick-switch list of recent targets
+                    else if app.recent_targets_selected.is_some() {
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Char('o') | KeyCode::Char('O') => {
+                                app.close_recent_targets();
+                            }
+                            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => {
+                                app.recent_targets_move(-1);
+                            }
+                            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => {
+                                app.recent_targets_move(1);
+                            }
+                            KeyCode::Enter => {
+                                app.pick_recent_target();
+                            }
+                            KeyCode::Char(c @ '1'..='9') => {
+                                // Number keys pick an entry directly
+                                let idx = c as usize - '1' as usize;
+                                if idx < app.recent_targets.len() {
+                                    app.recent_targets_selected = Some(idx);
+                                    app.pick_recent_target();
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
                     // Handle inline edit input
                     else if let Some(edit_field) = app.inline_edit {
                         if app.inline_edit_confirm_focused {
@@ -720,13 +1830,9 @@ async fn run_app(
                                 KeyCode::Char(c) => {
                                     // Typing immediately replaces value (for text fields)
                                     if edit_field != HeaderEditField::Colors {
-                                        // Clear and start fresh with typed char
-                                        app.inline_edit_buffer.clear();
-                                        app.inline_edit_cursor = 0;
-                                        app.inline_edit_selected = false;
+                                        // The whole value is selected, so it's replaced
+                                        app.inline_edit_activate_input();
                                         app.inline_edit_char(c);
-                                        // Activate input mode so subsequent chars are added
-                                        app.inline_edit_input_active = true;
                                     }
                                 }
                                 _ => {}
@@ -734,13 +1840,21 @@ async fn run_app(
                         }
                     } else if app.settings_open {
                         // Handle settings menu input
-                        if app.settings_input_active {
+                        if app.scheme_compare {
+                            match key.code {
+                                KeyCode::Left => app.settings_decrease(),
+                                KeyCode::Right => app.settings_increase(),
+                                KeyCode::Enter => app.close_scheme_compare(true),
+                                KeyCode::Esc => app.close_scheme_compare(false),
+                                _ => {}
+                            }
+                        } else if app.settings_input_active {
                             // Text input mode
                             match key.code {
                                 KeyCode::Esc => {
                                     // Cancel text input, restore previous value
                                     app.settings_input_active = false;
-                                    app.settings_input_buffer.clear();
+                                    app.settings_input = TextInput::default();
                                 }
                                 KeyCode::Enter => {
                                     // Confirm the text input
@@ -770,14 +1884,15 @@ async fn run_app(
                                     // Handle based on current field
                                     if app.settings_field.is_text_input() {
                                         app.settings_start_input();
-                                    } else if app.settings_field == ui::app::SettingsField::Confirm
-                                    {
-                                        app.apply_settings();
-                                        app.settings_open = false;
-                                    } else if app.settings_field == ui::app::SettingsField::Cancel {
+                                    } else if app.settings_field == SettingsField::Confirm {
+                                        if app.apply_settings() {
+                                            app.settings_open = false;
+                                        }
+                                    } else if app.settings_field == SettingsField::Cancel {
                                         app.cancel_settings();
                                     } else {
-                                        // ColorScheme or HideCursor - just cycle with enter
+                                        // ColorScheme, Unit, HideCursor, HideIndicator or ConfirmQuit - just
+                                        // cycle with enter
                                         app.settings_increase();
                                     }
                                 }
@@ -790,7 +1905,7 @@ async fn run_app(
                                 KeyCode::Left => {
                                     // On buttons, left goes to Confirm
                                     if app.settings_field.is_button() {
-                                        app.settings_field = ui::app::SettingsField::Confirm;
+                                        app.settings_field = SettingsField::Confirm;
                                     } else {
                                         app.settings_decrease();
                                     }
@@ -798,11 +1913,16 @@ async fn run_app(
                                 KeyCode::Right => {
                                     // On buttons, right goes to Cancel
                                     if app.settings_field.is_button() {
-                                        app.settings_field = ui::app::SettingsField::Cancel;
+                                        app.settings_field = SettingsField::Cancel;
                                     } else {
                                         app.settings_increase();
                                     }
                                 }
+                                KeyCode::Char('p') | KeyCode::Char('P')
+                                    if app.settings_field == SettingsField::ColorScheme =>
+                                {
+                                    app.open_scheme_compare();
+                                }
                                 KeyCode::Char(c) => {
                                     // Start typing immediately on text fields
                                     if app.settings_field.is_text_input() {
@@ -814,6 +1934,12 @@ async fn run_app(
                             }
                         }
                     } else {
+                        // Copy popup details to clipboard (keeps popup open)
+                        if app.popup.is_some() && matches!(key.code, KeyCode::Char('y')) {
+                            app.copy_popup_to_clipboard();
+                            continue;
+                        }
+
                         // Close popup on any key (except for header navigation)
                         if key.code != KeyCode::Tab
                             && key.code != KeyCode::BackTab
@@ -834,8 +1960,13 @@ async fn run_app(
                                 }
                             }
                             KeyCode::Esc => {
-                                // Esc deselects header, or shows quit confirm
-                                if app.header_selected.is_some() {
+                                // Esc dismisses the banner, clears a selection, deselects
+                                // header, or shows quit confirm
+                                if app.banner.is_some() {
+                                    app.banner = None;
+                                } else if app.selection.is_some() {
+                                    app.clear_selection();
+                                } else if app.header_selected.is_some() {
                                     app.header_deselect();
                                 } else if app.view_end_row.is_some() {
                                     app.jump_to_live();
@@ -864,17 +1995,80 @@ async fn run_app(
                             KeyCode::Char(' ') => {
                                 app.toggle_pause();
                             }
+                            KeyCode::Char('p') | KeyCode::Char('P') => {
+                                app.toggle_probing();
+                            }
+                            KeyCode::Char('b') | KeyCode::Char('B') => {
+                                app.toggle_baseline();
+                            }
+                            KeyCode::Char('n') | KeyCode::Char('N') => {
+                                app.toggle_numeric_view();
+                            }
+                            KeyCode::Char('d') | KeyCode::Char('D') => {
+                                app.toggle_dots_view();
+                            }
+                            KeyCode::Char('l') | KeyCode::Char('L') => {
+                                app.toggle_loss_view();
+                            }
+                            KeyCode::Char('c') | KeyCode::Char('C') => {
+                                app.toggle_line_view();
+                            }
+                            KeyCode::Char('i') | KeyCode::Char('I') => {
+                                app.toggle_heatmap_view();
+                            }
+                            KeyCode::Char('t') | KeyCode::Char('T') => {
+                                app.open_time_jump();
+                            }
+                            KeyCode::Char('a') | KeyCode::Char('A') => {
+                                app.open_note();
+                            }
+                            KeyCode::Char('x') | KeyCode::Char('X') => {
+                                app.toggle_loss_gaps();
+                            }
+                            KeyCode::Char('m') | KeyCode::Char('M') => {
+                                app.toggle_split();
+                            }
+                            KeyCode::Char('w') | KeyCode::Char('W') => {
+                                app.snapshot_requested = true;
+                            }
+                            KeyCode::Char('f') | KeyCode::Char('F') => {
+                                app.toggle_floor();
+                            }
+                            KeyCode::Char('u') | KeyCode::Char('U') => {
+                                app.toggle_utc();
+                            }
+                            KeyCode::Char('o') | KeyCode::Char('O') => {
+                                app.open_recent_targets();
+                            }
+                            KeyCode::Char('r') | KeyCode::Char('R') => {
+                                app.toggle_bands();
+                            }
+                            KeyCode::Char('v') | KeyCode::Char('V') => {
+                                app.toggle_grid();
+                            }
+                            KeyCode::Char('e') | KeyCode::Char('E') => {
+                                app.toggle_legend();
+                            }
+                            KeyCode::Char('z') | KeyCode::Char('Z') => {
+                                app.toggle_zen_mode();
+                            }
+                            KeyCode::Char('h') | KeyCode::Char('H') => {
+                                app.toggle_histogram();
+                            }
                             KeyCode::Up | KeyCode::PageUp => {
                                 let rows = if key.code == KeyCode::PageUp { 10 } else { 1 };
-                                app.scroll_up(rows);
+                                app.scroll_toward_top(rows);
                             }
                             KeyCode::Down | KeyCode::PageDown => {
                                 let rows = if key.code == KeyCode::PageDown { 10 } else { 1 };
-                                app.scroll_down(rows);
+                                app.scroll_toward_bottom(rows);
                             }
-                            KeyCode::Home => {
+                            KeyCode::Home | KeyCode::End | KeyCode::Char('G') => {
                                 app.jump_to_live();
                             }
+                            KeyCode::Char('g') => {
+                                app.jump_to_oldest();
+                            }
                             _ => {}
                         }
                     }
@@ -913,6 +2107,36 @@ async fn run_app(
                             }
                         }
                     }
+                    // Handle target change confirmation dialog mouse events
+                    else if app.target_confirm.is_some() {
+                        if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+                            if let Some((x, y, w)) = app.target_confirm_yes_area
+                                && mouse.row == y
+                                && mouse.column >= x
+                                && mouse.column < x + w
+                            {
+                                // If already focused, activate; otherwise just focus
+                                if !app.target_confirm_no_focused {
+                                    app.confirm_target_change();
+                                } else {
+                                    app.target_confirm_no_focused = false;
+                                }
+                                continue;
+                            }
+                            if let Some((x, y, w)) = app.target_confirm_no_area
+                                && mouse.row == y
+                                && mouse.column >= x
+                                && mouse.column < x + w
+                            {
+                                if app.target_confirm_no_focused {
+                                    app.cancel_target_change();
+                                } else {
+                                    app.target_confirm_no_focused = true;
+                                }
+                                continue;
+                            }
+                        }
+                    }
                     // Handle inline edit popup mouse events
                     else if app.inline_edit.is_some() {
                         match mouse.kind {
@@ -937,7 +2161,7 @@ async fn run_app(
                                 let (px, py) = app.inline_edit_pos;
                                 let size = terminal.size()?;
                                 let popup_width =
-                                    30u16.max(app.inline_edit_buffer.len() as u16 + 6);
+                                    30u16.max(app.inline_edit_input.text.len() as u16 + 6);
                                 let popup_height = 4u16;
                                 let popup_x = px
                                     .saturating_sub(1)
@@ -976,6 +2200,9 @@ async fn run_app(
                             }
                             _ => {}
                         }
+                    } else if app.settings_open && app.scheme_compare {
+                        // The settings menu is hidden while comparing color schemes, which
+                        // only takes keys
                     } else if app.settings_open {
                         // Handle mouse in settings menu
                         match mouse.kind {
@@ -1027,6 +2254,9 @@ async fn run_app(
                                                             my,
                                                         );
                                                     }
+                                                    HeaderField::Mode => {
+                                                        app.cycle_mode();
+                                                    }
                                                     HeaderField::Interval => {
                                                         app.start_inline_edit(
                                                             HeaderEditField::Interval,
@@ -1100,12 +2330,14 @@ async fn run_app(
                                 if !handled && let Some((gx, gy, gw, gh)) = app.graph_area {
                                     let mx = mouse.column;
                                     let my = mouse.row;
+                                    // A new click replaces any previous range selection
+                                    app.clear_selection();
 
                                     if mx >= gx && mx < gx + gw && my >= gy && my < gy + gh {
-                                        let screen_col = (mx - gx) as usize;
+                                        let screen_col = (mx - gx) as usize / app.cell_width();
                                         let screen_row = (my - gy) as usize;
 
-                                        let width = gw as usize;
+                                        let width = app.row_len(gw as usize);
                                         let total_rows = app.total_rows(width);
                                         let view_end = app.view_end_row.unwrap_or(total_rows);
 
@@ -1117,16 +2349,21 @@ async fn run_app(
                                             view_end,
                                             screen_row,
                                             screen_col,
+                                            app.config.top_down,
+                                            app.config.sweep && app.view_end_row.is_none(),
+                                            app.samples_per_cell(),
                                         ) {
-                                            // Freeze the view if we're in live mode
-                                            if app.view_end_row.is_none() {
+                                            // Freeze the view if we're in live mode (a sweep
+                                            // keeps its layout and carries on instead)
+                                            if app.view_end_row.is_none() && !app.config.sweep {
                                                 app.popup_was_live = true;
-                                                app.view_end_row = Some(total_rows);
+                                                app.freeze_view_at(total_rows, width);
                                             } else {
                                                 app.popup_was_live = false;
                                             }
                                             // Convert VecDeque index to stable sequence number
                                             let result_seq = app.result_base_seq + idx;
+                                            app.drag_start_seq = Some(result_seq);
                                             app.popup = Some(PingPopup {
                                                 result_seq,
                                                 screen_x: mx,
@@ -1168,11 +2405,11 @@ async fn run_app(
                                     let my = mouse.row;
 
                                     if mx >= gx && mx < gx + gw && my >= gy && my < gy + gh {
-                                        let screen_col = (mx - gx) as usize;
+                                        let screen_col = (mx - gx) as usize / app.cell_width();
                                         let screen_row = (my - gy) as usize;
 
                                         // Calculate which result was clicked/dragged over
-                                        let width = gw as usize;
+                                        let width = app.row_len(gw as usize);
                                         let total_rows = app.total_rows(width);
                                         let view_end = app.view_end_row.unwrap_or(total_rows);
 
@@ -1184,14 +2421,20 @@ async fn run_app(
                                             view_end,
                                             screen_row,
                                             screen_col,
+                                            app.config.top_down,
+                                            app.config.sweep && app.view_end_row.is_none(),
+                                            app.samples_per_cell(),
                                         ) {
                                             // Convert VecDeque index to stable sequence number
                                             let result_seq = app.result_base_seq + idx;
-                                            app.popup = Some(PingPopup {
-                                                result_seq,
-                                                screen_x: mx,
-                                                screen_y: my,
-                                            });
+                                            // Dragging away from the start selects a range
+                                            if !app.drag_select_to(result_seq) {
+                                                app.popup = Some(PingPopup {
+                                                    result_seq,
+                                                    screen_x: mx,
+                                                    screen_y: my,
+                                                });
+                                            }
                                         } else {
                                             app.popup = None;
                                         }
@@ -1204,17 +2447,21 @@ async fn run_app(
                                 // Hide tooltip and clear highlight when mouse button released
                                 app.popup = None;
                                 app.highlight_rtt_range = None;
+                                app.drag_start_seq = None;
                                 // Restore live mode if we were in live mode before the popup
-                                if app.popup_was_live {
-                                    app.view_end_row = None;
+                                // (a range selection keeps the view frozen until cleared)
+                                if app.popup_was_live && app.selection.is_none() {
+                                    app.follow_live_view();
                                     app.popup_was_live = false;
                                 }
                             }
                             MouseEventKind::ScrollUp => {
-                                app.scroll_up(3);
+                                let rows = app.wheel_rows(true, Instant::now());
+                                app.scroll_toward_top(rows);
                             }
                             MouseEventKind::ScrollDown => {
-                                app.scroll_down(3);
+                                let rows = app.wheel_rows(false, Instant::now());
+                                app.scroll_toward_bottom(rows);
                             }
                             _ => {}
                         }
@@ -1224,12 +2471,43 @@ async fn run_app(
             }
         }
 
-        // Process any pending ping results (discard if paused)
+        // Process all pending ping results, coalesced into the next frame (discard if paused)
+        let mut received = false;
         while let Ok(result) = rx.try_recv() {
-            if !app.paused {
-                app.record_result(result);
+            needs_redraw = true;
+            received = true;
+            if let Some(idx) = app.primary_target_idx {
+                app.record_target_result(idx, &result);
             }
+            app.check_wait(&result);
             // When paused, results are discarded - pings continue but aren't recorded
+            if app.paused {
+                app.skip_result();
+            } else {
+                app.record_result(result);
+            }
+        }
+        if received {
+            app.publish_metrics(app.config.host.as_deref().unwrap_or(resolved_ip));
+        }
+
+        // Process results for the multi-target summary
+        while let Ok((idx, result)) = targets_rx.try_recv() {
+            needs_redraw = true;
+            app.record_target_result(idx, &result);
+        }
+
+        app.update_sweep_interval();
+        if app.update_scheme_transition(Instant::now()) {
+            needs_redraw = true;
+        }
+        app.track_sends(Instant::now());
+        if app.update_cursor_blink(Instant::now()) {
+            needs_redraw = true;
+        }
+
+        if app.deadline_passed() || terminate.load(Ordering::Relaxed) {
+            app.quit();
         }
 
         if app.should_quit {
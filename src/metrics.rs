@@ -0,0 +1,278 @@
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::ping::{PingResult, PingStats};
+
+/// Latest statistics published to the metrics endpoint
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub target: String,
+    pub stats: PingStats,
+    /// RTT of the most recent reply (None before the first reply or after a timeout)
+    pub last_rtt_ms: Option<f64>,
+}
+
+/// Snapshot shared between the ping loop (writer) and the HTTP server (reader)
+#[derive(Debug, Clone, Default)]
+pub struct SharedMetrics(Arc<Mutex<MetricsSnapshot>>);
+
+impl SharedMetrics {
+    /// Publish the current stats for `target` and the latest result
+    pub fn update(&self, target: &str, stats: &PingStats, latest: Option<&PingResult>) {
+        *self.0.lock().unwrap() = MetricsSnapshot {
+            target: target.to_string(),
+            stats: stats.clone(),
+            last_rtt_ms: latest.and_then(PingResult::rtt_ms_f64),
+        };
+    }
+
+    /// Current metrics in Prometheus text exposition format
+    pub fn render(&self) -> String {
+        render(&self.0.lock().unwrap())
+    }
+
+    /// Current stats as a JSON object
+    pub fn render_json(&self) -> String {
+        render_json(&self.0.lock().unwrap())
+    }
+}
+
+/// Stats snapshot written to `--stats-socket` clients
+#[derive(Debug, Serialize)]
+struct JsonSnapshot<'a> {
+    target: &'a str,
+    sent: u64,
+    received: u64,
+    lost: u64,
+    loss_percent: f64,
+    last_rtt_ms: Option<f64>,
+    min_ms: Option<f64>,
+    avg_ms: Option<f64>,
+    max_ms: Option<f64>,
+    max_loss_streak: u64,
+}
+
+/// Format a snapshot as a single line of JSON
+pub fn render_json(snapshot: &MetricsSnapshot) -> String {
+    let stats = &snapshot.stats;
+    let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+    let json = JsonSnapshot {
+        target: &snapshot.target,
+        sent: stats.total_sent,
+        received: stats.total_received,
+        lost: stats.total_lost,
+        loss_percent: stats.loss_percent(),
+        last_rtt_ms: snapshot.last_rtt_ms,
+        min_ms: stats.min_rtt.map(ms),
+        avg_ms: stats.avg_rtt().map(ms),
+        max_ms: stats.max_rtt.map(ms),
+        max_loss_streak: stats.max_loss_streak,
+    };
+    serde_json::to_string(&json).unwrap_or_default()
+}
+
+/// Escape a label value for the exposition format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Format a snapshot in Prometheus text exposition format
+pub fn render(snapshot: &MetricsSnapshot) -> String {
+    let labels = format!("{{target=\"{}\"}}", escape_label(&snapshot.target));
+    let stats = &snapshot.stats;
+    let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: Option<f64>| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        if let Some(value) = value {
+            let _ = writeln!(out, "{}{} {}", name, labels, value);
+        }
+    };
+
+    metric(
+        "ping_sent_total",
+        "counter",
+        "Pings sent",
+        Some(stats.total_sent as f64),
+    );
+    metric(
+        "ping_received_total",
+        "counter",
+        "Replies received",
+        Some(stats.total_received as f64),
+    );
+    metric(
+        "ping_loss_total",
+        "counter",
+        "Pings that timed out",
+        Some(stats.total_lost as f64),
+    );
+    metric(
+        "ping_loss_ratio",
+        "gauge",
+        "Fraction of pings lost",
+        Some(stats.loss_percent() / 100.0),
+    );
+    metric(
+        "ping_rtt_ms",
+        "gauge",
+        "Round-trip time of the latest reply in milliseconds",
+        snapshot.last_rtt_ms,
+    );
+    metric(
+        "ping_rtt_min_ms",
+        "gauge",
+        "Minimum round-trip time in milliseconds",
+        stats.min_rtt.map(ms),
+    );
+    metric(
+        "ping_rtt_avg_ms",
+        "gauge",
+        "Average round-trip time in milliseconds",
+        stats.avg_rtt().map(ms),
+    );
+    metric(
+        "ping_rtt_max_ms",
+        "gauge",
+        "Maximum round-trip time in milliseconds",
+        stats.max_rtt.map(ms),
+    );
+
+    out
+}
+
+/// Pause after a failed accept (e.g. out of file descriptors) before trying again
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// How long a scraper gets to send its request before the connection is dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Bind the metrics listener on all interfaces, so a port in use fails at startup
+pub async fn bind(port: u16) -> anyhow::Result<TcpListener> {
+    TcpListener::bind(("0.0.0.0", port))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind metrics port {}: {}", port, e))
+}
+
+/// Answer scrapes on `listener` until the task is aborted
+pub async fn serve(listener: TcpListener, metrics: SharedMetrics) {
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+            continue;
+        };
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            let _ = handle_request(stream, &metrics).await;
+        });
+    }
+}
+
+async fn handle_request(mut stream: TcpStream, metrics: &SharedMetrics) -> std::io::Result<()> {
+    // Only the request line matters; the rest of the request is ignored
+    let mut buf = [0u8; 1024];
+    let len = tokio::time::timeout(REQUEST_TIMEOUT, stream.read(&mut buf))
+        .await
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+    let request = String::from_utf8_lossy(&buf[..len]);
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+
+    let response = if path == "/metrics" || path == "/" {
+        let body = metrics.render();
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Removes the stats socket file when dropped, so the next run can bind the path again
+#[cfg(unix)]
+pub struct SocketFile(std::path::PathBuf);
+
+#[cfg(unix)]
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Listen on a Unix socket at `path`, replacing a socket left behind by an earlier run
+/// (any other kind of file there is an error)
+#[cfg(unix)]
+pub fn bind_socket(path: &str) -> anyhow::Result<(tokio::net::UnixListener, SocketFile)> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+        let _ = std::fs::remove_file(path);
+    }
+    let listener = tokio::net::UnixListener::bind(path)
+        .map_err(|e| anyhow::anyhow!("Failed to bind stats socket {}: {}", path, e))?;
+    Ok((listener, SocketFile(path.into())))
+}
+
+/// Write a JSON stats snapshot to each client that connects, then hang up
+#[cfg(unix)]
+pub async fn serve_socket(listener: tokio::net::UnixListener, metrics: SharedMetrics) {
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+            continue;
+        };
+        let snapshot = metrics.render_json() + "\n";
+        tokio::spawn(async move {
+            let _ = stream.write_all(snapshot.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_render() {
+        let now = Instant::now();
+        let results = [
+            PingResult::success(1, Duration::from_millis(20), now, None),
+            PingResult::timeout(2, now),
+        ];
+        let mut stats = PingStats::new();
+        results.iter().for_each(|r| stats.record(r));
+
+        let metrics = SharedMetrics::default();
+        metrics.update("example.com", &stats, results.last());
+        let text = metrics.render();
+        assert!(text.contains("# TYPE ping_sent_total counter\n"));
+        assert!(text.contains("ping_sent_total{target=\"example.com\"} 2\n"));
+        assert!(text.contains("ping_loss_ratio{target=\"example.com\"} 0.5\n"));
+        assert!(text.contains("ping_rtt_max_ms{target=\"example.com\"} 20\n"));
+        // No sample for the latest RTT after a timeout
+        assert!(!text.contains("ping_rtt_ms{"));
+        assert!(text.contains("# TYPE ping_rtt_ms gauge\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&metrics.render_json()).unwrap();
+        assert_eq!(json["target"], "example.com");
+        assert_eq!(json["lost"], 1);
+        assert_eq!(json["max_ms"], 20.0);
+        assert!(json["last_rtt_ms"].is_null());
+    }
+}
@@ -0,0 +1,257 @@
+use super::{
+    IntervalSweep, JitterTracker, MissedTicks, PingResult, Pinger, ProbeSeq, ProbeSwitch,
+    ProbeTicker, Rng,
+};
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Address shown for demo targets (TEST-NET-1, reserved for documentation)
+pub const DEMO_ADDR: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+/// Typical RTT the synthetic samples hover around, in ms
+const BASELINE_MS: f64 = 24.0;
+/// Chance per sample of a latency spike starting
+const SPIKE_CHANCE: f64 = 0.03;
+/// Chance per sample of a single lost ping
+const LOSS_CHANCE: f64 = 0.01;
+/// Chance per sample of a short outage (several pings lost in a row)
+const OUTAGE_CHANCE: f64 = 0.002;
+
+/// Generator of a realistic looking RTT series: a slowly wandering baseline with
+/// noise, occasional decaying spikes and sporadic loss (None = lost)
+///
+/// Seeded deterministically, so a demo run looks the same every time
+#[derive(Debug, Clone)]
+pub struct DemoSignal {
+    rng: Rng,
+    /// Samples generated so far
+    step: u64,
+    /// Extra latency of the current spike in ms, decaying every sample
+    spike_ms: f64,
+    /// Pings still to lose in the current outage
+    outage_left: u32,
+}
+
+impl DemoSignal {
+    /// Each seed gives a different, but repeatable, series
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            step: 0,
+            spike_ms: 0.0,
+            outage_left: 0,
+        }
+    }
+
+    /// Seed derived from a host name (FNV-1a), so every demo target looks different
+    pub fn for_host(host: &str) -> Self {
+        let seed = host.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+        Self::new(seed)
+    }
+
+    /// RTT of the next sample in ms (None = lost)
+    pub fn next_rtt_ms(&mut self) -> Option<f64> {
+        self.step += 1;
+        if self.outage_left > 0 {
+            self.outage_left -= 1;
+            return None;
+        }
+        if self.rng.chance(OUTAGE_CHANCE) {
+            self.outage_left = 2 + (self.rng.next_f64() * 6.0) as u32;
+            return None;
+        }
+        if self.rng.chance(LOSS_CHANCE) {
+            return None;
+        }
+
+        // Slow drift of the baseline plus roughly normal noise (sum of uniforms)
+        let t = self.step as f64;
+        let drift = 3.0 * (t / 90.0).sin() + 1.5 * (t / 23.0).sin();
+        let noise = (0..4).map(|_| self.rng.next_f64()).sum::<f64>() - 2.0;
+        if self.rng.chance(SPIKE_CHANCE) {
+            self.spike_ms += 40.0 + self.rng.next_f64() * 120.0;
+        }
+        let rtt = BASELINE_MS + drift + noise * 2.5 + self.spike_ms;
+        self.spike_ms *= 0.45;
+        Some(rtt.max(0.5))
+    }
+}
+
+/// Pinger that sends nothing and reports synthetic samples instead (`--demo`)
+///
+/// Replies arrive after their made-up RTT and losses after the timeout, so the UI
+/// behaves as it would with a real target
+pub struct DemoPinger {
+    signal: DemoSignal,
+    interval_ms: u64,
+    timeout_ms: u64,
+    /// Skips sending while stopped
+    switch: ProbeSwitch,
+    /// Hands out sequence numbers (shared with a pinger this one replaces)
+    seq: ProbeSeq,
+    /// Interval sweep and when it started (None = fixed interval)
+    sweep: Option<(IntervalSweep, Instant)>,
+    /// How sends missed while falling behind schedule are made up for
+    missed_ticks: MissedTicks,
+    /// Randomization of the gaps between sends, in percent of the interval
+    interval_jitter: f64,
+}
+
+impl DemoPinger {
+    pub fn new(host: &str, interval_ms: u64, timeout_ms: u64) -> Self {
+        Self {
+            signal: DemoSignal::for_host(host),
+            interval_ms,
+            timeout_ms,
+            switch: ProbeSwitch::default(),
+            seq: ProbeSeq::default(),
+            sweep: None,
+            missed_ticks: MissedTicks::default(),
+            interval_jitter: 0.0,
+        }
+    }
+
+    /// Share a switch that stops and resumes sending
+    pub fn switch(mut self, switch: ProbeSwitch) -> Self {
+        self.switch = switch;
+        self
+    }
+
+    /// Share a sequence counter, e.g. to continue the numbering of a pinger this one replaces
+    pub fn seq(mut self, seq: ProbeSeq) -> Self {
+        self.seq = seq;
+        self
+    }
+
+    /// Follow an interval sweep that started at `started` instead of the fixed interval
+    pub fn sweep(mut self, sweep: IntervalSweep, started: Instant) -> Self {
+        self.sweep = Some((sweep, started));
+        self
+    }
+
+    /// Choose how sends missed while falling behind schedule are made up for
+    pub fn missed_ticks(mut self, missed_ticks: MissedTicks) -> Self {
+        self.missed_ticks = missed_ticks;
+        self
+    }
+
+    /// Randomize each gap between sends by up to ±`percent`% of the interval
+    pub fn interval_jitter(mut self, percent: f64) -> Self {
+        self.interval_jitter = percent;
+        self
+    }
+}
+
+impl Pinger for DemoPinger {
+    fn start(
+        mut self: Box<Self>,
+        tx: mpsc::UnboundedSender<PingResult>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = ProbeTicker::new(self.interval_ms, self.sweep, self.missed_ticks)
+                .interval_jitter(self.interval_jitter);
+            let mut jitter = JitterTracker::default();
+
+            loop {
+                let sched_jitter = ticker.tick().await;
+                if self.switch.is_stopped() {
+                    continue;
+                }
+
+                let sent_at = Instant::now();
+                let seq = self.seq.next();
+                let rtt = self
+                    .signal
+                    .next_rtt_ms()
+                    .map(|ms| Duration::from_secs_f64(ms / 1000.0))
+                    .filter(|rtt| rtt.as_millis() < self.timeout_ms as u128);
+                // Samples are generated in send order, so jitter can be tracked up front
+                let ping_result = match rtt {
+                    Some(rtt) => {
+                        let prev = jitter.complete(seq, Some(rtt));
+                        PingResult::success(seq, rtt, sent_at, prev)
+                            .with_smoothed_jitter(jitter.smoothed())
+                    }
+                    None => {
+                        jitter.complete(seq, None);
+                        PingResult::timeout(seq, sent_at)
+                    }
+                }
+                .with_sched_jitter(sched_jitter);
+                let delay = rtt.unwrap_or(Duration::from_millis(self.timeout_ms));
+                let tx_clone = tx.clone();
+
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    // Arrival time is when the reply would have come in
+                    let ping_result = PingResult {
+                        received_at: ping_result.rtt.map(|_| Instant::now()),
+                        ..ping_result
+                    };
+                    let _ = tx_clone.send(ping_result);
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_signal() {
+        let samples: Vec<Option<f64>> = {
+            let mut signal = DemoSignal::for_host("demo");
+            (0..2000).map(|_| signal.next_rtt_ms()).collect()
+        };
+        // Repeatable for the same host, different for another
+        let mut again = DemoSignal::for_host("demo");
+        assert!(samples.iter().all(|&rtt| rtt == again.next_rtt_ms()));
+        let mut other = DemoSignal::for_host("other");
+        assert!(samples.iter().any(|&rtt| rtt != other.next_rtt_ms()));
+
+        let replies: Vec<f64> = samples.iter().flatten().copied().collect();
+        let lost = samples.len() - replies.len();
+        assert!(lost > 0 && lost < 100, "{} lost", lost);
+        let spikes = replies.iter().filter(|&&rtt| rtt > 60.0).count();
+        assert!(spikes > 0 && spikes < 200, "{} spikes", spikes);
+        let mut sorted = replies.clone();
+        sorted.sort_by(f64::total_cmp);
+        let median = sorted[sorted.len() / 2];
+        assert!((median - BASELINE_MS).abs() < 5.0, "median {}", median);
+    }
+
+    #[tokio::test]
+    async fn test_handoff() {
+        let seq = ProbeSeq::default();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let start = |tx| Box::new(DemoPinger::new("demo", 5, 50).seq(seq.clone())).start(tx);
+
+        // A second pinger takes over from the first on the same channel
+        let mut seqs = Vec::new();
+        let first = start(tx.clone());
+        for _ in 0..3 {
+            seqs.push(rx.recv().await.unwrap().seq);
+        }
+        let second = start(tx);
+        first.abort();
+        for _ in 0..3 {
+            seqs.push(rx.recv().await.unwrap().seq);
+        }
+        second.abort();
+        // Probes already sent are still answered (within the 50ms timeout)
+        while let Ok(Some(result)) =
+            tokio::time::timeout(Duration::from_millis(200), rx.recv()).await
+        {
+            seqs.push(result.seq);
+        }
+
+        // Numbered on without gaps or repeats
+        seqs.sort_unstable();
+        assert_eq!(seqs, (1..=seqs.len() as u64).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+/// Ratio between the bounds of neighboring buckets: a percentile read from the
+/// histogram is within about 1% of the exact sample
+const BUCKET_GROWTH: f64 = 1.02;
+/// Buckets cover 1µs up to about 17 minutes; longer RTTs land in the last one
+const BUCKET_COUNT: usize = 1047;
+
+/// Fixed-size histogram of RTTs in log-spaced buckets, so recording stays O(1) and
+/// percentiles are cheap however long the session runs
+#[derive(Debug, Clone, Default)]
+pub struct RttHistogram {
+    /// Replies per bucket (empty until the first one, so unused stats stay small)
+    counts: Vec<u64>,
+    total: u64,
+    /// Exact extremes, reported for their buckets so the ends of the range aren't rounded
+    min: Duration,
+    max: Duration,
+}
+
+impl RttHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bucket an RTT falls into (anything under 1µs goes into the first one)
+    fn bucket(rtt: Duration) -> usize {
+        let us = rtt.as_secs_f64() * 1_000_000.0;
+        if us <= 1.0 {
+            return 0;
+        }
+        ((us.ln() / BUCKET_GROWTH.ln()) as usize).min(BUCKET_COUNT - 1)
+    }
+
+    /// Geometric middle of a bucket, the value its samples are reported as
+    fn bucket_value(bucket: usize) -> Duration {
+        Duration::from_secs_f64(BUCKET_GROWTH.powf(bucket as f64 + 0.5) / 1_000_000.0)
+    }
+
+    pub fn record(&mut self, rtt: Duration) {
+        if self.counts.is_empty() {
+            self.counts = vec![0; BUCKET_COUNT];
+        }
+        self.counts[Self::bucket(rtt)] += 1;
+        self.min = if self.total == 0 {
+            rtt
+        } else {
+            self.min.min(rtt)
+        };
+        self.max = self.max.max(rtt);
+        self.total += 1;
+    }
+
+    /// Number of RTTs recorded
+    pub fn len(&self) -> u64 {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// RTT at a percentile (nearest rank, 0-100), to within the bucket resolution and
+    /// never outside the smallest and largest RTT (None when empty)
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.total == 0 {
+            return None;
+        }
+        let rank = ((percentile / 100.0 * self.total as f64).ceil() as u64).clamp(1, self.total);
+        let mut seen = 0;
+        let bucket = self.counts.iter().position(|&count| {
+            seen += count;
+            seen >= rank
+        })?;
+        Some(if bucket == Self::bucket(self.max) {
+            self.max
+        } else if bucket == Self::bucket(self.min) {
+            self.min
+        } else {
+            Self::bucket_value(bucket)
+        })
+    }
+
+    /// Add the RTTs of another histogram, e.g. to combine targets or time windows
+    pub fn merge(&mut self, other: &RttHistogram) {
+        if other.total == 0 {
+            return;
+        }
+        if self.counts.is_empty() {
+            self.counts = vec![0; BUCKET_COUNT];
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.min = if self.total == 0 {
+            other.min
+        } else {
+            self.min.min(other.min)
+        };
+        self.max = self.max.max(other.max);
+        self.total += other.total;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_accuracy() {
+        let mut histogram = RttHistogram::new();
+        assert_eq!(histogram.percentile(50.0), None);
+        // 0.1ms to 1000ms
+        for us in (100..=1_000_000).step_by(100) {
+            histogram.record(Duration::from_micros(us));
+        }
+        assert_eq!(histogram.len(), 10_000);
+        for (percentile, exact_ms) in [(50.0, 500.0), (95.0, 950.0), (99.0, 990.0), (1.0, 10.0)] {
+            let ms = histogram.percentile(percentile).unwrap().as_secs_f64() * 1000.0;
+            let error = (ms - exact_ms).abs() / exact_ms;
+            assert!(error < 0.011, "p{} = {}ms", percentile, ms);
+        }
+
+        // The ends are exact, even out of the range of the buckets
+        histogram.record(Duration::ZERO);
+        histogram.record(Duration::from_secs(3600));
+        assert_eq!(histogram.percentile(0.0), Some(Duration::ZERO));
+        assert_eq!(histogram.percentile(100.0), Some(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut low = RttHistogram::new();
+        let mut high = RttHistogram::new();
+        for ms in 1..=50 {
+            low.record(Duration::from_millis(ms));
+            high.record(Duration::from_millis(ms + 50));
+        }
+        low.merge(&high);
+        low.merge(&RttHistogram::new());
+        assert_eq!(low.len(), 100);
+        let median = low.percentile(50.0).unwrap().as_secs_f64() * 1000.0;
+        assert!((median - 50.0).abs() < 0.5, "median = {}ms", median);
+    }
+}
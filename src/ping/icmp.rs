@@ -1,14 +1,31 @@
-use super::{PingResult, Pinger};
-use std::net::IpAddr;
+use super::{
+    IntervalSweep, JitterTracker, LossReason, MissedTicks, PingResult, Pinger, ProbeSeq,
+    ProbeSwitch, ProbeTicker, split_port, split_zone,
+};
+use ping_rs::{IpStatus, PingError};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
-use tokio::time::interval;
 
 /// ICMP ping implementation using ping_rs
+///
+/// Every probe is sent from its own socket (an unprivileged ping socket on Linux,
+/// `IcmpSendEcho` on Windows), so overlapping probes only ever see their own reply
 pub struct IcmpPinger {
     target: IpAddr,
     interval_ms: u64,
     timeout_ms: u64,
+    /// Skips sending while stopped
+    switch: ProbeSwitch,
+    /// Hands out sequence numbers (shared with a pinger this one replaces)
+    seq: ProbeSeq,
+    /// Interval sweep and when it started (None = fixed interval)
+    sweep: Option<(IntervalSweep, Instant)>,
+    /// How sends missed while falling behind schedule are made up for
+    missed_ticks: MissedTicks,
+    /// Randomization of the gaps between sends, in percent of the interval
+    interval_jitter: f64,
 }
 
 impl IcmpPinger {
@@ -17,8 +34,137 @@ impl IcmpPinger {
             target,
             interval_ms,
             timeout_ms,
+            switch: ProbeSwitch::default(),
+            seq: ProbeSeq::default(),
+            sweep: None,
+            missed_ticks: MissedTicks::default(),
+            interval_jitter: 0.0,
         }
     }
+
+    /// Share a switch that stops and resumes sending
+    pub fn switch(mut self, switch: ProbeSwitch) -> Self {
+        self.switch = switch;
+        self
+    }
+
+    /// Share a sequence counter, e.g. to continue the numbering of a pinger this one replaces
+    pub fn seq(mut self, seq: ProbeSeq) -> Self {
+        self.seq = seq;
+        self
+    }
+
+    /// Follow an interval sweep that started at `started` instead of the fixed interval
+    pub fn sweep(mut self, sweep: IntervalSweep, started: Instant) -> Self {
+        self.sweep = Some((sweep, started));
+        self
+    }
+
+    /// Choose how sends missed while falling behind schedule are made up for
+    pub fn missed_ticks(mut self, missed_ticks: MissedTicks) -> Self {
+        self.missed_ticks = missed_ticks;
+        self
+    }
+
+    /// Randomize each gap between sends by up to ±`percent`% of the interval
+    pub fn interval_jitter(mut self, percent: f64) -> Self {
+        self.interval_jitter = percent;
+        self
+    }
+}
+
+/// Reason behind a failed ping, if the error tells (anything else is a plain timeout)
+/// Windows reports the ICMP error itself; on Linux only errors the socket reports
+/// (e.g. no route to the host) are known
+fn loss_reason(error: &PingError) -> Option<LossReason> {
+    match error {
+        PingError::IpError(
+            IpStatus::DestinationNetworkUnreachable
+            | IpStatus::DestinationHostUnreachable
+            | IpStatus::DestinationProtocolUnreachable
+            | IpStatus::DestinationPortUnreachable
+            | IpStatus::DestinationUnreachable
+            | IpStatus::BadRoute,
+        ) => Some(LossReason::Unreachable),
+        PingError::IpError(IpStatus::DestinationProhibited) => Some(LossReason::Prohibited),
+        PingError::IpError(
+            IpStatus::TtlExpired | IpStatus::TtlReassemblyTimeExceeded | IpStatus::TimeExceeded,
+        ) => Some(LossReason::TtlExceeded),
+        PingError::OsError(code, _) => {
+            match std::io::Error::from_raw_os_error(*code as i32).kind() {
+                std::io::ErrorKind::NetworkUnreachable | std::io::ErrorKind::HostUnreachable => {
+                    Some(LossReason::Unreachable)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Why pings of the target's IP family can't be sent at all, if the error says so
+/// (an error creating or sending from the socket, rather than a lost ping)
+fn support_error(target: IpAddr, error: &PingError) -> Option<String> {
+    let PingError::OsError(code, message) = error else {
+        return None;
+    };
+    let family = if target.is_ipv6() { "ICMPv6" } else { "ICMP" };
+    let kind = std::io::Error::from_raw_os_error(*code as i32).kind();
+    Some(if kind == std::io::ErrorKind::PermissionDenied {
+        // Linux has no separate setting for ICMPv6 ping sockets
+        format!(
+            "{} pings are not permitted ({}): on Linux, add your group to \
+             net.ipv4.ping_group_range",
+            family, message
+        )
+    } else if target.is_ipv6() {
+        format!(
+            "ICMPv6 pings can't be sent ({}): IPv6 may be disabled on this system",
+            message
+        )
+    } else {
+        format!("{} pings can't be sent ({})", family, message)
+    })
+}
+
+/// Check that this platform and user can send pings of the target's IP family, by
+/// pinging the loopback address of that family
+/// Without this, a missing privilege or a system without IPv6 only shows as timeouts
+pub fn check_support(target: IpAddr) -> anyhow::Result<()> {
+    let loopback = match target {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+    };
+    match ping_rs::send_ping(&loopback, Duration::from_millis(500), &[1, 2, 3, 4], None) {
+        Err(error) => match support_error(target, &error) {
+            Some(message) => anyhow::bail!(message),
+            // A lost loopback ping (e.g. a firewall) says nothing about the target
+            None => Ok(()),
+        },
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Check that ICMP mode can ping `host` as given
+/// ICMP has no ports, and ping_rs only takes an address, so a zone id ("fe80::1%eth0")
+/// can't pick the interface
+pub fn check_target(host: &str) -> anyhow::Result<()> {
+    let (addr, port) = split_port(host);
+    if let Some(port) = port {
+        anyhow::bail!(
+            "ICMP mode has no ports: ping {} instead, or use UDP (-m udp-client) or TLS (-m tls-handshake) mode to probe port {}",
+            addr,
+            port
+        );
+    }
+    if let (_, Some(zone)) = split_zone(addr) {
+        anyhow::bail!(
+            "ICMP mode can't use the zone id '%{}' of {}: use UDP mode (-m udp-client) for link-local targets",
+            zone,
+            host
+        );
+    }
+    Ok(())
 }
 
 impl Pinger for IcmpPinger {
@@ -27,53 +173,145 @@ impl Pinger for IcmpPinger {
         tx: mpsc::UnboundedSender<PingResult>,
     ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
-            let mut seq: u64 = 0;
-            let mut ticker = interval(Duration::from_millis(self.interval_ms));
-            let prev_rtt: std::sync::Arc<std::sync::Mutex<Option<Duration>>> =
-                std::sync::Arc::new(std::sync::Mutex::new(None));
+            let mut ticker = ProbeTicker::new(self.interval_ms, self.sweep, self.missed_ticks)
+                .interval_jitter(self.interval_jitter);
+            let jitter = Arc::new(Mutex::new(JitterTracker::default()));
 
             loop {
-                ticker.tick().await;
+                let sched_jitter = ticker.tick().await;
+                if self.switch.is_stopped() {
+                    continue;
+                }
 
                 let sent_at = Instant::now();
-                seq += 1;
-                let current_seq = seq;
+                let current_seq = self.seq.next();
                 let target = self.target;
                 let timeout = Duration::from_millis(self.timeout_ms);
                 let tx_clone = tx.clone();
-                let prev_rtt_clone = prev_rtt.clone();
+                let jitter = jitter.clone();
 
                 // Spawn ping in background so we don't block the interval
                 tokio::spawn(async move {
-                    let ping_start = Instant::now();
+                    // Measure RTT ourselves for sub-millisecond precision (ping_rs on
+                    // Windows only returns whole milliseconds), timing only the blocking
+                    // call so a busy blocking pool doesn't inflate it
                     let result = tokio::task::spawn_blocking(move || {
+                        let ping_start = Instant::now();
                         ping_rs::send_ping(&target, timeout, &[1, 2, 3, 4], None)
+                            .map(|reply| (reply, ping_start.elapsed()))
                     })
                     .await;
 
                     let ping_result = match result {
-                        Ok(Ok(_reply)) => {
-                            // Measure RTT ourselves for sub-millisecond precision
-                            // (ping_rs on Windows only returns whole milliseconds)
-                            let rtt = ping_start.elapsed();
-                            let prev = {
-                                let mut guard = prev_rtt_clone.lock().unwrap();
-                                let prev = *guard;
-                                *guard = Some(rtt);
-                                prev
-                            };
+                        Ok(Ok((_, rtt))) => {
+                            let mut jitter = jitter.lock().unwrap();
+                            let prev = jitter.complete(current_seq, Some(rtt));
                             PingResult::success(current_seq, rtt, sent_at, prev)
+                                .with_smoothed_jitter(jitter.smoothed())
+                        }
+                        Ok(Err(error)) => {
+                            jitter.lock().unwrap().complete(current_seq, None);
+                            match loss_reason(&error) {
+                                Some(reason) => PingResult::failed(current_seq, sent_at, reason),
+                                None => PingResult::timeout(current_seq, sent_at),
+                            }
                         }
-                        _ => {
-                            // Clear previous RTT on timeout
-                            *prev_rtt_clone.lock().unwrap() = None;
+                        Err(_) => {
+                            jitter.lock().unwrap().complete(current_seq, None);
                             PingResult::timeout(current_seq, sent_at)
                         }
                     };
 
-                    let _ = tx_clone.send(ping_result);
+                    let _ = tx_clone.send(ping_result.with_sched_jitter(sched_jitter));
                 });
             }
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ping::PingStats;
+
+    #[test]
+    fn test_ipv6_loopback() {
+        // Pings ::1 for real: either it can be pinged, or this system can't send ICMPv6
+        // pings and says so
+        match check_support(IpAddr::V6(Ipv6Addr::LOCALHOST)) {
+            Ok(()) => {}
+            Err(e) => assert!(e.to_string().starts_with("ICMPv6 pings"), "{}", e),
+        }
+    }
+
+    #[test]
+    fn test_support_error() {
+        let loopback = IpAddr::V6(Ipv6Addr::LOCALHOST);
+        // No ping socket privileges or no IPv6: a clear error, not timeouts
+        let disabled = PingError::OsError(97, "Address family not supported".to_string());
+        assert!(
+            support_error(loopback, &disabled)
+                .unwrap()
+                .starts_with("ICMPv6 pings can't be sent")
+        );
+
+        let denied = PingError::OsError(13, "Permission denied".to_string());
+        #[cfg(unix)]
+        assert!(
+            support_error(loopback, &denied)
+                .unwrap()
+                .contains("not permitted (Permission denied)")
+        );
+        assert_eq!(support_error(loopback, &PingError::TimedOut), None);
+    }
+
+    #[test]
+    fn test_check_target() {
+        assert!(check_target("example.com").is_ok());
+        assert!(check_target("fe80::1").is_ok());
+        let error = check_target("fe80::1%eth0").unwrap_err().to_string();
+        assert!(error.contains("zone id '%eth0'"), "{}", error);
+        let error = check_target("example.com:8080").unwrap_err().to_string();
+        assert!(error.contains("ping example.com instead"), "{}", error);
+    }
+
+    #[test]
+    fn test_loss_reason() {
+        let ip_error = |status| loss_reason(&PingError::IpError(status));
+        assert_eq!(
+            ip_error(IpStatus::DestinationHostUnreachable),
+            Some(LossReason::Unreachable)
+        );
+        assert_eq!(
+            ip_error(IpStatus::DestinationProhibited),
+            Some(LossReason::Prohibited)
+        );
+        assert_eq!(
+            ip_error(IpStatus::TtlExpired),
+            Some(LossReason::TtlExceeded)
+        );
+        assert_eq!(ip_error(IpStatus::TimedOut), None);
+        assert_eq!(loss_reason(&PingError::TimedOut), None);
+        // ENETUNREACH on Linux
+        #[cfg(target_os = "linux")]
+        assert_eq!(
+            loss_reason(&PingError::OsError(101, String::new())),
+            Some(LossReason::Unreachable)
+        );
+
+        let now = Instant::now();
+        let failed = PingResult::failed(1, now, LossReason::Unreachable);
+        assert_eq!(failed.loss_label(), "DEST UNREACHABLE");
+        assert_eq!(PingResult::timeout(2, now).loss_label(), "TIMEOUT");
+
+        let mut stats = PingStats::new();
+        stats.record(&failed);
+        stats.record(&PingResult::timeout(2, now));
+        assert_eq!(stats.total_lost, 2);
+        assert!(
+            stats
+                .format_stats()
+                .contains("Lost: 2 (100.0%, 1 dest unreachable)")
+        );
+    }
+}
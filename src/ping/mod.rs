@@ -1,25 +1,513 @@
+pub mod demo;
+pub mod histogram;
 pub mod icmp;
+pub mod socks;
+pub mod spawn;
+pub mod tls;
 pub mod udp;
 
-use chrono::{DateTime, Local};
-use std::net::IpAddr;
+pub use histogram::RttHistogram;
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use hickory_resolver::TokioAsyncResolver;
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+
+/// Clean up a user-supplied target: trims whitespace and strips a URL scheme,
+/// credentials and path, then checks the remaining hostname syntax. An explicit port
+/// is kept ("example.com:8080", "[2001:db8::1]:53"), for the modes that use one
+pub fn normalize_host(input: &str) -> anyhow::Result<String> {
+    let mut host = input.trim();
+
+    // "https://user@example.com:8080/path?q" -> "example.com"
+    if let Some((scheme, rest)) = host.split_once("://")
+        && !scheme.is_empty()
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    {
+        host = rest;
+    }
+    host = host.split(['/', '?', '#']).next().unwrap_or("");
+    if let Some((_, rest)) = host.rsplit_once('@') {
+        host = rest;
+    }
+
+    // Bracketed IPv6, optionally with a port: "[::1]:80"
+    let mut port = None;
+    if let Some(rest) = host.strip_prefix('[') {
+        let (addr, after) = rest.split_once(']').unwrap_or((rest, ""));
+        host = addr;
+        port = after.strip_prefix(':');
+    } else if let Some((name, digits)) = host.rsplit_once(':')
+        && !name.contains(':')
+        && digits.chars().all(|c| c.is_ascii_digit())
+    {
+        host = name;
+        port = Some(digits);
+    }
+    check_host_syntax(host, input)?;
+
+    match port.filter(|port| !port.is_empty()) {
+        Some(port) => {
+            if !port.parse::<u16>().is_ok_and(|port| port > 0) {
+                anyhow::bail!("Invalid port in '{}'", input.trim());
+            }
+            if host.contains(':') {
+                Ok(format!("[{}]:{}", host, port))
+            } else {
+                Ok(format!("{}:{}", host, port))
+            }
+        }
+        None => Ok(host.to_string()),
+    }
+}
+
+/// Split the explicit port off a normalized target, e.g. "example.com:8080" ->
+/// ("example.com", Some(8080)) and "[fe80::1%eth0]:53" -> ("fe80::1%eth0", Some(53))
+pub fn split_port(target: &str) -> (&str, Option<u16>) {
+    if let Some(rest) = target.strip_prefix('[')
+        && let Some((addr, port)) = rest.split_once("]:")
+    {
+        return (addr, port.parse().ok());
+    }
+    match target.rsplit_once(':') {
+        Some((name, port)) if !name.contains(':') => match port.parse() {
+            Ok(port) => (name, Some(port)),
+            Err(_) => (target, None),
+        },
+        _ => (target, None),
+    }
+}
+
+/// Check the syntax of a target without its port: an IP address (IPv6 optionally with a
+/// zone id) or a hostname
+fn check_host_syntax(host: &str, input: &str) -> anyhow::Result<()> {
+    if host.parse::<IpAddr>().is_ok() {
+        return Ok(());
+    }
+
+    // IPv6 literal with a zone id: "fe80::1%eth0"
+    if let (addr, Some(zone)) = split_zone(host)
+        && addr.parse::<IpAddr>().is_ok()
+    {
+        let valid = !zone.is_empty()
+            && zone
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            anyhow::bail!("Invalid zone id in '{}'", input.trim());
+        }
+        return Ok(());
+    }
+
+    let name = host.strip_suffix('.').unwrap_or(host);
+    let valid = !name.is_empty()
+        && name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        });
+    if !valid {
+        anyhow::bail!("Invalid hostname syntax: '{}'", input.trim());
+    }
+    Ok(())
+}
+
+/// Split an IPv6 zone id off a host, e.g. "fe80::1%eth0" -> ("fe80::1", Some("eth0"))
+pub fn split_zone(host: &str) -> (&str, Option<&str>) {
+    match host.split_once('%') {
+        Some((addr, zone)) => (addr, Some(zone)),
+        None => (host, None),
+    }
+}
+
+/// Interface index for an IPv6 zone id: either a number, or an interface name on
+/// platforms where names can be looked up (Linux)
+pub fn zone_index(zone: &str) -> anyhow::Result<u32> {
+    if let Ok(index) = zone.parse::<u32>() {
+        if index == 0 {
+            anyhow::bail!("Zone id must be a non-zero interface index");
+        }
+        return Ok(index);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let index = std::fs::read_to_string(format!("/sys/class/net/{}/ifindex", zone))
+            .ok()
+            .and_then(|index| index.trim().parse().ok());
+        index.ok_or_else(|| anyhow::anyhow!("Unknown network interface '{}' in zone id", zone))
+    }
+    #[cfg(not(target_os = "linux"))]
+    anyhow::bail!(
+        "Zone id '{}' must be a numeric interface index on this platform",
+        zone
+    )
+}
+
+/// Scope id to reach a host given as "addr%zone" (0 = no zone)
+/// The zone is validated by `resolve_host`, so an unknown interface here also gives 0
+pub fn scope_id(host: &str) -> u32 {
+    split_zone(split_port(host).0)
+        .1
+        .and_then(|zone| zone_index(zone).ok())
+        .unwrap_or(0)
+}
+
+/// Location of the system hosts file
+fn system_hosts_file() -> Option<PathBuf> {
+    if cfg!(windows) {
+        let root = std::env::var_os("SystemRoot")?;
+        Some(PathBuf::from(root).join(r"System32\drivers\etc\hosts"))
+    } else {
+        Some(PathBuf::from("/etc/hosts"))
+    }
+}
+
+/// Find a name in hosts file contents ("ip name [aliases...]" lines, '#' comments)
+/// Names match case-insensitively; the first matching line wins
+pub fn lookup_hosts(contents: &str, host: &str) -> Option<IpAddr> {
+    let host = host.trim_end_matches('.');
+    contents.lines().find_map(|line| {
+        let line = line.split('#').next().unwrap_or("");
+        let mut fields = line.split_whitespace();
+        // Entries with a zone id ("fe80::1%eth0") don't parse and are skipped
+        let ip = fields.next()?.parse::<IpAddr>().ok()?;
+        fields
+            .any(|name| name.trim_end_matches('.').eq_ignore_ascii_case(host))
+            .then_some(ip)
+    })
+}
+
+/// Look a name up in `hosts_file`, or the system hosts file if None
+/// A hosts file given explicitly must be readable; a missing system one is skipped
+fn resolve_from_hosts_file(
+    host: &str,
+    hosts_file: Option<&Path>,
+) -> anyhow::Result<Option<IpAddr>> {
+    let contents = match hosts_file {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read hosts file {}: {}", path.display(), e))?,
+        None => match system_hosts_file().and_then(|path| std::fs::read_to_string(path).ok()) {
+            Some(contents) => contents,
+            None => return Ok(None),
+        },
+    };
+    Ok(lookup_hosts(&contents, host))
+}
+
+/// Resolve hostname to IP address, through `dns_server` if set (otherwise the system resolver)
+/// Names listed in `hosts_file` (the system hosts file if None) win over DNS
+/// A zone id ("fe80::1%eth0") is only accepted on link-local IPv6 addresses; it isn't part of
+/// the returned address, see `scope_id`
+pub async fn resolve_host(
+    host: &str,
+    dns_server: Option<SocketAddr>,
+    hosts_file: Option<&Path>,
+) -> anyhow::Result<IpAddr> {
+    let target = normalize_host(host)?;
+    let host = split_port(&target).0;
+
+    if let (addr, Some(zone)) = split_zone(host) {
+        let ip: IpAddr = addr.parse()?;
+        if !matches!(ip, IpAddr::V6(v6) if v6.is_unicast_link_local()) {
+            anyhow::bail!(
+                "Zone ids are only supported on link-local IPv6 addresses (fe80::/10), not {}",
+                ip
+            );
+        }
+        zone_index(zone)?;
+        return Ok(ip);
+    }
 
-/// Resolve hostname to IP address
-pub async fn resolve_host(host: &str) -> anyhow::Result<IpAddr> {
     // First try parsing as IP address
     if let Ok(ip) = host.parse::<IpAddr>() {
         return Ok(ip);
     }
 
-    // Try DNS resolution
-    let mut addrs = tokio::net::lookup_host(format!("{}:0", host)).await?;
+    // Then fixed mappings, so they hold even when DNS is flaky
+    if let Some(ip) = resolve_from_hosts_file(host, hosts_file)? {
+        return Ok(ip);
+    }
+
+    if let Some(server) = dns_server {
+        let servers = NameServerConfigGroup::from_ips_clear(&[server.ip()], server.port(), true);
+        let resolver = TokioAsyncResolver::tokio(
+            ResolverConfig::from_parts(None, vec![], servers),
+            ResolverOpts::default(),
+        );
+        let lookup = resolver.lookup_ip(host).await.map_err(|e| {
+            anyhow::anyhow!("DNS resolution failed for {} via {}: {}", host, server, e)
+        })?;
+        return lookup.iter().next().ok_or_else(|| {
+            anyhow::anyhow!(
+                "DNS resolution failed for {} via {}: no addresses found",
+                host,
+                server
+            )
+        });
+    }
+
+    // Try DNS resolution with the system resolver
+    let mut addrs = tokio::net::lookup_host(format!("{}:0", host))
+        .await
+        .map_err(|e| anyhow::anyhow!("DNS resolution failed for {}: {}", host, e))?;
     if let Some(addr) = addrs.next() {
         return Ok(addr.ip());
     }
 
-    anyhow::bail!("Could not resolve hostname: {}", host)
+    anyhow::bail!("DNS resolution failed for {}: no addresses found", host)
+}
+
+/// Check that a source address can be used to reach the target (same IP family)
+pub fn check_source_family(source: IpAddr, target: IpAddr) -> anyhow::Result<()> {
+    if source.is_ipv4() != target.is_ipv4() {
+        anyhow::bail!(
+            "Source address {} and target {} are different IP families",
+            source,
+            target
+        );
+    }
+    Ok(())
+}
+
+/// Check that a resolved target can be pinged
+/// Unspecified, multicast and broadcast addresses are rejected; loopback and
+/// link-local addresses are allowed but return a warning to show the user
+pub fn check_target_ip(ip: IpAddr) -> anyhow::Result<Option<&'static str>> {
+    let broadcast = matches!(ip, IpAddr::V4(v4) if v4.is_broadcast());
+    if ip.is_unspecified() || ip.is_multicast() || broadcast {
+        anyhow::bail!(
+            "{} is an unspecified, multicast or broadcast address and cannot be pinged",
+            ip
+        );
+    }
+
+    let link_local = match ip {
+        IpAddr::V4(v4) => v4.is_link_local(),
+        IpAddr::V6(v6) => v6.is_unicast_link_local(),
+    };
+    Ok(if ip.is_loopback() {
+        Some("target is a loopback address (pinging this machine)")
+    } else if link_local {
+        Some("target is a link-local address (only reachable on the local segment)")
+    } else {
+        None
+    })
+}
+
+/// Linear change of the ping interval over time (`--interval-sweep`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntervalSweep {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub duration_ms: u64,
+}
+
+impl IntervalSweep {
+    /// Interval to use `elapsed` after the sweep started (`end_ms` once it's over)
+    pub fn interval_at(&self, elapsed: Duration) -> u64 {
+        let progress = (elapsed.as_secs_f64() * 1000.0 / self.duration_ms as f64).min(1.0);
+        let ms = self.start_ms as f64 + (self.end_ms as f64 - self.start_ms as f64) * progress;
+        (ms.round() as u64).max(1)
+    }
+}
+
+/// What the send loop does after falling behind schedule (e.g. a stalled runtime)
+///
+/// Sends are scheduled on a fixed grid (start + n × interval), so the interval itself
+/// never drifts; this only decides how missed send times are made up for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum MissedTicks {
+    /// Send once right away, then continue on the original grid (missed sends are dropped)
+    #[default]
+    Skip,
+    /// Send all missed pings back-to-back to catch up with the grid
+    Burst,
+    /// Send once right away and restart the grid from there (keeps the interval
+    /// between sends, shifting later send times)
+    Delay,
+}
+
+impl From<MissedTicks> for MissedTickBehavior {
+    fn from(missed: MissedTicks) -> Self {
+        match missed {
+            MissedTicks::Skip => MissedTickBehavior::Skip,
+            MissedTicks::Burst => MissedTickBehavior::Burst,
+            MissedTicks::Delay => MissedTickBehavior::Delay,
+        }
+    }
+}
+
+/// Small PRNG (xorshift64*), good enough for synthetic data and spreading out sends
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed | 1)
+    }
+
+    /// Seeded from the clock and process id, so separate instances differ
+    pub(crate) fn from_clock() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self::new(nanos ^ (std::process::id() as u64).rotate_left(32))
+    }
+
+    /// Uniform in [0, 1)
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub(crate) fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+}
+
+/// Send timer for the pingers: a fixed interval, or one that follows an interval sweep
+pub(crate) struct ProbeTicker {
+    ticker: tokio::time::Interval,
+    period_ms: u64,
+    sweep: Option<(IntervalSweep, Instant)>,
+    missed: MissedTicks,
+    /// How long after its scheduled time the previous tick fired
+    last_lateness: Option<Duration>,
+    /// Each gap is randomized by up to this fraction of the period either way, with
+    /// the generator to do it (None = regular gaps)
+    jitter: Option<(f64, Rng)>,
+}
+
+impl ProbeTicker {
+    pub(crate) fn new(
+        interval_ms: u64,
+        sweep: Option<(IntervalSweep, Instant)>,
+        missed: MissedTicks,
+    ) -> Self {
+        let period_ms = match sweep {
+            Some((sweep, started)) => sweep.interval_at(started.elapsed()),
+            None => interval_ms,
+        };
+        let mut ticker = tokio::time::interval(Duration::from_millis(period_ms));
+        ticker.set_missed_tick_behavior(missed.into());
+        Self {
+            ticker,
+            period_ms,
+            sweep,
+            missed,
+            last_lateness: None,
+            jitter: None,
+        }
+    }
+
+    /// Randomize every gap between sends by up to ±`percent`% of the interval, so
+    /// instances started together don't send in lockstep
+    pub(crate) fn interval_jitter(mut self, percent: f64) -> Self {
+        self.jitter = (percent > 0.0).then(|| (percent / 100.0, Rng::from_clock()));
+        self
+    }
+
+    /// Wait for the next send time, picking up the sweep's current period
+    /// Returns how far the gap since the previous tick strayed from the intended
+    /// interval (the scheduling jitter of this send; None for the first tick)
+    pub(crate) async fn tick(&mut self) -> Option<Duration> {
+        let scheduled = self.ticker.tick().await;
+        let lateness = tokio::time::Instant::now().saturating_duration_since(scheduled);
+        let sched_jitter = self.last_lateness.map(|last| lateness.abs_diff(last));
+        self.last_lateness = Some(lateness);
+        if let Some((sweep, started)) = self.sweep {
+            let period_ms = sweep.interval_at(started.elapsed());
+            if period_ms != self.period_ms {
+                let period = Duration::from_millis(period_ms);
+                self.period_ms = period_ms;
+                self.ticker =
+                    tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+                self.ticker.set_missed_tick_behavior(self.missed.into());
+            }
+        }
+        if let Some((fraction, rng)) = &mut self.jitter {
+            let factor = 1.0 + *fraction * (rng.next_f64() * 2.0 - 1.0);
+            let gap = Duration::from_secs_f64(self.period_ms as f64 * factor / 1000.0);
+            // The next send is due after the randomized gap; the measured lateness
+            // stays relative to that
+            self.ticker.reset_at(scheduled + gap);
+        }
+        sched_jitter
+    }
+}
+
+/// Tracks the latest completed probe so jitter is measured in send order, even
+/// when overlapping probes complete out of order
+/// Also maintains an RFC 3550 style smoothed jitter estimate (`J += (|D| - J) / 16`)
+#[derive(Debug, Default)]
+pub struct JitterTracker {
+    last_seq: u64,
+    last_rtt: Option<Duration>,
+    /// Smoothed jitter in seconds (None until two consecutive replies)
+    smoothed: Option<f64>,
+}
+
+impl JitterTracker {
+    /// Record a completed probe (`rtt` None = timeout), returning the RTT its
+    /// jitter should be measured against
+    /// A probe completing after a newer one gets no jitter and leaves the state alone
+    pub fn complete(&mut self, seq: u64, rtt: Option<Duration>) -> Option<Duration> {
+        if seq <= self.last_seq {
+            return None;
+        }
+        let prev = self.last_rtt;
+        self.last_seq = seq;
+        self.last_rtt = rtt;
+        if let (Some(rtt), Some(prev)) = (rtt, prev) {
+            let delta = rtt.abs_diff(prev).as_secs_f64();
+            let j = self.smoothed.unwrap_or(0.0);
+            self.smoothed = Some(j + (delta - j) / 16.0);
+        }
+        prev
+    }
+
+    /// Current smoothed jitter estimate
+    pub fn smoothed(&self) -> Option<Duration> {
+        self.smoothed.map(Duration::from_secs_f64)
+    }
+}
+
+/// Why a ping was lost, when an ICMP error said so instead of the reply staying away
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LossReason {
+    /// Destination network, host, protocol or port unreachable
+    Unreachable,
+    /// Communication administratively prohibited (e.g. by a firewall)
+    Prohibited,
+    /// TTL / hop limit ran out in transit (e.g. a routing loop)
+    TtlExceeded,
+}
+
+impl std::fmt::Display for LossReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LossReason::Unreachable => write!(f, "dest unreachable"),
+            LossReason::Prohibited => write!(f, "admin prohibited"),
+            LossReason::TtlExceeded => write!(f, "TTL exceeded"),
+        }
+    }
 }
 
 /// Result of a single ping attempt
@@ -30,15 +518,25 @@ pub struct PingResult {
     /// Round-trip time (None if timeout/error)
     pub rtt: Option<Duration>,
     /// When this ping was sent (monotonic)
-    #[allow(dead_code)]
     pub sent_at: Instant,
     /// When the response was received (if any)
-    #[allow(dead_code)]
     pub received_at: Option<Instant>,
     /// Wall-clock timestamp when ping was sent (for display)
     pub timestamp: DateTime<Local>,
-    /// Jitter (difference from previous RTT, None if first ping or timeout)
+    /// Jitter (difference from the previous RTT)
+    /// Undefined (None) unless this reply and the one sent before it both succeeded:
+    /// for the first reply, timeouts and the first reply after a timeout. UI surfaces
+    /// show it as "-" ([`RttUnit::format_jitter`]) and aggregates skip it ([`mean_jitter`])
     pub jitter: Option<Duration>,
+    /// Smoothed (RFC 3550) jitter estimate of the pinger when this reply arrived
+    pub smoothed_jitter: Option<Duration>,
+    /// A reply arrived but didn't match what was sent (UDP mode); counted as lost
+    pub corrupted: bool,
+    /// ICMP error the ping was lost to (None = plain timeout, or the reason is unknown)
+    pub loss_reason: Option<LossReason>,
+    /// How far the time since the previous send strayed from the intended interval
+    /// (scheduler-induced, as opposed to network jitter; None if unknown)
+    pub sched_jitter: Option<Duration>,
 }
 
 impl PingResult {
@@ -51,9 +549,25 @@ impl PingResult {
             received_at: Some(Instant::now()),
             timestamp: Local::now(),
             jitter,
+            smoothed_jitter: None,
+            corrupted: false,
+            loss_reason: None,
+            sched_jitter: None,
         }
     }
 
+    /// Attach the pinger's smoothed jitter estimate
+    pub fn with_smoothed_jitter(mut self, smoothed_jitter: Option<Duration>) -> Self {
+        self.smoothed_jitter = smoothed_jitter;
+        self
+    }
+
+    /// Attach the scheduling jitter of the send
+    pub fn with_sched_jitter(mut self, sched_jitter: Option<Duration>) -> Self {
+        self.sched_jitter = sched_jitter;
+        self
+    }
+
     pub fn timeout(seq: u64, sent_at: Instant) -> Self {
         Self {
             seq,
@@ -62,10 +576,40 @@ impl PingResult {
             received_at: None,
             timestamp: Local::now(),
             jitter: None,
+            smoothed_jitter: None,
+            corrupted: false,
+            loss_reason: None,
+            sched_jitter: None,
+        }
+    }
+
+    /// A reply whose payload was damaged in transit; it has no RTT, like a timeout
+    pub fn corrupted(seq: u64, sent_at: Instant) -> Self {
+        Self {
+            received_at: Some(Instant::now()),
+            corrupted: true,
+            ..Self::timeout(seq, sent_at)
+        }
+    }
+
+    /// A ping answered by an ICMP error instead of a reply; counted as lost
+    pub fn failed(seq: u64, sent_at: Instant, reason: LossReason) -> Self {
+        Self {
+            received_at: Some(Instant::now()),
+            loss_reason: Some(reason),
+            ..Self::timeout(seq, sent_at)
+        }
+    }
+
+    /// What became of a lost ping, e.g. "TIMEOUT" or "DEST UNREACHABLE"
+    pub fn loss_label(&self) -> String {
+        match self.loss_reason {
+            _ if self.corrupted => "CORRUPTED".to_string(),
+            Some(reason) => reason.to_string().to_uppercase(),
+            None => "TIMEOUT".to_string(),
         }
     }
 
-    #[allow(dead_code)]
     pub fn rtt_ms(&self) -> Option<u64> {
         self.rtt.map(|d| d.as_millis() as u64)
     }
@@ -75,14 +619,344 @@ impl PingResult {
         self.rtt.map(|d| d.as_secs_f64() * 1000.0)
     }
 
-    /// Get jitter in milliseconds as f64
+    /// Get jitter in milliseconds as f64 (None while undefined, see [`PingResult::jitter`])
     pub fn jitter_ms_f64(&self) -> Option<f64> {
         self.jitter.map(|d| d.as_secs_f64() * 1000.0)
     }
 
-    /// Format timestamp as HH:MM:SS.mmm
-    pub fn timestamp_str(&self) -> String {
-        self.timestamp.format("%H:%M:%S%.3f").to_string()
+    /// Get smoothed jitter in milliseconds as f64
+    pub fn smoothed_jitter_ms_f64(&self) -> Option<f64> {
+        self.smoothed_jitter.map(|d| d.as_secs_f64() * 1000.0)
+    }
+
+    /// Format timestamp as HH:MM:SS.mmm (in UTC with `utc`)
+    pub fn timestamp_str(&self, utc: bool) -> String {
+        format_time(self.timestamp, "%H:%M:%S%.3f", utc)
+    }
+
+    /// Full RFC 3339 timestamp with its UTC offset, so samples from different
+    /// machines line up (as UTC with `utc`)
+    pub fn timestamp_rfc3339(&self, utc: bool) -> String {
+        rfc3339(self.timestamp, utc)
+    }
+}
+
+/// Format a wall-clock time in local time, or in UTC with `utc`
+pub fn format_time(time: DateTime<Local>, fmt: &str, utc: bool) -> String {
+    if utc {
+        time.with_timezone(&Utc).format(fmt).to_string()
+    } else {
+        time.format(fmt).to_string()
+    }
+}
+
+/// RFC 3339 timestamp with the local offset, or in UTC ("Z") with `utc`
+pub fn rfc3339(time: DateTime<Local>, utc: bool) -> String {
+    if utc {
+        time.with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    } else {
+        time.to_rfc3339()
+    }
+}
+
+/// Mean jitter in ms between consecutive replies in `results` (None without any)
+pub fn mean_jitter<'a>(results: impl IntoIterator<Item = &'a PingResult>) -> Option<f64> {
+    let (sum, count) = results
+        .into_iter()
+        .filter_map(PingResult::jitter_ms_f64)
+        .fold((0.0, 0), |(sum, count), ms| (sum + ms, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Top of an auto-ranged view with outliers clipped: `factor` × the median of `rtts`
+/// (None without any), so a single huge spike doesn't flatten everything else
+pub fn outlier_limit(rtts: impl IntoIterator<Item = f64>, factor: f64) -> Option<f64> {
+    let mut rtts: Vec<f64> = rtts.into_iter().collect();
+    if rtts.is_empty() {
+        return None;
+    }
+    rtts.sort_unstable_by(f64::total_cmp);
+    Some(rtts[rtts.len() / 2] * factor)
+}
+
+/// Unit RTTs are displayed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum RttUnit {
+    /// Milliseconds
+    #[default]
+    Ms,
+    /// Microseconds, for sub-millisecond LAN latencies
+    Us,
+    /// Microseconds below 1 ms, milliseconds above
+    Auto,
+}
+
+impl std::fmt::Display for RttUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RttUnit::Ms => write!(f, "ms"),
+            RttUnit::Us => write!(f, "µs"),
+            RttUnit::Auto => write!(f, "Auto"),
+        }
+    }
+}
+
+impl RttUnit {
+    /// Get the next unit in the cycle
+    pub fn next(self) -> Self {
+        match self {
+            RttUnit::Ms => RttUnit::Us,
+            RttUnit::Us => RttUnit::Auto,
+            RttUnit::Auto => RttUnit::Ms,
+        }
+    }
+
+    /// Get the previous unit in the cycle
+    pub fn prev(self) -> Self {
+        match self {
+            RttUnit::Ms => RttUnit::Auto,
+            RttUnit::Us => RttUnit::Ms,
+            RttUnit::Auto => RttUnit::Us,
+        }
+    }
+
+    /// Concrete unit for a value in ms (Auto picks µs below 1 ms)
+    pub fn resolve(self, ms: f64) -> Self {
+        match self {
+            RttUnit::Auto if ms < 1.0 => RttUnit::Us,
+            RttUnit::Auto => RttUnit::Ms,
+            unit => unit,
+        }
+    }
+
+    /// Concrete unit shared by values shown side by side (e.g. min/avg/max),
+    /// picked from the largest so they stay comparable
+    pub fn resolve_all(self, ms: impl IntoIterator<Item = f64>) -> Self {
+        self.resolve(ms.into_iter().fold(0.0, f64::max))
+    }
+
+    /// Suffix after formatted values
+    pub fn suffix(self) -> &'static str {
+        match self.resolve(f64::INFINITY) {
+            RttUnit::Us => "µs",
+            _ => "ms",
+        }
+    }
+
+    /// Value in ms as a bare number in this unit; microseconds are shown whole,
+    /// milliseconds with the given number of decimals
+    pub fn number(self, ms: f64, decimals: usize) -> String {
+        match self.resolve(ms) {
+            RttUnit::Us => format!("{:.0}", ms * 1000.0),
+            _ => format!("{:.*}", decimals, ms),
+        }
+    }
+
+    /// Value in ms with its unit, e.g. "12.34ms" or "340µs"
+    pub fn format(self, ms: f64, decimals: usize) -> String {
+        let unit = self.resolve(ms);
+        format!("{}{}", unit.number(ms, decimals), unit.suffix())
+    }
+
+    /// Jitter in ms as "±12.34ms", or "-" while undefined (see [`PingResult::jitter`])
+    pub fn format_jitter(self, jitter_ms: Option<f64>, decimals: usize) -> String {
+        jitter_ms.map_or_else(
+            || "-".to_string(),
+            |ms| format!("±{}", self.format(ms, decimals)),
+        )
+    }
+
+    /// Duration with its unit (see [`RttUnit::format`])
+    pub fn format_duration(self, d: Duration, decimals: usize) -> String {
+        self.format(d.as_secs_f64() * 1000.0, decimals)
+    }
+}
+
+/// Decimals RTTs in ms are shown with unless `--precision` says otherwise
+pub const DEFAULT_PRECISION: usize = 2;
+
+/// Condition `--wait-up` / `--wait-down` end the session on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitFor {
+    /// The first reply
+    Up,
+    /// This many pings lost in a row
+    Down(u64),
+}
+
+impl std::fmt::Display for WaitFor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WaitFor::Up => write!(f, "up"),
+            WaitFor::Down(_) => write!(f, "down"),
+        }
+    }
+}
+
+/// Follows the results until a `WaitFor` condition is met
+#[derive(Debug, Clone)]
+pub struct WaitTracker {
+    pub wait: WaitFor,
+    /// Pings lost in a row so far
+    losses: u64,
+}
+
+impl WaitTracker {
+    pub fn new(wait: WaitFor) -> Self {
+        Self { wait, losses: 0 }
+    }
+
+    /// Record a result, returning true once the condition is met
+    pub fn update(&mut self, result: &PingResult) -> bool {
+        match (self.wait, result.rtt) {
+            (WaitFor::Up, rtt) => rtt.is_some(),
+            (WaitFor::Down(_), Some(_)) => {
+                self.losses = 0;
+                false
+            }
+            (WaitFor::Down(losses), None) => {
+                self.losses += 1;
+                self.losses >= losses
+            }
+        }
+    }
+}
+
+/// How the pings grouped into one graph cell by `--aggregate` are reduced to one sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Reducer {
+    /// The newest ping of the group
+    #[default]
+    Last,
+    /// Mean RTT of the replies (lost only if every ping was)
+    Avg,
+    /// Highest RTT of the replies (lost only if every ping was)
+    Max,
+    /// Lost if any ping was, otherwise the highest RTT
+    WorstLoss,
+}
+
+impl std::fmt::Display for Reducer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reducer::Last => write!(f, "Last"),
+            Reducer::Avg => write!(f, "Avg"),
+            Reducer::Max => write!(f, "Max"),
+            Reducer::WorstLoss => write!(f, "Worst loss"),
+        }
+    }
+}
+
+impl Reducer {
+    /// Get the next reducer in the cycle
+    pub fn next(self) -> Self {
+        match self {
+            Reducer::Last => Reducer::Avg,
+            Reducer::Avg => Reducer::Max,
+            Reducer::Max => Reducer::WorstLoss,
+            Reducer::WorstLoss => Reducer::Last,
+        }
+    }
+
+    /// Get the previous reducer in the cycle
+    pub fn prev(self) -> Self {
+        match self {
+            Reducer::Last => Reducer::WorstLoss,
+            Reducer::Avg => Reducer::Last,
+            Reducer::Max => Reducer::Avg,
+            Reducer::WorstLoss => Reducer::Max,
+        }
+    }
+
+    /// Reduce a group of pings (oldest first) to the one sample graphed for them
+    ///
+    /// The sample keeps the newest ping's details (seq, time) with the reduced RTT, or
+    /// is the newest lost ping of the group when the group counts as lost
+    pub fn reduce(self, group: &[PingResult]) -> Option<PingResult> {
+        let newest = group.last()?;
+        let replies = || group.iter().filter_map(|r| r.rtt);
+        let newest_loss = || group.iter().rev().find(|r| r.rtt.is_none()).cloned();
+        let with_rtt = |rtt: Duration| PingResult {
+            rtt: Some(rtt),
+            loss_reason: None,
+            ..newest.clone()
+        };
+        match self {
+            Reducer::Last => Some(newest.clone()),
+            Reducer::Avg => {
+                let count = replies().count() as u32;
+                match replies().sum::<Duration>().checked_div(count) {
+                    Some(avg) => Some(with_rtt(avg)),
+                    None => newest_loss(),
+                }
+            }
+            Reducer::Max => replies().max().map(with_rtt).or_else(newest_loss),
+            Reducer::WorstLoss => newest_loss().or_else(|| replies().max().map(with_rtt)),
+        }
+    }
+}
+
+/// Shared switch that stops pingers from sending probes without tearing them down
+/// Clones control the same switch
+#[derive(Debug, Clone, Default)]
+pub struct ProbeSwitch(Arc<AtomicBool>);
+
+impl ProbeSwitch {
+    pub fn set_stopped(&self, stopped: bool) {
+        self.0.store(stopped, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Shared counter handing out probe sequence numbers, starting at 1
+/// Clones count on together, so a pinger started with a clone continues the numbering
+/// of the one it replaces
+#[derive(Debug, Clone, Default)]
+pub struct ProbeSeq(Arc<AtomicU64>);
+
+impl ProbeSeq {
+    /// Take the next sequence number
+    pub fn next(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Number of sequence numbers handed out so far, i.e. probes sent
+    pub fn sent(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Shared count of probes awaiting a reply, kept up to date by pingers that track them
+/// Clones read and update the same count
+#[derive(Debug, Clone, Default)]
+pub struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+    pub fn set(&self, count: usize) {
+        self.0.store(count, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Shared local address a pinger's socket is bound to, set once it is known
+/// Clones read and update the same address
+#[derive(Debug, Clone, Default)]
+pub struct LocalAddr(Arc<std::sync::Mutex<Option<SocketAddr>>>);
+
+impl LocalAddr {
+    pub fn set(&self, addr: SocketAddr) {
+        *self.0.lock().unwrap() = Some(addr);
+    }
+
+    pub fn get(&self) -> Option<SocketAddr> {
+        *self.0.lock().unwrap()
     }
 }
 
@@ -103,6 +977,18 @@ pub struct PingStats {
     pub min_rtt: Option<Duration>,
     pub max_rtt: Option<Duration>,
     pub sum_rtt: Duration,
+    /// Number of consecutive losses in the current run (0 after a reply)
+    pub current_loss_streak: u64,
+    /// Longest run of consecutive losses seen
+    pub max_loss_streak: u64,
+    /// Lost pings whose reply arrived corrupted (included in `total_lost`)
+    pub total_corrupted: u64,
+    /// Lost pings answered by an ICMP error, by reason (included in `total_lost`)
+    pub loss_reasons: BTreeMap<LossReason, u64>,
+    /// Send time of the most recent lost ping
+    pub last_loss_at: Option<DateTime<Local>>,
+    /// Distribution of the replies, for percentiles over the whole session
+    pub rtt_histogram: RttHistogram,
 }
 
 impl PingStats {
@@ -116,6 +1002,8 @@ impl PingStats {
         if let Some(rtt) = result.rtt {
             self.total_received += 1;
             self.sum_rtt += rtt;
+            self.rtt_histogram.record(rtt);
+            self.current_loss_streak = 0;
 
             self.min_rtt = Some(match self.min_rtt {
                 Some(min) => min.min(rtt),
@@ -128,7 +1016,55 @@ impl PingStats {
             });
         } else {
             self.total_lost += 1;
+            self.total_corrupted += result.corrupted as u64;
+            if let Some(reason) = result.loss_reason {
+                *self.loss_reasons.entry(reason).or_default() += 1;
+            }
+            self.last_loss_at = Some(result.timestamp);
+            self.current_loss_streak += 1;
+            self.max_loss_streak = self.max_loss_streak.max(self.current_loss_streak);
+        }
+    }
+
+    /// Combine with the stats of another target or time window. Loss runs can't be
+    /// joined across the two, so the streaks are the longer of each
+    pub fn merge(&mut self, other: &PingStats) {
+        self.total_sent += other.total_sent;
+        self.total_received += other.total_received;
+        self.total_lost += other.total_lost;
+        self.min_rtt = self.min_rtt.into_iter().chain(other.min_rtt).min();
+        self.max_rtt = self.max_rtt.into_iter().chain(other.max_rtt).max();
+        self.sum_rtt += other.sum_rtt;
+        self.current_loss_streak = self.current_loss_streak.max(other.current_loss_streak);
+        self.max_loss_streak = self.max_loss_streak.max(other.max_loss_streak);
+        self.total_corrupted += other.total_corrupted;
+        for (reason, count) in &other.loss_reasons {
+            *self.loss_reasons.entry(*reason).or_default() += count;
+        }
+        self.last_loss_at = self.last_loss_at.max(other.last_loss_at);
+        self.rtt_histogram.merge(&other.rtt_histogram);
+    }
+
+    /// RTT percentile (nearest rank, 0-100) over all replies, to within about 1%
+    /// (None before any reply)
+    pub fn rtt_percentile(&self, percentile: f64) -> Option<Duration> {
+        self.rtt_histogram.percentile(percentile)
+    }
+
+    pub fn median_rtt(&self) -> Option<Duration> {
+        self.rtt_percentile(50.0)
+    }
+
+    /// Average RTT with every lost ping counted as taking `penalty`, one number that
+    /// reflects both latency and loss (None before any ping)
+    pub fn effective_rtt(&self, penalty: Duration) -> Option<Duration> {
+        if self.total_sent == 0 {
+            return None;
         }
+        let penalties = penalty.as_secs_f64() * self.total_lost as f64;
+        Some(Duration::from_secs_f64(
+            (self.sum_rtt.as_secs_f64() + penalties) / self.total_sent as f64,
+        ))
     }
 
     pub fn avg_rtt(&self) -> Option<Duration> {
@@ -147,29 +1083,510 @@ impl PingStats {
         }
     }
 
+    /// Share of pings answered since the start, as a percentage (100 before any are sent)
+    pub fn uptime_percent(&self) -> f64 {
+        if self.total_sent > 0 {
+            (self.total_received as f64 / self.total_sent as f64) * 100.0
+        } else {
+            100.0
+        }
+    }
+
     pub fn format_stats(&self) -> String {
-        let min = self
-            .min_rtt
-            .map(|d| format!("{:.1}", d.as_secs_f64() * 1000.0))
-            .unwrap_or("-".to_string());
-        let avg = self
-            .avg_rtt()
-            .map(|d| format!("{:.1}", d.as_secs_f64() * 1000.0))
-            .unwrap_or("-".to_string());
-        let max = self
-            .max_rtt
-            .map(|d| format!("{:.1}", d.as_secs_f64() * 1000.0))
-            .unwrap_or("-".to_string());
+        self.format_stats_in(RttUnit::Ms, DEFAULT_PRECISION)
+    }
+
+    /// RTT min/avg/max in one shared unit, e.g. "0.8/1.2/3.4 ms" ("-" before any reply)
+    pub fn rtt_summary(&self, unit: RttUnit, decimals: usize) -> String {
+        let ms = |d: Option<Duration>| d.map(|d| d.as_secs_f64() * 1000.0);
+        let rtts = [ms(self.min_rtt), ms(self.avg_rtt()), ms(self.max_rtt)];
+        let unit = unit.resolve_all(rtts.iter().flatten().copied());
+        let [min, avg, max] = rtts.map(|rtt| {
+            rtt.map(|ms| unit.number(ms, decimals))
+                .unwrap_or_else(|| "-".to_string())
+        });
+        format!("{}/{}/{} {}", min, avg, max, unit.suffix())
+    }
+
+    /// [`PingStats::format_stats`] with RTTs in the given unit and decimals
+    pub fn format_stats_in(&self, unit: RttUnit, decimals: usize) -> String {
+        let mut corrupted = if self.total_corrupted > 0 {
+            format!(", {} corrupted", self.total_corrupted)
+        } else {
+            String::new()
+        };
+        for (reason, count) in &self.loss_reasons {
+            corrupted += &format!(", {} {}", count, reason);
+        }
 
         format!(
-            "Sent: {} | Rcvd: {} | Lost: {} ({:.1}%) | RTT min/avg/max: {}/{}/{} ms",
+            "Sent: {} | Rcvd: {} | Lost: {} ({:.1}%{}) | Uptime: {:.1}% | Max burst: {} | RTT min/avg/max: {}",
             self.total_sent,
             self.total_received,
             self.total_lost,
             self.loss_percent(),
-            min,
-            avg,
-            max
+            corrupted,
+            self.uptime_percent(),
+            self.max_loss_streak,
+            self.rtt_summary(unit, decimals)
         )
     }
 }
+
+/// Letter grade (A-F) summarizing loss, latency and jitter for non-expert users
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quality {
+    pub grade: char,
+    /// The metric holding the grade down (None for an A)
+    pub reason: Option<&'static str>,
+}
+
+impl Quality {
+    /// Grade against the values at which each metric counts as bad (grade D)
+    /// Each metric is graded A below a quarter of its threshold, B below half, C below the
+    /// threshold, D below twice the threshold and F beyond; the worst metric decides
+    pub fn assess(
+        loss_percent: f64,
+        avg_rtt_ms: Option<f64>,
+        jitter_ms: Option<f64>,
+        (loss_bad, rtt_bad, jitter_bad): (f64, f64, f64),
+    ) -> Self {
+        let level = |value: f64, bad: f64| match value / bad.max(f64::EPSILON) {
+            r if r < 0.25 => 0,
+            r if r < 0.5 => 1,
+            r if r < 1.0 => 2,
+            r if r < 2.0 => 3,
+            _ => 4,
+        };
+        // With only timeouts there's no RTT to grade, but the loss alone fails it
+        let metrics = [
+            (level(loss_percent, loss_bad), "elevated loss", "high loss"),
+            (
+                avg_rtt_ms.map_or(0, |ms| level(ms, rtt_bad)),
+                "elevated latency",
+                "high latency",
+            ),
+            (
+                jitter_ms.map_or(0, |ms| level(ms, jitter_bad)),
+                "elevated jitter",
+                "high jitter",
+            ),
+        ];
+        let (worst, elevated, high) = metrics
+            .into_iter()
+            .fold((0, "", ""), |acc, m| if m.0 > acc.0 { m } else { acc });
+
+        Self {
+            grade: ['A', 'B', 'C', 'D', 'F'][worst],
+            reason: match worst {
+                0 => None,
+                1 | 2 => Some(elevated),
+                _ => Some(high),
+            },
+        }
+    }
+}
+
+/// Direction latency is heading in, for an at-a-glance "weather" arrow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Flat,
+}
+
+impl Trend {
+    /// Compare a short-term average RTT to a longer-term one; they must differ by more
+    /// than `sensitivity` percent of the longer-term average to count as a trend
+    pub fn assess(recent_ms: f64, longer_ms: f64, sensitivity: f64) -> Self {
+        let margin = longer_ms * sensitivity / 100.0;
+        if recent_ms > longer_ms + margin {
+            Trend::Rising
+        } else if recent_ms < longer_ms - margin {
+            Trend::Falling
+        } else {
+            Trend::Flat
+        }
+    }
+
+    pub fn arrow(self) -> char {
+        match self {
+            Trend::Rising => '↑',
+            Trend::Falling => '↓',
+            Trend::Flat => '→',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loss_streaks() {
+        let mut stats = PingStats::new();
+        let now = Instant::now();
+        let ok = |seq| PingResult::success(seq, Duration::from_millis(10), now, None);
+
+        stats.record(&ok(1));
+        stats.record(&PingResult::timeout(2, now));
+        stats.record(&PingResult::timeout(3, now));
+        stats.record(&PingResult::timeout(4, now));
+        stats.record(&ok(5));
+        stats.record(&PingResult::timeout(6, now));
+
+        assert_eq!(stats.current_loss_streak, 1);
+        assert_eq!(stats.max_loss_streak, 3);
+        assert!((stats.uptime_percent() - 100.0 / 3.0).abs() < 1e-9);
+        assert!(stats.last_loss_at.is_some());
+    }
+
+    #[test]
+    fn test_merge_and_percentiles() {
+        let now = Instant::now();
+        let stats_of = |rtts: &[u64]| {
+            let mut stats = PingStats::new();
+            for (seq, &ms) in rtts.iter().enumerate() {
+                stats.record(&match ms {
+                    0 => PingResult::timeout(seq as u64, now),
+                    ms => PingResult::success(seq as u64, Duration::from_millis(ms), now, None),
+                });
+            }
+            stats
+        };
+        let mut stats = stats_of(&[10, 20, 0, 30]);
+        stats.merge(&stats_of(&[40, 0, 0, 50]));
+        assert_eq!(stats.total_sent, 8);
+        assert_eq!(stats.total_lost, 3);
+        assert_eq!(stats.max_loss_streak, 2);
+        assert_eq!(stats.min_rtt, Some(Duration::from_millis(10)));
+        assert_eq!(stats.max_rtt, Some(Duration::from_millis(50)));
+        assert_eq!(stats.avg_rtt(), Some(Duration::from_millis(30)));
+
+        let ms = |rtt: Option<Duration>| rtt.unwrap().as_secs_f64() * 1000.0;
+        assert!((ms(stats.median_rtt()) - 30.0).abs() < 0.3);
+        // The ends are exact
+        assert_eq!(ms(stats.rtt_percentile(100.0)), 50.0);
+        assert_eq!(ms(stats.rtt_percentile(0.0)), 10.0);
+        assert_eq!(PingStats::new().median_rtt(), None);
+
+        // 3 of 8 lost at a 1s penalty: (150ms + 3s) / 8
+        let penalty = Duration::from_secs(1);
+        assert_eq!(
+            stats.effective_rtt(penalty),
+            Some(Duration::from_micros(393_750))
+        );
+        assert_eq!(PingStats::new().effective_rtt(penalty), None);
+    }
+
+    #[test]
+    fn test_quality_grade() {
+        let thresholds = (5.0, 100.0, 30.0);
+        let grade = |loss, rtt, jitter| Quality::assess(loss, rtt, jitter, thresholds);
+
+        assert_eq!(
+            grade(0.0, Some(10.0), Some(1.0)),
+            Quality {
+                grade: 'A',
+                reason: None
+            }
+        );
+        assert_eq!(
+            grade(0.0, Some(10.0), Some(10.0)),
+            Quality {
+                grade: 'B',
+                reason: Some("elevated jitter")
+            }
+        );
+        // The worst metric decides
+        assert_eq!(grade(3.0, Some(150.0), Some(10.0)).grade, 'D');
+        assert_eq!(
+            grade(3.0, Some(150.0), Some(10.0)).reason,
+            Some("high latency")
+        );
+        assert_eq!(grade(100.0, None, None).grade, 'F');
+    }
+
+    #[test]
+    fn test_trend() {
+        assert_eq!(Trend::assess(12.0, 10.0, 10.0), Trend::Rising);
+        assert_eq!(Trend::assess(10.5, 10.0, 10.0), Trend::Flat);
+        assert_eq!(Trend::assess(8.0, 10.0, 10.0), Trend::Falling);
+        assert_eq!(Trend::assess(10.5, 10.0, 1.0), Trend::Rising);
+    }
+
+    #[test]
+    fn test_interval_sweep() {
+        let sweep = IntervalSweep {
+            start_ms: 1000,
+            end_ms: 100,
+            duration_ms: 60_000,
+        };
+        assert_eq!(sweep.interval_at(Duration::ZERO), 1000);
+        assert_eq!(sweep.interval_at(Duration::from_secs(30)), 550);
+        assert_eq!(sweep.interval_at(Duration::from_secs(60)), 100);
+        assert_eq!(sweep.interval_at(Duration::from_secs(600)), 100);
+    }
+
+    #[test]
+    fn test_jitter_tracker_out_of_order() {
+        let ms = Duration::from_millis;
+        let mut jitter = JitterTracker::default();
+        assert_eq!(jitter.complete(1, Some(ms(10))), None);
+        // Seq 3 replies before the slow seq 2
+        assert_eq!(jitter.complete(3, Some(ms(12))), Some(ms(10)));
+        assert_eq!(jitter.complete(2, Some(ms(900))), None);
+        assert_eq!(jitter.complete(4, Some(ms(11))), Some(ms(12)));
+        // A late timeout doesn't clear the newer reply either
+        assert_eq!(jitter.complete(6, Some(ms(13))), Some(ms(11)));
+        assert_eq!(jitter.complete(5, None), None);
+        assert_eq!(jitter.complete(7, None), Some(ms(13)));
+        assert_eq!(jitter.complete(8, Some(ms(14))), None);
+    }
+
+    #[test]
+    fn test_jitter_undefined_after_timeout() {
+        let ms = Duration::from_millis;
+        let now = Instant::now();
+        let mut tracker = JitterTracker::default();
+        let mut reply = |seq, rtt| {
+            let prev = tracker.complete(seq, rtt);
+            match rtt {
+                Some(rtt) => PingResult::success(seq, rtt, now, prev),
+                None => PingResult::timeout(seq, now),
+            }
+        };
+
+        let results = [
+            reply(1, Some(ms(10))),
+            reply(2, Some(ms(14))),
+            reply(3, None),
+            reply(4, Some(ms(30))),
+            reply(5, Some(ms(31))),
+        ];
+        let jitter: Vec<Option<f64>> = results.iter().map(PingResult::jitter_ms_f64).collect();
+        // Only defined after two replies in a row: not for the first reply after the timeout
+        assert_eq!(jitter, [None, Some(4.0), None, None, Some(1.0)]);
+        assert_eq!(mean_jitter(&results), Some(2.5));
+        assert_eq!(outlier_limit([20.0, 2000.0, 10.0], 3.0), Some(60.0));
+        assert_eq!(outlier_limit([], 3.0), None);
+        assert_eq!(RttUnit::Ms.format_jitter(jitter[3], 2), "-");
+        assert_eq!(RttUnit::Ms.format_jitter(jitter[1], 2), "±4.00ms");
+        assert_eq!(RttUnit::Ms.format_jitter(jitter[1], 0), "±4ms");
+    }
+
+    #[test]
+    fn test_smoothed_jitter() {
+        let ms = Duration::from_millis;
+        let mut jitter = JitterTracker::default();
+        jitter.complete(1, Some(ms(10)));
+        assert_eq!(jitter.smoothed(), None);
+
+        // J starts at 0 and moves 1/16 of the way towards each delta
+        jitter.complete(2, Some(ms(26)));
+        let j = jitter.smoothed().unwrap().as_secs_f64() * 1000.0;
+        assert!((j - 1.0).abs() < 1e-9);
+        jitter.complete(3, Some(ms(10)));
+        let j = jitter.smoothed().unwrap().as_secs_f64() * 1000.0;
+        assert!((j - (1.0 + 15.0 / 16.0)).abs() < 1e-9);
+
+        // Timeouts leave the estimate alone
+        jitter.complete(4, None);
+        jitter.complete(5, Some(ms(10)));
+        let after = jitter.smoothed().unwrap().as_secs_f64() * 1000.0;
+        assert!((after - j).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(normalize_host("  example.com ").unwrap(), "example.com");
+        assert_eq!(
+            normalize_host("https://user@example.com/path?q=1").unwrap(),
+            "example.com"
+        );
+        assert_eq!(
+            normalize_host("https://example.com:8443/").unwrap(),
+            "example.com:8443"
+        );
+        assert_eq!(normalize_host("8.8.8.8:53").unwrap(), "8.8.8.8:53");
+        assert_eq!(
+            normalize_host("[2001:db8::1]:80").unwrap(),
+            "[2001:db8::1]:80"
+        );
+        assert_eq!(normalize_host("[2001:db8::1]").unwrap(), "2001:db8::1");
+        assert_eq!(normalize_host("example.com:").unwrap(), "example.com");
+        assert!(normalize_host("example.com:99999").is_err());
+        assert!(normalize_host("example.com:0").is_err());
+        assert_eq!(normalize_host("2001:db8::1").unwrap(), "2001:db8::1");
+        assert!(normalize_host("bad host").is_err());
+        assert!(normalize_host("-leading.example").is_err());
+        assert!(normalize_host("").is_err());
+        assert_eq!(normalize_host("fe80::1%eth0").unwrap(), "fe80::1%eth0");
+        assert_eq!(normalize_host("[fe80::1%3]:80").unwrap(), "[fe80::1%3]:80");
+        assert!(normalize_host("fe80::1%").is_err());
+        assert!(normalize_host("fe80::1%eth 0").is_err());
+    }
+
+    #[test]
+    fn test_split_port() {
+        assert_eq!(split_port("example.com:8080"), ("example.com", Some(8080)));
+        assert_eq!(split_port("example.com"), ("example.com", None));
+        assert_eq!(split_port("[fe80::1%eth0]:53"), ("fe80::1%eth0", Some(53)));
+        assert_eq!(split_port("2001:db8::1"), ("2001:db8::1", None));
+        assert_eq!(scope_id("[fe80::1%3]:53"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_zone() {
+        assert_eq!(
+            resolve_host("fe80::1%3", None, None).await.unwrap(),
+            "fe80::1".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(scope_id("fe80::1%3"), 3);
+        assert_eq!(scope_id("fe80::1"), 0);
+        assert!(resolve_host("2001:db8::1%3", None, None).await.is_err());
+        assert!(resolve_host("fe80::1%0", None, None).await.is_err());
+        assert!(
+            resolve_host("fe80::1%no-such-interface0", None, None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hosts_file() {
+        let contents = "# comment\n127.0.0.1 localhost\n10.1.2.3  Build.Lab  build # ci box\n\
+                        fe80::1%eth0 router\n2001:db8::7 build\n";
+        assert_eq!(lookup_hosts(contents, "build.lab"), "10.1.2.3".parse().ok());
+        assert_eq!(lookup_hosts(contents, "build."), "10.1.2.3".parse().ok());
+        assert_eq!(lookup_hosts(contents, "router"), None);
+        assert_eq!(lookup_hosts(contents, "box"), None);
+
+        let path = std::env::temp_dir().join(format!("rttui-hosts-{}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let ip = resolve_host("build", None, Some(&path)).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(ip, "10.1.2.3".parse::<IpAddr>().unwrap());
+        assert!(resolve_host("build", None, Some(&path)).await.is_err());
+    }
+
+    #[test]
+    fn test_check_target_ip() {
+        let check = |ip: &str| check_target_ip(ip.parse().unwrap());
+        assert!(check("0.0.0.0").is_err());
+        assert!(check("::").is_err());
+        assert!(check("224.0.0.1").is_err());
+        assert!(check("255.255.255.255").is_err());
+        assert!(check("127.0.0.1").unwrap().is_some());
+        assert!(check("169.254.1.1").unwrap().is_some());
+        assert!(check("fe80::1").unwrap().is_some());
+        assert_eq!(check("1.1.1.1").unwrap(), None);
+    }
+
+    #[test]
+    fn test_wait_tracker() {
+        let now = Instant::now();
+        let reply = PingResult::success(1, Duration::from_millis(10), now, None);
+        let lost = PingResult::timeout(2, now);
+
+        let mut up = WaitTracker::new(WaitFor::Up);
+        assert!(!up.update(&lost));
+        assert!(up.update(&reply));
+
+        // Only losses in a row count
+        let mut down = WaitTracker::new(WaitFor::Down(2));
+        assert!(!down.update(&lost));
+        assert!(!down.update(&reply));
+        assert!(!down.update(&lost));
+        assert!(down.update(&lost));
+    }
+
+    #[test]
+    fn test_rtt_unit() {
+        assert_eq!(RttUnit::Ms.format(0.345, 2), "0.34ms");
+        assert_eq!(RttUnit::Us.format(0.345, 2), "345µs");
+        assert_eq!(RttUnit::Us.format(12.5, 2), "12500µs");
+        assert_eq!(RttUnit::Auto.format(0.345, 2), "345µs");
+        assert_eq!(RttUnit::Auto.format(12.5, 2), "12.50ms");
+        assert_eq!(RttUnit::Ms.format(12.5, 0), "12ms");
+        assert_eq!(RttUnit::Ms.format(0.345, 3), "0.345ms");
+
+        // min/avg/max share the unit picked from the largest value
+        let mut stats = PingStats::default();
+        for us in [300, 900] {
+            stats.record(&PingResult::success(
+                0,
+                Duration::from_micros(us),
+                Instant::now(),
+                None,
+            ));
+        }
+        assert_eq!(stats.rtt_summary(RttUnit::Auto, 2), "300/600/900 µs");
+        stats.record(&PingResult::success(
+            0,
+            Duration::from_micros(1500),
+            Instant::now(),
+            None,
+        ));
+        assert_eq!(stats.rtt_summary(RttUnit::Auto, 2), "0.30/0.90/1.50 ms");
+    }
+
+    #[test]
+    fn test_utc_timestamps() {
+        let time = DateTime::parse_from_rfc3339("2024-03-01T23:30:15.250+02:00")
+            .unwrap()
+            .with_timezone(&Local);
+        assert_eq!(format_time(time, "%H:%M:%S%.3f", true), "21:30:15.250");
+        assert_eq!(rfc3339(time, true), "2024-03-01T21:30:15.250Z");
+        // Local times keep their offset, so they still describe the same instant
+        assert_eq!(
+            DateTime::parse_from_rfc3339(&rfc3339(time, false)).unwrap(),
+            time
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_missed_ticks() {
+        // Stall past two send times, then count the sends that fire at once
+        async fn catch_up_sends(missed: MissedTicks) -> usize {
+            let mut ticker = ProbeTicker::new(40, None, missed);
+            assert_eq!(ticker.tick().await, None);
+            tokio::time::advance(Duration::from_millis(90)).await;
+            let mut sends = 0;
+            while let Ok(sched_jitter) =
+                tokio::time::timeout(Duration::from_millis(10), ticker.tick()).await
+            {
+                // The first send after the stall is 50 ms late
+                if sends == 0 {
+                    assert_eq!(sched_jitter, Some(Duration::from_millis(50)));
+                }
+                sends += 1;
+            }
+            sends
+        }
+        assert_eq!(catch_up_sends(MissedTicks::Skip).await, 1);
+        assert_eq!(catch_up_sends(MissedTicks::Burst).await, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interval_jitter() {
+        let mut ticker = ProbeTicker::new(20, None, MissedTicks::Skip).interval_jitter(50.0);
+        ticker.tick().await;
+        let mut last = tokio::time::Instant::now();
+        let mut gaps = Vec::new();
+        for _ in 0..20 {
+            ticker.tick().await;
+            gaps.push(last.elapsed().as_secs_f64() * 1000.0);
+            last = tokio::time::Instant::now();
+        }
+        // Between 10 and 30 ms, and not all the same
+        assert!(
+            gaps.iter().all(|&gap| (10.0..=30.0).contains(&gap)),
+            "{:?}",
+            gaps
+        );
+        let spread = gaps.iter().copied().fold(f64::MIN, f64::max)
+            - gaps.iter().copied().fold(f64::MAX, f64::min);
+        assert!(spread > 4.0, "{:?}", gaps);
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Default SOCKS port when the proxy URL doesn't give one
+const DEFAULT_PORT: u16 = 1080;
+
+/// SOCKS5 proxy (RFC 1928, no authentication) that TCP probes connect through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Socks5Proxy {
+    host: String,
+    port: u16,
+}
+
+impl Socks5Proxy {
+    /// Parse "socks5://host:port" (port 1080 unless given; IPv6 hosts in brackets)
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let rest = input
+            .trim()
+            .strip_prefix("socks5://")
+            .ok_or_else(|| format!("Proxy '{}' must start with socks5://", input))?;
+        let rest = rest.trim_end_matches('/');
+        let (host, port) = match rest.strip_prefix('[') {
+            // "[::1]:1080"
+            Some(bracketed) => {
+                let (host, port) = bracketed
+                    .split_once(']')
+                    .ok_or_else(|| format!("Invalid proxy address '{}'", rest))?;
+                (host, port.strip_prefix(':'))
+            }
+            None => match rest.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (rest, None),
+            },
+        };
+        if host.is_empty() || host.contains(['/', '@', ' ']) {
+            return Err(format!("Invalid proxy address '{}'", rest));
+        }
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .map_err(|_| format!("Invalid proxy port '{}'", port))?,
+            None => DEFAULT_PORT,
+        };
+        Ok(Self {
+            host: host.to_string(),
+            port,
+        })
+    }
+
+    /// Open a TCP connection to `target` through the proxy
+    pub async fn connect(&self, target: SocketAddr) -> std::io::Result<TcpStream> {
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port)).await?;
+        stream.set_nodelay(true)?;
+
+        // Greeting: version 5, one method, "no authentication"
+        stream.write_all(&[5, 1, 0]).await?;
+        let mut reply = [0u8; 2];
+        stream.read_exact(&mut reply).await?;
+        if reply != [5, 0] {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "SOCKS5 proxy requires authentication",
+            ));
+        }
+
+        // CONNECT to the target's address
+        let mut request = vec![5, 1, 0];
+        match target {
+            SocketAddr::V4(addr) => {
+                request.push(1);
+                request.extend_from_slice(&addr.ip().octets());
+            }
+            SocketAddr::V6(addr) => {
+                request.push(4);
+                request.extend_from_slice(&addr.ip().octets());
+            }
+        }
+        request.extend_from_slice(&target.port().to_be_bytes());
+        stream.write_all(&request).await?;
+
+        // Reply: version, status, reserved, then the bound address (skipped)
+        let mut header = [0u8; 4];
+        stream.read_exact(&mut header).await?;
+        if header[1] != 0 {
+            return Err(Error::new(
+                ErrorKind::ConnectionRefused,
+                format!("SOCKS5 proxy refused the connection (code {})", header[1]),
+            ));
+        }
+        let addr_len = match header[3] {
+            1 => 4,
+            4 => 16,
+            3 => stream.read_u8().await? as usize,
+            _ => return Err(ErrorKind::InvalidData.into()),
+        };
+        let mut bound = vec![0u8; addr_len + 2];
+        stream.read_exact(&mut bound).await?;
+
+        Ok(stream)
+    }
+}
+
+impl fmt::Display for Socks5Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "socks5://[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "socks5://{}:{}", self.host, self.port)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proxy() {
+        let proxy = Socks5Proxy::parse("socks5://bastion:9050").unwrap();
+        assert_eq!(proxy.to_string(), "socks5://bastion:9050");
+        let proxy = Socks5Proxy::parse("socks5://[::1]").unwrap();
+        assert_eq!(proxy.to_string(), "socks5://[::1]:1080");
+        assert!(Socks5Proxy::parse("http://proxy:8080").is_err());
+        assert!(Socks5Proxy::parse("socks5://proxy:port").is_err());
+        assert!(Socks5Proxy::parse("socks5://").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_through_proxy() {
+        // A proxy that accepts a CONNECT to 192.0.2.1:443 and then echoes
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut greeting = [0u8; 3];
+            stream.read_exact(&mut greeting).await.unwrap();
+            stream.write_all(&[5, 0]).await.unwrap();
+            let mut request = [0u8; 10];
+            stream.read_exact(&mut request).await.unwrap();
+            assert_eq!(request, [5, 1, 0, 1, 192, 0, 2, 1, 1, 187]);
+            stream
+                .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0])
+                .await
+                .unwrap();
+            let mut byte = [0u8; 1];
+            stream.read_exact(&mut byte).await.unwrap();
+            stream.write_all(&byte).await.unwrap();
+        });
+
+        let proxy = Socks5Proxy::parse(&format!("socks5://127.0.0.1:{}", port)).unwrap();
+        let mut stream = proxy
+            .connect("192.0.2.1:443".parse().unwrap())
+            .await
+            .unwrap();
+        stream.write_all(b"x").await.unwrap();
+        assert_eq!(stream.read_u8().await.unwrap(), b'x');
+    }
+}
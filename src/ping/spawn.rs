@@ -0,0 +1,271 @@
+use super::demo::DemoPinger;
+use super::icmp::IcmpPinger;
+use super::socks::Socks5Proxy;
+use super::tls::TlsPinger;
+use super::udp::UdpClientPinger;
+use super::{
+    InFlight, IntervalSweep, LocalAddr, MissedTicks, PingResult, Pinger, ProbeSeq, ProbeSwitch,
+    scope_id, split_port, split_zone,
+};
+use std::net::{IpAddr, SocketAddr, SocketAddrV6};
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// What a pinger started from a [`PingerConfig`] measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Probe {
+    /// ICMP echo requests (a port or zone id in the host isn't used, see
+    /// [`icmp::check_target`](super::icmp::check_target))
+    Icmp,
+    /// UDP echo packets to a rttui server on this port
+    Udp(u16),
+    /// TCP connect + TLS handshake to this port
+    Tls(u16),
+    /// Simulated replies, without sending anything
+    Demo,
+}
+
+/// Everything needed to start a pinger, built up with setters
+/// The defaults match the CLI: ICMP, a 1000ms interval and a 3000ms timeout
+pub struct PingerConfig {
+    /// Target as given, for a port, IPv6 zone or TLS server name it carries
+    host: String,
+    ip: IpAddr,
+    probe: Probe,
+    interval_ms: u64,
+    timeout_ms: u64,
+    /// Source address to send from (UDP)
+    source: Option<IpAddr>,
+    /// Accept replies that don't match a sent probe (UDP)
+    lenient: bool,
+    /// Server name for the TLS handshake (None = the host)
+    sni: Option<String>,
+    /// SOCKS5 proxy to connect through (TLS)
+    proxy: Option<Socks5Proxy>,
+    /// Interval sweep and when it started (None = fixed interval)
+    sweep: Option<(IntervalSweep, Instant)>,
+    missed_ticks: MissedTicks,
+    interval_jitter: f64,
+    switch: ProbeSwitch,
+    seq: ProbeSeq,
+    in_flight: InFlight,
+    local_addr: LocalAddr,
+}
+
+impl PingerConfig {
+    /// Ping `host`, already resolved to `ip` (e.g. with [`resolve_host`](super::resolve_host))
+    pub fn new(host: &str, ip: IpAddr) -> Self {
+        Self {
+            host: host.to_string(),
+            ip,
+            probe: Probe::Icmp,
+            interval_ms: 1000,
+            timeout_ms: 3000,
+            source: None,
+            lenient: false,
+            sni: None,
+            proxy: None,
+            sweep: None,
+            missed_ticks: MissedTicks::default(),
+            interval_jitter: 0.0,
+            switch: ProbeSwitch::default(),
+            seq: ProbeSeq::default(),
+            in_flight: InFlight::default(),
+            local_addr: LocalAddr::default(),
+        }
+    }
+
+    /// Choose what to measure
+    pub fn probe(mut self, probe: Probe) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// Time between sends in milliseconds, at least 1 (a zero period would make the
+    /// ticker panic)
+    pub fn interval(mut self, interval_ms: u64) -> Self {
+        self.interval_ms = interval_ms.max(1);
+        self
+    }
+
+    /// Time to wait for a reply in milliseconds, at least 1 (a zero timeout would count
+    /// every probe as lost before it could be answered)
+    pub fn timeout(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms.max(1);
+        self
+    }
+
+    /// Send from this source address (UDP)
+    pub fn source(mut self, source: Option<IpAddr>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Accept replies that don't match a sent probe (UDP)
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Server name to send in the TLS handshake instead of the host
+    pub fn sni(mut self, sni: Option<String>) -> Self {
+        self.sni = sni;
+        self
+    }
+
+    /// Connect through a SOCKS5 proxy (TLS)
+    pub fn proxy(mut self, proxy: Option<Socks5Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Follow an interval sweep that started at `started` instead of the fixed interval
+    pub fn sweep(mut self, sweep: IntervalSweep, started: Instant) -> Self {
+        self.sweep = Some((sweep, started));
+        self
+    }
+
+    /// Choose how sends missed while falling behind schedule are made up for
+    pub fn missed_ticks(mut self, missed_ticks: MissedTicks) -> Self {
+        self.missed_ticks = missed_ticks;
+        self
+    }
+
+    /// Randomize each gap between sends by up to ±`percent`% of the interval
+    pub fn interval_jitter(mut self, percent: f64) -> Self {
+        self.interval_jitter = percent;
+        self
+    }
+
+    /// Share a switch that stops and resumes sending
+    pub fn switch(mut self, switch: ProbeSwitch) -> Self {
+        self.switch = switch;
+        self
+    }
+
+    /// Share a sequence counter, e.g. to continue the numbering of a pinger this one replaces
+    pub fn seq(mut self, seq: ProbeSeq) -> Self {
+        self.seq = seq;
+        self
+    }
+
+    /// Share a counter of probes awaiting a reply (UDP)
+    pub fn in_flight(mut self, in_flight: InFlight) -> Self {
+        self.in_flight = in_flight;
+        self
+    }
+
+    /// Share the local address the socket got bound to (UDP)
+    pub fn local_addr(mut self, local_addr: LocalAddr) -> Self {
+        self.local_addr = local_addr;
+        self
+    }
+
+    /// Start the pinger, sending its results to `tx`
+    /// Sending into an existing channel lets a new pinger take over from an old one
+    pub fn start(self, tx: mpsc::UnboundedSender<PingResult>) -> JoinHandle<()> {
+        match self.probe {
+            Probe::Demo => {
+                let mut pinger = DemoPinger::new(&self.host, self.interval_ms, self.timeout_ms)
+                    .switch(self.switch)
+                    .seq(self.seq)
+                    .missed_ticks(self.missed_ticks)
+                    .interval_jitter(self.interval_jitter);
+                if let Some((sweep, started)) = self.sweep {
+                    pinger = pinger.sweep(sweep, started);
+                }
+                Box::new(pinger).start(tx)
+            }
+            Probe::Icmp => {
+                let mut pinger = IcmpPinger::new(self.ip, self.interval_ms, self.timeout_ms)
+                    .switch(self.switch)
+                    .seq(self.seq)
+                    .missed_ticks(self.missed_ticks)
+                    .interval_jitter(self.interval_jitter);
+                if let Some((sweep, started)) = self.sweep {
+                    pinger = pinger.sweep(sweep, started);
+                }
+                Box::new(pinger).start(tx)
+            }
+            Probe::Udp(port) => {
+                let target = socket_addr(&self.host, self.ip, port);
+                let mut pinger = UdpClientPinger::new(target, self.interval_ms, self.timeout_ms)
+                    .source(self.source)
+                    .lenient(self.lenient)
+                    .in_flight(self.in_flight)
+                    .local_addr(self.local_addr)
+                    .switch(self.switch)
+                    .seq(self.seq)
+                    .missed_ticks(self.missed_ticks)
+                    .interval_jitter(self.interval_jitter);
+                if let Some((sweep, started)) = self.sweep {
+                    pinger = pinger.sweep(sweep, started);
+                }
+                Box::new(pinger).start(tx)
+            }
+            Probe::Tls(port) => {
+                let target = socket_addr(&self.host, self.ip, port);
+                let server_name = self
+                    .sni
+                    .as_deref()
+                    .unwrap_or_else(|| split_zone(split_port(&self.host).0).0);
+                let mut pinger =
+                    TlsPinger::new(target, server_name, self.interval_ms, self.timeout_ms)
+                        .proxy(self.proxy)
+                        .switch(self.switch)
+                        .seq(self.seq)
+                        .missed_ticks(self.missed_ticks)
+                        .interval_jitter(self.interval_jitter);
+                if let Some((sweep, started)) = self.sweep {
+                    pinger = pinger.sweep(sweep, started);
+                }
+                Box::new(pinger).start(tx)
+            }
+        }
+    }
+}
+
+/// Start a pinger on the current tokio runtime and return its task with the stream of
+/// results it sends, the same [`PingResult`]s the TUI graphs
+/// Aborting the task stops the pinger; replies to probes already sent may still arrive
+///
+/// ```
+/// use rttui::{PingStats, PingerConfig, Probe, spawn_pinger};
+/// # use std::net::{IpAddr, Ipv4Addr};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let ip = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+/// // `ip` from `resolve_host("example.com", None, None).await?`, and `Probe::Icmp`
+/// // to ping it for real
+/// let config = PingerConfig::new("example.com", ip)
+///     .probe(Probe::Demo)
+///     .interval(10);
+/// let (handle, mut rx) = spawn_pinger(config);
+///
+/// let mut stats = PingStats::new();
+/// while let Some(result) = rx.recv().await {
+///     stats.record(&result);
+///     if stats.total_sent == 5 {
+///         break;
+///     }
+/// }
+/// handle.abort();
+/// assert_eq!(stats.total_sent, 5);
+/// # }
+/// ```
+pub fn spawn_pinger(config: PingerConfig) -> (JoinHandle<()>, mpsc::UnboundedReceiver<PingResult>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    (config.start(tx), rx)
+}
+
+/// Socket address of a target, keeping the zone of a link-local one ("fe80::1%eth0");
+/// a port given with the target ("example.com:8080") overrides `port`
+pub fn socket_addr(host: &str, ip: IpAddr, port: u16) -> SocketAddr {
+    let port = split_port(host).1.unwrap_or(port);
+    match ip {
+        IpAddr::V6(ip) => SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope_id(host))),
+        IpAddr::V4(_) => SocketAddr::new(ip, port),
+    }
+}
@@ -0,0 +1,218 @@
+use super::socks::Socks5Proxy;
+use super::{
+    IntervalSweep, JitterTracker, MissedTicks, PingResult, Pinger, ProbeSeq, ProbeSwitch,
+    ProbeTicker,
+};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+
+/// TLS handshake timing: every probe opens a TCP connection and completes a TLS
+/// handshake, reporting connect + handshake time as the RTT
+///
+/// This is closer to the latency users of an HTTPS service see than an echo. A
+/// failed connect or handshake (including an untrusted certificate) is a timeout
+pub struct TlsPinger {
+    target: SocketAddr,
+    /// Sent as SNI and checked against the certificate (None = invalid name, every
+    /// probe fails)
+    server_name: Option<ServerName<'static>>,
+    interval_ms: u64,
+    timeout_ms: u64,
+    /// Skips sending while stopped
+    switch: ProbeSwitch,
+    /// Hands out sequence numbers (shared with a pinger this one replaces)
+    seq: ProbeSeq,
+    /// Interval sweep and when it started (None = fixed interval)
+    sweep: Option<(IntervalSweep, Instant)>,
+    /// How sends missed while falling behind schedule are made up for
+    missed_ticks: MissedTicks,
+    /// Randomization of the gaps between sends, in percent of the interval
+    interval_jitter: f64,
+    /// Proxy to connect through (the RTT then includes the hop to the proxy)
+    proxy: Option<Socks5Proxy>,
+}
+
+impl TlsPinger {
+    pub fn new(target: SocketAddr, server_name: &str, interval_ms: u64, timeout_ms: u64) -> Self {
+        Self {
+            target,
+            server_name: parse_server_name(server_name).ok(),
+            interval_ms,
+            timeout_ms,
+            switch: ProbeSwitch::default(),
+            seq: ProbeSeq::default(),
+            sweep: None,
+            missed_ticks: MissedTicks::default(),
+            interval_jitter: 0.0,
+            proxy: None,
+        }
+    }
+
+    /// Share a switch that stops and resumes sending
+    pub fn switch(mut self, switch: ProbeSwitch) -> Self {
+        self.switch = switch;
+        self
+    }
+
+    /// Share a sequence counter, e.g. to continue the numbering of a pinger this one replaces
+    pub fn seq(mut self, seq: ProbeSeq) -> Self {
+        self.seq = seq;
+        self
+    }
+
+    /// Follow an interval sweep that started at `started` instead of the fixed interval
+    pub fn sweep(mut self, sweep: IntervalSweep, started: Instant) -> Self {
+        self.sweep = Some((sweep, started));
+        self
+    }
+
+    /// Choose how sends missed while falling behind schedule are made up for
+    pub fn missed_ticks(mut self, missed_ticks: MissedTicks) -> Self {
+        self.missed_ticks = missed_ticks;
+        self
+    }
+
+    /// Randomize each gap between sends by up to ±`percent`% of the interval
+    pub fn interval_jitter(mut self, percent: f64) -> Self {
+        self.interval_jitter = percent;
+        self
+    }
+
+    /// Connect through a SOCKS5 proxy instead of directly
+    pub fn proxy(mut self, proxy: Option<Socks5Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+}
+
+/// Check a TLS server name (hostname or IP address)
+pub fn parse_server_name(name: &str) -> anyhow::Result<ServerName<'static>> {
+    ServerName::try_from(name.to_string())
+        .map_err(|_| anyhow::anyhow!("Invalid TLS server name '{}'", name))
+}
+
+/// Connector trusting the Mozilla root certificates bundled with webpki-roots
+fn connector() -> TlsConnector {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+/// Connect and complete a handshake, returning how long that took
+async fn handshake(
+    connector: &TlsConnector,
+    target: SocketAddr,
+    server_name: ServerName<'static>,
+    proxy: Option<&Socks5Proxy>,
+) -> std::io::Result<Duration> {
+    let start = Instant::now();
+    let tcp = match proxy {
+        Some(proxy) => proxy.connect(target).await?,
+        None => TcpStream::connect(target).await?,
+    };
+    tcp.set_nodelay(true)?;
+    let mut tls = connector.connect(server_name, tcp).await?;
+    let rtt = start.elapsed();
+    // Close cleanly (untimed) so servers don't log aborted handshakes
+    let _ = tls.shutdown().await;
+    Ok(rtt)
+}
+
+impl Pinger for TlsPinger {
+    fn start(
+        self: Box<Self>,
+        tx: mpsc::UnboundedSender<PingResult>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = ProbeTicker::new(self.interval_ms, self.sweep, self.missed_ticks)
+                .interval_jitter(self.interval_jitter);
+            let jitter = Arc::new(Mutex::new(JitterTracker::default()));
+            let connector = connector();
+
+            loop {
+                let sched_jitter = ticker.tick().await;
+                if self.switch.is_stopped() {
+                    continue;
+                }
+
+                let sent_at = Instant::now();
+                let current_seq = self.seq.next();
+                let target = self.target;
+                let server_name = self.server_name.clone();
+                let timeout = Duration::from_millis(self.timeout_ms);
+                let connector = connector.clone();
+                let proxy = self.proxy.clone();
+                let tx_clone = tx.clone();
+                let jitter = jitter.clone();
+
+                // Each probe is its own connection, so a slow handshake doesn't hold up the next
+                tokio::spawn(async move {
+                    let result = match server_name {
+                        Some(server_name) => {
+                            tokio::time::timeout(
+                                timeout,
+                                handshake(&connector, target, server_name, proxy.as_ref()),
+                            )
+                            .await
+                        }
+                        None => Ok(Err(std::io::ErrorKind::InvalidInput.into())),
+                    };
+
+                    let ping_result = match result {
+                        Ok(Ok(rtt)) => {
+                            let mut jitter = jitter.lock().unwrap();
+                            let prev = jitter.complete(current_seq, Some(rtt));
+                            PingResult::success(current_seq, rtt, sent_at, prev)
+                                .with_smoothed_jitter(jitter.smoothed())
+                        }
+                        _ => {
+                            jitter.lock().unwrap().complete(current_seq, None);
+                            PingResult::timeout(current_seq, sent_at)
+                        }
+                    };
+
+                    let _ = tx_clone.send(ping_result.with_sched_jitter(sched_jitter));
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failed_handshake_is_timeout() {
+        // A server that accepts and hangs up without speaking TLS
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                drop(stream);
+            }
+        });
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let handle = Box::new(TlsPinger::new(target, "localhost", 50, 1000)).start(tx);
+        let result = rx.recv().await.unwrap();
+        handle.abort();
+        assert_eq!(result.seq, 1);
+        assert!(result.rtt.is_none());
+
+        assert!(parse_server_name("example.com").is_ok());
+        assert!(parse_server_name("192.0.2.1").is_ok());
+        assert!(parse_server_name("not a name").is_err());
+    }
+}
@@ -1,6 +1,9 @@
-use super::{PingResult, Pinger};
-use std::collections::HashMap;
-use std::net::SocketAddr;
+use super::{
+    InFlight, IntervalSweep, JitterTracker, LocalAddr, MissedTicks, PingResult, Pinger, ProbeSeq,
+    ProbeSwitch, ProbeTicker,
+};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr, SocketAddrV6};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::net::UdpSocket;
@@ -10,12 +13,15 @@ use tokio::time::interval;
 /// Magic bytes for UDP ping packets
 const MAGIC: &[u8; 4] = b"PING";
 
+/// Length of an encoded ping packet
+const PACKET_LEN: usize = 20;
+
 /// UDP packet structure (20 bytes total):
 /// - Magic: 4 bytes "PING"
 /// - Sequence: 8 bytes (u64 big-endian)
 /// - Timestamp: 8 bytes (microseconds since start, u64 big-endian)
-fn encode_packet(seq: u64, timestamp_us: u64) -> [u8; 20] {
-    let mut buf = [0u8; 20];
+fn encode_packet(seq: u64, timestamp_us: u64) -> [u8; PACKET_LEN] {
+    let mut buf = [0u8; PACKET_LEN];
     buf[0..4].copy_from_slice(MAGIC);
     buf[4..12].copy_from_slice(&seq.to_be_bytes());
     buf[12..20].copy_from_slice(&timestamp_us.to_be_bytes());
@@ -34,21 +40,130 @@ fn decode_packet(buf: &[u8]) -> Option<(u64, u64)> {
     Some((seq, timestamp))
 }
 
+/// Whether an echo is byte-for-byte the packet that was sent
+/// The server echoes packets verbatim, so any difference means corruption in transit
+fn echo_intact(sent: &[u8; PACKET_LEN], echoed: &[u8]) -> bool {
+    echoed == sent
+}
+
+/// Pings awaiting a reply: send time, scheduling jitter and packet by sequence number
+type Pending = HashMap<u64, (Instant, Option<Duration>, [u8; PACKET_LEN])>;
+
+/// Sequence number of the pending ping a reply belongs to, and whether the reply is
+/// an echo of it
+/// In lenient mode a reply that isn't an echo of a pending ping is matched to the
+/// oldest ping still awaiting a reply
+fn match_reply(pending: &Pending, reply: &[u8], lenient: bool) -> Option<(u64, bool)> {
+    let echoed = decode_packet(reply)
+        .map(|(seq, _timestamp)| seq)
+        .filter(|seq| pending.contains_key(seq));
+    match echoed {
+        Some(seq) => Some((seq, true)),
+        None if lenient => pending.keys().min().map(|&seq| (seq, false)),
+        None => None,
+    }
+}
+
 /// UDP client pinger
 pub struct UdpClientPinger {
     target: SocketAddr,
     interval_ms: u64,
     timeout_ms: u64,
+    /// Local address to send from (None = any address of the target's family)
+    source: Option<IpAddr>,
+    /// Skips sending while stopped
+    switch: ProbeSwitch,
+    /// Hands out sequence numbers (shared with a pinger this one replaces)
+    seq: ProbeSeq,
+    /// Interval sweep and when it started (None = fixed interval)
+    sweep: Option<(IntervalSweep, Instant)>,
+    /// How sends missed while falling behind schedule are made up for
+    missed_ticks: MissedTicks,
+    /// Randomization of the gaps between sends, in percent of the interval
+    interval_jitter: f64,
+    /// Accept replies that aren't echoes of the packet sent
+    lenient: bool,
+    /// Updated with the number of pings awaiting a reply
+    in_flight: InFlight,
+    /// Set to the address the socket ends up bound to
+    local_addr: LocalAddr,
 }
 
 impl UdpClientPinger {
+    /// For a link-local IPv6 target, `target` must carry the scope id of the interface to use
     pub fn new(target: SocketAddr, interval_ms: u64, timeout_ms: u64) -> Self {
         Self {
             target,
             interval_ms,
             timeout_ms,
+            source: None,
+            switch: ProbeSwitch::default(),
+            seq: ProbeSeq::default(),
+            sweep: None,
+            missed_ticks: MissedTicks::default(),
+            interval_jitter: 0.0,
+            lenient: false,
+            in_flight: InFlight::default(),
+            local_addr: LocalAddr::default(),
         }
     }
+
+    /// Accept replies that aren't echoes of the packet sent (e.g. from a generic UDP
+    /// echo service), matching them to the oldest ping awaiting a reply
+    /// This gives a best-effort RTT: a late reply is attributed to a later ping
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Share a count that's kept at the number of pings awaiting a reply
+    pub fn in_flight(mut self, in_flight: InFlight) -> Self {
+        self.in_flight = in_flight;
+        self
+    }
+
+    /// Share an address that's set to where the socket got bound (the port is
+    /// ephemeral), e.g. for firewall rules or matching up packet captures
+    pub fn local_addr(mut self, local_addr: LocalAddr) -> Self {
+        self.local_addr = local_addr;
+        self
+    }
+
+    /// Share a switch that stops and resumes sending
+    pub fn switch(mut self, switch: ProbeSwitch) -> Self {
+        self.switch = switch;
+        self
+    }
+
+    /// Share a sequence counter, e.g. to continue the numbering of a pinger this one replaces
+    pub fn seq(mut self, seq: ProbeSeq) -> Self {
+        self.seq = seq;
+        self
+    }
+
+    /// Follow an interval sweep that started at `started` instead of the fixed interval
+    pub fn sweep(mut self, sweep: IntervalSweep, started: Instant) -> Self {
+        self.sweep = Some((sweep, started));
+        self
+    }
+
+    /// Choose how sends missed while falling behind schedule are made up for
+    pub fn missed_ticks(mut self, missed_ticks: MissedTicks) -> Self {
+        self.missed_ticks = missed_ticks;
+        self
+    }
+
+    /// Randomize each gap between sends by up to ±`percent`% of the interval
+    pub fn interval_jitter(mut self, percent: f64) -> Self {
+        self.interval_jitter = percent;
+        self
+    }
+
+    /// Bind the local socket to a specific source address (e.g. to pick an uplink)
+    pub fn source(mut self, source: Option<IpAddr>) -> Self {
+        self.source = source;
+        self
+    }
 }
 
 impl Pinger for UdpClientPinger {
@@ -57,11 +172,24 @@ impl Pinger for UdpClientPinger {
         tx: mpsc::UnboundedSender<PingResult>,
     ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
-            // Bind to matching address family (IPv4 or IPv6)
-            let bind_addr = if self.target.is_ipv4() {
-                "0.0.0.0:0"
-            } else {
-                "[::]:0"
+            // Bind to the source address, or any address of the matching family
+            let bind_ip = match self.source {
+                Some(source) => {
+                    if let Err(e) = super::check_source_family(source, self.target.ip()) {
+                        eprintln!("{}", e);
+                        return;
+                    }
+                    source
+                }
+                None if self.target.is_ipv4() => IpAddr::from([0u8; 4]),
+                None => IpAddr::from([0u16; 8]),
+            };
+            // A link-local source needs the target's zone to pick the interface
+            let bind_addr = match (bind_ip, self.target) {
+                (IpAddr::V6(ip), SocketAddr::V6(target)) if ip.is_unicast_link_local() => {
+                    SocketAddr::V6(SocketAddrV6::new(ip, 0, 0, target.scope_id()))
+                }
+                _ => SocketAddr::new(bind_ip, 0),
             };
             let socket = match UdpSocket::bind(bind_addr).await {
                 Ok(s) => Arc::new(s),
@@ -71,53 +199,54 @@ impl Pinger for UdpClientPinger {
                 }
             };
 
-            if let Err(e) = socket.connect(self.target).await {
-                eprintln!("Failed to connect to {}: {}", self.target, e);
-                return;
-            }
+            // Without a route the connect fails; it's retried before each send so pinging
+            // picks up once the target is reachable again
+            let mut connected = false;
 
-            // Track pending pings for timeout detection
-            let pending: Arc<Mutex<HashMap<u64, Instant>>> = Arc::new(Mutex::new(HashMap::new()));
+            // Track pending pings (send time and packet) for timeout and corruption detection
+            let pending: Arc<Mutex<Pending>> = Arc::new(Mutex::new(HashMap::new()));
             let start_time = Instant::now();
-            let mut seq: u64 = 0;
-            let mut ticker = interval(Duration::from_millis(self.interval_ms));
+            let mut ticker = ProbeTicker::new(self.interval_ms, self.sweep, self.missed_ticks)
+                .interval_jitter(self.interval_jitter);
 
             // Spawn receiver task
             let socket_recv = socket.clone();
             let pending_recv = pending.clone();
             let tx_recv = tx.clone();
             let timeout_ms = self.timeout_ms;
-            let prev_rtt: Arc<Mutex<Option<Duration>>> = Arc::new(Mutex::new(None));
-            let prev_rtt_recv = prev_rtt.clone();
+            let lenient = self.lenient;
+            let jitter = Arc::new(Mutex::new(JitterTracker::default()));
+            let jitter_recv = jitter.clone();
+            let in_flight_recv = self.in_flight.clone();
 
             tokio::spawn(async move {
                 let mut buf = [0u8; 32];
                 loop {
                     match socket_recv.recv(&mut buf).await {
                         Ok(len) => {
-                            if let Some((seq, _timestamp)) = decode_packet(&buf[..len]) {
-                                let mut pending = pending_recv.lock().await;
-                                if let Some(sent_at) = pending.remove(&seq) {
-                                    let rtt = sent_at.elapsed();
-                                    let prev = {
-                                        let mut guard = prev_rtt_recv.lock().await;
-                                        let prev = *guard;
-                                        *guard = Some(rtt);
-                                        prev
-                                    };
-                                    let _ =
-                                        tx_recv.send(PingResult::success(seq, rtt, sent_at, prev));
-                                }
+                            let mut pending = pending_recv.lock().await;
+                            if let Some((seq, echoed)) = match_reply(&pending, &buf[..len], lenient)
+                                && let Some((sent_at, sched_jitter, packet)) = pending.remove(&seq)
+                            {
+                                in_flight_recv.set(pending.len());
+                                let rtt = sent_at.elapsed();
+                                let mut jitter = jitter_recv.lock().await;
+                                // A lenient match has nothing to check against
+                                let result = if !echoed || echo_intact(&packet, &buf[..len]) {
+                                    let prev = jitter.complete(seq, Some(rtt));
+                                    PingResult::success(seq, rtt, sent_at, prev)
+                                        .with_smoothed_jitter(jitter.smoothed())
+                                } else {
+                                    jitter.complete(seq, None);
+                                    PingResult::corrupted(seq, sent_at)
+                                };
+                                let _ = tx_recv.send(result.with_sched_jitter(sched_jitter));
                             }
                         }
-                        Err(e) => {
-                            // Ignore WSAECONNRESET (10054) on Windows - this happens when
-                            // the server isn't listening and we get ICMP port unreachable.
-                            // The timeout checker will handle reporting the lost packet.
-                            let is_connection_reset = e.raw_os_error() == Some(10054);
-                            if !is_connection_reset {
-                                eprintln!("Recv error: {}", e);
-                            }
+                        Err(_) => {
+                            // Errors such as ICMP port unreachable (ECONNREFUSED, or
+                            // WSAECONNRESET on Windows) are expected while the target is
+                            // down. The timeout checker will handle reporting the lost packet.
                         }
                     }
                 }
@@ -127,7 +256,8 @@ impl Pinger for UdpClientPinger {
             let pending_timeout = pending.clone();
             let tx_timeout = tx.clone();
             let timeout_duration = Duration::from_millis(timeout_ms);
-            let prev_rtt_timeout = prev_rtt.clone();
+            let jitter_timeout = jitter.clone();
+            let in_flight_timeout = self.in_flight.clone();
 
             tokio::spawn(async move {
                 let mut check_interval = interval(Duration::from_millis(100));
@@ -135,25 +265,33 @@ impl Pinger for UdpClientPinger {
                     check_interval.tick().await;
                     let now = Instant::now();
                     let mut pending = pending_timeout.lock().await;
-                    let timed_out: Vec<(u64, Instant)> = pending
+                    let mut timed_out: Vec<(u64, Instant, Option<Duration>)> = pending
                         .iter()
-                        .filter(|(_, sent_at)| now.duration_since(**sent_at) > timeout_duration)
-                        .map(|(seq, sent_at)| (*seq, *sent_at))
+                        .filter(|(_, (sent_at, _, _))| {
+                            now.duration_since(*sent_at) > timeout_duration
+                        })
+                        .map(|(seq, (sent_at, sched_jitter, _))| (*seq, *sent_at, *sched_jitter))
                         .collect();
+                    timed_out.sort_unstable_by_key(|(seq, _, _)| *seq);
 
-                    for (seq, sent_at) in timed_out {
+                    for (seq, sent_at, sched_jitter) in timed_out {
                         pending.remove(&seq);
-                        // Clear prev_rtt on timeout
-                        *prev_rtt_timeout.lock().await = None;
-                        let _ = tx_timeout.send(PingResult::timeout(seq, sent_at));
+                        jitter_timeout.lock().await.complete(seq, None);
+                        let _ = tx_timeout.send(
+                            PingResult::timeout(seq, sent_at).with_sched_jitter(sched_jitter),
+                        );
                     }
+                    in_flight_timeout.set(pending.len());
                 }
             });
 
             // Main send loop - timer based
             loop {
-                ticker.tick().await;
-                seq += 1;
+                let sched_jitter = ticker.tick().await;
+                if self.switch.is_stopped() {
+                    continue;
+                }
+                let seq = self.seq.next();
 
                 let sent_at = Instant::now();
                 let timestamp_us = start_time.elapsed().as_micros() as u64;
@@ -161,17 +299,96 @@ impl Pinger for UdpClientPinger {
 
                 {
                     let mut pending = pending.lock().await;
-                    pending.insert(seq, sent_at);
+                    pending.insert(seq, (sent_at, sched_jitter, packet));
+                    self.in_flight.set(pending.len());
                 }
 
-                if let Err(e) = socket.send(&packet).await {
-                    eprintln!("Send error: {}", e);
+                if !connected && socket.connect(self.target).await.is_ok() {
+                    connected = true;
+                    // Connecting settles the source address picked for the route to the target
+                    if let Ok(addr) = socket.local_addr() {
+                        self.local_addr.set(addr);
+                    }
+                }
+                let sent = connected && socket.send(&packet).await.is_ok();
+                if !sent {
+                    // Unreachable (no route, network down): the probe is lost right away,
+                    // so the graph shows loss instead of freezing
+                    let mut pending = pending.lock().await;
+                    if pending.remove(&seq).is_some() {
+                        jitter.lock().await.complete(seq, None);
+                        let _ = tx.send(
+                            PingResult::timeout(seq, sent_at).with_sched_jitter(sched_jitter),
+                        );
+                    }
+                    self.in_flight.set(pending.len());
                 }
             }
         })
     }
 }
 
+/// How often the UDP server prints a traffic summary
+const SERVER_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Traffic counters for the UDP server's periodic summary
+#[derive(Debug, Default)]
+struct ServerStats {
+    /// Packets echoed since startup
+    total_echoed: u64,
+    /// Packets echoed since the last report
+    period_echoed: u64,
+    /// Clients seen since startup
+    clients: HashSet<SocketAddr>,
+    /// Clients seen since the last report
+    period_clients: HashSet<SocketAddr>,
+    /// Whether the previous report saw any traffic
+    was_active: bool,
+}
+
+impl ServerStats {
+    fn record(&mut self, src: SocketAddr) {
+        self.total_echoed += 1;
+        self.period_echoed += 1;
+        self.clients.insert(src);
+        self.period_clients.insert(src);
+    }
+
+    /// Summary line for the period that just ended, then start a new period
+    /// Idle periods are only reported once, when traffic stops
+    fn report(&mut self, period: Duration) -> Option<String> {
+        let active = self.period_echoed > 0;
+        let line = (active || self.was_active).then(|| {
+            format!(
+                "[{}] {:.1} pkt/s echoed, {} active client(s) ({} echoed, {} client(s) total)",
+                chrono::Local::now().format("%H:%M:%S"),
+                self.period_echoed as f64 / period.as_secs_f64(),
+                self.period_clients.len(),
+                self.total_echoed,
+                self.clients.len()
+            )
+        });
+        self.was_active = active;
+        self.period_echoed = 0;
+        self.period_clients.clear();
+        line
+    }
+}
+
+/// Lines typed on stdin; a background thread is used so a pending read never blocks shutdown
+fn stdin_lines() -> mpsc::UnboundedReceiver<String> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
 /// UDP server that echoes ping packets back
 pub struct UdpServer {
     bind: Option<String>,
@@ -183,23 +400,39 @@ impl UdpServer {
         Self { bind, port }
     }
 
-    async fn handle_packet(socket: &UdpSocket, buf: &[u8], len: usize, src: SocketAddr) {
-        if len >= 20
-            && &buf[0..4] == MAGIC
-            && let Err(e) = socket.send_to(&buf[..len], src).await
-        {
-            eprintln!("Failed to send response to {}: {}", src, e);
+    async fn handle_packet(
+        socket: &UdpSocket,
+        buf: &[u8],
+        len: usize,
+        src: SocketAddr,
+        stats: &mut ServerStats,
+    ) {
+        if len >= PACKET_LEN && &buf[0..4] == MAGIC {
+            match socket.send_to(&buf[..len], src).await {
+                Ok(_) => stats.record(src),
+                Err(e) => eprintln!("Failed to send response to {}: {}", src, e),
+            }
         }
     }
 
+    /// Whether a line typed on stdin asks the server to stop
+    fn is_quit(line: Option<String>) -> bool {
+        // Closed stdin (e.g. running detached) is not a request to stop
+        line.is_some_and(|line| line.trim().eq_ignore_ascii_case("q"))
+    }
+
     pub async fn run(&self) -> anyhow::Result<()> {
         // If a specific bind address is provided, use only that
         if let Some(bind_addr) = &self.bind {
             let addr = format!("{}:{}", bind_addr, self.port);
             let socket = UdpSocket::bind(&addr).await?;
             println!("UDP ping server listening on {}", addr);
-            println!("Press Ctrl+C to stop");
+            println!("Press Ctrl+C or type q + Enter to stop");
 
+            let mut stats = ServerStats::default();
+            let mut report = interval(SERVER_REPORT_INTERVAL);
+            report.tick().await;
+            let mut input = stdin_lines();
             let mut buf = [0u8; 32];
             loop {
                 tokio::select! {
@@ -207,9 +440,20 @@ impl UdpServer {
                         println!("\nShutting down...");
                         break;
                     }
+                    line = input.recv(), if !input.is_closed() => {
+                        if Self::is_quit(line) {
+                            println!("Shutting down...");
+                            break;
+                        }
+                    }
+                    _ = report.tick() => {
+                        if let Some(line) = stats.report(SERVER_REPORT_INTERVAL) {
+                            println!("{}", line);
+                        }
+                    }
                     result = socket.recv_from(&mut buf) => {
                         if let Ok((len, src)) = result {
-                            Self::handle_packet(&socket, &buf, len, src).await;
+                            Self::handle_packet(&socket, &buf, len, src, &mut stats).await;
                         }
                     }
                 }
@@ -233,8 +477,12 @@ impl UdpServer {
                 self.port
             );
         }
-        println!("Press Ctrl+C to stop");
+        println!("Press Ctrl+C or type q + Enter to stop");
 
+        let mut stats = ServerStats::default();
+        let mut report = interval(SERVER_REPORT_INTERVAL);
+        report.tick().await;
+        let mut input = stdin_lines();
         let mut buf_v4 = [0u8; 32];
         let mut buf_v6 = [0u8; 32];
 
@@ -244,9 +492,20 @@ impl UdpServer {
                     println!("\nShutting down...");
                     break;
                 }
+                line = input.recv(), if !input.is_closed() => {
+                    if Self::is_quit(line) {
+                        println!("Shutting down...");
+                        break;
+                    }
+                }
+                _ = report.tick() => {
+                    if let Some(line) = stats.report(SERVER_REPORT_INTERVAL) {
+                        println!("{}", line);
+                    }
+                }
                 result = socket_v4.recv_from(&mut buf_v4) => {
                     if let Ok((len, src)) = result {
-                        Self::handle_packet(&socket_v4, &buf_v4, len, src).await;
+                        Self::handle_packet(&socket_v4, &buf_v4, len, src, &mut stats).await;
                     }
                 }
                 result = async {
@@ -259,7 +518,7 @@ impl UdpServer {
                     }
                 } => {
                     if let Some(Ok((len, src))) = result {
-                        Self::handle_packet(socket_v6.as_ref().unwrap(), &buf_v6, len, src).await;
+                        Self::handle_packet(socket_v6.as_ref().unwrap(), &buf_v6, len, src, &mut stats).await;
                     }
                 }
             }
@@ -289,4 +548,125 @@ mod tests {
         assert!(decode_packet(&[0; 10]).is_none()); // Too short
         assert!(decode_packet(b"NOPE12345678901234567890").is_none()); // Wrong magic
     }
+
+    #[test]
+    fn test_corrupted_echo() {
+        let packet = encode_packet(42, 1000);
+        assert!(echo_intact(&packet, &packet));
+
+        // A flipped timestamp bit still decodes to the right sequence, but isn't intact
+        let mut echoed = packet;
+        echoed[19] ^= 0x01;
+        assert_eq!(decode_packet(&echoed), Some((42, 1001)));
+        assert!(!echo_intact(&packet, &echoed));
+
+        // So is a truncated or padded echo
+        assert!(!echo_intact(&packet, &packet[..19]));
+        assert!(!echo_intact(&packet, &[&packet[..], &[0]].concat()));
+    }
+
+    #[test]
+    fn test_match_reply() {
+        let now = Instant::now();
+        let pending: Pending = [7, 5, 6]
+            .into_iter()
+            .map(|seq| (seq, (now, None, encode_packet(seq, 0))))
+            .collect();
+
+        assert_eq!(
+            match_reply(&pending, &encode_packet(6, 0), false),
+            Some((6, true))
+        );
+        assert_eq!(match_reply(&pending, b"hello", false), None);
+        assert_eq!(match_reply(&pending, &encode_packet(9, 0), false), None);
+
+        // Lenient mode pairs anything else with the oldest unanswered ping
+        assert_eq!(match_reply(&pending, b"hello", true), Some((5, false)));
+        assert_eq!(
+            match_reply(&pending, &encode_packet(6, 0), true),
+            Some((6, true))
+        );
+        assert_eq!(match_reply(&HashMap::new(), b"hello", true), None);
+    }
+
+    #[test]
+    fn test_server_stats_report() {
+        let mut stats = ServerStats::default();
+        let a: SocketAddr = "10.0.0.1:5000".parse().unwrap();
+        let b: SocketAddr = "10.0.0.2:5000".parse().unwrap();
+
+        // Nothing to report before any traffic
+        assert!(stats.report(Duration::from_secs(5)).is_none());
+
+        for _ in 0..8 {
+            stats.record(a);
+        }
+        stats.record(b);
+        stats.record(b);
+        let line = stats.report(Duration::from_secs(5)).unwrap();
+        assert!(
+            line.ends_with("2.0 pkt/s echoed, 2 active client(s) (10 echoed, 2 client(s) total)")
+        );
+
+        // Traffic stopping is reported once, then idle periods are quiet
+        stats.record(a);
+        stats.report(Duration::from_secs(5));
+        let line = stats.report(Duration::from_secs(5)).unwrap();
+        assert!(
+            line.contains("0.0 pkt/s echoed, 0 active client(s) (11 echoed, 2 client(s) total)")
+        );
+        assert!(stats.report(Duration::from_secs(5)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_count() {
+        // A target that never answers: probes pile up until they time out
+        let silent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let target = silent.local_addr().unwrap();
+        let in_flight = InFlight::default();
+        let local_addr = LocalAddr::default();
+        let switch = ProbeSwitch::default();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let handle = Box::new(
+            UdpClientPinger::new(target, 10, 150)
+                .in_flight(in_flight.clone())
+                .local_addr(local_addr.clone())
+                .switch(switch.clone()),
+        )
+        .start(tx);
+
+        let result = rx.recv().await.unwrap();
+        assert!(result.rtt.is_none());
+        let count = in_flight.get();
+        assert!((1..=20).contains(&count), "{} in flight", count);
+        // The ephemeral port the socket got, as seen from the target's side
+        let bound = local_addr.get().unwrap();
+        assert_eq!(bound.ip(), target.ip());
+        assert_ne!(bound.port(), 0);
+
+        // Once sending stops, everything outstanding times out
+        switch.set_stopped(true);
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        handle.abort();
+        assert_eq!(in_flight.get(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_send_error_is_loss() {
+        // Broadcast without SO_BROADCAST is refused by the OS on every send
+        let target: SocketAddr = "255.255.255.255:9".parse().unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let handle = Box::new(UdpClientPinger::new(target, 10, 60_000)).start(tx);
+
+        // Each probe shows up as lost right away instead of the pinger going quiet
+        for seq in 1..=3 {
+            let result = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(result.seq, seq);
+            assert!(result.rtt.is_none());
+        }
+        handle.abort();
+    }
 }
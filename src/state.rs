@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+use clap::ArgMatches;
+use clap::parser::ValueSource;
+use serde::{Deserialize, Serialize};
+
+use crate::color::ColorScheme;
+use crate::config::Config;
+use crate::ui::app::App;
+use crate::ui::legend::LegendMode;
+
+/// Preferences remembered between runs, separate from the command line
+/// Options given on the command line or environment always win over the remembered values
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    /// Last graphed target, offered in the settings dialog when no host is given
+    #[serde(default)]
+    pub target: Option<String>,
+    #[serde(default)]
+    pub scale: Option<u64>,
+    #[serde(default)]
+    pub colors: Option<ColorScheme>,
+    #[serde(default)]
+    pub loss_gaps: Option<bool>,
+    #[serde(default)]
+    pub confirm_quit: Option<bool>,
+    #[serde(default)]
+    pub legend: Option<LegendMode>,
+    /// Targets offered in the quick-switch list, most recent first
+    #[serde(default)]
+    pub recent_targets: Vec<String>,
+}
+
+impl SessionState {
+    /// State file location: `$XDG_STATE_HOME/rttui/state.json`, falling back to
+    /// `~/.local/state` (or `%LOCALAPPDATA%` on Windows)
+    pub fn path() -> Option<PathBuf> {
+        let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+        let dir = if cfg!(windows) {
+            PathBuf::from(var("LOCALAPPDATA")?)
+        } else if let Some(state_home) = var("XDG_STATE_HOME") {
+            PathBuf::from(state_home)
+        } else {
+            PathBuf::from(var("HOME")?).join(".local").join("state")
+        };
+        Some(dir.join("rttui").join("state.json"))
+    }
+
+    /// Read the state file; a missing or unreadable file gives an empty state
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the state file, creating its directory if needed
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow::anyhow!("No directory to save state in"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| anyhow::anyhow!("Failed to write state file {}: {}", path.display(), e))
+    }
+
+    /// Current target and display preferences of a running session
+    pub fn from_app(app: &App) -> Self {
+        Self {
+            target: app.config.host.clone(),
+            scale: Some(app.color_scale.max_rtt),
+            colors: Some(app.color_scale.scheme),
+            loss_gaps: Some(app.loss_gaps),
+            confirm_quit: Some(!app.config.no_confirm_quit),
+            legend: Some(app.legend_mode),
+            recent_targets: app.recent_targets.clone(),
+        }
+    }
+
+    /// Fill in preferences that weren't given on the command line or environment
+    pub fn apply(&self, config: &mut Config, matches: &ArgMatches) {
+        let explicit = |id| {
+            matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        };
+        if let Some(scale) = self.scale.filter(|_| !explicit("scale")) {
+            config.scale = scale;
+        }
+        if let Some(colors) = self.colors.filter(|_| !explicit("colors")) {
+            config.colors = colors;
+        }
+        if let Some(loss_gaps) = self.loss_gaps.filter(|_| !explicit("loss_gaps")) {
+            config.loss_gaps = loss_gaps;
+        }
+        if let Some(confirm_quit) = self.confirm_quit.filter(|_| !explicit("no_confirm_quit")) {
+            config.no_confirm_quit = !confirm_quit;
+        }
+        if let Some(legend) = self.legend.filter(|_| !explicit("legend")) {
+            config.legend = legend;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    #[test]
+    fn test_apply_keeps_cli_options() {
+        let state = SessionState {
+            target: Some("example.com".to_string()),
+            scale: Some(50),
+            colors: Some(ColorScheme::Ocean),
+            loss_gaps: Some(true),
+            confirm_quit: Some(false),
+            legend: Some(LegendMode::Off),
+            recent_targets: vec!["example.com".to_string()],
+        };
+        let matches = Config::command().get_matches_from(["rttui", "-s", "300"]);
+        let mut config = Config::from_arg_matches(&matches).unwrap();
+        state.apply(&mut config, &matches);
+
+        assert_eq!(config.scale, 300);
+        assert_eq!(config.colors, ColorScheme::Ocean);
+        assert!(config.loss_gaps);
+        assert!(config.no_confirm_quit);
+        assert_eq!(config.legend, LegendMode::Off);
+        // The target is only offered in the settings dialog, never started automatically
+        assert_eq!(config.host, None);
+    }
+}
@@ -1,11 +1,92 @@
 use crate::color::{ColorScale, ColorScheme};
-use crate::config::Config;
-use crate::ping::{PingResult, PingStats};
+use crate::config::{Config, Mode, parse_duration_ms};
+use crate::export::{Comparison, ExportSummary};
+use crate::metrics::SharedMetrics;
+use crate::ping::{
+    InFlight, LocalAddr, PingResult, PingStats, ProbeSeq, ProbeSwitch, Quality, Reducer, RttUnit,
+    Trend, WaitTracker, check_target_ip, format_time, icmp, normalize_host, split_port,
+};
+use crate::ui::graph::{DOTS_PER_CELL, GraphCache, NUMERIC_CELL_WIDTH};
+use crate::ui::input::{InputKind, TextInput};
+use crate::ui::legend::{
+    HIDE_WIDTH_FOR_LEGEND, LegendMode, MIN_WIDTH_FOR_FORCED_LEGEND, MIN_WIDTH_FOR_LEGEND,
+};
+use crate::ui::losses::LossEvents;
+use crate::ui::settings::{AGGREGATE_BY_LINE, MENU_HEIGHT, MENU_WIDTH};
+use crate::ui::snapshot::{buffer_to_svg, snapshot_file_name};
+use ratatui::buffer::Buffer;
 use std::collections::VecDeque;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
 
 /// Maximum number of recent pings to track for footer sparkline (enough for wide terminals)
 const MAX_RECENT_RTT_COUNT: usize = 500;
 
+/// Recent replies the median for spike detection is taken over, and how many are
+/// needed before spikes are detected at all
+const SPIKE_WINDOW: usize = 50;
+const SPIKE_MIN_SAMPLES: usize = 10;
+/// Largest interval and scale (ms) accepted by the editors
+const MAX_INTERVAL_MS: u64 = 100000;
+const MAX_SCALE_MS: u64 = 100000;
+/// Largest timeout (ms) accepted by the settings menu
+const MAX_TIMEOUT_MS: u64 = 600000;
+/// Timeouts more than this many intervals long get a warning (that many probes can be
+/// outstanding, and losses show up long after they happen)
+const TIMEOUT_WARN_RATIO: u64 = 100;
+/// Most results space is reserved for up front, however large the buffer
+const MAX_PREALLOCATED_RESULTS: usize = 100000;
+/// How long the graph takes to fade to a newly chosen color scheme
+const SCHEME_TRANSITION: std::time::Duration = std::time::Duration::from_millis(400);
+/// How long the cursor stays on, then off, with --blink-cursor
+const CURSOR_BLINK: Duration = Duration::from_millis(530);
+/// Largest buffer size (MB) accepted by the settings menu
+const MAX_BUFFER_MB: u64 = 1000;
+/// Span of recent sends the achieved send rate is measured over, stretched at slow
+/// intervals to include at least RATE_MIN_SENDS sends
+const RATE_WINDOW: std::time::Duration = std::time::Duration::from_secs(2);
+const RATE_MIN_SENDS: usize = 3;
+/// Number of recent sends the scheduling jitter is averaged over
+const SCHED_JITTER_SENDS: usize = 100;
+/// Rows scrolled per mouse wheel tick without acceleration
+const WHEEL_ROWS: usize = 3;
+/// Wheel ticks closer together than this continue a run that accelerates
+const WHEEL_RUN_GAP: std::time::Duration = std::time::Duration::from_millis(80);
+/// Most rows a single accelerated wheel tick scrolls
+const WHEEL_MAX_ROWS: usize = 30;
+/// Weight of the newest reply in the smoothed current RTT (EWMA)
+const SMOOTHED_RTT_ALPHA: f64 = 0.2;
+/// Losses in a row after which the smoothed current RTT starts over from the next reply
+/// (fewer leave it as it was, so a stray timeout doesn't blank the reading)
+const SMOOTHED_RTT_RESET_LOSSES: u64 = 3;
+/// Replies the recent and longer-term averages of the latency trend are taken over
+const TREND_RECENT_REPLIES: usize = 10;
+const TREND_LONGER_REPLIES: usize = 100;
+/// Number of targets kept in the quick-switch list
+pub const MAX_RECENT_TARGETS: usize = 10;
+
+/// Something that happened mid-session, marked on the graph where it happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// The pinger was restarted without clearing history (e.g. a new interval)
+    Restart,
+    /// Split marker dropped with 'm' to compare the samples before and after it
+    Split,
+    /// Start of a latency spike (`--mark-spikes`)
+    Spike,
+    /// Text note added with 'a'
+    Note,
+}
+
+/// Text note pinned to a point in the session, e.g. "rebooted router"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    /// Stable sequence number of the first sample after the note
+    pub seq: usize,
+    pub timestamp: chrono::DateTime<chrono::Local>,
+    pub text: String,
+}
+
 /// Popup info for clicked ping
 #[derive(Clone)]
 pub struct PingPopup {
@@ -15,14 +96,130 @@ pub struct PingPopup {
     pub screen_y: u16,
 }
 
+/// How a reply compares to the replies before it in the buffer (for the ping popup)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleContext {
+    /// RTT minus the average RTT of the earlier replies, in ms
+    pub vs_avg_ms: f64,
+    /// Lower than every earlier reply
+    pub new_min: bool,
+    /// Higher than every earlier reply
+    pub new_max: bool,
+}
+
+/// Loss and RTT totals over one rolling summary window, kept up to date as results
+/// arrive and leave the window rather than rescanned every frame
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowStats {
+    pub sent: u64,
+    pub lost: u64,
+    pub sum_rtt: Duration,
+    /// How much of the window the kept results cover (less than the whole window
+    /// early in a session or with a small buffer)
+    pub covered: Duration,
+    /// Stable sequence number of the oldest result counted
+    start: usize,
+}
+
+impl WindowStats {
+    fn add(&mut self, result: &PingResult) {
+        self.sent += 1;
+        match result.rtt {
+            Some(rtt) => self.sum_rtt += rtt,
+            None => self.lost += 1,
+        }
+    }
+
+    fn remove(&mut self, result: &PingResult) {
+        self.sent -= 1;
+        match result.rtt {
+            Some(rtt) => self.sum_rtt -= rtt,
+            None => self.lost -= 1,
+        }
+        self.start += 1;
+    }
+
+    pub fn loss_percent(&self) -> f64 {
+        if self.sent == 0 {
+            0.0
+        } else {
+            self.lost as f64 / self.sent as f64 * 100.0
+        }
+    }
+
+    pub fn avg_rtt(&self) -> Option<Duration> {
+        let received = self.sent - self.lost;
+        (received > 0).then(|| self.sum_rtt / received as u32)
+    }
+}
+
+/// Stats of the results on either side of the split marker, so the comparison isn't
+/// rebuilt from the whole buffer every frame
+struct SplitSums {
+    /// Split marker these are for
+    seq: usize,
+    /// The results before the marker, summarized once when it was placed
+    before: ExportSummary,
+    /// The results after the marker, recorded as they arrive
+    after: PingStats,
+    /// Sum and count of the jitter of the results after the marker
+    after_jitter: (f64, u64),
+}
+
+impl SplitSums {
+    fn record(&mut self, result: &PingResult) {
+        self.after.record(result);
+        if let Some(jitter) = result.jitter_ms_f64() {
+            self.after_jitter.0 += jitter;
+            self.after_jitter.1 += 1;
+        }
+    }
+}
+
+/// State of one monitored target in the multi-target summary
+pub struct TargetState {
+    /// Host as given by the user
+    pub host: String,
+    /// Resolved address (None if resolution failed)
+    pub ip: Option<IpAddr>,
+    pub stats: PingStats,
+    /// Recent RTT values (ms as f64, None = timeout)
+    pub recent_rtts: VecDeque<Option<f64>>,
+}
+
+impl TargetState {
+    pub fn new(host: String, ip: Option<IpAddr>) -> Self {
+        Self {
+            host,
+            ip,
+            stats: PingStats::new(),
+            recent_rtts: VecDeque::with_capacity(MAX_RECENT_RTT_COUNT),
+        }
+    }
+
+    pub fn record(&mut self, result: &PingResult) {
+        self.stats.record(result);
+        self.recent_rtts.push_back(result.rtt_ms_f64());
+        while self.recent_rtts.len() > MAX_RECENT_RTT_COUNT {
+            self.recent_rtts.pop_front();
+        }
+    }
+}
+
 /// Settings menu field being edited
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum SettingsField {
     Target,
     Interval,
+    Timeout,
     Scale,
     ColorScheme,
+    Unit,
+    AggregateBy,
     HideCursor,
+    HideIndicator,
+    TopDown,
+    ConfirmQuit,
     BufferSize,
     Confirm,
     Cancel,
@@ -32,19 +229,31 @@ pub enum SettingsField {
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum HeaderEditField {
     Target,
+    /// Cycled in place rather than edited in a popup
+    Mode,
     Interval,
     Scale,
     Colors,
+    /// Time of day to jump to in the history (opened with 't', not shown in the header)
+    Time,
+    /// Text of a note to add (opened with 'a', not shown in the header)
+    Note,
 }
 
 impl SettingsField {
     pub fn next(self) -> Self {
         match self {
             SettingsField::Target => SettingsField::Interval,
-            SettingsField::Interval => SettingsField::Scale,
+            SettingsField::Interval => SettingsField::Timeout,
+            SettingsField::Timeout => SettingsField::Scale,
             SettingsField::Scale => SettingsField::ColorScheme,
-            SettingsField::ColorScheme => SettingsField::HideCursor,
-            SettingsField::HideCursor => SettingsField::BufferSize,
+            SettingsField::ColorScheme => SettingsField::Unit,
+            SettingsField::Unit => SettingsField::AggregateBy,
+            SettingsField::AggregateBy => SettingsField::HideCursor,
+            SettingsField::HideCursor => SettingsField::HideIndicator,
+            SettingsField::HideIndicator => SettingsField::TopDown,
+            SettingsField::TopDown => SettingsField::ConfirmQuit,
+            SettingsField::ConfirmQuit => SettingsField::BufferSize,
             SettingsField::BufferSize => SettingsField::Confirm,
             SettingsField::Confirm => SettingsField::Cancel,
             SettingsField::Cancel => SettingsField::Target,
@@ -55,10 +264,16 @@ impl SettingsField {
         match self {
             SettingsField::Target => SettingsField::Cancel,
             SettingsField::Interval => SettingsField::Target,
-            SettingsField::Scale => SettingsField::Interval,
+            SettingsField::Timeout => SettingsField::Interval,
+            SettingsField::Scale => SettingsField::Timeout,
             SettingsField::ColorScheme => SettingsField::Scale,
-            SettingsField::HideCursor => SettingsField::ColorScheme,
-            SettingsField::BufferSize => SettingsField::HideCursor,
+            SettingsField::Unit => SettingsField::ColorScheme,
+            SettingsField::AggregateBy => SettingsField::Unit,
+            SettingsField::HideCursor => SettingsField::AggregateBy,
+            SettingsField::HideIndicator => SettingsField::HideCursor,
+            SettingsField::TopDown => SettingsField::HideIndicator,
+            SettingsField::ConfirmQuit => SettingsField::TopDown,
+            SettingsField::BufferSize => SettingsField::ConfirmQuit,
             SettingsField::Confirm => SettingsField::BufferSize,
             SettingsField::Cancel => SettingsField::Confirm,
         }
@@ -66,13 +281,31 @@ impl SettingsField {
 
     /// Returns true if this field supports text input
     pub fn is_text_input(self) -> bool {
-        matches!(
-            self,
-            SettingsField::Target
-                | SettingsField::Interval
-                | SettingsField::Scale
-                | SettingsField::BufferSize
-        )
+        self.input_kind().is_some()
+    }
+
+    /// What can be typed into this field (None = not a text field)
+    pub fn input_kind(self) -> Option<InputKind> {
+        match self {
+            SettingsField::Target => Some(InputKind::Text),
+            SettingsField::Interval => Some(InputKind::Duration {
+                max: MAX_INTERVAL_MS,
+            }),
+            SettingsField::Timeout => Some(InputKind::Duration {
+                max: MAX_TIMEOUT_MS,
+            }),
+            SettingsField::Scale => Some(InputKind::Number { max: MAX_SCALE_MS }),
+            SettingsField::BufferSize => Some(InputKind::Number { max: MAX_BUFFER_MB }),
+            SettingsField::ColorScheme
+            | SettingsField::Unit
+            | SettingsField::AggregateBy
+            | SettingsField::HideCursor
+            | SettingsField::HideIndicator
+            | SettingsField::TopDown
+            | SettingsField::ConfirmQuit
+            | SettingsField::Confirm
+            | SettingsField::Cancel => None,
+        }
     }
 
     /// Returns true if this is a button field
@@ -87,6 +320,15 @@ pub struct App {
     pub color_scale: ColorScale,
     pub stats: PingStats,
     pub results: VecDeque<PingResult>,
+    /// Running totals for each configured summary window, in `config.windows` order
+    window_sums: Vec<WindowStats>,
+    /// Both sides of the split marker, built when first compared after it was placed
+    split_sums: Option<SplitSums>,
+    /// Summary of this session for the `--compare` comparison, built when first needed
+    /// after results were added or dropped
+    run_summary: Option<ExportSummary>,
+    /// Outages among the buffered results, for the loss timeline
+    pub loss_events: LossEvents,
     /// Maximum history size (calculated from buffer_mb)
     pub max_history: usize,
     /// Base sequence number - total results ever recorded minus current buffer size
@@ -97,9 +339,15 @@ pub struct App {
     pub recent_rtts: VecDeque<Option<f64>>,
     /// Whether the display is paused (not recording new pings)
     pub paused: bool,
+    /// Results dropped while paused, which still count toward --count
+    paused_results: u64,
     /// The row index we're viewing at the BOTTOM of the screen (None = live/follow mode)
     /// When Some(row), we're showing rows ending at `row`, and new data won't shift the view
+    /// Derived from `view_anchor_seq` for the current row length on every draw
     pub view_end_row: Option<usize>,
+    /// Stable sequence number just past the last sample in view (None = live/follow mode)
+    /// Unlike rows this doesn't depend on graph width, so resizes keep the same pings on screen
+    pub view_anchor_seq: Option<usize>,
     /// Currently displayed popup (if any)
     pub popup: Option<PingPopup>,
     /// Graph area dimensions for mouse calculations
@@ -116,26 +364,49 @@ pub struct App {
     pub settings_target: String,
     /// Temporary interval value being edited
     pub settings_interval: u64,
+    /// Temporary timeout value being edited
+    pub settings_timeout: u64,
     /// Temporary scale value being edited
     pub settings_scale: u64,
     /// Temporary color scheme being edited
     pub settings_colors: ColorScheme,
-    /// Text input buffer for typing values
-    pub settings_input_buffer: String,
-    /// Cursor position within input buffer
-    pub settings_input_cursor: usize,
+    /// Color scheme selected before the current one, to compare against
+    pub settings_previous_colors: ColorScheme,
+    /// Whether settings show the graph in the previous and the selected color scheme
+    /// (one above the other) instead of the menu
+    pub scheme_compare: bool,
+    /// Text being typed into the selected field, with its validation error
+    pub settings_input: TextInput,
     /// Whether we're in text input mode
     pub settings_input_active: bool,
-    /// Whether the entire input is selected (for select-all behavior)
-    pub settings_input_selected: bool,
     /// Original values when settings was opened (for cancel)
     pub settings_original_scale: u64,
     /// Original color scheme when settings was opened (for cancel)
     pub settings_original_colors: ColorScheme,
     /// Original hide cursor when settings was opened (for cancel)
     pub settings_original_hide_cursor: bool,
+    /// Original hide indicator when settings was opened (for cancel)
+    pub settings_original_hide_indicator: bool,
+    /// Original graph direction when settings was opened (for cancel)
+    pub settings_original_top_down: bool,
+    /// Original RTT unit when settings was opened (for cancel)
+    pub settings_original_unit: RttUnit,
+    /// Interval when settings was opened (to tell whether it was edited)
+    pub settings_original_interval: u64,
+    /// Timeout when settings was opened (to tell whether it was edited)
+    pub settings_original_timeout: u64,
     /// Temporary hide cursor value being edited
     pub settings_hide_cursor: bool,
+    /// Temporary hide indicator value being edited
+    pub settings_hide_indicator: bool,
+    /// Temporary graph direction being edited (true = newest row at the top)
+    pub settings_top_down: bool,
+    /// Temporary RTT unit being edited
+    pub settings_unit: RttUnit,
+    /// Temporary --aggregate reducer being edited
+    pub settings_aggregate_by: Reducer,
+    /// Temporary quit confirmation value being edited
+    pub settings_confirm_quit: bool,
     /// Temporary buffer size being edited (in MB)
     pub settings_buffer_mb: u64,
     /// Whether pinger needs to be restarted (target or interval changed)
@@ -144,16 +415,14 @@ pub struct App {
     pub new_target: Option<String>,
     /// New interval for pinger restart (if changed)
     pub new_interval: Option<u64>,
+    /// New mode for pinger restart (if changed)
+    pub new_mode: Option<Mode>,
     /// Inline edit popup for header fields
     pub inline_edit: Option<HeaderEditField>,
     /// Inline edit popup position (x, y)
     pub inline_edit_pos: (u16, u16),
-    /// Inline edit input buffer
-    pub inline_edit_buffer: String,
-    /// Inline edit cursor position
-    pub inline_edit_cursor: usize,
-    /// Inline edit text selected
-    pub inline_edit_selected: bool,
+    /// Inline edit text, with its validation error (shown in the popup)
+    pub inline_edit_input: TextInput,
     /// Original value before inline edit (for cancel)
     pub inline_edit_original: String,
     /// Whether inline edit is in text input mode (vs navigation mode)
@@ -172,6 +441,18 @@ pub struct App {
     pub quit_confirm_yes_area: Option<(u16, u16, u16)>,
     /// Quit dialog No button area (x, y, width)
     pub quit_confirm_no_area: Option<(u16, u16, u16)>,
+    /// Target waiting for confirmation before history is cleared (--confirm-target-change)
+    pub target_confirm: Option<String>,
+    /// Which button is focused in the target change dialog (false = Yes, true = No)
+    pub target_confirm_no_focused: bool,
+    /// Target change dialog Yes button area (x, y, width)
+    pub target_confirm_yes_area: Option<(u16, u16, u16)>,
+    /// Target change dialog No button area (x, y, width)
+    pub target_confirm_no_area: Option<(u16, u16, u16)>,
+    /// Targets successfully switched to, most recent first (remembered between runs)
+    pub recent_targets: Vec<String>,
+    /// Selected entry of the open quick-switch list (None = closed)
+    pub recent_targets_selected: Option<usize>,
     /// Legend area dimensions for mouse calculations
     pub legend_area: Option<(u16, u16, u16, u16)>, // x, y, width, height
     /// Currently highlighted RTT range from legend hover (min_rtt, max_rtt, is_timeout)
@@ -179,29 +460,169 @@ pub struct App {
     pub highlight_rtt_range: Option<(f64, f64, bool)>,
     /// Whether we were in live mode before the popup was shown (to restore when popup closes)
     pub popup_was_live: bool,
+    /// Stable sequence number of the sample where a graph drag started
+    pub drag_start_seq: Option<usize>,
+    /// Selected range of samples as inclusive stable sequence numbers (start <= end)
+    pub selection: Option<(usize, usize)>,
+    /// Reference RTT (ms) pinned on the graph; samples above it are tinted
+    pub baseline_ms: Option<f64>,
+    /// Whether the graph shows numeric RTT values instead of colored squares
+    pub numeric_view: bool,
+    /// Whether the graph groups samples into braille dot density cells
+    pub dots_view: bool,
+    /// Whether timeouts are drawn as blank gaps instead of `X` marks
+    pub loss_gaps: bool,
+    /// Whether samples close to the lowest RTT on screen are tinted
+    pub floor_visible: bool,
+    /// Whether the graph shades and labels quarters of the color scale
+    pub bands_visible: bool,
+    /// Whether vertical gridlines are drawn every `--grid-every` samples
+    pub grid_visible: bool,
+    /// Whether the RTT distribution histogram panel is shown
+    pub histogram_visible: bool,
+    /// Whether the loss timeline replaces the graph
+    pub loss_view: bool,
+    /// Whether the graph fills the whole terminal, hiding header, footer and side panels
+    pub zen_mode: bool,
+    /// Whether the braille line chart is shown instead of the graph
+    pub line_view: bool,
+    /// Whether the minute-by-second heatmap is shown instead of the graph
+    pub heatmap_view: bool,
+    /// Number of newest loss events scrolled past in the loss timeline
+    pub loss_scroll: usize,
+    /// Last mouse wheel tick (time, towards the top) and the ticks in its run before it
+    pub wheel_run: Option<(Instant, bool, usize)>,
+    /// Smoothing factor for the footer sparkline's moving average (None = raw values)
+    pub spark_alpha: Option<f64>,
+    /// Targets monitored in the multi-target summary
+    pub targets: Vec<TargetState>,
+    /// Index of the summary target that is also the main graph target (shares its pinger)
+    pub primary_target_idx: Option<usize>,
+    /// Whether the legend is currently shown (with hysteresis around the width threshold)
+    pub legend_visible: bool,
+    /// Whether the legend follows the terminal width or is turned on or off
+    pub legend_mode: LegendMode,
+    /// Show times in UTC (starts at --utc, switched with 'u'; exports keep following --utc)
+    pub display_utc: bool,
+    /// System clipboard handle (created lazily, kept alive so copied text persists on X11)
+    clipboard: Option<arboard::Clipboard>,
+    /// Results recorded since the last frame was drawn
+    pub results_since_draw: usize,
+    /// Samples the last frame drew beyond the newest one, when pings arrive faster than
+    /// frames (0 = one sample or none per frame)
+    pub frame_coalesced: usize,
+    /// Exponentially weighted moving average of the replies in ms, a steadier
+    /// "current" RTT than the newest sample (None before a reply or after an outage)
+    pub smoothed_rtt: Option<f64>,
+    /// Losses in a row since the last reply, for resetting `smoothed_rtt`
+    smoothed_rtt_losses: u64,
+    /// Latency spikes seen: replies over `--spike-factor` × the recent median,
+    /// counting a run of them once
+    pub spike_count: u64,
+    /// Whether the latest reply was part of a spike
+    in_spike: bool,
+    /// Most recent notable event, shown on the footer border
+    pub footer_event: Option<String>,
+    /// Events marked on the graph, by stable sequence number of the first sample after them
+    pub events: Vec<(usize, EventKind)>,
+    /// Notes added with 'a', oldest first (also marked in `events`)
+    pub notes: Vec<Note>,
+    /// Graph rows drawn in earlier frames, reused while they haven't changed
+    pub graph_cache: GraphCache,
+    /// Whether the next timeout freezes the view (`--pause-on-loss`, re-armed on jump to live)
+    pub loss_freeze_armed: bool,
+    /// One-time notice shown across the top of the graph until dismissed with Esc
+    pub banner: Option<String>,
+    /// When the pinger started on a target that hasn't had a result yet, for the
+    /// "waiting for first reply" notice over the blank graph
+    pub awaiting_first_result: Option<Instant>,
+    /// Whether the next frame drawn should be saved as an SVG snapshot
+    pub snapshot_requested: bool,
+    /// When to quit because the `--duration` limit is reached (None = run until quit)
+    pub deadline: Option<tokio::time::Instant>,
+    /// Condition from --wait-up / --wait-down to quit on (None = not waiting)
+    pub wait: Option<WaitTracker>,
+    /// Whether the wait condition was met, so the exit status reports success
+    pub wait_met: bool,
+    /// Stops all pingers from sending probes (unlike `paused`, which only stops recording)
+    pub probe_switch: ProbeSwitch,
+    /// Numbers the probes of the main pinger, carried over when it is handed off to a new one
+    pub probe_seq: ProbeSeq,
+    /// When `probe_seq` was seen to go up and to what count, oldest first, for the send
+    /// rate (independent of the order results arrive in)
+    send_counts: VecDeque<(Instant, u64)>,
+    /// Scheme the graph was last drawn with, to notice when it changes
+    pub shown_scheme: ColorScheme,
+    /// Scheme being faded out after a scheme change, and when the fade started
+    pub scheme_transition: Option<(ColorScheme, Instant)>,
+    /// Whether the cursor is in the on phase of its blink (always with blinking off),
+    /// and when it last turned on or off
+    pub cursor_lit: bool,
+    cursor_blinked_at: Instant,
+    /// Probes of the main pinger awaiting a reply (only tracked in UDP client mode)
+    pub in_flight: InFlight,
+    /// Local address the main pinger's socket is bound to (only known in UDP client mode)
+    pub local_addr: LocalAddr,
+    /// Samples still to be left out of the statistics after a (re)start (`--warmup`)
+    pub warmup_remaining: u64,
+    /// Summary of a previous run loaded with `--compare`
+    pub previous_run: Option<ExportSummary>,
+    /// When the `--interval-sweep` started (pinger restarts continue the same sweep)
+    pub sweep_started: Instant,
+    /// Snapshot served on `--metrics-port`
+    pub metrics: Option<SharedMetrics>,
 }
 
 impl App {
-    pub fn new(config: Config) -> Self {
+    pub fn new(mut config: Config) -> Self {
+        if let Some(sweep) = config.interval_sweep {
+            config.interval = sweep.start_ms;
+        }
         let color_scale = ColorScale::new(config.scale, config.colors);
         let settings_interval = config.interval;
+        let settings_timeout = config.timeout;
         let settings_scale = config.scale;
         let settings_colors = config.colors;
         let settings_target = config.host.clone().unwrap_or_default();
         let settings_hide_cursor = config.hide_cursor;
+        let settings_hide_indicator = config.hide_indicator;
+        let settings_top_down = config.top_down;
+        let settings_confirm_quit = !config.no_confirm_quit;
+        let settings_unit = config.unit;
+        let settings_aggregate_by = config.aggregate_by;
+        let wait = config.wait_for().map(WaitTracker::new);
         let settings_buffer_mb = config.buffer_mb;
         let max_history = config.max_history();
+        let baseline_ms = config.baseline;
+        let spark_alpha = config.spark_smoothing;
+        let loss_gaps = config.loss_gaps;
+        let dots_view = config.dots;
+        let legend_mode = config.legend;
+        let display_utc = config.utc;
+        let floor_visible = config.floor;
+        let bands_visible = config.bands;
+        let grid_visible = config.grid;
+        let loss_freeze_armed = config.pause_on_loss;
+        let warmup_remaining = config.warmup;
+        let awaiting_first_result = config.host.is_some().then(Instant::now);
+        let window_sums = vec![WindowStats::default(); config.windows.len()];
         Self {
             max_history,
             result_base_seq: 0,
             config,
             color_scale,
             stats: PingStats::new(),
-            results: VecDeque::with_capacity(max_history.min(100000)),
+            results: VecDeque::with_capacity(max_history.min(MAX_PREALLOCATED_RESULTS)),
+            window_sums,
+            split_sums: None,
+            run_summary: None,
+            loss_events: LossEvents::default(),
             should_quit: false,
             recent_rtts: VecDeque::with_capacity(MAX_RECENT_RTT_COUNT),
             paused: false,
+            paused_results: 0,
             view_end_row: None, // None = live mode (follow newest)
+            view_anchor_seq: None,
             popup: None,
             graph_area: None,
             header_area: None,
@@ -210,25 +631,35 @@ impl App {
             settings_field: SettingsField::Target,
             settings_target,
             settings_interval,
+            settings_timeout,
             settings_scale,
             settings_colors,
-            settings_input_buffer: String::new(),
-            settings_input_cursor: 0,
+            settings_previous_colors: settings_colors,
+            scheme_compare: false,
+            settings_input: TextInput::default(),
             settings_input_active: false,
-            settings_input_selected: false,
             settings_original_scale: settings_scale,
             settings_original_colors: settings_colors,
             settings_original_hide_cursor: settings_hide_cursor,
+            settings_original_hide_indicator: settings_hide_indicator,
+            settings_original_top_down: settings_top_down,
+            settings_original_unit: settings_unit,
+            settings_original_interval: settings_interval,
+            settings_original_timeout: settings_timeout,
             settings_hide_cursor,
+            settings_hide_indicator,
+            settings_top_down,
+            settings_confirm_quit,
+            settings_unit,
+            settings_aggregate_by,
             settings_buffer_mb,
             needs_pinger_restart: false,
             new_target: None,
             new_interval: None,
+            new_mode: None,
             inline_edit: None,
             inline_edit_pos: (0, 0),
-            inline_edit_buffer: String::new(),
-            inline_edit_cursor: 0,
-            inline_edit_selected: false,
+            inline_edit_input: TextInput::default(),
             inline_edit_original: String::new(),
             inline_edit_input_active: false,
             inline_edit_confirm_area: None,
@@ -238,28 +669,252 @@ impl App {
             quit_confirm_no_focused: false,
             quit_confirm_yes_area: None,
             quit_confirm_no_area: None,
+            target_confirm: None,
+            target_confirm_no_focused: false,
+            target_confirm_yes_area: None,
+            target_confirm_no_area: None,
+            recent_targets: Vec::new(),
+            recent_targets_selected: None,
             legend_area: None,
             highlight_rtt_range: None,
             popup_was_live: false,
+            drag_start_seq: None,
+            selection: None,
+            baseline_ms,
+            numeric_view: false,
+            dots_view,
+            loss_gaps,
+            floor_visible,
+            bands_visible,
+            grid_visible,
+            histogram_visible: false,
+            zen_mode: false,
+            loss_view: false,
+            line_view: false,
+            heatmap_view: false,
+            loss_scroll: 0,
+            wheel_run: None,
+            spark_alpha,
+            targets: Vec::new(),
+            primary_target_idx: None,
+            legend_visible: false,
+            legend_mode,
+            display_utc,
+            clipboard: None,
+            deadline: None,
+            wait,
+            wait_met: false,
+            previous_run: None,
+            sweep_started: Instant::now(),
+            metrics: None,
+            probe_switch: ProbeSwitch::default(),
+            probe_seq: ProbeSeq::default(),
+            send_counts: VecDeque::new(),
+            in_flight: InFlight::default(),
+            local_addr: LocalAddr::default(),
+            shown_scheme: settings_colors,
+            scheme_transition: None,
+            cursor_lit: true,
+            cursor_blinked_at: Instant::now(),
+            warmup_remaining,
+            results_since_draw: 0,
+            frame_coalesced: 0,
+            smoothed_rtt: None,
+            smoothed_rtt_losses: 0,
+            spike_count: 0,
+            in_spike: false,
+            footer_event: None,
+            events: Vec::new(),
+            notes: Vec::new(),
+            graph_cache: GraphCache::default(),
+            loss_freeze_armed,
+            banner: None,
+            awaiting_first_result,
+            snapshot_requested: false,
         }
     }
 
     pub fn record_result(&mut self, result: PingResult) {
-        self.stats.record(&result);
+        // Warmup samples are graphed but kept out of the statistics
+        if self.warmup_remaining > 0 {
+            self.warmup_remaining -= 1;
+        } else {
+            self.stats.record(&result);
+        }
+        self.results_since_draw += 1;
+        self.awaiting_first_result = None;
 
         // Track recent RTT for sparkline
         let rtt_ms = result.rtt_ms_f64();
+        if let Some(rtt_ms) = rtt_ms {
+            self.detect_spike(rtt_ms);
+        }
+        self.update_smoothed_rtt(rtt_ms);
         self.recent_rtts.push_back(rtt_ms);
         while self.recent_rtts.len() > MAX_RECENT_RTT_COUNT {
             self.recent_rtts.pop_front();
         }
 
+        self.check_count();
+
+        let timed_out_at = result.rtt.is_none().then_some(result.timestamp);
+        for sums in &mut self.window_sums {
+            sums.add(&result);
+        }
+        if let Some(split) = &mut self.split_sums {
+            split.record(&result);
+        }
+        self.loss_events.record(&result);
+        self.run_summary = None;
         self.results.push_back(result);
 
+        // --pause-on-loss: freeze the view with the timeout on the bottom row, so it's on
+        // screen when the user comes back
+        if self.loss_freeze_armed
+            && self.view_end_row.is_none()
+            && let Some(timestamp) = timed_out_at
+            && let Some((_, _, width, _)) = self.graph_area
+        {
+            let row_len = self.row_len(width as usize).max(1);
+            let seq = self.result_base_seq + self.results.len() - 1;
+            self.freeze_view_at(seq / row_len + 1, row_len);
+            self.loss_freeze_armed = false;
+            self.footer_event = Some(format!(
+                "auto-paused on loss at {} (End resumes)",
+                format_time(timestamp, "%H:%M:%S", self.display_utc)
+            ));
+        }
+
+        self.trim_history();
+    }
+
+    /// Evict the oldest results beyond `max_history` (and older than `--retain`),
+    /// advancing `result_base_seq` so stable sequence numbers stay the same
+    fn trim_history(&mut self) {
         // Keep history bounded to max_history
         while self.results.len() > self.max_history {
-            self.results.pop_front();
-            self.result_base_seq += 1;
+            self.evict_oldest();
+        }
+
+        // Also drop results older than --retain
+        if let Some(retain_ms) = self.config.retain {
+            let cutoff = chrono::Local::now() - chrono::Duration::milliseconds(retain_ms as i64);
+            while self.results.front().is_some_and(|r| r.timestamp < cutoff) {
+                self.evict_oldest();
+            }
+        }
+
+        // Forget markers whose samples have been evicted
+        let base = self.result_base_seq;
+        self.events.retain(|&(seq, _)| seq >= base);
+    }
+
+    /// Drop the oldest result, taking it out of the summary windows still counting it
+    fn evict_oldest(&mut self) {
+        let Some(result) = self.results.pop_front() else {
+            return;
+        };
+        for sums in &mut self.window_sums {
+            if sums.start == self.result_base_seq {
+                sums.remove(&result);
+            }
+        }
+        self.loss_events.evict(&result, self.results.front());
+        self.result_base_seq += 1;
+        self.run_summary = None;
+    }
+
+    /// Apply a new `max_history` right away: trim to it when shrinking (releasing the
+    /// memory), or make room for it when growing
+    fn resize_history(&mut self, max_history: usize) {
+        self.max_history = max_history;
+        self.trim_history();
+        let capacity = max_history.min(MAX_PREALLOCATED_RESULTS);
+        if capacity < self.results.capacity() {
+            self.results.shrink_to(capacity);
+        } else {
+            self.results
+                .reserve(capacity.saturating_sub(self.results.len()));
+        }
+    }
+
+    /// Whether to show the notice that the pinger is waiting for its first result. Past
+    /// the timeout plus an interval a result should have arrived, so it gives up then
+    /// rather than claim to be waiting forever
+    pub fn startup_notice_visible(&self) -> bool {
+        let limit = Duration::from_millis(self.config.timeout + self.config.interval);
+        self.awaiting_first_result
+            .is_some_and(|started| started.elapsed() < limit)
+    }
+
+    /// Mark an event on the graph before the next sample to arrive
+    pub fn mark_event(&mut self, kind: EventKind) {
+        self.events
+            .push((self.result_base_seq + self.results.len(), kind));
+    }
+
+    /// Leave the next `--warmup` samples out of the statistics (after the pinger restarts)
+    pub fn start_warmup(&mut self) {
+        self.warmup_remaining = self.config.warmup;
+    }
+
+    /// Account for the samples about to be shown in a new frame
+    pub fn begin_frame(&mut self) {
+        self.frame_coalesced = self.results_since_draw.saturating_sub(1);
+        self.results_since_draw = 0;
+    }
+
+    /// Count a spike when a reply goes over `--spike-factor` times the median of the
+    /// recent replies (timeouts neither start nor end a spike)
+    fn detect_spike(&mut self, rtt_ms: f64) {
+        let mut recent: Vec<f64> = self
+            .recent_rtts
+            .iter()
+            .rev()
+            .flatten()
+            .take(SPIKE_WINDOW)
+            .copied()
+            .collect();
+        if recent.len() < SPIKE_MIN_SAMPLES {
+            return;
+        }
+        recent.sort_by(f64::total_cmp);
+        let median = recent[recent.len() / 2];
+        let spiking = rtt_ms > median * self.config.spike_factor;
+        if spiking && !self.in_spike {
+            self.spike_count += 1;
+            if self.config.mark_spikes {
+                self.mark_event(EventKind::Spike);
+            }
+        }
+        self.in_spike = spiking;
+    }
+
+    /// Fold a reply into the smoothed current RTT; a run of
+    /// `SMOOTHED_RTT_RESET_LOSSES` losses clears it, so it doesn't carry a stale
+    /// reading across an outage
+    fn update_smoothed_rtt(&mut self, rtt_ms: Option<f64>) {
+        match rtt_ms {
+            Some(rtt_ms) => {
+                self.smoothed_rtt_losses = 0;
+                self.smoothed_rtt = Some(match self.smoothed_rtt {
+                    Some(smoothed) => smoothed + SMOOTHED_RTT_ALPHA * (rtt_ms - smoothed),
+                    None => rtt_ms,
+                });
+            }
+            None => {
+                self.smoothed_rtt_losses += 1;
+                if self.smoothed_rtt_losses >= SMOOTHED_RTT_RESET_LOSSES {
+                    self.smoothed_rtt = None;
+                }
+            }
+        }
+    }
+
+    /// Record a result for a target in the multi-target summary
+    pub fn record_target_result(&mut self, idx: usize, result: &PingResult) {
+        if let Some(target) = self.targets.get_mut(idx) {
+            target.record(result);
         }
     }
 
@@ -271,11 +926,250 @@ impl App {
     /// Clear all stats, results, and history (used when target changes)
     pub fn clear_all_data(&mut self) {
         self.stats = PingStats::new();
+        self.paused_results = 0;
         self.results.clear();
+        self.window_sums.fill(WindowStats::default());
+        self.split_sums = None;
+        self.run_summary = None;
+        self.loss_events.clear();
         self.recent_rtts.clear();
+        // Losses of a previous target don't count toward --wait-down
+        if let Some(wait) = &mut self.wait {
+            *wait = WaitTracker::new(wait.wait);
+        }
         self.result_base_seq = 0;
+        self.follow_live_view();
+        self.popup = None;
+        self.smoothed_rtt = None;
+        self.smoothed_rtt_losses = 0;
+        self.spike_count = 0;
+        self.in_spike = false;
+        self.footer_event = None;
+        self.events.clear();
+        self.notes.clear();
+        self.selection = None;
+        self.drag_start_seq = None;
+    }
+
+    /// Fade from the previously shown scheme after the scheme changed (from any of the
+    /// places it can be changed), blending the color scale for the current frame
+    /// Returns true while a fade is running, so frames keep being drawn
+    pub fn update_scheme_transition(&mut self, now: Instant) -> bool {
+        let scheme = self.color_scale.scheme;
+        if scheme != self.shown_scheme {
+            self.scheme_transition = Some((self.shown_scheme, now));
+            self.shown_scheme = scheme;
+        }
+        let Some((from, started)) = self.scheme_transition else {
+            return false;
+        };
+        let t = now.duration_since(started).as_secs_f64() / SCHEME_TRANSITION.as_secs_f64();
+        let scale = ColorScale::new(self.color_scale.max_rtt, scheme);
+        if t >= 1.0 {
+            self.scheme_transition = None;
+            self.color_scale = scale;
+            return false;
+        }
+        self.color_scale = scale.blend_from(from, t);
+        true
+    }
+
+    /// Advance the cursor blink of --blink-cursor. Returns true when the cursor turned
+    /// on or off, so frames are only drawn at the blink cadence
+    pub fn update_cursor_blink(&mut self, now: Instant) -> bool {
+        if !self.config.blink_cursor || now.duration_since(self.cursor_blinked_at) < CURSOR_BLINK {
+            return false;
+        }
+        self.cursor_lit = !self.cursor_lit;
+        self.cursor_blinked_at = now;
+        true
+    }
+
+    /// Follow `--interval-sweep` in `config.interval`, so the header shows the live interval
+    pub fn update_sweep_interval(&mut self) {
+        if let Some(sweep) = self.config.interval_sweep {
+            self.config.interval = sweep.interval_at(self.sweep_started.elapsed());
+        }
+    }
+
+    /// Publish the current stats to the `--metrics-port` endpoint, if enabled
+    pub fn publish_metrics(&self, target: &str) {
+        if let Some(metrics) = &self.metrics {
+            metrics.update(target, &self.stats, self.results.back());
+        }
+    }
+
+    /// Number of screen columns one sample occupies in the graph
+    pub fn cell_width(&self) -> usize {
+        if self.numeric_view {
+            NUMERIC_CELL_WIDTH
+        } else if self.dots_view {
+            1
+        } else {
+            self.config.cell_width
+        }
+    }
+
+    /// Number of samples one graph cell stands for (several in dot density mode or
+    /// with --aggregate)
+    pub fn samples_per_cell(&self) -> usize {
+        if self.dots_view && !self.numeric_view {
+            DOTS_PER_CELL
+        } else {
+            self.config.aggregate
+        }
+    }
+
+    /// Number of samples per graph row for a given graph width (in columns), unless
+    /// fixed with --samples-per-row
+    pub fn row_len(&self, graph_width: usize) -> usize {
+        self.config
+            .samples_per_row
+            .unwrap_or(graph_width / self.cell_width() * self.samples_per_cell())
+    }
+
+    /// Columns a row of `row_len` samples takes
+    pub fn row_columns(&self, row_len: usize) -> usize {
+        row_len.div_ceil(self.samples_per_cell()) * self.cell_width()
+    }
+
+    /// Update legend visibility for the terminal width
+    /// In auto mode it shows at MIN_WIDTH_FOR_LEGEND and hides only below
+    /// HIDE_WIDTH_FOR_LEGEND; turned on it shows down to MIN_WIDTH_FOR_FORCED_LEGEND
+    pub fn update_legend_visibility(&mut self, terminal_width: u16) -> bool {
+        match self.legend_mode {
+            LegendMode::Auto if terminal_width >= MIN_WIDTH_FOR_LEGEND => {
+                self.legend_visible = true;
+            }
+            LegendMode::Auto if terminal_width < HIDE_WIDTH_FOR_LEGEND => {
+                self.legend_visible = false;
+            }
+            LegendMode::Auto => {}
+            LegendMode::On => self.legend_visible = terminal_width >= MIN_WIDTH_FOR_FORCED_LEGEND,
+            LegendMode::Off => self.legend_visible = false,
+        }
+        self.legend_visible
+    }
+
+    /// Turn the legend off if it's showing, otherwise on, overriding the automatic
+    /// behavior for the terminal width
+    pub fn toggle_legend(&mut self) {
+        self.legend_mode = if self.legend_visible {
+            LegendMode::Off
+        } else {
+            LegendMode::On
+        };
+    }
+
+    /// Recompute `view_end_row` from the view anchor for the current row length
+    /// Called on every draw so resizes and legend toggles keep the same pings on screen
+    pub fn sync_view_to_row_len(&mut self, row_len: usize) {
+        if let Some(anchor) = self.view_anchor_seq
+            && row_len > 0
+        {
+            self.view_end_row = Some(anchor.div_ceil(row_len).max(1));
+        }
+    }
+
+    /// Freeze the view so that `end_row` is the bottom row for the given row length
+    pub fn freeze_view_at(&mut self, end_row: usize, row_len: usize) {
+        self.view_end_row = Some(end_row);
+        self.view_anchor_seq = Some(end_row * row_len);
+    }
+
+    /// Return the view to live mode (follow newest data)
+    pub fn follow_live_view(&mut self) {
         self.view_end_row = None;
+        self.view_anchor_seq = None;
+    }
+
+    /// Toggle between the colored grid and the numeric RTT readout
+    pub fn toggle_numeric_view(&mut self) {
+        self.numeric_view = !self.numeric_view;
+        self.popup = None;
+    }
+
+    /// Toggle dot density cells (several samples per cell) on or off
+    pub fn toggle_dots_view(&mut self) {
+        self.dots_view = !self.dots_view;
+        self.popup = None;
+    }
+
+    /// Toggle between blank gaps and `X` marks for timeouts
+    pub fn toggle_loss_gaps(&mut self) {
+        self.loss_gaps = !self.loss_gaps;
+    }
+
+    /// Show or hide the tint on samples near the lowest RTT on screen
+    pub fn toggle_floor(&mut self) {
+        self.floor_visible = !self.floor_visible;
+    }
+
+    /// Show or hide the RTT bands shading quarters of the color scale
+    pub fn toggle_bands(&mut self) {
+        self.bands_visible = !self.bands_visible;
+    }
+
+    /// Show or hide the vertical gridlines
+    pub fn toggle_grid(&mut self) {
+        self.grid_visible = !self.grid_visible;
+    }
+
+    /// Switch the times shown (and typed to jump to) between local time and UTC
+    /// Samples keep their local timestamp with its offset, so only the display changes;
+    /// `--export` timestamps stay as `--utc` chose
+    pub fn toggle_utc(&mut self) {
+        self.display_utc = !self.display_utc;
+        self.footer_event = Some(
+            if self.display_utc {
+                "times in UTC"
+            } else {
+                "times in local time"
+            }
+            .to_string(),
+        );
+    }
+
+    /// Switch between the graph and the timeline of loss events
+    pub fn toggle_loss_view(&mut self) {
+        self.loss_view = !self.loss_view;
+        self.line_view = false;
+        self.heatmap_view = false;
+        self.loss_scroll = 0;
+        self.popup = None;
+        self.clear_selection();
+    }
+
+    /// Switch between the graph and the RTT line chart
+    pub fn toggle_line_view(&mut self) {
+        self.line_view = !self.line_view;
+        self.loss_view = false;
+        self.heatmap_view = false;
+        self.popup = None;
+        self.clear_selection();
+    }
+
+    /// Switch between the graph and the time-of-day heatmap
+    pub fn toggle_heatmap_view(&mut self) {
+        self.heatmap_view = !self.heatmap_view;
+        self.loss_view = false;
+        self.line_view = false;
+        self.popup = None;
+        self.clear_selection();
+    }
+
+    /// Show or hide the RTT distribution histogram panel
+    pub fn toggle_histogram(&mut self) {
+        self.histogram_visible = !self.histogram_visible;
+        self.popup = None;
+    }
+
+    /// Show only the graph, filling the whole terminal (or bring the rest back)
+    pub fn toggle_zen_mode(&mut self) {
+        self.zen_mode = !self.zen_mode;
         self.popup = None;
+        // The header can't be navigated while it's hidden
+        self.header_selected = None;
     }
 
     /// Calculate current total rows of data (using stable sequence numbers)
@@ -289,7 +1183,6 @@ impl App {
     }
 
     /// Get the current view end row (for display purposes)
-    #[allow(dead_code)]
     pub fn current_view_end_row(&self, width: usize) -> usize {
         match self.view_end_row {
             Some(row) => row,
@@ -297,17 +1190,28 @@ impl App {
         }
     }
 
+    /// Stop or resume sending probes (e.g. to save traffic on a metered link)
+    pub fn toggle_probing(&mut self) {
+        self.probe_switch
+            .set_stopped(!self.probe_switch.is_stopped());
+    }
+
     pub fn toggle_pause(&mut self) {
         self.paused = !self.paused;
         if !self.paused {
             // Resume: jump to live view
-            self.view_end_row = None;
+            self.follow_live_view();
         }
     }
 
     pub fn scroll_up(&mut self, rows: usize) {
+        if self.loss_view {
+            // Clamped to the number of events when drawn
+            self.loss_scroll += rows;
+            return;
+        }
         if let Some((_, _, width, _)) = self.graph_area {
-            let width = width as usize;
+            let width = self.row_len(width as usize);
             let total_rows = self.total_rows(width);
 
             if total_rows == 0 {
@@ -321,14 +1225,18 @@ impl App {
             // Allow scrolling all the way to row 1 (view_end_row = 1) so only first row is visible
             let new_end = current_end.saturating_sub(rows).max(1);
 
-            self.view_end_row = Some(new_end);
+            self.freeze_view_at(new_end, width);
             // Note: scrolling doesn't pause - pings keep collecting, just view is locked
         }
     }
 
     pub fn scroll_down(&mut self, rows: usize) {
+        if self.loss_view {
+            self.loss_scroll = self.loss_scroll.saturating_sub(rows);
+            return;
+        }
         if let Some((_, _, width, _)) = self.graph_area {
-            let width = width as usize;
+            let width = self.row_len(width as usize);
             let total_rows = self.total_rows(width);
 
             if let Some(current_end) = self.view_end_row {
@@ -336,122 +1244,656 @@ impl App {
 
                 if new_end >= total_rows {
                     // Reached the end, switch to live mode
-                    self.view_end_row = None;
+                    self.follow_live_view();
                 } else {
-                    self.view_end_row = Some(new_end);
+                    self.freeze_view_at(new_end, width);
                 }
             }
             // If already in live mode, do nothing
         }
     }
 
+    /// Scroll as if moving the view towards the top of the screen, which shows newer
+    /// rows when the graph is drawn top-down (the loss list always scrolls the same way)
+    pub fn scroll_toward_top(&mut self, rows: usize) {
+        if self.config.top_down && !self.loss_view {
+            self.scroll_down(rows);
+        } else {
+            self.scroll_up(rows);
+        }
+    }
+
+    /// Rows a mouse wheel tick at `now` scrolls (`up` = towards the top)
+    /// With --wheel-accel, ticks in a quick run in one direction scroll more each time
+    pub fn wheel_rows(&mut self, up: bool, now: Instant) -> usize {
+        let accel = self.config.wheel_accel;
+        if accel <= 0.0 {
+            return WHEEL_ROWS;
+        }
+        let run = match self.wheel_run {
+            Some((last, last_up, run))
+                if last_up == up && now.saturating_duration_since(last) < WHEEL_RUN_GAP =>
+            {
+                run + 1
+            }
+            _ => 0,
+        };
+        self.wheel_run = Some((now, up, run));
+        (1 + (run as f64 * accel) as usize).min(WHEEL_MAX_ROWS)
+    }
+
+    /// Scroll as if moving the view towards the bottom of the screen
+    pub fn scroll_toward_bottom(&mut self, rows: usize) {
+        if self.config.top_down && !self.loss_view {
+            self.scroll_up(rows);
+        } else {
+            self.scroll_down(rows);
+        }
+    }
+
     pub fn jump_to_live(&mut self) {
-        self.view_end_row = None;
+        self.follow_live_view();
         self.paused = false;
+        self.loss_freeze_armed = self.config.pause_on_loss;
+    }
+
+    /// Scroll back to the oldest data still in the buffer
+    pub fn jump_to_oldest(&mut self) {
+        if let Some((_, _, width, height)) = self.graph_area {
+            let width = self.row_len(width as usize);
+            let total_rows = self.total_rows(width);
+
+            if total_rows == 0 {
+                return;
+            }
+
+            // First row that still has buffered data, shown at the top of the screen
+            let first_row = self.result_base_seq / width;
+            let new_end = first_row + (height as usize).max(1);
+
+            if new_end >= total_rows {
+                // Everything buffered already fits on screen
+                self.follow_live_view();
+            } else {
+                self.freeze_view_at(new_end, width);
+            }
+        }
     }
 
     /// Check if we're in live mode (following newest data)
-    #[allow(dead_code)]
     pub fn is_live(&self) -> bool {
         self.view_end_row.is_none()
     }
 
+    /// Pin the baseline at the current average RTT, or clear it if already set
+    pub fn toggle_baseline(&mut self) {
+        if self.baseline_ms.is_some() {
+            self.baseline_ms = None;
+        } else if let Some(avg) = self.stats.avg_rtt() {
+            self.baseline_ms = Some(avg.as_secs_f64() * 1000.0);
+        }
+    }
+
     /// Get the PingResult at a given index if it exists
-    #[allow(dead_code)]
     pub fn get_result(&self, idx: usize) -> Option<&PingResult> {
         self.results.get(idx)
     }
 
-    pub fn quit(&mut self) {
-        self.should_quit = true;
+    /// Whether the `--duration` limit has been reached
+    pub fn deadline_passed(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| tokio::time::Instant::now() >= deadline)
     }
 
-    /// Open settings menu (unconditional)
-    pub fn open_settings(&mut self) {
-        self.settings_open = true;
-        // Always start with the first field selected
-        self.settings_field = SettingsField::Target;
-        // Initialize with current active values (not startup values)
-        self.settings_target = self.config.host.clone().unwrap_or_default();
-        self.settings_interval = self.config.interval;
-        self.settings_scale = self.color_scale.max_rtt;
-        self.settings_colors = self.color_scale.scheme;
-        self.settings_hide_cursor = self.config.hide_cursor;
-        self.settings_input_buffer.clear();
-        self.settings_input_cursor = 0;
-        self.settings_input_active = false;
-        self.settings_input_selected = false;
-        // Store originals for cancel
-        self.settings_original_scale = self.color_scale.max_rtt;
-        self.settings_original_colors = self.color_scale.scheme;
-        self.settings_original_hide_cursor = self.config.hide_cursor;
+    /// Note a result dropped while paused: it isn't recorded, but was still sent
+    pub fn skip_result(&mut self) {
+        self.paused_results += 1;
+        self.check_count();
     }
 
-    /// Toggle settings menu
-    pub fn toggle_settings(&mut self) {
-        if self.settings_open {
-            self.settings_open = false;
-        } else {
-            self.open_settings();
+    /// Stop once the requested number of pings (including timeouts and those dropped
+    /// while paused) is done
+    fn check_count(&mut self) {
+        if self
+            .config
+            .count
+            .is_some_and(|count| self.stats.total_sent + self.paused_results >= count)
+        {
+            self.should_quit = true;
         }
     }
 
-    /// Cancel settings and restore original values
-    pub fn cancel_settings(&mut self) {
-        // Restore original values
-        self.color_scale =
-            ColorScale::new(self.settings_original_scale, self.settings_original_colors);
-        self.config.hide_cursor = self.settings_original_hide_cursor;
-        self.settings_open = false;
-        self.settings_input_active = false;
+    /// Follow a result of the main target for --wait-up / --wait-down (also while
+    /// paused), quitting once the condition is met
+    pub fn check_wait(&mut self, result: &PingResult) {
+        if let Some(wait) = &mut self.wait
+            && wait.update(result)
+        {
+            self.wait_met = true;
+            self.should_quit = true;
+        }
     }
 
-    /// Apply settings changes
-    pub fn apply_settings(&mut self) {
-        // Check if target changed
-        let current_target = self.config.host.clone().unwrap_or_default();
-        let target_changed =
-            !self.settings_target.is_empty() && self.settings_target != current_target;
+    /// Terminal window title summarizing the latest RTT and overall loss
+    pub fn window_title(&self) -> String {
+        let host = self.config.host.as_deref().unwrap_or("no target");
+        let rtt = match self.recent_rtts.back() {
+            Some(Some(ms)) => self.config.unit.format(*ms, 0),
+            Some(None) => "timeout".to_string(),
+            None => "-".to_string(),
+        };
+        format!(
+            "rttui {}: {} {:.1}% loss",
+            host,
+            rtt,
+            self.stats.loss_percent()
+        )
+    }
+
+    /// Extend a graph drag to the sample at `seq`, selecting the range from the drag start
+    /// Returns true if a range (more than one sample) is now selected
+    pub fn drag_select_to(&mut self, seq: usize) -> bool {
+        let Some(start) = self.drag_start_seq else {
+            return false;
+        };
+        if seq == start {
+            self.selection = None;
+            return false;
+        }
+        self.selection = Some((start.min(seq), start.max(seq)));
+        self.popup = None;
+        true
+    }
+
+    /// Drop the range selection, resuming live view if the drag started in live mode
+    pub fn clear_selection(&mut self) {
+        if self.selection.take().is_some() && self.popup_was_live {
+            self.follow_live_view();
+            self.popup_was_live = false;
+        }
+        self.drag_start_seq = None;
+    }
+
+    /// Aggregate stats over the selected samples that are still in the buffer
+    pub fn selection_stats(&self) -> Option<PingStats> {
+        let mut stats = PingStats::new();
+        for result in self.results.range(self.selection_range()?) {
+            stats.record(result);
+        }
+        Some(stats)
+    }
+
+    /// Sequence numbers of the first and last selected results still in the buffer
+    pub fn selection_seqs(&self) -> Option<(u64, u64)> {
+        let range = self.selection_range()?;
+        let first = self.results.get(range.start)?;
+        let last = self.results.get(range.end.checked_sub(1)?)?;
+        Some((first.seq, last.seq))
+    }
+
+    /// Buffer indices of the drag selection, limited to the results still kept
+    fn selection_range(&self) -> Option<std::ops::Range<usize>> {
+        let (start, end) = self.selection?;
+        let first = start.saturating_sub(self.result_base_seq);
+        let last = (end + 1).saturating_sub(self.result_base_seq);
+        Some(first.min(self.results.len())..last.min(self.results.len()))
+    }
+
+    /// Take results sent before each summary window out of its totals
+    /// Results are taken in the order they arrived, so a late timeout sent before
+    /// the window still counts until the results that arrived before it have left
+    fn expire_windows(&mut self, now: chrono::DateTime<chrono::Local>) {
+        let end = self.result_base_seq + self.results.len();
+        for (sums, &window_ms) in self.window_sums.iter_mut().zip(&self.config.windows) {
+            let cutoff = now - chrono::Duration::milliseconds(window_ms as i64);
+            while sums.start < end {
+                let result = &self.results[sums.start - self.result_base_seq];
+                if result.timestamp >= cutoff {
+                    break;
+                }
+                sums.remove(result);
+            }
+            // Full once any kept result is from before the window
+            sums.covered = self
+                .results
+                .front()
+                .and_then(|result| (now - result.timestamp).to_std().ok())
+                .unwrap_or_default()
+                .min(Duration::from_millis(window_ms));
+        }
+    }
+
+    /// Lowest RTT in ms over the last `window_ms` of results (the best-case path),
+    /// or None without replies in that time
+    pub fn best_rtt(&self, window_ms: u64) -> Option<f64> {
+        let cutoff = chrono::Local::now() - chrono::Duration::milliseconds(window_ms as i64);
+        self.results
+            .iter()
+            .rev()
+            .take_while(|result| result.timestamp >= cutoff)
+            .filter_map(|result| result.rtt_ms_f64())
+            .min_by(f64::total_cmp)
+    }
+
+    /// Stable sequence number of the first sample after the split marker
+    pub fn split_seq(&self) -> Option<usize> {
+        self.events
+            .iter()
+            .find(|(_, kind)| *kind == EventKind::Split)
+            .map(|&(seq, _)| seq)
+    }
+
+    /// Drop a split marker before the next sample, or remove the one already placed
+    pub fn toggle_split(&mut self) {
+        if self.split_seq().is_some() {
+            self.events.retain(|(_, kind)| *kind != EventKind::Split);
+        } else {
+            self.mark_event(EventKind::Split);
+        }
+    }
+
+    /// Change of the samples after the split marker relative to those before it
+    /// (None until both sides have samples)
+    /// The side before the marker is the buffer as it was when the marker was first
+    /// compared, so evicting its oldest samples later doesn't change it
+    pub fn split_comparison(&mut self) -> Option<Comparison> {
+        let seq = self.split_seq()?;
+        if self
+            .split_sums
+            .as_ref()
+            .is_none_or(|split| split.seq != seq)
+        {
+            let idx = seq
+                .checked_sub(self.result_base_seq)?
+                .min(self.results.len());
+            let mut before = PingStats::new();
+            self.results
+                .range(..idx)
+                .for_each(|result| before.record(result));
+            let mut split = SplitSums {
+                seq,
+                before: ExportSummary::new(&before, self.results.range(..idx)),
+                after: PingStats::new(),
+                after_jitter: (0.0, 0),
+            };
+            self.results
+                .range(idx..)
+                .for_each(|result| split.record(result));
+            self.split_sums = Some(split);
+        }
+
+        let split = self.split_sums.as_ref()?;
+        if split.before.sent == 0 || split.after.total_sent == 0 {
+            return None;
+        }
+        let (jitter_sum, jitter_count) = split.after_jitter;
+        let after = ExportSummary {
+            jitter_ms: (jitter_count > 0).then(|| jitter_sum / jitter_count as f64),
+            ..ExportSummary::new(&split.after, std::iter::empty())
+        };
+        Some(Comparison::new(&after, &split.before))
+    }
+
+    /// Change of this session relative to the run loaded with `--compare`
+    pub fn run_comparison(&mut self) -> Option<Comparison> {
+        let previous = self.previous_run.as_ref()?;
+        let current = self
+            .run_summary
+            .get_or_insert_with(|| ExportSummary::new(&self.stats, &self.results));
+        Some(Comparison::new(current, previous))
+    }
+
+    /// Connection quality grade from the session stats and the latest smoothed jitter
+    pub fn quality(&self) -> Option<Quality> {
+        if self.stats.total_sent == 0 {
+            return None;
+        }
+        let jitter = self
+            .results
+            .iter()
+            .rev()
+            .find_map(PingResult::smoothed_jitter_ms_f64);
+        Some(Quality::assess(
+            self.stats.loss_percent(),
+            self.stats.avg_rtt().map(|d| d.as_secs_f64() * 1000.0),
+            jitter,
+            (
+                self.config.loss_crit,
+                self.config.quality_rtt,
+                self.config.quality_jitter,
+            ),
+        ))
+    }
+
+    /// Whether latency is heading up or down: the average of the newest replies against
+    /// a longer run of them (None until there are twice as many as the recent average needs)
+    pub fn trend(&self) -> Option<Trend> {
+        let rtts: Vec<f64> = self
+            .results
+            .iter()
+            .rev()
+            .filter_map(PingResult::rtt_ms_f64)
+            .take(TREND_LONGER_REPLIES)
+            .collect();
+        if rtts.len() < TREND_RECENT_REPLIES * 2 {
+            return None;
+        }
+        let mean = |rtts: &[f64]| rtts.iter().sum::<f64>() / rtts.len() as f64;
+        Some(Trend::assess(
+            mean(&rtts[..TREND_RECENT_REPLIES]),
+            mean(&rtts),
+            self.config.trend_sensitivity,
+        ))
+    }
+
+    /// Achieved send rate in pings per second over the most recent sends, to tell
+    /// whether the configured interval is actually being met
+    /// Counted from the pinger's sends (see `track_sends`), since a timeout only arrives
+    /// a timeout after it was sent, behind replies to later sends
+    pub fn send_rate(&self) -> Option<f64> {
+        let &(newest_at, newest) = self.send_counts.back()?;
+        let (mut oldest_at, mut oldest) = (newest_at, newest);
+        for &(at, count) in self.send_counts.iter().rev() {
+            if newest - oldest + 1 >= RATE_MIN_SENDS as u64
+                && newest_at.saturating_duration_since(at) > RATE_WINDOW
+            {
+                break;
+            }
+            (oldest_at, oldest) = (at, count);
+        }
+        let span = newest_at.saturating_duration_since(oldest_at).as_secs_f64();
+        (newest > oldest && span > 0.0).then(|| (newest - oldest) as f64 / span)
+    }
+
+    /// Note the main pinger's send count at `now` (called every loop), for `send_rate`
+    pub fn track_sends(&mut self, now: Instant) {
+        let count = self.probe_seq.sent();
+        match self.send_counts.back() {
+            Some(&(_, last)) if last == count => return,
+            // A new pinger that starts numbering over
+            Some(&(_, last)) if last > count => self.send_counts.clear(),
+            _ => {}
+        }
+        self.send_counts.push_back((now, count));
+        // Only the window (and at least RATE_MIN_SENDS sends) is needed
+        while self.send_counts.len() > RATE_MIN_SENDS
+            && now.saturating_duration_since(self.send_counts[1].0) > RATE_WINDOW
+        {
+            self.send_counts.pop_front();
+        }
+    }
+
+    /// Average RTT in ms with losses counted as the --loss-penalty (or the timeout),
+    /// when --effective-rtt is on
+    pub fn effective_rtt(&self) -> Option<f64> {
+        if !self.config.effective_rtt {
+            return None;
+        }
+        let penalty = self.config.loss_penalty.unwrap_or(self.config.timeout);
+        self.stats
+            .effective_rtt(Duration::from_millis(penalty))
+            .map(|rtt| rtt.as_secs_f64() * 1000.0)
+    }
+
+    /// Mean scheduling jitter in ms over the most recent sends, to tell whether
+    /// irregular send times (rather than the network) are behind observed jitter
+    pub fn sched_jitter(&self) -> Option<f64> {
+        let (sum, count) = self
+            .results
+            .iter()
+            .rev()
+            .take(SCHED_JITTER_SENDS)
+            .filter_map(|result| result.sched_jitter)
+            .fold((0.0, 0), |(sum, count), jitter| {
+                (sum + jitter.as_secs_f64() * 1000.0, count + 1)
+            });
+        (count > 0).then(|| sum / count as f64)
+    }
+
+    /// Totals for each configured summary window, paired with the window length
+    /// (None without results in the window)
+    pub fn window_summaries(&mut self) -> Vec<(u64, Option<WindowStats>)> {
+        self.expire_windows(chrono::Local::now());
+        self.config
+            .windows
+            .iter()
+            .zip(&self.window_sums)
+            .map(|(&window_ms, sums)| (window_ms, (sums.sent > 0).then(|| sums.clone())))
+            .collect()
+    }
+
+    /// Compare the reply at buffer index `idx` to the replies before it
+    /// (None for a timeout or when there are no earlier replies)
+    pub fn sample_context(&self, idx: usize) -> Option<SampleContext> {
+        let rtt = self.results.get(idx)?.rtt_ms_f64()?;
+        let (mut sum, mut count) = (0.0, 0);
+        let (mut min, mut max) = (f64::MAX, f64::MIN);
+        for earlier in self.results.range(..idx).filter_map(|r| r.rtt_ms_f64()) {
+            sum += earlier;
+            count += 1;
+            min = min.min(earlier);
+            max = max.max(earlier);
+        }
+        (count > 0).then(|| SampleContext {
+            vs_avg_ms: rtt - sum / count as f64,
+            new_min: rtt < min,
+            new_max: rtt > max,
+        })
+    }
+
+    /// Get the ping result currently shown in the popup (if it's still in the buffer)
+    pub fn popup_result(&self) -> Option<&PingResult> {
+        let popup = self.popup.as_ref()?;
+        let idx = popup.result_seq.checked_sub(self.result_base_seq)?;
+        self.results.get(idx)
+    }
+
+    /// Save a drawn frame as a timestamped SVG in the current directory, reporting where
+    /// it went (or why it couldn't be written) in the footer
+    pub fn save_snapshot(&mut self, frame: &Buffer) {
+        let path = snapshot_file_name(chrono::Local::now());
+        self.footer_event = Some(match std::fs::write(&path, buffer_to_svg(frame)) {
+            Ok(()) => format!("Saved snapshot to {}", path),
+            Err(e) => format!("Failed to save snapshot: {}", e),
+        });
+    }
+
+    /// Copy details of the ping shown in the popup to the system clipboard
+    /// Does nothing if no clipboard is available (e.g. headless or SSH sessions)
+    pub fn copy_popup_to_clipboard(&mut self) {
+        let Some(result) = self.popup_result() else {
+            return;
+        };
+
+        let (unit, precision) = (self.config.unit, self.config.precision);
+        let rtt_str = result
+            .rtt_ms_f64()
+            .map(|ms| unit.format(ms, precision))
+            .unwrap_or_else(|| result.loss_label());
+        let jitter_str = unit.format_jitter(result.jitter_ms_f64(), precision);
+        let smoothed_str = unit.format_jitter(result.smoothed_jitter_ms_f64(), precision);
+        let text = format!(
+            "Time: {} | RTT: {} | Jitter: {} | Smoothed jitter: {} | Seq: {}",
+            result.timestamp_rfc3339(self.display_utc),
+            rtt_str,
+            jitter_str,
+            smoothed_str,
+            result.seq
+        );
+
+        if self.clipboard.is_none() {
+            self.clipboard = arboard::Clipboard::new().ok();
+        }
+        if let Some(clipboard) = self.clipboard.as_mut() {
+            let _ = clipboard.set_text(text);
+        }
+    }
+
+    pub fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    /// Open settings menu (unconditional)
+    pub fn open_settings(&mut self) {
+        self.settings_open = true;
+        // Always start with the first field selected
+        self.settings_field = SettingsField::Target;
+        // Initialize with current active values (not startup values)
+        self.settings_target = self.config.host.clone().unwrap_or_default();
+        self.settings_interval = self.config.interval;
+        self.settings_timeout = self.config.timeout;
+        self.settings_scale = self.color_scale.max_rtt;
+        self.settings_colors = self.color_scale.scheme;
+        self.settings_previous_colors = self.color_scale.scheme;
+        self.scheme_compare = false;
+        self.settings_hide_cursor = self.config.hide_cursor;
+        self.settings_hide_indicator = self.config.hide_indicator;
+        self.settings_top_down = self.config.top_down;
+        self.settings_confirm_quit = !self.config.no_confirm_quit;
+        self.settings_unit = self.config.unit;
+        self.settings_aggregate_by = self.config.aggregate_by;
+        self.settings_input = TextInput::default();
+        self.settings_input_active = false;
+        // Store originals for cancel
+        self.settings_original_scale = self.color_scale.max_rtt;
+        self.settings_original_colors = self.color_scale.scheme;
+        self.settings_original_hide_cursor = self.config.hide_cursor;
+        self.settings_original_hide_indicator = self.config.hide_indicator;
+        self.settings_original_top_down = self.config.top_down;
+        self.settings_original_unit = self.config.unit;
+        self.settings_original_interval = self.config.interval;
+        self.settings_original_timeout = self.config.timeout;
+    }
+
+    /// Toggle settings menu
+    pub fn toggle_settings(&mut self) {
+        if self.settings_open {
+            self.settings_open = false;
+        } else {
+            self.open_settings();
+        }
+    }
 
-        // Check if interval changed
-        let interval_changed = self.settings_interval != self.config.interval;
+    /// Cancel settings and restore original values
+    pub fn cancel_settings(&mut self) {
+        // Restore original values
+        self.color_scale =
+            ColorScale::new(self.settings_original_scale, self.settings_original_colors);
+        self.config.hide_cursor = self.settings_original_hide_cursor;
+        self.config.hide_indicator = self.settings_original_hide_indicator;
+        self.config.top_down = self.settings_original_top_down;
+        self.config.unit = self.settings_original_unit;
+        self.settings_open = false;
+        self.settings_input_active = false;
+        self.scheme_compare = false;
+    }
+
+    /// Compare the selected color scheme with the previous one on the graph
+    pub fn open_scheme_compare(&mut self) {
+        self.settings_field = SettingsField::ColorScheme;
+        self.scheme_compare = true;
+    }
 
-        // Apply target
-        if !self.settings_target.is_empty() {
+    /// Back to the settings menu from the scheme comparison, with the selected scheme,
+    /// or the previous one when `keep_selected` is false
+    pub fn close_scheme_compare(&mut self, keep_selected: bool) {
+        if !keep_selected {
+            std::mem::swap(
+                &mut self.settings_colors,
+                &mut self.settings_previous_colors,
+            );
+            self.color_scale = ColorScale::new(self.settings_scale, self.settings_colors);
+        }
+        self.scheme_compare = false;
+    }
+
+    /// Apply settings changes
+    /// Returns false (leaving the menu open with an error) if the target can't be pinged
+    /// Also refuses while the field being typed in holds an invalid value
+    pub fn apply_settings(&mut self) -> bool {
+        if self.settings_input_active && self.settings_input.error.is_some() {
+            return false;
+        }
+        self.settings_interval = self.settings_interval.clamp(1, MAX_INTERVAL_MS);
+        self.settings_timeout = self.settings_timeout.clamp(1, MAX_TIMEOUT_MS);
+        self.settings_scale = self.settings_scale.clamp(1, MAX_SCALE_MS);
+        self.settings_buffer_mb = self.settings_buffer_mb.clamp(1, MAX_BUFFER_MB);
+        self.settings_target = clean_target(&self.settings_target);
+        if let Err(e) = check_target_literal(&self.settings_target) {
+            self.settings_input.error = Some(e);
+            self.settings_field = SettingsField::Target;
+            return false;
+        }
+
+        // Check if target changed
+        let current_target = self.config.host.clone().unwrap_or_default();
+        let target_changed =
+            !self.settings_target.is_empty() && self.settings_target != current_target;
+
+        // Check if interval changed (compared to when the menu opened, since a sweep
+        // keeps moving config.interval)
+        let interval_changed = self.settings_interval != self.settings_original_interval;
+        // Pingers capture the timeout when they start
+        let timeout_changed = self.settings_timeout != self.settings_original_timeout;
+
+        // Apply target, unless clearing the history waits for confirmation
+        let target_changed =
+            target_changed && !self.confirm_target_first(&self.settings_target.clone());
+        if target_changed {
             self.config.host = Some(self.settings_target.clone());
         }
-        // Apply interval
+        // Apply interval and timeout
         self.config.interval = self.settings_interval;
+        self.config.timeout = self.settings_timeout;
+        if (interval_changed || timeout_changed)
+            && let Some(warning) = timeout_warning(self.settings_timeout, self.settings_interval)
+        {
+            self.footer_event = Some(warning);
+        }
         // Apply scale and colors
         self.config.scale = self.settings_scale;
         self.config.colors = self.settings_colors;
         self.color_scale = ColorScale::new(self.settings_scale, self.settings_colors);
-        // Apply hide cursor
+        // Apply hide cursor and indicator
         self.config.hide_cursor = self.settings_hide_cursor;
+        self.config.hide_indicator = self.settings_hide_indicator;
+        self.config.top_down = self.settings_top_down;
+        self.config.no_confirm_quit = !self.settings_confirm_quit;
+        self.config.unit = self.settings_unit;
+        self.config.aggregate_by = self.settings_aggregate_by;
         // Apply buffer size
         self.config.buffer_mb = self.settings_buffer_mb;
-        self.max_history = self.config.max_history();
+        self.resize_history(self.config.max_history());
 
-        // Signal pinger restart if target or interval changed
-        if target_changed || interval_changed {
+        // Signal pinger restart if target, interval or timeout changed
+        if target_changed || interval_changed || timeout_changed {
             self.needs_pinger_restart = true;
             if target_changed {
                 self.new_target = Some(self.settings_target.clone());
             }
             if interval_changed {
                 self.new_interval = Some(self.settings_interval);
+                // A manually set interval ends the sweep
+                self.config.interval_sweep = None;
             }
         }
+        true
     }
 
     /// Navigate to next settings field
     pub fn settings_next_field(&mut self) {
         self.settings_field = self.settings_field.next();
+        if self.settings_field == SettingsField::AggregateBy && self.config.aggregate <= 1 {
+            self.settings_field = self.settings_field.next();
+        }
     }
 
     /// Navigate to previous settings field
     pub fn settings_prev_field(&mut self) {
         self.settings_field = self.settings_field.prev();
+        if self.settings_field == SettingsField::AggregateBy && self.config.aggregate <= 1 {
+            self.settings_field = self.settings_field.prev();
+        }
     }
 
     /// Increase current settings value
@@ -459,25 +1901,54 @@ impl App {
         match self.settings_field {
             SettingsField::Target | SettingsField::Confirm | SettingsField::Cancel => {}
             SettingsField::Interval => {
-                self.settings_interval = self.settings_interval.saturating_add(1).min(100000);
+                self.settings_interval = self
+                    .settings_interval
+                    .saturating_add(1)
+                    .min(MAX_INTERVAL_MS);
+            }
+            SettingsField::Timeout => {
+                self.settings_timeout = self.settings_timeout.saturating_add(1).min(MAX_TIMEOUT_MS);
             }
             SettingsField::Scale => {
-                self.settings_scale = self.settings_scale.saturating_add(1).min(100000);
+                self.settings_scale = self.settings_scale.saturating_add(1).min(MAX_SCALE_MS);
                 // Apply immediately
                 self.color_scale = ColorScale::new(self.settings_scale, self.settings_colors);
             }
             SettingsField::ColorScheme => {
+                self.settings_previous_colors = self.settings_colors;
                 self.settings_colors = self.settings_colors.next();
                 // Apply immediately
                 self.color_scale = ColorScale::new(self.settings_scale, self.settings_colors);
             }
+            SettingsField::Unit => {
+                self.settings_unit = self.settings_unit.next();
+                // Apply immediately for live preview
+                self.config.unit = self.settings_unit;
+            }
+            SettingsField::AggregateBy => {
+                self.settings_aggregate_by = self.settings_aggregate_by.next();
+            }
             SettingsField::HideCursor => {
                 self.settings_hide_cursor = !self.settings_hide_cursor;
                 // Apply immediately for live preview
                 self.config.hide_cursor = self.settings_hide_cursor;
             }
+            SettingsField::HideIndicator => {
+                self.settings_hide_indicator = !self.settings_hide_indicator;
+                // Apply immediately for live preview
+                self.config.hide_indicator = self.settings_hide_indicator;
+            }
+            SettingsField::TopDown => {
+                self.settings_top_down = !self.settings_top_down;
+                // Apply immediately for live preview
+                self.config.top_down = self.settings_top_down;
+            }
+            SettingsField::ConfirmQuit => {
+                self.settings_confirm_quit = !self.settings_confirm_quit;
+            }
             SettingsField::BufferSize => {
-                self.settings_buffer_mb = self.settings_buffer_mb.saturating_add(1).min(1000);
+                self.settings_buffer_mb =
+                    self.settings_buffer_mb.saturating_add(1).min(MAX_BUFFER_MB);
             }
         }
     }
@@ -489,21 +1960,46 @@ impl App {
             SettingsField::Interval => {
                 self.settings_interval = self.settings_interval.saturating_sub(1).max(1);
             }
+            SettingsField::Timeout => {
+                self.settings_timeout = self.settings_timeout.saturating_sub(1).max(1);
+            }
             SettingsField::Scale => {
                 self.settings_scale = self.settings_scale.saturating_sub(1).max(1);
                 // Apply immediately
                 self.color_scale = ColorScale::new(self.settings_scale, self.settings_colors);
             }
             SettingsField::ColorScheme => {
+                self.settings_previous_colors = self.settings_colors;
                 self.settings_colors = self.settings_colors.prev();
                 // Apply immediately
                 self.color_scale = ColorScale::new(self.settings_scale, self.settings_colors);
             }
+            SettingsField::Unit => {
+                self.settings_unit = self.settings_unit.prev();
+                // Apply immediately for live preview
+                self.config.unit = self.settings_unit;
+            }
+            SettingsField::AggregateBy => {
+                self.settings_aggregate_by = self.settings_aggregate_by.prev();
+            }
             SettingsField::HideCursor => {
                 self.settings_hide_cursor = !self.settings_hide_cursor;
                 // Apply immediately for live preview
                 self.config.hide_cursor = self.settings_hide_cursor;
             }
+            SettingsField::HideIndicator => {
+                self.settings_hide_indicator = !self.settings_hide_indicator;
+                // Apply immediately for live preview
+                self.config.hide_indicator = self.settings_hide_indicator;
+            }
+            SettingsField::TopDown => {
+                self.settings_top_down = !self.settings_top_down;
+                // Apply immediately for live preview
+                self.config.top_down = self.settings_top_down;
+            }
+            SettingsField::ConfirmQuit => {
+                self.settings_confirm_quit = !self.settings_confirm_quit;
+            }
             SettingsField::BufferSize => {
                 self.settings_buffer_mb = self.settings_buffer_mb.saturating_sub(1).max(1);
             }
@@ -512,131 +2008,96 @@ impl App {
 
     /// Start text input mode for current field
     pub fn settings_start_input(&mut self) {
-        if self.settings_field.is_text_input() {
-            self.settings_input_active = true;
-            self.settings_input_selected = true; // Select all on entry
-            self.settings_input_buffer = match self.settings_field {
+        if let Some(kind) = self.settings_field.input_kind() {
+            let text = match self.settings_field {
                 SettingsField::Target => self.settings_target.clone(),
                 SettingsField::Interval => self.settings_interval.to_string(),
+                SettingsField::Timeout => self.settings_timeout.to_string(),
                 SettingsField::Scale => self.settings_scale.to_string(),
                 SettingsField::BufferSize => self.settings_buffer_mb.to_string(),
                 SettingsField::ColorScheme
+                | SettingsField::Unit
+                | SettingsField::AggregateBy
                 | SettingsField::HideCursor
+                | SettingsField::HideIndicator
+                | SettingsField::TopDown
+                | SettingsField::ConfirmQuit
                 | SettingsField::Confirm
                 | SettingsField::Cancel => String::new(),
             };
-            self.settings_input_cursor = self.settings_input_buffer.len();
+            self.settings_input_active = true;
+            self.settings_input = TextInput::new(kind, &text);
+            self.settings_input.select_all(); // Select all on entry
         }
     }
 
     /// Handle character input in text mode
     pub fn settings_input_char(&mut self, c: char) {
-        if self.settings_input_active {
-            // If text is selected, clear it and start fresh
-            if self.settings_input_selected {
-                self.settings_input_buffer.clear();
-                self.settings_input_cursor = 0;
-                self.settings_input_selected = false;
-            }
-
-            match self.settings_field {
-                SettingsField::Target => {
-                    self.settings_input_buffer
-                        .insert(self.settings_input_cursor, c);
-                    self.settings_input_cursor += 1;
-                    self.settings_target = self.settings_input_buffer.clone();
-                }
-                SettingsField::Interval | SettingsField::Scale | SettingsField::BufferSize => {
-                    if c.is_ascii_digit() {
-                        self.settings_input_buffer
-                            .insert(self.settings_input_cursor, c);
-                        self.settings_input_cursor += 1;
-                        if let Ok(val) = self.settings_input_buffer.parse::<u64>() {
-                            let clamped = val.clamp(1, 100000);
-                            match self.settings_field {
-                                SettingsField::Interval => self.settings_interval = clamped,
-                                SettingsField::Scale => {
-                                    self.settings_scale = clamped;
-                                    self.color_scale =
-                                        ColorScale::new(self.settings_scale, self.settings_colors);
-                                }
-                                SettingsField::BufferSize => {
-                                    self.settings_buffer_mb = clamped;
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                }
-                SettingsField::ColorScheme
-                | SettingsField::HideCursor
-                | SettingsField::Confirm
-                | SettingsField::Cancel => {}
-            }
+        if self.settings_input_active && self.settings_input.insert(c) {
+            self.settings_input_changed();
         }
     }
 
     /// Handle backspace in text mode
     pub fn settings_input_backspace(&mut self) {
         if self.settings_input_active {
-            // If text is selected, clear all
-            if self.settings_input_selected {
-                self.settings_input_buffer.clear();
-                self.settings_input_cursor = 0;
-                self.settings_input_selected = false;
-            } else if self.settings_input_cursor > 0 {
-                self.settings_input_cursor -= 1;
-                self.settings_input_buffer
-                    .remove(self.settings_input_cursor);
-            }
-
-            match self.settings_field {
-                SettingsField::Target => {
-                    self.settings_target = self.settings_input_buffer.clone();
-                }
-                SettingsField::Interval => {
-                    self.settings_interval = self.settings_input_buffer.parse().unwrap_or(1).max(1);
-                }
-                SettingsField::Scale => {
-                    self.settings_scale = self.settings_input_buffer.parse().unwrap_or(1).max(1);
-                    self.color_scale = ColorScale::new(self.settings_scale, self.settings_colors);
-                }
-                SettingsField::BufferSize => {
-                    self.settings_buffer_mb =
-                        self.settings_input_buffer.parse().unwrap_or(1).max(1);
-                }
-                SettingsField::ColorScheme
-                | SettingsField::HideCursor
-                | SettingsField::Confirm
-                | SettingsField::Cancel => {}
-            }
+            self.settings_input.backspace();
+            self.settings_input_changed();
         }
     }
 
     /// Move cursor left in text input mode
     pub fn settings_input_left(&mut self) {
         if self.settings_input_active {
-            self.settings_input_selected = false;
-            if self.settings_input_cursor > 0 {
-                self.settings_input_cursor -= 1;
-            }
+            self.settings_input.left();
         }
     }
 
     /// Move cursor right in text input mode
     pub fn settings_input_right(&mut self) {
         if self.settings_input_active {
-            self.settings_input_selected = false;
-            if self.settings_input_cursor < self.settings_input_buffer.len() {
-                self.settings_input_cursor += 1;
+            self.settings_input.right();
+        }
+    }
+
+    /// Take over the value being typed into the selected field
+    /// An invalid number keeps the previous value (and previewed scale) until it's fixed
+    fn settings_input_changed(&mut self) {
+        if self.settings_field == SettingsField::Target {
+            self.settings_target = self.settings_input.text.clone();
+            return;
+        }
+        let Ok(value) = self.settings_input.value() else {
+            return;
+        };
+        match self.settings_field {
+            SettingsField::Interval => self.settings_interval = value,
+            SettingsField::Timeout => self.settings_timeout = value,
+            SettingsField::Scale => {
+                self.settings_scale = value;
+                self.color_scale = ColorScale::new(value, self.settings_colors);
             }
+            SettingsField::BufferSize => self.settings_buffer_mb = value,
+            SettingsField::Target
+            | SettingsField::ColorScheme
+            | SettingsField::Unit
+            | SettingsField::AggregateBy
+            | SettingsField::HideCursor
+            | SettingsField::HideIndicator
+            | SettingsField::TopDown
+            | SettingsField::ConfirmQuit
+            | SettingsField::Confirm
+            | SettingsField::Cancel => {}
         }
     }
 
-    /// Confirm text input
+    /// Confirm text input (stays in input mode while the value is invalid)
     pub fn settings_confirm_input(&mut self) {
+        if self.settings_input.error.is_some() {
+            return;
+        }
         self.settings_input_active = false;
-        self.settings_input_selected = false;
+        self.settings_input.selected = false;
     }
 
     /// Handle mouse click in settings menu
@@ -649,8 +2110,8 @@ impl App {
         area_height: u16,
     ) -> bool {
         // Calculate settings menu position (same as in SettingsMenu render)
-        let menu_width = 65u16.min(area_width.saturating_sub(4));
-        let menu_height = 19u16.min(area_height.saturating_sub(4));
+        let menu_width = MENU_WIDTH.min(area_width.saturating_sub(4));
+        let menu_height = MENU_HEIGHT.min(area_height.saturating_sub(4));
         let menu_x = (area_width.saturating_sub(menu_width)) / 2;
         let menu_y = (area_height.saturating_sub(menu_height)) / 2;
 
@@ -666,42 +2127,56 @@ impl App {
         // Convert to relative coordinates within menu (accounting for border)
         let rel_x = screen_x.saturating_sub(menu_x + 1); // +1 for border
         let rel_y = screen_y.saturating_sub(menu_y + 1); // +1 for border
+        // Lines below "Aggregate By" move up when it's left out
+        let rel_y = if self.config.aggregate <= 1 && rel_y as usize >= AGGREGATE_BY_LINE {
+            rel_y + 2
+        } else {
+            rel_y
+        };
 
         // Map y coordinate to fields (based on line numbers in render)
-        // Line 0: empty
-        // Line 1: Target
-        // Line 2: empty
-        // Line 3: Interval
-        // Line 4: empty
-        // Line 5: Scale
-        // Line 6: empty
-        // Line 7: ColorScheme
-        // Line 8: empty
-        // Line 9: HideCursor
         // Menu lines (relative y):
         // Line 0: empty
         // Line 1: Target
         // Line 2: empty
         // Line 3: Interval
         // Line 4: empty
-        // Line 5: Scale
+        // Line 5: Timeout
         // Line 6: empty
-        // Line 7: ColorScheme
+        // Line 7: Scale
         // Line 8: empty
-        // Line 9: HideCursor
+        // Line 9: ColorScheme
         // Line 10: empty
-        // Line 11: BufferSize
+        // Line 11: Unit
         // Line 12: empty
-        // Line 13: Buttons
+        // Line 13: AggregateBy
+        // Line 14: empty
+        // Line 15: HideCursor
+        // Line 16: empty
+        // Line 17: HideIndicator
+        // Line 18: empty
+        // Line 19: TopDown
+        // Line 20: empty
+        // Line 21: ConfirmQuit
+        // Line 22: empty
+        // Line 23: BufferSize
+        // Line 24: empty
+        // Line 25: Buttons
 
         let clicked_field = match rel_y {
             1 => Some(SettingsField::Target),
             3 => Some(SettingsField::Interval),
-            5 => Some(SettingsField::Scale),
-            7 => Some(SettingsField::ColorScheme),
-            9 => Some(SettingsField::HideCursor),
-            11 => Some(SettingsField::BufferSize),
-            13 => {
+            5 => Some(SettingsField::Timeout),
+            7 => Some(SettingsField::Scale),
+            9 => Some(SettingsField::ColorScheme),
+            11 => Some(SettingsField::Unit),
+            13 => Some(SettingsField::AggregateBy),
+            15 => Some(SettingsField::HideCursor),
+            17 => Some(SettingsField::HideIndicator),
+            19 => Some(SettingsField::TopDown),
+            21 => Some(SettingsField::ConfirmQuit),
+            23 => Some(SettingsField::BufferSize),
+            25 => {
                 // Buttons row - check x position
                 // "                    " (20 spaces) + " Confirm " (9) + "    " (4) + " Cancel " (8)
                 // Confirm: x 20-28, Cancel: x 33-40
@@ -723,29 +2198,37 @@ impl App {
                 match field {
                     SettingsField::Target
                     | SettingsField::Interval
+                    | SettingsField::Timeout
                     | SettingsField::Scale
                     | SettingsField::BufferSize => {
                         if !self.settings_input_active {
                             self.settings_start_input();
                         }
                     }
-                    SettingsField::ColorScheme => {
+                    SettingsField::ColorScheme
+                    | SettingsField::Unit
+                    | SettingsField::AggregateBy => {
                         self.settings_increase();
                     }
-                    SettingsField::HideCursor => {
+                    SettingsField::HideCursor
+                    | SettingsField::HideIndicator
+                    | SettingsField::TopDown
+                    | SettingsField::ConfirmQuit => {
                         self.settings_increase();
                     }
                     SettingsField::Confirm => {
-                        self.apply_settings();
-                        self.settings_open = false;
+                        if self.apply_settings() {
+                            self.settings_open = false;
+                        }
                     }
                     SettingsField::Cancel => {
                         self.cancel_settings();
                     }
                 }
             } else {
-                // Select the field
+                // Select the field (discarding any invalid text being typed)
                 self.settings_input_active = false;
+                self.settings_input.error = None;
                 self.settings_field = field;
             }
         }
@@ -753,41 +2236,39 @@ impl App {
         true
     }
 
-    /// Start inline edit for a header field
+    /// Start inline edit for a header field (Mode is cycled instead)
     pub fn start_inline_edit(&mut self, field: HeaderEditField, x: u16, y: u16) {
+        if field == HeaderEditField::Mode {
+            self.cycle_mode();
+            return;
+        }
         self.inline_edit = Some(field);
         self.inline_edit_pos = (x, y);
         // Start in navigation mode (not text input mode) - like settings menu
         self.inline_edit_input_active = false;
-        // Don't select initially - just focus, no selection
-        self.inline_edit_selected = false;
-        self.inline_edit_buffer = match field {
+        let text = match field {
             HeaderEditField::Target => self.config.host.clone().unwrap_or_default(),
+            HeaderEditField::Mode => format!("{}", self.config.mode),
             HeaderEditField::Interval => self.config.interval.to_string(),
             HeaderEditField::Scale => self.color_scale.max_rtt.to_string(),
             HeaderEditField::Colors => format!("{}", self.color_scale.scheme),
+            HeaderEditField::Time | HeaderEditField::Note => String::new(),
         };
-        self.inline_edit_original = self.inline_edit_buffer.clone();
-        // For Colors, cursor position is not used
-        self.inline_edit_cursor = if field == HeaderEditField::Colors {
-            0
-        } else {
-            self.inline_edit_buffer.len()
-        };
+        self.inline_edit_input = TextInput::new(inline_input_kind(field), &text);
+        self.inline_edit_original = text;
         // Clear header selection when opening inline edit
         self.header_selected = None;
         // Start with input focused (not confirm button)
         self.inline_edit_confirm_focused = false;
     }
 
-    /// Activate text input mode for inline edit
+    /// Activate text input mode for inline edit, selecting the whole value
     pub fn inline_edit_activate_input(&mut self) {
         if let Some(field) = self.inline_edit {
             // Don't activate input mode for Colors (it's an enum selector)
             if field != HeaderEditField::Colors {
                 self.inline_edit_input_active = true;
-                self.inline_edit_selected = false;
-                self.inline_edit_cursor = self.inline_edit_buffer.len();
+                self.inline_edit_input.select_all();
             }
         }
     }
@@ -825,174 +2306,232 @@ impl App {
         if let Some(field) = self.inline_edit {
             match field {
                 HeaderEditField::Target => {
-                    if !self.inline_edit_buffer.is_empty()
-                        && self.inline_edit_buffer != self.config.host.clone().unwrap_or_default()
+                    let target = clean_target(&self.inline_edit_input.text);
+                    self.inline_edit_input.set_text(&target);
+                    if let Err(e) = check_target_literal(&target) {
+                        self.inline_edit_rejected(e);
+                        return;
+                    }
+                    if !target.is_empty()
+                        && target != self.config.host.clone().unwrap_or_default()
+                        && !self.confirm_target_first(&target)
                     {
-                        self.config.host = Some(self.inline_edit_buffer.clone());
-                        self.new_target = Some(self.inline_edit_buffer.clone());
-                        self.needs_pinger_restart = true;
+                        self.change_target(target);
                     }
                 }
-                HeaderEditField::Interval => {
-                    if let Ok(val) = self.inline_edit_buffer.parse::<u64>() {
-                        let clamped = val.clamp(1, 100000);
-                        if clamped != self.config.interval {
-                            self.config.interval = clamped;
-                            self.new_interval = Some(clamped);
+                HeaderEditField::Interval => match self.inline_edit_input.value() {
+                    Ok(val) => {
+                        let original = parse_duration_ms(&self.inline_edit_original).ok();
+                        if original != Some(val) {
+                            // A manually set interval ends the sweep
+                            self.config.interval_sweep = None;
+                            self.config.interval = val;
+                            self.new_interval = Some(val);
                             self.needs_pinger_restart = true;
                         }
                     }
-                }
+                    Err(e) => {
+                        self.inline_edit_rejected(e);
+                        return;
+                    }
+                },
                 HeaderEditField::Scale => {
+                    if let Err(e) = self.inline_edit_input.value() {
+                        self.inline_edit_rejected(e);
+                        return;
+                    }
                     // Already applied via live preview
+                    self.color_scale = ColorScale::new(
+                        self.color_scale.max_rtt.clamp(1, MAX_SCALE_MS),
+                        self.color_scale.scheme,
+                    );
                     self.config.scale = self.color_scale.max_rtt;
                 }
                 HeaderEditField::Colors => {
                     // Already applied via live preview
                     self.config.colors = self.color_scale.scheme;
                 }
+                HeaderEditField::Time => {
+                    let result = parse_time_of_day(&self.inline_edit_input.text)
+                        .and_then(|time| self.jump_to_time(time));
+                    if let Err(e) = result {
+                        self.inline_edit_rejected(e);
+                        return;
+                    }
+                }
+                HeaderEditField::Note => {
+                    let text = self.inline_edit_input.text.trim().to_string();
+                    if !text.is_empty() {
+                        self.add_note(text);
+                    }
+                }
+                HeaderEditField::Mode => {}
             }
         }
         self.inline_edit = None;
     }
 
-    /// Handle character input in inline edit
-    pub fn inline_edit_char(&mut self, c: char) {
-        if self.inline_edit.is_none() {
+    /// Keep the popup open, back in the input, so a rejected value can be corrected
+    fn inline_edit_rejected(&mut self, error: String) {
+        self.inline_edit_input.error = Some(error);
+        self.inline_edit_confirm_focused = false;
+        self.inline_edit_input_active = true;
+    }
+
+    /// Open a popup at the top-left of the graph asking for a time of day to jump to
+    pub fn open_time_jump(&mut self) {
+        // Only the grid graph can be scrolled to a sample
+        let Some((x, y, ..)) = self.graph_area else {
             return;
-        }
-        let field = self.inline_edit.unwrap();
+        };
+        self.start_inline_edit(HeaderEditField::Time, x + 1, y);
+        self.inline_edit_input_active = true;
+    }
+
+    /// Open a popup at the top-left of the graph asking for a note to pin to the session
+    pub fn open_note(&mut self) {
+        let (x, y) = self.graph_area.map_or((0, 0), |(x, y, ..)| (x, y));
+        self.start_inline_edit(HeaderEditField::Note, x + 1, y);
+        self.inline_edit_input_active = true;
+    }
+
+    /// Pin a note before the next sample, marking it on the graph
+    pub fn add_note(&mut self, text: String) {
+        self.mark_event(EventKind::Note);
+        self.footer_event = Some(format!("note: {}", text));
+        self.notes.push(Note {
+            seq: self.result_base_seq + self.results.len(),
+            timestamp: chrono::Local::now(),
+            text,
+        });
+    }
 
-        if self.inline_edit_selected {
-            self.inline_edit_buffer.clear();
-            self.inline_edit_cursor = 0;
-            self.inline_edit_selected = false;
+    /// Note pinned to a sample (stable sequence number), for the ping popup
+    pub fn note_at(&self, seq: usize) -> Option<&Note> {
+        self.notes.iter().rev().find(|note| note.seq == seq)
+    }
+
+    /// Probe sequence number of the sample a note is pinned to, as exported (None until
+    /// that sample arrives, or once it's dropped from history)
+    pub fn note_probe_seq(&self, note: &Note) -> Option<u64> {
+        let idx = note.seq.checked_sub(self.result_base_seq)?;
+        self.results.get(idx).map(|result| result.seq)
+    }
+
+    /// Scroll the graph so the sample sent closest to `time` (today, or yesterday if that
+    /// is still to come) is in the middle of the screen
+    /// The time is taken as UTC with --utc, like the times shown
+    pub fn jump_to_time(&mut self, time: chrono::NaiveTime) -> Result<(), String> {
+        let Some((_, _, width, height)) = self.graph_area else {
+            return Err("Graph not shown".to_string());
+        };
+        let target = if self.display_utc {
+            most_recent(chrono::Utc::now(), time).map(|t| t.with_timezone(&chrono::Local))
+        } else {
+            most_recent(chrono::Local::now(), time)
         }
+        .ok_or_else(|| "No such time today".to_string())?;
+        let (idx, result) = self
+            .results
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, r)| (r.timestamp - target).num_milliseconds().abs())
+            .ok_or_else(|| "No samples yet".to_string())?;
+        self.footer_event = Some(format!(
+            "Showing {}",
+            result.timestamp_str(self.display_utc)
+        ));
+
+        let row_len = self.row_len(width as usize).max(1);
+        let row = (self.result_base_seq + idx) / row_len;
+        let end_row = row + (height as usize / 2).max(1);
+        if end_row >= self.total_rows(row_len) {
+            self.follow_live_view();
+        } else {
+            self.freeze_view_at(end_row.max(height as usize), row_len);
+        }
+        Ok(())
+    }
 
-        match field {
-            HeaderEditField::Target => {
-                self.inline_edit_buffer.insert(self.inline_edit_cursor, c);
-                self.inline_edit_cursor += 1;
-            }
-            HeaderEditField::Interval | HeaderEditField::Scale => {
-                if c.is_ascii_digit() {
-                    self.inline_edit_buffer.insert(self.inline_edit_cursor, c);
-                    self.inline_edit_cursor += 1;
-                    // Live preview for scale
-                    if field == HeaderEditField::Scale
-                        && let Ok(val) = self.inline_edit_buffer.parse::<u64>()
-                    {
-                        self.color_scale =
-                            ColorScale::new(val.clamp(1, 100000), self.color_scale.scheme);
-                    }
-                }
-            }
-            HeaderEditField::Colors => {
-                // Colors don't accept text input - use scroll wheel
-            }
+    /// Handle character input in inline edit
+    pub fn inline_edit_char(&mut self, c: char) {
+        let Some(field) = self.inline_edit else {
+            return;
+        };
+        // Colors don't accept text input - use scroll wheel
+        if matches!(field, HeaderEditField::Colors | HeaderEditField::Mode) {
+            return;
+        }
+        if self.inline_edit_input.insert(c) {
+            self.inline_edit_preview(field);
         }
     }
 
     /// Handle backspace in inline edit
     pub fn inline_edit_backspace(&mut self) {
-        if self.inline_edit.is_none() {
+        let Some(field) = self.inline_edit else {
             return;
-        }
-        let field = self.inline_edit.unwrap();
-
-        if self.inline_edit_selected {
-            self.inline_edit_buffer.clear();
-            self.inline_edit_cursor = 0;
-            self.inline_edit_selected = false;
-        } else if self.inline_edit_cursor > 0 {
-            self.inline_edit_cursor -= 1;
-            self.inline_edit_buffer.remove(self.inline_edit_cursor);
-        }
+        };
+        self.inline_edit_input.backspace();
+        self.inline_edit_preview(field);
+    }
 
-        // Live preview for scale
-        if field == HeaderEditField::Scale {
-            let val = self.inline_edit_buffer.parse::<u64>().unwrap_or(1).max(1);
+    /// Preview a valid scale being typed live
+    fn inline_edit_preview(&mut self, field: HeaderEditField) {
+        if field == HeaderEditField::Scale
+            && let Ok(val) = self.inline_edit_input.value()
+        {
             self.color_scale = ColorScale::new(val, self.color_scale.scheme);
         }
     }
 
     /// Move cursor left in inline edit
     pub fn inline_edit_left(&mut self) {
-        self.inline_edit_selected = false;
-        if self.inline_edit_cursor > 0 {
-            self.inline_edit_cursor -= 1;
-        }
+        self.inline_edit_input.left();
     }
 
     /// Move cursor right in inline edit
     pub fn inline_edit_right(&mut self) {
-        self.inline_edit_selected = false;
-        if self.inline_edit_cursor < self.inline_edit_buffer.len() {
-            self.inline_edit_cursor += 1;
-        }
+        self.inline_edit_input.right();
     }
 
     /// Increase value in inline edit (for scroll wheel)
     pub fn inline_edit_increase(&mut self) {
-        if let Some(field) = self.inline_edit {
-            match field {
-                HeaderEditField::Interval => {
-                    if let Ok(val) = self.inline_edit_buffer.parse::<u64>() {
-                        let new_val = val.saturating_add(1).min(100000);
-                        self.inline_edit_buffer = new_val.to_string();
-                        self.inline_edit_cursor = self.inline_edit_buffer.len();
-                        self.inline_edit_selected = false;
-                    }
-                }
-                HeaderEditField::Scale => {
-                    if let Ok(val) = self.inline_edit_buffer.parse::<u64>() {
-                        let new_val = val.saturating_add(1).min(100000);
-                        self.inline_edit_buffer = new_val.to_string();
-                        self.inline_edit_cursor = self.inline_edit_buffer.len();
-                        self.inline_edit_selected = false;
-                        self.color_scale = ColorScale::new(new_val, self.color_scale.scheme);
-                    }
-                }
-                HeaderEditField::Colors => {
-                    let new_scheme = self.color_scale.scheme.next();
-                    self.color_scale = ColorScale::new(self.color_scale.max_rtt, new_scheme);
-                    self.inline_edit_buffer = format!("{}", new_scheme);
-                    self.inline_edit_selected = false;
-                }
-                HeaderEditField::Target => {}
-            }
-        }
+        self.inline_edit_step(1);
     }
 
     /// Decrease value in inline edit (for scroll wheel)
     pub fn inline_edit_decrease(&mut self) {
-        if let Some(field) = self.inline_edit {
-            match field {
-                HeaderEditField::Interval => {
-                    if let Ok(val) = self.inline_edit_buffer.parse::<u64>() {
-                        let new_val = val.saturating_sub(1).max(1);
-                        self.inline_edit_buffer = new_val.to_string();
-                        self.inline_edit_cursor = self.inline_edit_buffer.len();
-                        self.inline_edit_selected = false;
-                    }
-                }
-                HeaderEditField::Scale => {
-                    if let Ok(val) = self.inline_edit_buffer.parse::<u64>() {
-                        let new_val = val.saturating_sub(1).max(1);
-                        self.inline_edit_buffer = new_val.to_string();
-                        self.inline_edit_cursor = self.inline_edit_buffer.len();
-                        self.inline_edit_selected = false;
-                        self.color_scale = ColorScale::new(new_val, self.color_scale.scheme);
-                    }
-                }
-                HeaderEditField::Colors => {
-                    let new_scheme = self.color_scale.scheme.prev();
-                    self.color_scale = ColorScale::new(self.color_scale.max_rtt, new_scheme);
-                    self.inline_edit_buffer = format!("{}", new_scheme);
-                    self.inline_edit_selected = false;
+        self.inline_edit_step(-1);
+    }
+
+    /// Step the interval or scale by `delta` (1ms), or cycle the color scheme
+    fn inline_edit_step(&mut self, delta: i64) {
+        let Some(field) = self.inline_edit else {
+            return;
+        };
+        match field {
+            HeaderEditField::Interval => {
+                self.inline_edit_input.step(delta);
+            }
+            HeaderEditField::Scale => {
+                if let Some(val) = self.inline_edit_input.step(delta) {
+                    self.color_scale = ColorScale::new(val, self.color_scale.scheme);
                 }
-                HeaderEditField::Target => {}
             }
+            HeaderEditField::Colors => {
+                let new_scheme = if delta > 0 {
+                    self.color_scale.scheme.next()
+                } else {
+                    self.color_scale.scheme.prev()
+                };
+                self.color_scale = ColorScale::new(self.color_scale.max_rtt, new_scheme);
+                self.inline_edit_input.set_text(&format!("{}", new_scheme));
+            }
+            HeaderEditField::Target
+            | HeaderEditField::Mode
+            | HeaderEditField::Time
+            | HeaderEditField::Note => {}
         }
     }
 
@@ -1000,10 +2539,13 @@ impl App {
     pub fn header_next_field(&mut self) {
         self.header_selected = Some(match self.header_selected {
             None => HeaderEditField::Target,
-            Some(HeaderEditField::Target) => HeaderEditField::Interval,
+            Some(HeaderEditField::Target) => HeaderEditField::Mode,
+            Some(HeaderEditField::Mode) => HeaderEditField::Interval,
             Some(HeaderEditField::Interval) => HeaderEditField::Scale,
             Some(HeaderEditField::Scale) => HeaderEditField::Colors,
-            Some(HeaderEditField::Colors) => HeaderEditField::Target,
+            Some(HeaderEditField::Colors | HeaderEditField::Time | HeaderEditField::Note) => {
+                HeaderEditField::Target
+            }
         });
     }
 
@@ -1012,9 +2554,11 @@ impl App {
         self.header_selected = Some(match self.header_selected {
             None => HeaderEditField::Colors,
             Some(HeaderEditField::Target) => HeaderEditField::Colors,
-            Some(HeaderEditField::Interval) => HeaderEditField::Target,
+            Some(HeaderEditField::Mode) => HeaderEditField::Target,
+            Some(HeaderEditField::Interval) => HeaderEditField::Mode,
             Some(HeaderEditField::Scale) => HeaderEditField::Interval,
             Some(HeaderEditField::Colors) => HeaderEditField::Scale,
+            Some(HeaderEditField::Time | HeaderEditField::Note) => HeaderEditField::Colors,
         });
     }
 
@@ -1031,8 +2575,38 @@ impl App {
         }
     }
 
-    /// Show quit confirmation dialog
+    /// Cycle through ICMP, UDP client and TLS handshake mode, restarting the pinger
+    /// Results from the previous mode are cleared since the RTTs aren't comparable
+    /// ICMP mode is skipped for a target it can't ping (one with a port, or a link-local
+    /// address with a zone id)
+    pub fn cycle_mode(&mut self) {
+        self.config.mode = match self.config.mode {
+            Mode::Icmp => Mode::UdpClient,
+            Mode::UdpClient => Mode::TlsHandshake,
+            Mode::TlsHandshake => {
+                let host = self.config.host.as_deref().unwrap_or_default();
+                match icmp::check_target(host) {
+                    _ if self.config.demo => Mode::Icmp,
+                    Ok(()) => Mode::Icmp,
+                    Err(e) => {
+                        self.footer_event = Some(e.to_string());
+                        Mode::UdpClient
+                    }
+                }
+            }
+            // The server has no TUI, so this can't happen
+            Mode::UdpServer => return,
+        };
+        self.new_mode = Some(self.config.mode);
+        self.needs_pinger_restart = true;
+    }
+
+    /// Show quit confirmation dialog (or quit right away with --no-confirm-quit)
     pub fn show_quit_confirm(&mut self) {
+        if self.config.no_confirm_quit {
+            self.quit();
+            return;
+        }
         self.quit_confirm = true;
         self.quit_confirm_no_focused = true; // Start with "No" focused (safer default)
     }
@@ -1047,4 +2621,1016 @@ impl App {
         self.quit_confirm = false;
         self.should_quit = true;
     }
+
+    /// Switch the main graph to another host, clearing its history
+    fn change_target(&mut self, host: String) {
+        self.config.host = Some(host.clone());
+        self.new_target = Some(host);
+        self.needs_pinger_restart = true;
+    }
+
+    /// A new target couldn't be resolved or used: go back to `previous` (None = no
+    /// target yet), show the error until dismissed, and reopen the target editor with
+    /// the rejected value so a typo can be fixed
+    pub fn target_change_failed(&mut self, host: &str, error: &str, previous: Option<String>) {
+        self.config.host = previous;
+        self.footer_event = Some(format!("Failed to use {}", host));
+        self.banner = Some(format!("Failed to use {}: {}. Esc to dismiss", host, error));
+        self.start_inline_edit(HeaderEditField::Target, 10, 1);
+        self.inline_edit_input.set_text(host);
+        self.inline_edit_input_active = true;
+        self.inline_edit_input.error = Some(error.to_string());
+    }
+
+    /// With --confirm-target-change, ask before a target change discards history
+    /// Returns true if the change now waits for the dialog
+    fn confirm_target_first(&mut self, host: &str) -> bool {
+        if !self.config.confirm_target_change || self.results.is_empty() {
+            return false;
+        }
+        self.target_confirm = Some(host.to_string());
+        self.target_confirm_no_focused = true; // Start with "No" focused (safer default)
+        true
+    }
+
+    /// Keep graphing the current target
+    pub fn cancel_target_change(&mut self) {
+        self.target_confirm = None;
+    }
+
+    /// Clear the history and switch to the target waiting for confirmation
+    pub fn confirm_target_change(&mut self) {
+        if let Some(host) = self.target_confirm.take() {
+            self.change_target(host);
+        }
+    }
+
+    /// Put a target that is now being graphed at the top of the quick-switch list
+    pub fn remember_target(&mut self, host: &str) {
+        self.recent_targets.retain(|recent| recent != host);
+        self.recent_targets.insert(0, host.to_string());
+        self.recent_targets.truncate(MAX_RECENT_TARGETS);
+    }
+
+    /// Open the quick-switch list, selecting the most recent target other than the
+    /// current one so Enter bounces back to it
+    pub fn open_recent_targets(&mut self) {
+        if self.recent_targets.is_empty() {
+            self.footer_event = Some("No recent targets yet".to_string());
+            return;
+        }
+        let current = self.config.host.as_deref();
+        let selected = self
+            .recent_targets
+            .iter()
+            .position(|host| Some(host.as_str()) != current)
+            .unwrap_or(0);
+        self.recent_targets_selected = Some(selected);
+    }
+
+    /// Close the quick-switch list without switching
+    pub fn close_recent_targets(&mut self) {
+        self.recent_targets_selected = None;
+    }
+
+    /// Move the quick-switch selection by `delta` entries, wrapping around
+    pub fn recent_targets_move(&mut self, delta: isize) {
+        if let Some(selected) = self.recent_targets_selected {
+            let len = self.recent_targets.len() as isize;
+            self.recent_targets_selected =
+                Some((selected as isize + delta).rem_euclid(len.max(1)) as usize);
+        }
+    }
+
+    /// Switch to the selected quick-switch target (through the confirmation dialog with
+    /// --confirm-target-change) and close the list
+    pub fn pick_recent_target(&mut self) {
+        let Some(selected) = self.recent_targets_selected.take() else {
+            return;
+        };
+        let Some(host) = self.recent_targets.get(selected).cloned() else {
+            return;
+        };
+        if self.config.host.as_deref() != Some(host.as_str()) && !self.confirm_target_first(&host) {
+            self.change_target(host);
+        }
+    }
+}
+
+/// The last time the clock showed `time` (today, or yesterday if that is still to come)
+fn most_recent<Tz: chrono::TimeZone>(
+    now: chrono::DateTime<Tz>,
+    time: chrono::NaiveTime,
+) -> Option<chrono::DateTime<Tz>> {
+    let target = now.with_time(time).earliest()?;
+    Some(if target > now {
+        target - chrono::Duration::days(1)
+    } else {
+        target
+    })
+}
+
+/// Normalize a typed/pasted target (e.g. a URL) down to its hostname; invalid
+/// input is only trimmed so resolving it reports the syntax error
+fn clean_target(target: &str) -> String {
+    normalize_host(target).unwrap_or_else(|_| target.trim().to_string())
+}
+
+/// Reject targets that are IP addresses which can't be pinged (hostnames are
+/// checked once resolved)
+fn check_target_literal(target: &str) -> Result<(), String> {
+    match split_port(target).0.parse::<IpAddr>() {
+        Ok(ip) => check_target_ip(ip).map(|_| ()).map_err(|e| e.to_string()),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Warning for a timeout that's extremely long compared to the interval
+fn timeout_warning(timeout_ms: u64, interval_ms: u64) -> Option<String> {
+    (timeout_ms > interval_ms.saturating_mul(TIMEOUT_WARN_RATIO)).then(|| {
+        format!(
+            "Timeout {}ms is {}x the interval: losses show up late",
+            timeout_ms,
+            timeout_ms / interval_ms.max(1)
+        )
+    })
+}
+
+/// Parse a typed time of day as HH:MM or HH:MM:SS
+fn parse_time_of_day(input: &str) -> Result<chrono::NaiveTime, String> {
+    chrono::NaiveTime::parse_from_str(input, "%H:%M:%S")
+        .or_else(|_| chrono::NaiveTime::parse_from_str(input, "%H:%M"))
+        .map_err(|_| "Enter a time as HH:MM or HH:MM:SS".to_string())
+}
+
+/// What can be typed into a header field's inline editor
+fn inline_input_kind(field: HeaderEditField) -> InputKind {
+    match field {
+        HeaderEditField::Interval => InputKind::Duration {
+            max: MAX_INTERVAL_MS,
+        },
+        HeaderEditField::Scale => InputKind::Number { max: MAX_SCALE_MS },
+        HeaderEditField::Time => InputKind::TimeOfDay,
+        HeaderEditField::Target
+        | HeaderEditField::Mode
+        | HeaderEditField::Colors
+        | HeaderEditField::Note => InputKind::Text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_scheme_transition() {
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--colors",
+            "ocean",
+            "example.com",
+        ]));
+        let start = Instant::now();
+        assert!(!app.update_scheme_transition(start));
+
+        // Switching fades over from the old scheme without holding anything up
+        app.color_scale = ColorScale::new(app.color_scale.max_rtt, ColorScheme::Fire);
+        assert!(app.update_scheme_transition(start));
+        let rtt = Some(app.color_scale.max_rtt as f64);
+        assert_eq!(
+            app.color_scale.color_for_rtt_f64(rtt),
+            ColorScale::new(200, ColorScheme::Ocean).color_for_rtt_f64(rtt)
+        );
+        assert!(app.update_scheme_transition(start + SCHEME_TRANSITION / 2));
+        assert_eq!(app.color_scale.scheme, ColorScheme::Fire);
+
+        // Done: exactly the new scheme again
+        assert!(!app.update_scheme_transition(start + SCHEME_TRANSITION));
+        assert!(app.scheme_transition.is_none());
+        assert_eq!(
+            app.color_scale.color_for_rtt_f64(rtt),
+            ColorScale::new(200, ColorScheme::Fire).color_for_rtt_f64(rtt)
+        );
+    }
+
+    #[test]
+    fn test_scheme_compare() {
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--colors",
+            "ocean",
+            "example.com",
+        ]));
+        app.open_settings();
+        app.open_scheme_compare();
+        app.settings_increase();
+        app.settings_increase();
+        assert_eq!(app.settings_previous_colors, ColorScheme::Ocean.next());
+        assert_eq!(app.settings_colors, ColorScheme::Ocean.next().next());
+
+        // Going back to the previous scheme, which becomes the one compared against
+        app.close_scheme_compare(false);
+        assert!(!app.scheme_compare);
+        assert_eq!(app.settings_colors, ColorScheme::Ocean.next());
+        assert_eq!(app.color_scale.scheme, ColorScheme::Ocean.next());
+        assert_eq!(
+            app.settings_previous_colors,
+            ColorScheme::Ocean.next().next()
+        );
+
+        app.open_scheme_compare();
+        app.close_scheme_compare(true);
+        assert_eq!(app.settings_colors, ColorScheme::Ocean.next());
+    }
+
+    #[test]
+    fn test_settings_timeout() {
+        let mut app = App::new(Config::parse_from(["rttui", "-i", "100", "example.com"]));
+        app.open_settings();
+        app.settings_field = SettingsField::Timeout;
+        app.settings_start_input();
+        for c in "20s".chars() {
+            app.settings_input_char(c);
+        }
+        assert!(app.settings_input.error.is_none());
+        app.settings_confirm_input();
+        assert!(app.apply_settings());
+
+        // Applied, with a restart so the pinger picks it up, and a warning since it's
+        // 200 intervals long
+        assert_eq!(app.config.timeout, 20000);
+        assert!(app.needs_pinger_restart);
+        assert!(app.footer_event.as_deref().unwrap().contains("200x"));
+        assert_eq!(timeout_warning(3000, 100), None);
+
+        // A timeout without a warning leaves other footer events alone
+        app.footer_event = Some("Switched to UDP mode".to_string());
+        app.open_settings();
+        app.settings_field = SettingsField::Timeout;
+        app.settings_start_input();
+        for c in "500ms".chars() {
+            app.settings_input_char(c);
+        }
+        app.settings_confirm_input();
+        assert!(app.apply_settings());
+        assert_eq!(app.footer_event.as_deref(), Some("Switched to UDP mode"));
+
+        app.open_settings();
+        app.settings_field = SettingsField::Timeout;
+        app.settings_start_input();
+        app.settings_input_char('0');
+        assert!(app.settings_input.error.is_some());
+        assert!(!app.apply_settings());
+    }
+
+    #[test]
+    fn test_selection_stats() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        let now = Instant::now();
+        for seq in 1..=6 {
+            app.record_result(if seq == 4 {
+                PingResult::timeout(seq, now)
+            } else {
+                PingResult::success(seq, Duration::from_millis(seq * 10), now, None)
+            });
+        }
+
+        app.drag_start_seq = Some(4);
+        assert!(app.drag_select_to(1));
+        assert_eq!(app.selection, Some((1, 4)));
+
+        let stats = app.selection_stats().unwrap();
+        assert_eq!(stats.total_sent, 4);
+        assert_eq!(stats.total_lost, 1);
+        assert_eq!(stats.min_rtt, Some(Duration::from_millis(20)));
+        assert_eq!(stats.max_rtt, Some(Duration::from_millis(50)));
+        // Probe sequence numbers, not buffer positions
+        assert_eq!(app.selection_seqs(), Some((2, 5)));
+    }
+
+    #[test]
+    fn test_confirm_target_change() {
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--confirm-target-change",
+            "example.com",
+        ]));
+        let edit_target = |app: &mut App, host: &str| {
+            app.inline_edit = Some(HeaderEditField::Target);
+            app.inline_edit_input.set_text(host);
+            app.apply_inline_edit();
+        };
+
+        // Nothing to lose yet: switch right away
+        edit_target(&mut app, "example.net");
+        assert_eq!(app.new_target.take().as_deref(), Some("example.net"));
+
+        app.record_result(PingResult::timeout(1, Instant::now()));
+        edit_target(&mut app, "example.org");
+        assert!(app.new_target.is_none());
+        assert_eq!(app.target_confirm.as_deref(), Some("example.org"));
+        app.cancel_target_change();
+        assert_eq!(app.config.host.as_deref(), Some("example.net"));
+
+        edit_target(&mut app, "example.org");
+        app.confirm_target_change();
+        assert_eq!(app.new_target.as_deref(), Some("example.org"));
+        assert_eq!(app.config.host.as_deref(), Some("example.org"));
+    }
+
+    #[test]
+    fn test_wheel_rows() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        let now = Instant::now();
+        assert_eq!(app.wheel_rows(true, now), 3);
+
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--wheel-accel",
+            "2",
+            "example.com",
+        ]));
+        let ms = |ms| now + Duration::from_millis(ms);
+        // A quick run speeds up, a pause or a change of direction starts over
+        let rows: Vec<usize> = [0, 20, 40, 60]
+            .into_iter()
+            .map(|t| app.wheel_rows(true, ms(t)))
+            .collect();
+        assert_eq!(rows, [1, 3, 5, 7]);
+        assert_eq!(app.wheel_rows(true, ms(500)), 1);
+        assert_eq!(app.wheel_rows(false, ms(510)), 1);
+        for t in 0..50 {
+            app.wheel_rows(false, ms(520 + t * 10));
+        }
+        assert_eq!(app.wheel_rows(false, ms(1020)), WHEEL_MAX_ROWS);
+    }
+
+    #[test]
+    fn test_legend_mode() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        assert!(app.update_legend_visibility(100));
+        assert!(app.update_legend_visibility(98));
+        assert!(!app.update_legend_visibility(80));
+
+        // Turned on it trades graph width for the legend, down to a limit
+        app.toggle_legend();
+        assert_eq!(app.legend_mode, LegendMode::On);
+        assert!(app.update_legend_visibility(80));
+        assert!(!app.update_legend_visibility(30));
+        assert!(app.update_legend_visibility(200));
+        app.toggle_legend();
+        assert_eq!(app.legend_mode, LegendMode::Off);
+        assert!(!app.update_legend_visibility(200));
+
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--legend",
+            "off",
+            "example.com",
+        ]));
+        assert!(!app.update_legend_visibility(200));
+    }
+
+    #[test]
+    fn test_recent_targets() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        app.open_recent_targets();
+        assert_eq!(app.recent_targets_selected, None);
+
+        for host in ["example.org", "example.net", "example.com"] {
+            app.remember_target(host);
+        }
+        app.remember_target("example.org");
+        assert_eq!(
+            app.recent_targets,
+            ["example.org", "example.com", "example.net"]
+        );
+        for i in 0..MAX_RECENT_TARGETS {
+            app.remember_target(&format!("host{}", i));
+        }
+        assert_eq!(app.recent_targets.len(), MAX_RECENT_TARGETS);
+        assert_eq!(app.recent_targets[0], "host9");
+
+        // The current target is skipped, so Enter goes back to the previous one
+        app.recent_targets = vec!["example.com".to_string(), "example.net".to_string()];
+        app.open_recent_targets();
+        assert_eq!(app.recent_targets_selected, Some(1));
+        app.recent_targets_move(1);
+        assert_eq!(app.recent_targets_selected, Some(0));
+        app.recent_targets_move(-1);
+        app.pick_recent_target();
+        assert_eq!(app.recent_targets_selected, None);
+        assert_eq!(app.new_target.as_deref(), Some("example.net"));
+        assert_eq!(app.config.host.as_deref(), Some("example.net"));
+    }
+
+    #[test]
+    fn test_target_change_failed() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        app.record_result(PingResult::timeout(1, Instant::now()));
+        app.inline_edit = Some(HeaderEditField::Target);
+        app.inline_edit_input.set_text("exmaple.com");
+        app.apply_inline_edit();
+        assert_eq!(app.new_target.take().as_deref(), Some("exmaple.com"));
+
+        // The old target and its history stay, the typo is back in the editor
+        app.target_change_failed(
+            "exmaple.com",
+            "no such host",
+            Some("example.com".to_string()),
+        );
+        assert_eq!(app.config.host.as_deref(), Some("example.com"));
+        assert_eq!(app.results.len(), 1);
+        assert!(app.inline_edit == Some(HeaderEditField::Target));
+        assert_eq!(app.inline_edit_input.text, "exmaple.com");
+        assert!(app.inline_edit_input_active);
+        assert_eq!(app.inline_edit_input.error.as_deref(), Some("no such host"));
+        assert!(app.banner.as_deref().unwrap().contains("no such host"));
+
+        // Fixing it starts the change again
+        app.inline_edit_input.set_text("example.org");
+        app.apply_inline_edit();
+        assert_eq!(app.new_target.as_deref(), Some("example.org"));
+        assert!(app.inline_edit.is_none());
+    }
+
+    #[test]
+    fn test_jump_to_oldest() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        let now = Instant::now();
+        for seq in 0..100 {
+            app.record_result(PingResult::success(
+                seq,
+                Duration::from_millis(10),
+                now,
+                None,
+            ));
+        }
+        // Pretend the oldest 25 samples were evicted from the buffer
+        app.results.drain(..25);
+        app.result_base_seq = 25;
+        app.graph_area = Some((0, 0, 10, 3));
+
+        app.jump_to_oldest();
+        // Row 2 holds seq 25 and is the top of a 3-row view ending at row 5
+        assert_eq!(app.view_end_row, Some(5));
+
+        app.graph_area = Some((0, 0, 10, 20));
+        app.jump_to_oldest();
+        assert!(app.is_live());
+    }
+
+    #[test]
+    fn test_window_stats() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        let now = Instant::now();
+        let wall_now = chrono::Local::now();
+        // One sample every 10s over the last 2 minutes, with a loss 30s ago
+        for seq in 0..=12 {
+            let age = chrono::Duration::seconds(120 - seq as i64 * 10);
+            let mut result = if seq == 9 {
+                PingResult::timeout(seq, now)
+            } else {
+                PingResult::success(seq, Duration::from_millis(10 + seq), now, None)
+            };
+            result.timestamp = wall_now - age;
+            app.record_result(result);
+        }
+
+        let windows = app.window_summaries();
+        let (window_ms, stats) = &windows[0];
+        let stats = stats.as_ref().unwrap();
+        assert_eq!(*window_ms, 60_000);
+        assert_eq!((stats.sent, stats.lost), (6, 1));
+        assert_eq!(stats.avg_rtt(), Some(Duration::from_micros(19_600)));
+        assert_eq!(stats.covered, Duration::from_secs(60));
+        // Only 2 minutes of history so far for a 5 minute window
+        let stats = windows[1].1.as_ref().unwrap();
+        assert_eq!((stats.sent, stats.lost), (13, 1));
+        assert!(stats.covered >= Duration::from_secs(120));
+        assert!(stats.covered < Duration::from_secs(121));
+
+        // The best case needs no full window
+        assert_eq!(app.best_rtt(60_000), Some(17.0));
+        assert_eq!(app.best_rtt(300_000), Some(10.0));
+        assert_eq!(app.best_rtt(5_000), Some(22.0));
+
+        // A timeout sent before the window, arriving behind replies sent within it,
+        // doesn't hide those replies
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        for (seq, age) in [(0, 59), (1, 58), (3, 50)] {
+            let mut result = PingResult::success(seq, Duration::from_millis(10), now, None);
+            result.timestamp = wall_now - chrono::Duration::seconds(age);
+            app.record_result(result);
+            if seq == 1 {
+                let mut late = PingResult::timeout(2, now);
+                late.timestamp = wall_now - chrono::Duration::seconds(62);
+                app.record_result(late);
+            }
+        }
+        let stats = app.window_summaries()[0].1.clone().unwrap();
+        assert_eq!((stats.sent, stats.lost), (4, 1));
+
+        // Evicted results leave the window totals too, and then the late timeout is
+        // the oldest one left and leaves as well
+        app.resize_history(2);
+        let stats = app.window_summaries()[0].1.clone().unwrap();
+        assert_eq!((stats.sent, stats.lost), (1, 0));
+    }
+
+    #[test]
+    fn test_warmup_excluded_from_stats() {
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--warmup",
+            "2",
+            "example.com",
+        ]));
+        let now = Instant::now();
+        for (seq, ms) in [(1, 300), (2, 150), (3, 20), (4, 30)] {
+            app.record_result(PingResult::success(
+                seq,
+                Duration::from_millis(ms),
+                now,
+                None,
+            ));
+        }
+
+        assert_eq!(app.results.len(), 4);
+        assert_eq!(app.stats.total_sent, 2);
+        assert_eq!(app.stats.max_rtt, Some(Duration::from_millis(30)));
+
+        app.start_warmup();
+        app.record_result(PingResult::timeout(5, now));
+        assert_eq!(app.stats.total_lost, 0);
+    }
+
+    #[test]
+    fn test_retain_evicts_old_results() {
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--retain",
+            "1m",
+            "example.com",
+        ]));
+        let now = Instant::now();
+        let wall_now = chrono::Local::now();
+        for (seq, age_s) in [(0, 180), (1, 90), (2, 30), (3, 0)] {
+            let mut result = PingResult::success(seq, Duration::from_millis(10), now, None);
+            result.timestamp = wall_now - chrono::Duration::seconds(age_s);
+            app.record_result(result);
+        }
+
+        assert_eq!(app.results.len(), 2);
+        assert_eq!(app.result_base_seq, 2);
+        assert_eq!(app.results[0].seq, 2);
+        // Cumulative stats still cover everything
+        assert_eq!(app.stats.total_sent, 4);
+    }
+
+    #[test]
+    fn test_inline_scale_validation() {
+        let mut app = App::new(Config::parse_from(["rttui", "-s", "200", "example.com"]));
+        app.start_inline_edit(HeaderEditField::Scale, 0, 0);
+        app.inline_edit_input.clear();
+
+        // Out of range: flagged, the live preview keeps the old scale and Confirm is refused
+        app.inline_edit_char('0');
+        assert!(app.inline_edit_input.error.is_some());
+        assert_eq!(app.color_scale.max_rtt, 200);
+        app.apply_inline_edit();
+        assert!(app.inline_edit.is_some());
+        assert_eq!(app.config.scale, 200);
+
+        // Fixed: applied
+        app.inline_edit_backspace();
+        assert!(app.inline_edit_input.error.is_some());
+        app.inline_edit_char('5');
+        app.inline_edit_char('0');
+        assert!(app.inline_edit_input.error.is_none());
+        app.apply_inline_edit();
+        assert!(app.inline_edit.is_none());
+        assert_eq!(app.config.scale, 50);
+    }
+
+    #[test]
+    fn test_cycle_mode() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        assert_eq!(app.config.mode, Mode::Icmp);
+
+        app.header_next_field();
+        app.header_next_field();
+        assert!(app.header_selected == Some(HeaderEditField::Mode));
+        app.header_open_selected();
+        assert_eq!(app.config.mode, Mode::UdpClient);
+        assert_eq!(app.new_mode, Some(Mode::UdpClient));
+        assert!(app.needs_pinger_restart);
+        assert!(app.inline_edit.is_none());
+
+        app.cycle_mode();
+        assert_eq!(app.config.mode, Mode::TlsHandshake);
+        app.cycle_mode();
+        assert_eq!(app.config.mode, Mode::Icmp);
+
+        // ICMP mode can't ping a zone id, so it's skipped
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "-m",
+            "tls-handshake",
+            "fe80::1%eth0",
+        ]));
+        app.cycle_mode();
+        assert_eq!(app.config.mode, Mode::UdpClient);
+        assert!(app.footer_event.is_some());
+    }
+
+    #[test]
+    fn test_jump_to_time() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        let now = chrono::Local::now();
+        for seq in 0..100 {
+            let mut result = PingResult::timeout(seq, Instant::now());
+            result.timestamp = now - chrono::Duration::minutes(100 - seq as i64);
+            app.record_result(result);
+        }
+        // 10 samples per row, 4 rows on screen
+        app.graph_area = Some((0, 0, 10, 4));
+
+        let time = app.results[45].timestamp.time();
+        app.jump_to_time(time).unwrap();
+        assert_eq!(app.view_end_row, Some(6));
+
+        // Typed times are read as UTC after switching to it, which leaves exports alone
+        app.toggle_utc();
+        assert!(!app.config.utc);
+        app.follow_live_view();
+        let time = app.results[45].timestamp.with_timezone(&chrono::Utc).time();
+        app.jump_to_time(time).unwrap();
+        assert_eq!(app.view_end_row, Some(6));
+        app.toggle_utc();
+
+        // The newest samples are shown live
+        let time = app.results[98].timestamp.time();
+        app.jump_to_time(time).unwrap();
+        assert_eq!(app.view_end_row, None);
+
+        assert!(parse_time_of_day("14:32").is_ok());
+        assert!(parse_time_of_day("25:00").is_err());
+    }
+
+    #[test]
+    fn test_notes() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        for seq in 0..3 {
+            app.record_result(PingResult::timeout(seq, Instant::now()));
+        }
+
+        // Blank notes are dropped
+        app.open_note();
+        app.inline_edit_char(' ');
+        app.apply_inline_edit();
+        assert!(app.inline_edit.is_none());
+        assert!(app.notes.is_empty());
+
+        app.open_note();
+        for c in " rebooted router".chars() {
+            app.inline_edit_char(c);
+        }
+        app.apply_inline_edit();
+        assert_eq!(app.notes.len(), 1);
+        assert_eq!(app.notes[0].text, "rebooted router");
+        // Pinned to the next sample, like other events
+        assert_eq!(app.events, vec![(3, EventKind::Note)]);
+        assert!(app.note_at(2).is_none());
+        assert_eq!(
+            app.note_at(3).map(|n| n.text.as_str()),
+            Some("rebooted router")
+        );
+        assert_eq!(app.note_probe_seq(&app.notes[0]), None);
+        app.record_result(PingResult::timeout(3, Instant::now()));
+        assert_eq!(app.note_probe_seq(&app.notes[0]), Some(3));
+
+        app.clear_all_data();
+        assert!(app.notes.is_empty());
+    }
+
+    #[test]
+    fn test_pause_on_loss() {
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--pause-on-loss",
+            "example.com",
+        ]));
+        app.graph_area = Some((0, 0, 10, 5));
+        let now = Instant::now();
+        for seq in 0..15 {
+            app.record_result(PingResult::success(
+                seq,
+                Duration::from_millis(10),
+                now,
+                None,
+            ));
+        }
+
+        // The timeout (stable seq 15) is on the bottom row and later samples don't move the view
+        app.record_result(PingResult::timeout(15, now));
+        app.record_result(PingResult::timeout(16, now));
+        assert_eq!(app.view_end_row, Some(2));
+        assert!(!app.loss_freeze_armed);
+
+        app.jump_to_live();
+        assert_eq!(app.view_end_row, None);
+        assert!(app.loss_freeze_armed);
+    }
+
+    #[test]
+    fn test_send_rate() {
+        let mut app = App::new(Config::parse_from(["rttui", "-i", "10", "example.com"]));
+        assert_eq!(app.send_rate(), None);
+
+        // 10 ms apart, but measured over the last 2 s only
+        let start = Instant::now();
+        for seq in 0..500 {
+            let sent_at = start + Duration::from_millis(seq * 10);
+            app.probe_seq.next();
+            app.track_sends(sent_at);
+            // Every 5th send is lost, its timeout arriving 3 s later behind later replies
+            if seq % 5 != 0 {
+                app.record_result(PingResult::success(
+                    seq,
+                    Duration::from_millis(1),
+                    sent_at,
+                    None,
+                ));
+            } else if seq >= 300 {
+                let lost = seq - 300;
+                app.record_result(PingResult::timeout(
+                    lost,
+                    start + Duration::from_millis(lost * 10),
+                ));
+            }
+        }
+        let rate = app.send_rate().unwrap();
+        assert!((rate - 100.0).abs() < 0.1, "{}", rate);
+
+        // At slow intervals the last few sends are used
+        for seq in 0..3 {
+            app.probe_seq.next();
+            app.track_sends(start + Duration::from_millis(10_000 + seq * 4000));
+        }
+        assert_eq!(app.send_rate(), Some(0.25));
+
+        // A new pinger numbering from scratch starts a new measurement
+        app.probe_seq = ProbeSeq::default();
+        app.probe_seq.next();
+        app.track_sends(start + Duration::from_millis(20_000));
+        assert_eq!(app.send_rate(), None);
+    }
+
+    #[test]
+    fn test_sample_context() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        let now = Instant::now();
+        for (seq, ms) in [(0, 20), (1, 10), (2, 30), (3, 60)] {
+            app.record_result(PingResult::success(
+                seq,
+                Duration::from_millis(ms),
+                now,
+                None,
+            ));
+        }
+        app.record_result(PingResult::timeout(4, now));
+
+        assert_eq!(app.sample_context(0), None);
+        let context = app.sample_context(1).unwrap();
+        assert_eq!(context.vs_avg_ms, -10.0);
+        assert!(context.new_min && !context.new_max);
+        let context = app.sample_context(3).unwrap();
+        assert_eq!(context.vs_avg_ms, 40.0);
+        assert!(context.new_max);
+        assert_eq!(app.sample_context(4), None);
+    }
+
+    #[test]
+    fn test_cursor_blink() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        let later = Instant::now() + CURSOR_BLINK;
+        assert!(!app.update_cursor_blink(later));
+        assert!(app.cursor_lit);
+
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--blink-cursor",
+            "example.com",
+        ]));
+        let start = Instant::now();
+        assert!(!app.update_cursor_blink(start));
+        assert!(app.update_cursor_blink(start + CURSOR_BLINK));
+        assert!(!app.cursor_lit);
+        // Nothing to redraw until the next phase
+        assert!(!app.update_cursor_blink(start + CURSOR_BLINK + CURSOR_BLINK / 2));
+        assert!(app.update_cursor_blink(start + CURSOR_BLINK * 2));
+        assert!(app.cursor_lit);
+    }
+
+    #[test]
+    fn test_count_while_paused() {
+        let mut app = App::new(Config::parse_from(["rttui", "-n", "3", "example.com"]));
+        let now = Instant::now();
+        app.record_result(PingResult::timeout(0, now));
+        app.skip_result();
+        assert!(!app.should_quit);
+        app.skip_result();
+        assert!(app.should_quit);
+        assert_eq!(app.results.len(), 1);
+    }
+
+    #[test]
+    fn test_startup_notice() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        assert!(app.startup_notice_visible());
+        app.record_result(PingResult::timeout(0, Instant::now()));
+        assert!(!app.startup_notice_visible());
+
+        // Without a target there's nothing to wait for
+        let app = App::new(Config::parse_from(["rttui"]));
+        assert!(!app.startup_notice_visible());
+    }
+
+    #[test]
+    fn test_smoothed_rtt() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        let now = Instant::now();
+        let reply = |seq, ms| PingResult::success(seq, Duration::from_millis(ms), now, None);
+        assert_eq!(app.smoothed_rtt, None);
+        app.record_result(reply(0, 10));
+        assert_eq!(app.smoothed_rtt, Some(10.0));
+        app.record_result(reply(1, 20));
+        assert!((app.smoothed_rtt.unwrap() - 12.0).abs() < 1e-9);
+
+        // A couple of losses keep the reading, a run of them starts it over
+        app.record_result(PingResult::timeout(2, now));
+        app.record_result(PingResult::timeout(3, now));
+        assert!(app.smoothed_rtt.is_some());
+        app.record_result(PingResult::timeout(4, now));
+        assert_eq!(app.smoothed_rtt, None);
+        app.record_result(reply(5, 40));
+        assert_eq!(app.smoothed_rtt, Some(40.0));
+    }
+
+    #[test]
+    fn test_spike_detection() {
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--mark-spikes",
+            "example.com",
+        ]));
+        let now = Instant::now();
+        let rtts = [10; 12].into_iter().chain([50, 60, 10, 45, 29]);
+        for (seq, ms) in rtts.enumerate() {
+            app.record_result(PingResult::success(
+                seq as u64,
+                Duration::from_millis(ms),
+                now,
+                None,
+            ));
+        }
+        // 50 and 60 are one spike, 45 another; 29 is under 3× the median
+        assert_eq!(app.spike_count, 2);
+        assert_eq!(
+            app.events,
+            vec![(12, EventKind::Spike), (15, EventKind::Spike)]
+        );
+
+        app.clear_all_data();
+        assert_eq!(app.spike_count, 0);
+    }
+
+    #[test]
+    fn test_split_comparison() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        let now = Instant::now();
+        let reply = |seq, ms| PingResult::success(seq, Duration::from_millis(ms), now, None);
+        for seq in 0..4 {
+            app.record_result(reply(seq, 10));
+        }
+        app.toggle_split();
+        assert_eq!(app.split_seq(), Some(4));
+        assert!(app.split_comparison().is_none());
+
+        app.record_result(reply(4, 30));
+        app.record_result(PingResult::timeout(5, now));
+        let split = app.split_comparison().unwrap();
+        assert_eq!(split.avg_ms, Some(20.0));
+        assert_eq!(split.loss_percent, 50.0);
+
+        app.toggle_split();
+        assert!(app.split_seq().is_none());
+        assert!(app.events.is_empty());
+
+        // A new marker compares against everything before it
+        app.toggle_split();
+        app.record_result(reply(6, 10));
+        let split = app.split_comparison().unwrap();
+        assert_eq!(split.avg_ms, Some(-4.0));
+    }
+
+    #[test]
+    fn test_resize_history() {
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        let now = Instant::now();
+        for seq in 0..20 {
+            if seq == 5 || seq == 15 {
+                app.mark_event(EventKind::Restart);
+            }
+            app.record_result(PingResult::success(
+                seq,
+                Duration::from_millis(10),
+                now,
+                None,
+            ));
+        }
+
+        // Shrinking trims right away, keeping stable sequence numbers and the markers
+        // that are still in range
+        app.resize_history(8);
+        assert_eq!(app.results.len(), 8);
+        assert_eq!(app.result_base_seq, 12);
+        assert_eq!(app.results.front().unwrap().seq, 12);
+        assert_eq!(app.events, vec![(15, EventKind::Restart)]);
+        assert!(app.results.capacity() < 100);
+
+        // Growing keeps everything and makes room up front
+        app.resize_history(1000);
+        assert_eq!(app.results.len(), 8);
+        assert_eq!(app.result_base_seq, 12);
+        assert!(app.results.capacity() >= 1000);
+
+        // Through the settings menu
+        app.open_settings();
+        app.settings_buffer_mb = 2;
+        assert!(app.apply_settings());
+        assert_eq!(app.max_history, app.config.max_history());
+        assert_eq!(app.results.len(), 8);
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let mut app = App::new(Config::parse_from([
+            "rttui",
+            "--aggregate",
+            "3",
+            "--aggregate-by",
+            "avg",
+            "example.com",
+        ]));
+        let now = Instant::now();
+        let ms = |seq, ms| PingResult::success(seq, Duration::from_millis(ms), now, None);
+        for result in [ms(1, 10), PingResult::timeout(2, now), ms(3, 30), ms(4, 40)] {
+            app.record_result(result);
+        }
+
+        // Only the graph combines pings: the history (and so exports) keeps every one,
+        // with three samples to a cell
+        assert_eq!(app.results.len(), 4);
+        assert_eq!(app.results[1].rtt, None);
+        assert_eq!(app.stats.total_sent, 4);
+        assert_eq!(app.samples_per_cell(), 3);
+        assert_eq!(app.row_len(10), 30);
+
+        let group = [ms(1, 10), ms(2, 50), PingResult::timeout(3, now), ms(4, 20)];
+        assert_eq!(
+            Reducer::Last.reduce(&group).unwrap().rtt,
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            Reducer::Max.reduce(&group).unwrap().rtt,
+            Some(Duration::from_millis(50))
+        );
+        let worst = Reducer::WorstLoss.reduce(&group).unwrap();
+        assert_eq!((worst.seq, worst.rtt), (3, None));
+        let lost = [PingResult::timeout(1, now), PingResult::timeout(2, now)];
+        assert_eq!(Reducer::Avg.reduce(&lost).unwrap().seq, 2);
+        assert!(Reducer::Avg.reduce(&[]).is_none());
+
+        // Changed from the settings menu
+        app.open_settings();
+        app.settings_field = SettingsField::Unit;
+        app.settings_next_field();
+        assert!(app.settings_field == SettingsField::AggregateBy);
+        app.settings_increase();
+        assert!(app.apply_settings());
+        assert_eq!(app.config.aggregate_by, Reducer::Max);
+
+        // Only offered when aggregating
+        let mut app = App::new(Config::parse_from(["rttui", "example.com"]));
+        app.open_settings();
+        app.settings_field = SettingsField::Unit;
+        app.settings_next_field();
+        assert!(app.settings_field == SettingsField::HideCursor);
+        app.settings_prev_field();
+        assert!(app.settings_field == SettingsField::Unit);
+    }
 }
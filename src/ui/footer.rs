@@ -6,19 +6,121 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph, Widget},
 };
 
-use crate::color::ColorScale;
-use crate::ping::PingStats;
+use crate::color::{ColorScale, grade_color, loss_color};
+use crate::config::{FooterSection, SparkScale};
+use crate::export::Comparison;
+use crate::ping::{
+    DEFAULT_PRECISION, LossReason, PingStats, Quality, RttUnit, Trend, outlier_limit,
+};
+use crate::ui::app::WindowStats;
 
 /// Sparkline characters for mini history (8 levels)
 const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
 const SPARK_TIMEOUT: char = '×';
 
+/// Apply an exponential moving average to an RTT series
+/// Timeouts pass through unchanged and reset the average, so the first reply after a loss
+/// starts fresh instead of being dragged towards pre-outage values
+fn smooth_rtts(rtts: &[Option<f64>], alpha: f64) -> Vec<Option<f64>> {
+    let mut ema: Option<f64> = None;
+    rtts.iter()
+        .map(|rtt| {
+            ema = rtt.map(|ms| match ema {
+                Some(prev) => prev + alpha * (ms - prev),
+                None => ms,
+            });
+            ema
+        })
+        .collect()
+}
+
+/// Short label for a window length, e.g. "90s", "5m" or "1h"
+fn window_label(window_ms: u64) -> String {
+    match window_ms {
+        ms if ms % 3_600_000 == 0 => format!("{}h", ms / 3_600_000),
+        ms if ms % 60_000 == 0 => format!("{}m", ms / 60_000),
+        ms if ms % 1000 == 0 => format!("{}s", ms / 1000),
+        ms => format!("{}ms", ms),
+    }
+}
+
+/// Color of a comparison delta, where lower is better: green or red, or gray if it
+/// rounds to zero at the one decimal shown
+fn delta_color(delta: Option<f64>) -> Color {
+    match delta.map(|d| (d * 10.0).round()) {
+        Some(d) if d < 0.0 => Color::Green,
+        Some(d) if d > 0.0 => Color::Red,
+        _ => Color::Gray,
+    }
+}
+
+/// Rough age of an event for display, e.g. "45s", "3m" or "2h"
+fn ago_label(secs: i64) -> String {
+    match secs.max(0) {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86400),
+    }
+}
+
+/// Send rate for display: one decimal at slow rates, whole numbers from 100 pps up
+fn format_rate(pps: f64) -> String {
+    if pps < 100.0 {
+        format!("{:.1}", pps)
+    } else {
+        format!("{:.0}", pps)
+    }
+}
+
 /// Footer widget showing statistics and recent ping history
 pub struct Footer<'a> {
     stats: &'a PingStats,
     recent_rtts: &'a [Option<f64>], // Recent RTT values in ms as f64 (None = timeout)
     color_scale: &'a ColorScale,
     terminal_width: u16, // Terminal width for scaling sparkline
+    /// Moving average factor applied to the sparkline (None = raw values)
+    smoothing: Option<f64>,
+    /// Range of the sparkline's bar heights (None = 0 to the color scale)
+    spark_scale: Option<SparkScale>,
+    /// Auto range tops out at this factor of the median RTT shown (None = no clipping)
+    clip_outliers: Option<f64>,
+    /// Loss percentages at which the loss figure turns yellow / red
+    loss_thresholds: (f64, f64),
+    /// Notable event shown on the right of the top border
+    event: Option<&'a str>,
+    /// Rolling window summaries (window length in ms, stats if enough data)
+    windows: &'a [(u64, Option<WindowStats>)],
+    /// Deltas against a previous run loaded with `--compare`
+    comparison: Option<Comparison>,
+    /// Samples after the split marker compared to those before it
+    split: Option<Comparison>,
+    /// Connection quality grade shown as a badge on the border
+    quality: Option<Quality>,
+    /// Latency trend shown as an arrow after the quality badge
+    trend: Option<Trend>,
+    /// Unit RTTs are shown in
+    unit: RttUnit,
+    /// Decimals RTTs in ms are shown with
+    precision: usize,
+    /// Achieved and configured send rate in pings per second
+    send_rate: Option<(f64, f64)>,
+    /// Mean deviation of the send times from the schedule in ms
+    sched_jitter: Option<f64>,
+    /// Average RTT in ms with losses counted as a penalty
+    effective_rtt: Option<f64>,
+    /// Lowest RTT in ms over a window (window length in ms, RTT if any replies)
+    best: Option<(u64, Option<f64>)>,
+    /// Latency spikes seen so far
+    spikes: u64,
+    /// ICMP error the newest ping was lost to (None = a plain timeout, or not lost)
+    last_loss_reason: Option<LossReason>,
+    /// Smoothed (EWMA) current RTT in ms, shown after the newest sample
+    smoothed_rtt: Option<f64>,
+    /// Probes awaiting a reply, and how many can be at most (timeout / interval)
+    in_flight: Option<(usize, u64)>,
+    /// Sections shown / hidden regardless of the terminal width
+    forced_sections: (&'a [FooterSection], &'a [FooterSection]),
 }
 
 impl<'a> Footer<'a> {
@@ -33,6 +135,170 @@ impl<'a> Footer<'a> {
             recent_rtts,
             color_scale,
             terminal_width,
+            smoothing: None,
+            spark_scale: None,
+            clip_outliers: None,
+            loss_thresholds: (1.0, 5.0),
+            event: None,
+            windows: &[],
+            comparison: None,
+            split: None,
+            quality: None,
+            trend: None,
+            unit: RttUnit::Ms,
+            precision: DEFAULT_PRECISION,
+            send_rate: None,
+            sched_jitter: None,
+            effective_rtt: None,
+            best: None,
+            spikes: 0,
+            last_loss_reason: None,
+            smoothed_rtt: None,
+            in_flight: None,
+            forced_sections: (&[], &[]),
+        }
+    }
+
+    /// Always show the `show` sections and always hide the `hide` ones, overriding
+    /// the width-based layout
+    pub fn sections(mut self, show: &'a [FooterSection], hide: &'a [FooterSection]) -> Self {
+        self.forced_sections = (show, hide);
+        self
+    }
+
+    /// Show the achieved send rate, highlighted when it falls short of `expected_pps`
+    pub fn send_rate(mut self, rate: Option<f64>, expected_pps: f64) -> Self {
+        self.send_rate = rate.map(|rate| (rate, expected_pps));
+        self
+    }
+
+    /// Show a smoothed current RTT next to the newest sample, e.g. "12.31ms ≈12.80ms"
+    pub fn smoothed_rtt(mut self, smoothed_rtt: Option<f64>) -> Self {
+        self.smoothed_rtt = smoothed_rtt;
+        self
+    }
+
+    /// Name the ICMP error the newest ping was lost to instead of "timeout"
+    pub fn last_loss_reason(mut self, reason: Option<LossReason>) -> Self {
+        self.last_loss_reason = reason;
+        self
+    }
+
+    /// Show how many latency spikes there have been next to the uptime
+    pub fn spikes(mut self, spikes: u64) -> Self {
+        self.spikes = spikes;
+        self
+    }
+
+    /// Show how many probes are awaiting a reply, highlighted once more than half of
+    /// `max` (as many as can be outstanding before timing out) are
+    pub fn in_flight(mut self, in_flight: Option<usize>, max: u64) -> Self {
+        self.in_flight = in_flight.map(|count| (count, max));
+        self
+    }
+
+    /// Show the effective RTT (in ms, losses counted as a penalty) next to the best RTT
+    pub fn effective_rtt(mut self, effective_rtt: Option<f64>) -> Self {
+        self.effective_rtt = effective_rtt;
+        self
+    }
+
+    /// Show the scheduling jitter of the sends (in ms) next to the send rate
+    pub fn sched_jitter(mut self, sched_jitter: Option<f64>) -> Self {
+        self.sched_jitter = sched_jitter;
+        self
+    }
+
+    /// Show the lowest RTT over the last `window_ms` (None = not shown)
+    pub fn best(mut self, best: Option<(u64, Option<f64>)>) -> Self {
+        self.best = best;
+        self
+    }
+
+    /// Show RTTs in this unit
+    pub fn unit(mut self, unit: RttUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Show RTTs in ms with this many decimals
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Show avg/p95/loss deltas against a previous run on the footer border
+    pub fn comparison(mut self, comparison: Option<Comparison>) -> Self {
+        self.comparison = comparison;
+        self
+    }
+
+    /// Show avg/p95/jitter/loss deltas of the samples after the split marker
+    pub fn split(mut self, split: Option<Comparison>) -> Self {
+        self.split = split;
+        self
+    }
+
+    /// Show whether latency is trending worse (red ↑), better (green ↓) or steady (→)
+    pub fn trend(mut self, trend: Option<Trend>) -> Self {
+        self.trend = trend;
+        self
+    }
+
+    /// Show a connection quality badge (e.g. "Quality: B (elevated jitter)") on the border
+    pub fn quality(mut self, quality: Option<Quality>) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Show loss and average RTT over rolling windows on the footer border
+    pub fn windows(mut self, windows: &'a [(u64, Option<WindowStats>)]) -> Self {
+        self.windows = windows;
+        self
+    }
+
+    /// Show a notable event on the footer border
+    pub fn event(mut self, event: Option<&'a str>) -> Self {
+        self.event = event;
+        self
+    }
+
+    /// Loss percentages above which loss is colored yellow (`warn`) and red (`crit`)
+    pub fn loss_thresholds(mut self, warn: f64, crit: f64) -> Self {
+        self.loss_thresholds = (warn, crit);
+        self
+    }
+
+    /// Smooth the sparkline with an exponential moving average (alpha in (0, 1])
+    pub fn smoothing(mut self, alpha: Option<f64>) -> Self {
+        self.smoothing = alpha;
+        self
+    }
+
+    /// Range of the sparkline's bar heights, instead of 0 to the color scale
+    pub fn spark_scale(mut self, scale: Option<SparkScale>) -> Self {
+        self.spark_scale = scale;
+        self
+    }
+
+    /// Cap the auto range at `factor` × the median RTT shown, drawing outliers full height
+    pub fn clip_outliers(mut self, factor: Option<f64>) -> Self {
+        self.clip_outliers = factor;
+        self
+    }
+
+    /// Whether a section is shown, given whether the width-based layout would show it
+    fn section_visible(&self, section: FooterSection, fits: bool) -> bool {
+        let (show, hide) = self.forced_sections;
+        !hide.contains(&section) && (fits || show.contains(&section))
+    }
+
+    /// Width a section takes up in the layout (nothing when it's forced hidden)
+    fn section_len(&self, section: FooterSection, len: usize) -> usize {
+        if self.forced_sections.1.contains(&section) {
+            0
+        } else {
+            len
         }
     }
 
@@ -50,18 +316,44 @@ impl<'a> Footer<'a> {
             }
         }
 
+        // Smooth over the whole history so the visible part starts warmed up
+        let rtts = match self.smoothing {
+            Some(alpha) => smooth_rtts(self.recent_rtts, alpha),
+            None => self.recent_rtts.to_vec(),
+        };
+
         // Take the last `sparkline_width` RTTs (or all if less)
         let start_idx = rtt_count.saturating_sub(sparkline_width);
+        let shown = &rtts[start_idx..];
+
+        // Bar heights span `low..high`; colors keep following the color scale
+        let (low, high) = match self.spark_scale {
+            Some(SparkScale::Fixed(ms)) => (0.0, ms as f64),
+            Some(SparkScale::Auto) => shown
+                .iter()
+                .flatten()
+                .fold(None, |range: Option<(f64, f64)>, &ms| {
+                    Some(range.map_or((ms, ms), |(low, high)| (low.min(ms), high.max(ms))))
+                })
+                .map(|(low, high)| {
+                    let limit = self
+                        .clip_outliers
+                        .and_then(|factor| outlier_limit(shown.iter().flatten().copied(), factor));
+                    (low, limit.map_or(high, |limit| high.min(limit)))
+                })
+                .unwrap_or((0.0, 0.0)),
+            None => (0.0, self.color_scale.max_rtt as f64),
+        };
 
-        for rtt in &self.recent_rtts[start_idx..] {
+        for rtt in shown {
             match rtt {
                 None => spans.push(Span::styled(
                     SPARK_TIMEOUT.to_string(),
                     Style::default().fg(Color::Indexed(240)),
                 )),
                 Some(ms) => {
-                    // Map RTT to sparkline character (0-7)
-                    let ratio = (*ms / self.color_scale.max_rtt as f64).min(1.0);
+                    // Map RTT to sparkline character (0-7); a flat auto range is all low bars
+                    let ratio = ((*ms - low) / (high - low).max(f64::EPSILON)).clamp(0.0, 1.0);
                     let idx = ((ratio * 7.0).round() as usize).min(7);
                     let color = self.color_scale.color_for_rtt_f64(Some(*ms));
                     spans.push(Span::styled(
@@ -80,71 +372,63 @@ impl Widget for Footer<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let width = self.terminal_width as usize;
 
-        let min = self
-            .stats
-            .min_rtt
-            .map(|d| format!("{:.2}", d.as_secs_f64() * 1000.0))
-            .unwrap_or_else(|| "-".to_string());
-        let avg = self
-            .stats
-            .avg_rtt()
-            .map(|d| format!("{:.2}", d.as_secs_f64() * 1000.0))
-            .unwrap_or_else(|| "-".to_string());
-        let max = self
-            .stats
-            .max_rtt
-            .map(|d| format!("{:.2}", d.as_secs_f64() * 1000.0))
-            .unwrap_or_else(|| "-".to_string());
-
-        let loss_color = if self.stats.loss_percent() > 5.0 {
-            Color::Red
-        } else if self.stats.loss_percent() > 1.0 {
-            Color::Yellow
-        } else {
-            Color::Green
-        };
+        let (loss_warn, loss_crit) = self.loss_thresholds;
+        let loss_color = loss_color(self.stats.loss_percent(), loss_warn, loss_crit);
 
         // Calculate lengths for different sections
         let sent_rcvd_section = format!(
             "Sent: {} │ Rcvd: {} │ ",
             self.stats.total_sent, self.stats.total_received
         );
+        // Longest outage, shown once there has been any loss
+        let mut burst_text = if self.stats.max_loss_streak > 0 {
+            format!(" burst {}", self.stats.max_loss_streak)
+        } else {
+            String::new()
+        };
+        if self.stats.total_corrupted > 0 {
+            burst_text += &format!(" corrupt {}", self.stats.total_corrupted);
+        }
+        for (reason, count) in &self.stats.loss_reasons {
+            burst_text += &format!(" {} {}", reason, count);
+        }
+        let loss_text = self
+            .last_loss_reason
+            .map_or_else(|| "timeout".to_string(), |reason| reason.to_string());
         let loss_section = format!(
-            "Lost: {} ({:.1}%) │ ",
+            "Lost: {} ({:.1}%){} │ ",
             self.stats.total_lost,
-            self.stats.loss_percent()
+            self.stats.loss_percent(),
+            burst_text
         );
-        let rtt_section = format!("RTT min/avg/max: {}/{}/{} ms", min, avg, max);
+        let rtt_summary = self.stats.rtt_summary(self.unit, self.precision);
+        let rtt_section = format!("RTT min/avg/max: {}", rtt_summary);
         let recent_label = " │ Recent: ";
+        let smoothed_text = self
+            .smoothed_rtt
+            .map(|ms| format!(" ≈{}", self.unit.format(ms, self.precision)));
         let last_rtt_text = if let Some(last_rtt) = self.recent_rtts.last() {
             match last_rtt {
-                Some(ms) => format!(" {:.2}ms", ms),
-                None => " timeout".to_string(),
+                Some(ms) => format!(" {}", self.unit.format(*ms, self.precision)),
+                None => format!(" {}", loss_text),
             }
         } else {
             " ---.--ms".to_string()
-        };
+        } + smoothed_text.as_deref().unwrap_or("");
         let quit_button = "[q: quit]";
 
-        // Calculate total lengths for different display modes
-        let full_static_len = sent_rcvd_section.len()
-            + loss_section.len()
-            + rtt_section.len()
-            + recent_label.len()
-            + last_rtt_text.len()
-            + quit_button.len()
-            + 2; // 2 spaces before button
-        let no_sent_rcvd_len = loss_section.len()
-            + rtt_section.len()
-            + recent_label.len()
-            + last_rtt_text.len()
-            + quit_button.len()
-            + 2;
-        let no_recent_len = sent_rcvd_section.len()
-            + loss_section.len()
-            + rtt_section.len()
-            + quit_button.len()
-            + 2;
+        // Calculate total lengths for different display modes (forced hidden sections
+        // leave their room to the others)
+        let sent_rcvd_len = self.section_len(FooterSection::Sent, sent_rcvd_section.len());
+        let loss_len = self.section_len(FooterSection::Loss, loss_section.len());
+        let recent_len = self.section_len(
+            FooterSection::Recent,
+            recent_label.len() + last_rtt_text.len(),
+        );
+        let full_static_len =
+            sent_rcvd_len + loss_len + rtt_section.len() + recent_len + quit_button.len() + 2; // 2 spaces before button
+        let no_sent_rcvd_len = loss_len + rtt_section.len() + recent_len + quit_button.len() + 2;
+        let no_recent_len = sent_rcvd_len + loss_len + rtt_section.len() + quit_button.len() + 2;
         let minimal_len = loss_section.len() + rtt_section.len() + quit_button.len() + 2;
 
         // Determine what to show based on terminal width
@@ -154,10 +438,15 @@ impl Widget for Footer<'_> {
         // - Medium (> no_recent): show sent/rcvd, stats, recent text only (no sparkline)
         // - Narrow (> minimal): show loss, RTT stats, quit
         // - Very narrow: show only RTT stats and quit
+        // --footer-show / --footer-hide override these per section
 
-        let show_sent_rcvd = width > no_sent_rcvd_len + 10;
-        let show_recent_section = width > no_recent_len + 10;
-        let show_sparkline = width > full_static_len + 10;
+        let show_sent_rcvd =
+            self.section_visible(FooterSection::Sent, width > no_sent_rcvd_len + 10);
+        let show_loss = self.section_visible(FooterSection::Loss, width > minimal_len);
+        let show_recent_section =
+            self.section_visible(FooterSection::Recent, width > no_recent_len + 10);
+        let show_sparkline =
+            self.section_visible(FooterSection::Sparkline, width > full_static_len + 10);
 
         // Build left-side spans (without sparkline first to calculate remaining space)
         let mut base_spans = Vec::new();
@@ -181,7 +470,7 @@ impl Widget for Footer<'_> {
         }
 
         // Loss section (always show if there's room)
-        if width > minimal_len {
+        if show_loss {
             base_spans.extend(vec![
                 Span::styled("Lost: ", Style::default().fg(Color::DarkGray)),
                 Span::styled(
@@ -192,6 +481,7 @@ impl Widget for Footer<'_> {
                     ),
                     Style::default().fg(loss_color),
                 ),
+                Span::styled(burst_text.clone(), Style::default().fg(Color::DarkGray)),
                 Span::raw(" │ "),
             ]);
         }
@@ -199,10 +489,7 @@ impl Widget for Footer<'_> {
         // RTT section (always show)
         base_spans.extend(vec![
             Span::styled("RTT min/avg/max: ", Style::default().fg(Color::DarkGray)),
-            Span::styled(
-                format!("{}/{}/{} ms", min, avg, max),
-                Style::default().fg(Color::White),
-            ),
+            Span::styled(rtt_summary, Style::default().fg(Color::White)),
         ]);
 
         // Build the "Recent: " label and last RTT text spans
@@ -222,13 +509,13 @@ impl Widget for Footer<'_> {
                 match last_rtt {
                     Some(ms) => {
                         last_rtt_spans.push(Span::styled(
-                            format!("{:.2}ms", ms),
+                            self.unit.format(*ms, self.precision),
                             Style::default().fg(Color::White),
                         ));
                     }
                     None => {
                         last_rtt_spans.push(Span::styled(
-                            "timeout",
+                            loss_text.clone(),
                             Style::default().fg(Color::Indexed(240)),
                         ));
                     }
@@ -241,6 +528,14 @@ impl Widget for Footer<'_> {
                     Style::default().fg(Color::DarkGray),
                 ));
             }
+            if let Some(smoothed_text) = &smoothed_text {
+                last_rtt_spans.push(Span::styled(
+                    smoothed_text.clone(),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+        } else if show_sparkline {
+            recent_spans.push(Span::raw(" │ "));
         }
 
         // Calculate widths using Line::width() for proper Unicode handling
@@ -252,7 +547,7 @@ impl Widget for Footer<'_> {
         // Calculate sparkline width (fill ALL remaining space)
         let content_width = area.width as usize;
         let fixed_width = base_width + recent_width + last_rtt_width + right_section_len;
-        let sparkline_width = if show_sparkline && show_recent_section {
+        let sparkline_width = if show_sparkline {
             content_width.saturating_sub(fixed_width)
         } else {
             0
@@ -277,11 +572,314 @@ impl Widget for Footer<'_> {
 
         let line = Line::from(spans);
 
-        let block = Block::default()
+        let mut block = Block::default()
             .borders(Borders::TOP)
             .border_style(Style::default().fg(Color::DarkGray));
+        let mut title = Vec::new();
+        if !self.windows.is_empty() {
+            for (i, (window_ms, stats)) in self.windows.iter().enumerate() {
+                if i > 0 {
+                    title.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
+                }
+                // Early on, how much of the window there is data for so far
+                let covered = stats
+                    .as_ref()
+                    .map_or(0, |stats| stats.covered.as_millis() as u64);
+                let label = if stats.is_some() && covered < *window_ms {
+                    format!(
+                        "{} ({} so far) ",
+                        window_label(*window_ms),
+                        ago_label((covered / 1000) as i64)
+                    )
+                } else {
+                    format!("{} ", window_label(*window_ms))
+                };
+                title.push(Span::styled(label, Style::default().fg(Color::DarkGray)));
+                match stats {
+                    Some(stats) => {
+                        let avg = stats
+                            .avg_rtt()
+                            .map(|d| format!(" {}", self.unit.format_duration(d, self.precision)))
+                            .unwrap_or_else(|| " -".to_string());
+                        title.push(Span::styled(
+                            format!("{:.1}%", stats.loss_percent()),
+                            Style::default().fg(crate::color::loss_color(
+                                stats.loss_percent(),
+                                loss_warn,
+                                loss_crit,
+                            )),
+                        ));
+                        title.push(Span::styled(avg, Style::default().fg(Color::Gray)));
+                    }
+                    None => title.push(Span::styled("-", Style::default().fg(Color::DarkGray))),
+                }
+            }
+        }
+        // Uptime since the start, with how long ago the last ping was lost
+        if self.stats.total_sent > 0 {
+            if !title.is_empty() {
+                title.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
+            }
+            title.push(Span::styled("up ", Style::default().fg(Color::DarkGray)));
+            title.push(Span::styled(
+                format!("{:.1}%", self.stats.uptime_percent()),
+                Style::default().fg(loss_color),
+            ));
+            if let Some(last_loss) = self.stats.last_loss_at {
+                let ago = (chrono::Local::now() - last_loss).num_seconds();
+                title.push(Span::styled(
+                    format!(" last loss {} ago", ago_label(ago)),
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+            title.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
+            title.push(Span::styled(
+                "spikes ",
+                Style::default().fg(Color::DarkGray),
+            ));
+            title.push(Span::styled(
+                self.spikes.to_string(),
+                Style::default().fg(if self.spikes > 0 {
+                    Color::LightYellow
+                } else {
+                    Color::Gray
+                }),
+            ));
+        }
+        // The best-case path, to tell a second slower path from the route degrading
+        if let Some((window_ms, best)) = self.best {
+            if !title.is_empty() {
+                title.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
+            }
+            title.push(Span::styled(
+                format!("best {} ", window_label(window_ms)),
+                Style::default().fg(Color::DarkGray),
+            ));
+            title.push(match best {
+                Some(best) => Span::styled(
+                    self.unit.format(best, self.precision),
+                    Style::default().fg(self.color_scale.color_for_rtt_f64(Some(best))),
+                ),
+                None => Span::styled("-", Style::default().fg(Color::DarkGray)),
+            });
+        }
+        // Loss and latency in one number, colored like a sample of that RTT
+        if let Some(effective) = self.effective_rtt {
+            if !title.is_empty() {
+                title.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
+            }
+            title.push(Span::styled("eff ", Style::default().fg(Color::DarkGray)));
+            title.push(Span::styled(
+                self.unit.format(effective, self.precision),
+                Style::default().fg(self.color_scale.color_for_rtt_f64(Some(effective))),
+            ));
+        }
+        // Under 90% of the configured rate means the interval isn't being met
+        if let Some((rate, expected)) = self.send_rate {
+            if !title.is_empty() {
+                title.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
+            }
+            title.push(Span::styled("rate ", Style::default().fg(Color::DarkGray)));
+            title.push(Span::styled(
+                format!("{} pps", format_rate(rate)),
+                Style::default().fg(if rate < expected * 0.9 {
+                    Color::Yellow
+                } else {
+                    Color::Gray
+                }),
+            ));
+        }
+        // A growing count means replies are slow or being dropped, before they time out
+        if let Some((count, max)) = self.in_flight {
+            if !title.is_empty() {
+                title.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
+            }
+            title.push(Span::styled(
+                "in flight ",
+                Style::default().fg(Color::DarkGray),
+            ));
+            title.push(Span::styled(
+                count.to_string(),
+                Style::default().fg(if count as u64 * 2 > max.max(1) {
+                    Color::Yellow
+                } else {
+                    Color::Gray
+                }),
+            ));
+        }
+        if let Some(sched_jitter) = self.sched_jitter {
+            if !title.is_empty() {
+                title.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
+            }
+            title.push(Span::styled(
+                "sched jitter ",
+                Style::default().fg(Color::DarkGray),
+            ));
+            title.push(Span::styled(
+                self.unit.format(sched_jitter, self.precision),
+                Style::default().fg(Color::Gray),
+            ));
+        }
+        let comparisons = [("vs prev", self.comparison), ("after split", self.split)];
+        for (name, comparison) in comparisons {
+            let Some(comparison) = comparison else {
+                continue;
+            };
+            if !title.is_empty() {
+                title.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
+            }
+            title.push(Span::styled(name, Style::default().fg(Color::DarkGray)));
+            for (label, value, delta) in comparison.parts() {
+                title.push(Span::styled(
+                    format!(" {} ", label),
+                    Style::default().fg(Color::DarkGray),
+                ));
+                title.push(Span::styled(value, Style::default().fg(delta_color(delta))));
+            }
+        }
+        if !title.is_empty() {
+            title.insert(0, Span::raw(" "));
+            title.push(Span::raw(" "));
+            block = block.title(Line::from(title));
+        }
+        if let Some(quality) = self.quality {
+            let color = grade_color(quality.grade);
+            let mut badge = vec![
+                Span::styled(" Quality: ", Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    format!(" {} ", quality.grade),
+                    Style::default().fg(Color::Black).bg(color),
+                ),
+            ];
+            if let Some(reason) = quality.reason {
+                badge.push(Span::styled(
+                    format!(" ({})", reason),
+                    Style::default().fg(color),
+                ));
+            }
+            if let Some(trend) = self.trend {
+                let color = match trend {
+                    Trend::Rising => Color::Red,
+                    Trend::Falling => Color::Green,
+                    Trend::Flat => Color::Gray,
+                };
+                badge.push(Span::styled(
+                    format!(" {}", trend.arrow()),
+                    Style::default().fg(color),
+                ));
+            }
+            badge.push(Span::raw(" "));
+            block = block.title(Line::from(badge).right_aligned());
+        }
+        if let Some(event) = self.event {
+            block = block.title(
+                Line::from(Span::styled(
+                    format!(" {} ", event),
+                    Style::default().fg(Color::Yellow),
+                ))
+                .right_aligned(),
+            );
+        }
 
         let paragraph = Paragraph::new(line).block(block);
         paragraph.render(area, buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorScheme;
+
+    #[test]
+    fn test_window_label() {
+        assert_eq!(window_label(60_000), "1m");
+        assert_eq!(window_label(7_200_000), "2h");
+        assert_eq!(window_label(90_000), "90s");
+        assert_eq!(window_label(1500), "1500ms");
+    }
+
+    #[test]
+    fn test_delta_color() {
+        assert_eq!(delta_color(Some(-3.2)), Color::Green);
+        assert_eq!(delta_color(Some(0.4)), Color::Red);
+        // "-0.0" and "+0.0" are no change
+        assert_eq!(delta_color(Some(-0.04)), Color::Gray);
+        assert_eq!(delta_color(Some(0.04)), Color::Gray);
+        assert_eq!(delta_color(None), Color::Gray);
+    }
+
+    #[test]
+    fn test_ago_label() {
+        assert_eq!(ago_label(45), "45s");
+        assert_eq!(ago_label(200), "3m");
+        assert_eq!(ago_label(7300), "2h");
+        assert_eq!(ago_label(-1), "0s");
+    }
+
+    #[test]
+    fn test_smooth_rtts_resets_on_timeout() {
+        let rtts = [Some(10.0), Some(20.0), None, Some(50.0)];
+        let smoothed = smooth_rtts(&rtts, 0.5);
+        assert_eq!(smoothed, vec![Some(10.0), Some(15.0), None, Some(50.0)]);
+    }
+    #[test]
+    fn test_forced_sections() {
+        let stats = PingStats::new();
+        let rtts = [Some(10.0)];
+        let scale = ColorScale::new(100, ColorScheme::default());
+        let render = |footer: Footer| {
+            let area = Rect::new(0, 0, 60, 2);
+            let mut buf = Buffer::empty(area);
+            footer.render(area, &mut buf);
+            (0..area.width)
+                .map(|x| buf[(x, 1)].symbol())
+                .collect::<String>()
+        };
+
+        // Too narrow for Sent/Rcvd and Recent by default
+        let line = render(Footer::new(&stats, &rtts, &scale, 60));
+        assert!(line.starts_with("Lost: 0"), "{}", line);
+        assert!(!line.contains("Recent"));
+
+        let show = [FooterSection::Sent];
+        let hide = [FooterSection::Loss];
+        let line = render(Footer::new(&stats, &rtts, &scale, 60).sections(&show, &hide));
+        assert!(line.starts_with("Sent: 0 │ Rcvd: 0 │ RTT"), "{}", line);
+        assert!(!line.contains("Lost"));
+    }
+
+    #[test]
+    fn test_spark_scale() {
+        let stats = PingStats::new();
+        let rtts = [Some(10.0), Some(15.0), Some(20.0)];
+        let scale = ColorScale::new(200, ColorScheme::default());
+        let bars = |spark_scale| {
+            Footer::new(&stats, &rtts, &scale, 60)
+                .spark_scale(spark_scale)
+                .sparkline(3)
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect::<String>()
+        };
+
+        // Flattened against the color scale, spread out on their own range
+        assert_eq!(bars(None), "▁▂▂");
+        assert_eq!(bars(Some(SparkScale::Fixed(20))), "▅▆█");
+        assert_eq!(bars(Some(SparkScale::Auto)), "▁▅█");
+
+        // An outlier clipped at 2× the median leaves the rest spread out
+        let rtts = [Some(10.0), Some(15.0), Some(20.0), Some(2000.0)];
+        let bars = |clip| {
+            Footer::new(&stats, &rtts, &scale, 60)
+                .spark_scale(Some(SparkScale::Auto))
+                .clip_outliers(clip)
+                .sparkline(4)
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect::<String>()
+        };
+        assert_eq!(bars(None), "▁▁▁█");
+        assert_eq!(bars(Some(2.0)), "▁▂▃█");
+    }
+}
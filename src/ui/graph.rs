@@ -1,26 +1,182 @@
+use chrono::{DateTime, Local};
 use ratatui::{
-    buffer::Buffer,
+    buffer::{Buffer, Cell},
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     widgets::Widget,
 };
 
-use crate::color::ColorScale;
-use crate::ping::PingResult;
-use std::collections::VecDeque;
+use crate::color::{ColorScale, contrast_fg, tint};
+use crate::ping::{DEFAULT_PRECISION, PingResult, Reducer, RttUnit};
+use crate::ui::app::EventKind;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 /// The filled square character for the graph
-const FILLED_SQUARE: &str = "█";
-const TIMEOUT_CHAR: &str = "X";
+pub(crate) const FILLED_SQUARE: &str = "█";
+pub(crate) const TIMEOUT_CHAR: &str = "X";
+/// Marks a reply that arrived corrupted (drawn even in gaps mode)
+const CORRUPTED_CHAR: &str = "!";
 /// Cursor character showing current position
 const CURSOR_CHAR: &str = "▌";
+/// Width of one cell in numeric readout mode (5 digits + separator)
+pub const NUMERIC_CELL_WIDTH: usize = 6;
+/// Tint applied to samples above the baseline RTT (amber)
+const BASELINE_TINT: (u8, u8, u8) = (255, 170, 0);
+/// How strongly samples above the baseline are tinted
+const BASELINE_TINT_AMOUNT: f64 = 0.45;
+/// Tint applied to samples near the lowest RTT on screen (periwinkle)
+const FLOOR_TINT: (u8, u8, u8) = (140, 150, 255);
+/// How strongly samples near the floor are tinted
+const FLOOR_TINT_AMOUNT: f64 = 0.4;
+/// Samples up to this fraction above the lowest RTT on screen count as at the floor
+const FLOOR_MARGIN: f64 = 0.05;
+/// Fractions of the color scale marked by the RTT bands, with their labels
+const BANDS: [(f64, &str); 3] = [(0.25, "¼"), (0.5, "½"), (0.75, "¾")];
+/// How far samples in every other band are darkened towards the background
+const BAND_SHADE_AMOUNT: f64 = 0.25;
+/// How far the oldest faded samples are blended into the background
+const FADE_MAX: f64 = 0.7;
+/// Selected samples are lightened towards white by this factor
+const SELECTION_TINT_AMOUNT: f64 = 0.5;
+/// Highlight color for samples in the legend entry being hovered: bright red for visibility
+const HIGHLIGHT_COLOR: Color = Color::Rgb(255, 50, 50);
+/// Marker drawn over the highest RTT sample on screen
+const MAX_MARKER_CHAR: &str = "◆";
+/// Thin bar drawn over the left edge of the first sample after an event
+const EVENT_MARKER_CHAR: &str = "▐";
+const EVENT_MARKER_BLANK_CHAR: &str = "▏";
+/// Blank cells kept after the write head in sweep mode, separating new samples from old
+const SWEEP_GAP: usize = 2;
+/// Scrollbar track and thumb characters
+const SCROLLBAR_TRACK: &str = "│";
+const SCROLLBAR_THUMB: &str = "┃";
+/// Gridline glyph in empty cells, and how much darker samples on a gridline are drawn
+const GRID_CHAR: &str = "┊";
+const GRID_COLOR: Color = Color::Indexed(237);
+const GRID_SHADE_AMOUNT: f64 = 0.3;
+/// Samples grouped into each cell in dot density mode (one per braille dot)
+pub const DOTS_PER_CELL: usize = 8;
+/// Braille dot bits in the order they fill up: bottom row first, left before right
+const DENSITY_DOTS: [u32; DOTS_PER_CELL] = [0x40, 0x80, 0x04, 0x20, 0x02, 0x10, 0x01, 0x08];
+/// Fraction of the color scale from which a sample counts as high in dot density mode
+const DENSITY_HIGH_FRACTION: f64 = 0.5;
+
+/// Everything besides the samples themselves that decides how a row of samples is drawn
+#[derive(Clone, PartialEq)]
+struct RowStyle {
+    x: u16,
+    width: usize,
+    cell_width: usize,
+    numeric: bool,
+    aggregate: (usize, Reducer),
+    color_scale: ColorScale,
+    highlight_range: Option<(f64, f64, bool)>,
+    baseline_ms: Option<f64>,
+    floor_ms: Option<f64>,
+    bands: bool,
+    fade: bool,
+    background: Option<Color>,
+    loss_gaps: bool,
+    selection: Option<(usize, usize)>,
+}
+
+/// A complete row of samples as drawn in a previous frame
+struct CachedRow {
+    /// Send time of the row's first sample, telling the row apart from one with the
+    /// same number after the history was cleared
+    first: DateTime<Local>,
+    /// Rows back from the newest when drawn (only matters while fading)
+    rows_back: usize,
+    cells: Vec<Cell>,
+    /// Highest RTT in the row as (rtt, x, color), the newest if tied
+    max: Option<(f64, u16, Color)>,
+}
+
+/// Rows drawn in previous frames, kept by the caller between frames
+///
+/// Once a row of the scrolling view is full it never changes, only moves up a line
+/// as new rows arrive, so its cells are copied instead of colored again sample by
+/// sample. On a large terminal that leaves only the newest row and the overlays
+/// (cursor, markers, indicators) to draw in the live view.
+#[derive(Default)]
+pub struct GraphCache {
+    style: Option<RowStyle>,
+    rows: HashMap<usize, CachedRow>,
+}
+
+/// Position and length of the scrollbar thumb as (start, len) in screen rows,
+/// or None if all rows fit on screen
+fn scrollbar_thumb(view_end: usize, total_rows: usize, height: usize) -> Option<(usize, usize)> {
+    if height == 0 || total_rows <= height {
+        return None;
+    }
+
+    let thumb_len = (height * height).div_ceil(total_rows).clamp(1, height);
+    let view_start = view_end.saturating_sub(height);
+    let max_start = total_rows - height;
+    let track = height - thumb_len;
+    // Scale the scroll position so the thumb touches both ends of the track
+    let thumb_start = (view_start * track + max_start / 2) / max_start;
+
+    Some((thumb_start.min(track), thumb_len))
+}
+
+/// Braille character with `count` dots filled in, bottom-up
+fn density_char(count: usize) -> String {
+    let bits = DENSITY_DOTS
+        .iter()
+        .take(count)
+        .fold(0, |bits, dot| bits | dot);
+    char::from_u32(0x2800 + bits).map_or_else(String::new, String::from)
+}
+
+/// Screen row of a data row that is on screen when `view_end` rows are shown:
+/// bottom-up the row before `view_end` is the last screen row, top-down the first
+fn screen_row_of(data_row: usize, view_end: usize, height: usize, top_down: bool) -> usize {
+    if top_down {
+        view_end - 1 - data_row
+    } else {
+        height - (view_end - data_row)
+    }
+}
+
+/// Data row drawn on a screen row, or None if that screen row is empty
+fn data_row_at(screen_row: usize, view_end: usize, height: usize, top_down: bool) -> Option<usize> {
+    if screen_row >= height {
+        return None;
+    }
+    if top_down {
+        view_end.checked_sub(screen_row + 1)
+    } else {
+        view_end.checked_sub(height - screen_row)
+    }
+}
+
+/// Stable sequence number of the sample drawn in a sweep mode slot (cells in reading
+/// order, `capacity` of them), or None for the write head, the gap after it and slots
+/// not written yet
+fn sweep_seq_at(slot: usize, total_results: usize, capacity: usize) -> Option<usize> {
+    if slot >= capacity {
+        return None;
+    }
+    let head = total_results % capacity;
+    let ahead = (slot + capacity - head) % capacity;
+    if ahead <= SWEEP_GAP {
+        return None;
+    }
+    total_results.checked_sub(capacity - ahead)
+}
 
 /// Graph widget that displays ping results as colored squares
 ///
 /// Rendering behavior:
-/// - Content is aligned to the BOTTOM of the screen
+/// - Content is aligned to the BOTTOM of the screen (or the top when drawn top-down,
+///   with the newest row first and indicators moved to the bottom)
 /// - New pings fill the current row from left to right
 /// - When scrolled, view stays at fixed position (doesn't follow new data)
+/// - In sweep mode the live view is a fixed window instead: new samples overwrite the
+///   oldest in place behind a moving write head, like an oscilloscope
 pub struct Graph<'a> {
     results: &'a VecDeque<PingResult>,
     color_scale: &'a ColorScale,
@@ -34,8 +190,55 @@ pub struct Graph<'a> {
     paused: bool,
     /// Whether to hide the cursor
     hide_cursor: bool,
+    /// Whether to hide the corner status indicator (paused, scroll position, merged samples)
+    hide_indicator: bool,
+    /// Off phase of a blinking cursor
+    cursor_dark: bool,
     /// Optional RTT range to highlight (min_rtt, max_rtt, is_timeout)
     highlight_range: Option<(f64, f64, bool)>,
+    /// Optional baseline RTT (ms); samples above it are tinted
+    baseline_ms: Option<f64>,
+    /// Unit and ms decimals of the baseline label
+    unit: RttUnit,
+    precision: usize,
+    /// Whether samples near the lowest RTT on screen are tinted
+    floor: bool,
+    /// Whether every other quarter of the color scale is shaded, with the RTTs labeled
+    bands: bool,
+    /// Samples between vertical gridlines (None = no grid)
+    grid_every: Option<usize>,
+    /// Rows back from the newest over which live samples fade into the background (0 = off)
+    fade_rows: usize,
+    /// Whether to render RTT values as numbers instead of colored squares
+    numeric: bool,
+    /// Whether each cell groups `DOTS_PER_CELL` samples as a braille dot density
+    dots: bool,
+    /// Samples per cell and how they are reduced to the one drawn (--aggregate)
+    aggregate: (usize, Reducer),
+    /// Columns each colored square takes (numeric mode has its own width)
+    cell_width: usize,
+    /// Background color filling the whole graph area
+    background: Option<Color>,
+    /// Color of the live cursor
+    cursor_color: Color,
+    /// Whether to leave timeouts blank instead of drawing `TIMEOUT_CHAR`
+    loss_gaps: bool,
+    /// Selected range of samples as inclusive stable sequence numbers
+    selection: Option<(usize, usize)>,
+    /// Samples that arrived since the previous frame beyond the newest one
+    coalesced: usize,
+    /// Events to mark, by stable sequence number of the first sample after them
+    events: &'a [(usize, EventKind)],
+    /// Whether the pingers have stopped sending probes
+    sending_stopped: bool,
+    /// Whether the newest row is at the top instead of the bottom
+    top_down: bool,
+    /// Whether the live view overwrites the oldest samples in place
+    sweep: bool,
+    /// Samples per row regardless of the width (None = as many as fit)
+    row_len: Option<usize>,
+    /// Rows drawn in earlier frames, to copy instead of drawing again
+    cache: Option<&'a mut GraphCache>,
 }
 
 impl<'a> Graph<'a> {
@@ -58,12 +261,409 @@ impl<'a> Graph<'a> {
             result_base_seq,
             paused,
             hide_cursor,
+            hide_indicator: false,
+            cursor_dark: false,
             highlight_range,
+            baseline_ms: None,
+            unit: RttUnit::Ms,
+            precision: DEFAULT_PRECISION,
+            floor: false,
+            bands: false,
+            grid_every: None,
+            fade_rows: 0,
+            numeric: false,
+            dots: false,
+            aggregate: (1, Reducer::Last),
+            cell_width: 1,
+            loss_gaps: false,
+            background: None,
+            cursor_color: Color::White,
+            selection: None,
+            coalesced: 0,
+            events: &[],
+            sending_stopped: false,
+            top_down: false,
+            sweep: false,
+            row_len: None,
+            cache: None,
+        }
+    }
+
+    /// Put this many samples in each row however wide the area is, leaving the rest of
+    /// the area empty or cutting the rows off on the right
+    pub fn row_len(mut self, row_len: Option<usize>) -> Self {
+        self.row_len = row_len;
+        self
+    }
+
+    /// Reuse complete rows drawn in earlier frames (the cache must be kept between frames)
+    pub fn cache(mut self, cache: &'a mut GraphCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Overwrite the oldest samples in place while live instead of scrolling
+    pub fn sweep(mut self, sweep: bool) -> Self {
+        self.sweep = sweep;
+        self
+    }
+
+    /// Whether a blinking cursor is in its on phase (drawn only then)
+    pub fn cursor_lit(mut self, lit: bool) -> Self {
+        self.cursor_dark = !lit;
+        self
+    }
+
+    /// Leave out the corner status indicator (PAUSED, scroll position, "+N merged")
+    pub fn hide_indicator(mut self, hide_indicator: bool) -> Self {
+        self.hide_indicator = hide_indicator;
+        self
+    }
+
+    /// Show that no probes are being sent (as opposed to a paused display)
+    pub fn sending_stopped(mut self, sending_stopped: bool) -> Self {
+        self.sending_stopped = sending_stopped;
+        self
+    }
+
+    /// Number of samples drawn in this frame beyond the newest one
+    pub fn coalesced(mut self, coalesced: usize) -> Self {
+        self.coalesced = coalesced;
+        self
+    }
+
+    /// Draw the newest row at the top, with older rows below it
+    pub fn top_down(mut self, top_down: bool) -> Self {
+        self.top_down = top_down;
+        self
+    }
+
+    /// Mark events (e.g. pinger restarts) with a separator before the sample that followed
+    pub fn events(mut self, events: &'a [(usize, EventKind)]) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Color of the separator marking an event
+    fn event_color(kind: EventKind) -> Color {
+        match kind {
+            EventKind::Restart => Color::White,
+            EventKind::Split => Color::Cyan,
+            EventKind::Spike => Color::LightYellow,
+            EventKind::Note => Color::LightGreen,
+        }
+    }
+
+    /// Mark a range of samples (inclusive stable sequence numbers) as selected
+    pub fn selection(mut self, selection: Option<(usize, usize)>) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Render RTT values as numbers instead of colored squares
+    pub fn numeric(mut self, numeric: bool) -> Self {
+        self.numeric = numeric;
+        self
+    }
+
+    /// Group `DOTS_PER_CELL` samples per cell, colored by the group's highest RTT with
+    /// one braille dot for each of its samples that was high or lost (numeric mode wins)
+    pub fn dots(mut self, dots: bool) -> Self {
+        self.dots = dots;
+        self
+    }
+
+    /// Draw one cell per `samples` samples, reduced to one with `reducer` (dot density
+    /// mode groups its own samples instead)
+    pub fn aggregate(mut self, samples: usize, reducer: Reducer) -> Self {
+        self.aggregate = (samples.max(1), reducer);
+        self
+    }
+
+    /// Draw each colored square this many columns wide, for a chunkier graph
+    pub fn cell_width(mut self, cell_width: usize) -> Self {
+        self.cell_width = cell_width.max(1);
+        self
+    }
+
+    /// Fill the graph area with a background color
+    pub fn background(mut self, background: Option<Color>) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Color of the live cursor (None = white)
+    pub fn cursor_color(mut self, color: Option<Color>) -> Self {
+        self.cursor_color = color.unwrap_or(Color::White);
+        self
+    }
+
+    /// Leave timeouts as blank gaps instead of drawing `X` marks
+    pub fn loss_gaps(mut self, loss_gaps: bool) -> Self {
+        self.loss_gaps = loss_gaps;
+        self
+    }
+
+    /// Draw faint vertical gridlines every `every` samples; samples on them are shaded
+    /// darker rather than covered
+    pub fn grid(mut self, every: Option<usize>) -> Self {
+        self.grid_every = every;
+        self
+    }
+
+    /// Tint samples near the lowest RTT on screen so the floor latency stands out
+    pub fn floor(mut self, floor: bool) -> Self {
+        self.floor = floor;
+        self
+    }
+
+    /// Shade samples in the 25-50% and 75-100% quarters of the scale and label the RTTs
+    /// at 25/50/75%, so RTT can be read from color without the legend
+    pub fn bands(mut self, bands: bool) -> Self {
+        self.bands = bands;
+        self
+    }
+
+    /// Whether an RTT falls into a shaded band (every other quarter of the scale)
+    fn in_shaded_band(&self, rtt: f64) -> bool {
+        let quarter = (rtt * 4.0 / self.color_scale.max_rtt.max(1) as f64).floor();
+        quarter == 1.0 || quarter == 3.0
+    }
+
+    /// In live mode, fade samples into the background over this many rows back from the newest
+    pub fn fade(mut self, rows: usize) -> Self {
+        self.fade_rows = rows;
+        self
+    }
+
+    /// Format an RTT value for numeric readout mode (fits in 5 columns)
+    fn numeric_label(rtt_ms: Option<f64>) -> String {
+        let text = match rtt_ms {
+            None => "TO".to_string(),
+            Some(ms) if ms < 10.0 => format!("{:.2}", ms),
+            Some(ms) if ms < 100.0 => format!("{:.1}", ms),
+            Some(ms) => format!("{:.0}", ms),
+        };
+        format!("{:>5}", text)
+    }
+
+    /// Set a baseline RTT (ms) to compare samples against
+    pub fn baseline(mut self, baseline_ms: Option<f64>) -> Self {
+        self.baseline_ms = baseline_ms;
+        self
+    }
+
+    /// Label the baseline in this unit
+    pub fn unit(mut self, unit: RttUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Label the baseline in ms with this many decimals
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Whether a sample falls into the legend entry being highlighted
+    fn is_highlighted(&self, result: &PingResult) -> bool {
+        match self.highlight_range {
+            Some((_, _, true)) => result.rtt_ms_f64().is_none(),
+            Some((min_rtt, max_rtt, false)) => result
+                .rtt_ms_f64()
+                .is_some_and(|rtt| rtt >= min_rtt && rtt < max_rtt),
+            None => false,
+        }
+    }
+
+    /// Draw one sample, returning its RTT and color if it can be the highest on screen
+    #[allow(clippy::too_many_arguments)]
+    fn render_sample(
+        &self,
+        result: &PingResult,
+        seq_idx: usize,
+        x: u16,
+        y: u16,
+        rows_back: usize,
+        is_live: bool,
+        fade_target: (u8, u8, u8),
+        floor_ms: Option<f64>,
+        cell_width: usize,
+        buf: &mut Buffer,
+    ) -> Option<(f64, Color)> {
+        // Older rows fade towards the background so the newest stand out
+        let fade = if is_live && self.fade_rows > 0 {
+            (rows_back as f64 / self.fade_rows as f64).min(1.0) * FADE_MAX
+        } else {
+            0.0
+        };
+        let faded = |color: Color| {
+            if fade > 0.0 {
+                tint(color, fade_target, fade)
+            } else {
+                color
+            }
+        };
+
+        let is_highlighted = self.is_highlighted(result);
+        let is_selected = self
+            .selection
+            .is_some_and(|(start, end)| (start..=end).contains(&seq_idx));
+        let select = |color: Color| {
+            if is_selected {
+                tint(color, (255, 255, 255), SELECTION_TINT_AMOUNT)
+            } else {
+                color
+            }
+        };
+
+        // Gaps mode leaves timeouts blank unless they need to stand out
+        if self.loss_gaps
+            && result.rtt.is_none()
+            && !result.corrupted
+            && !is_highlighted
+            && !is_selected
+        {
+            return None;
+        }
+
+        if self.numeric {
+            let color = select(if is_highlighted {
+                HIGHLIGHT_COLOR
+            } else {
+                faded(self.color_scale.color_for_rtt_f64(result.rtt_ms_f64()))
+            });
+            buf.set_string(
+                x,
+                y,
+                Self::numeric_label(result.rtt_ms_f64()),
+                Style::default().fg(contrast_fg(color)).bg(color),
+            );
+            result.rtt_ms_f64().map(|rtt| (rtt, color))
+        } else if let Some(rtt) = result.rtt_ms_f64() {
+            let plain = self.color_scale.color_for_rtt_f64(Some(rtt));
+            let plain = if self.bands && self.in_shaded_band(rtt) {
+                tint(plain, fade_target, BAND_SHADE_AMOUNT)
+            } else {
+                plain
+            };
+            let color = select(if is_highlighted {
+                HIGHLIGHT_COLOR
+            } else if self.baseline_ms.is_some_and(|baseline| rtt > baseline) {
+                faded(tint(plain, BASELINE_TINT, BASELINE_TINT_AMOUNT))
+            } else if floor_ms.is_some_and(|floor| rtt <= floor * (1.0 + FLOOR_MARGIN)) {
+                faded(tint(plain, FLOOR_TINT, FLOOR_TINT_AMOUNT))
+            } else {
+                faded(plain)
+            });
+            buf.set_string(
+                x,
+                y,
+                FILLED_SQUARE.repeat(cell_width),
+                Style::default().fg(color),
+            );
+            Some((rtt, color))
+        } else {
+            let color = if is_highlighted {
+                HIGHLIGHT_COLOR
+            } else if is_selected {
+                Color::White
+            } else if result.corrupted {
+                Color::Magenta
+            } else {
+                Color::Indexed(240)
+            };
+            let mark = if result.corrupted {
+                CORRUPTED_CHAR
+            } else {
+                TIMEOUT_CHAR
+            };
+            buf.set_string(x, y, mark, Style::default().fg(color));
+            None
+        }
+    }
+
+    /// Draw samples grouped `DOTS_PER_CELL` to a cell: the background is the color of
+    /// the group's highest RTT (gray if all were lost) and the braille dots count its
+    /// samples that were lost or in the upper half of the scale
+    fn render_dot_cells(
+        &self,
+        cells: &[(usize, usize, usize, usize)],
+        area: Rect,
+        buf: &mut Buffer,
+        is_live: bool,
+        fade_target: (u8, u8, u8),
+    ) {
+        // Samples of each cell by (screen row, column)
+        let mut groups: BTreeMap<(usize, usize), Vec<(usize, usize)>> = BTreeMap::new();
+        for &(seq, col, screen_row, rows_back) in cells {
+            groups
+                .entry((screen_row, col / DOTS_PER_CELL))
+                .or_default()
+                .push((seq, rows_back));
+        }
+
+        let high_ms = self.color_scale.max_rtt as f64 * DENSITY_HIGH_FRACTION;
+        for ((screen_row, col), samples) in groups {
+            let results: Vec<&PingResult> = samples
+                .iter()
+                .map(|&(seq, _)| &self.results[seq - self.result_base_seq])
+                .collect();
+            let worst = results
+                .iter()
+                .filter_map(|result| result.rtt_ms_f64())
+                .max_by(f64::total_cmp);
+            let high = results
+                .iter()
+                .filter(|result| result.rtt_ms_f64().is_none_or(|rtt| rtt >= high_ms))
+                .count();
+
+            let color = if results.iter().any(|result| self.is_highlighted(result)) {
+                HIGHLIGHT_COLOR
+            } else {
+                let plain = match worst {
+                    Some(rtt) if self.baseline_ms.is_some_and(|baseline| rtt > baseline) => tint(
+                        self.color_scale.color_for_rtt_f64(Some(rtt)),
+                        BASELINE_TINT,
+                        BASELINE_TINT_AMOUNT,
+                    ),
+                    Some(rtt) => self.color_scale.color_for_rtt_f64(Some(rtt)),
+                    None => Color::Indexed(240),
+                };
+                // The group is as recent as its newest sample
+                let rows_back = samples.iter().map(|&(_, rows_back)| rows_back).min();
+                match rows_back {
+                    Some(rows_back) if is_live && self.fade_rows > 0 => {
+                        let fade = (rows_back as f64 / self.fade_rows as f64).min(1.0) * FADE_MAX;
+                        tint(plain, fade_target, fade)
+                    }
+                    _ => plain,
+                }
+            };
+            let is_selected = self.selection.is_some_and(|(start, end)| {
+                samples.iter().any(|&(seq, _)| (start..=end).contains(&seq))
+            });
+            let color = if is_selected {
+                tint(color, (255, 255, 255), SELECTION_TINT_AMOUNT)
+            } else {
+                color
+            };
+
+            buf.set_string(
+                area.x + col as u16,
+                area.y + screen_row as u16,
+                density_char(high),
+                Style::default().fg(contrast_fg(color)).bg(color),
+            );
         }
     }
 
     /// Calculate which result index corresponds to a screen position
-    /// Returns None if the position is empty
+    /// `width` is in samples; with `group` samples per cell (dot density mode) the
+    /// first sample of the cell's group is returned
+    /// Returns None if the position is empty (including columns past the last full cell,
+    /// which would otherwise spill over into the next row)
+    #[allow(clippy::too_many_arguments)]
     pub fn result_at_position(
         results_len: usize,
         result_base_seq: usize,
@@ -72,32 +672,30 @@ impl<'a> Graph<'a> {
         view_end_row: usize,
         screen_row: usize,
         screen_col: usize,
+        top_down: bool,
+        sweep: bool,
+        group: usize,
     ) -> Option<usize> {
-        if results_len == 0 || width == 0 || height == 0 {
+        let sample_col = screen_col * group.max(1);
+        if results_len == 0 || width == 0 || height == 0 || sample_col >= width {
             return None;
         }
 
         let total_results = result_base_seq + results_len;
+        if sweep {
+            let seq = sweep_seq_at(
+                screen_row * width + sample_col,
+                total_results,
+                width * height,
+            )?;
+            return seq.checked_sub(result_base_seq);
+        }
         let total_rows = total_results.div_ceil(width);
         let actual_end = view_end_row.min(total_rows);
-        let visible_rows = actual_end.min(height);
-        let view_start_row = actual_end.saturating_sub(visible_rows);
-
-        // Calculate empty rows at top
-        let empty_rows_at_top = height.saturating_sub(visible_rows);
-
-        if screen_row < empty_rows_at_top {
-            return None;
-        }
-
-        let data_row = view_start_row + (screen_row - empty_rows_at_top);
-
-        if data_row >= actual_end {
-            return None;
-        }
+        let data_row = data_row_at(screen_row, actual_end, height, top_down)?;
 
         // Calculate the stable sequence index
-        let seq_idx = data_row * width + screen_col;
+        let seq_idx = data_row * width + sample_col;
 
         // Convert to VecDeque index
         if seq_idx >= result_base_seq && seq_idx < total_results {
@@ -109,24 +707,53 @@ impl<'a> Graph<'a> {
 }
 
 impl Widget for Graph<'_> {
-    fn render(self, area: Rect, buf: &mut Buffer) {
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
         if area.width == 0 || area.height == 0 {
             return;
         }
 
-        let width = area.width as usize;
+        // In numeric mode each sample takes several columns, in dot density mode each
+        // column holds several samples
+        let dots = self.dots && !self.numeric;
+        let cell_width = if self.numeric {
+            NUMERIC_CELL_WIDTH
+        } else if dots {
+            1
+        } else {
+            self.cell_width
+        };
+        let group = if dots {
+            DOTS_PER_CELL
+        } else {
+            self.aggregate.0
+        };
+        // Samples per row, and how many of them fit on screen
+        let fit = area.width as usize / cell_width * group;
+        let width = self.row_len.unwrap_or(fit);
+        let shown = width.min(fit);
         let height = area.height as usize;
+        if shown == 0 {
+            return;
+        }
+        if let Some(bg) = self.background {
+            buf.set_style(area, Style::default().bg(bg));
+        }
         let result_count = self.results.len();
         let total_results = self.result_base_seq + result_count;
 
         if result_count == 0 {
             // Draw cursor at start position if not hidden
-            if !self.hide_cursor {
+            if !self.hide_cursor && !self.cursor_dark {
+                let y = if self.top_down {
+                    area.y
+                } else {
+                    area.y + area.height - 1
+                };
                 buf.set_string(
                     area.x,
-                    area.y + area.height - 1,
+                    y,
                     CURSOR_CHAR,
-                    Style::default().fg(Color::White),
+                    Style::default().fg(self.cursor_color),
                 );
             }
             return;
@@ -141,121 +768,966 @@ impl Widget for Graph<'_> {
         let visible_rows = view_end.min(height);
         let view_start_row = view_end.saturating_sub(visible_rows);
 
-        // Calculate empty rows at top (for bottom alignment)
-        let empty_rows_at_top = height.saturating_sub(visible_rows);
-
         let is_live = self.view_end_row.is_none();
+        let sweeping = self.sweep && is_live;
+        let capacity = width * height;
 
         // Calculate the first row that has data in our buffer
         let first_buffered_row = self.result_base_seq / width;
 
-        // Render results row by row (aligned to bottom)
-        for data_row in view_start_row..view_end {
-            let screen_row = empty_rows_at_top + (data_row - view_start_row);
+        // Samples to draw as (stable seq, column, screen row, rows back from the newest)
+        let mut cells: Vec<(usize, usize, usize, usize)> = if sweeping {
+            // Drawn oldest first like the scrolling view, so ties for the max go to the newest
+            let mut cells: Vec<_> = (0..capacity)
+                .filter_map(|slot| {
+                    let seq = sweep_seq_at(slot, total_results, capacity)?;
+                    let rows_back = (total_results - 1 - seq) / width;
+                    (seq >= self.result_base_seq).then_some((
+                        seq,
+                        slot % width,
+                        slot / width,
+                        rows_back,
+                    ))
+                })
+                .collect();
+            cells.sort_unstable_by_key(|&(seq, ..)| seq);
+            cells
+        } else {
+            // Skip rows that are before our buffer
+            (view_start_row.max(first_buffered_row)..view_end)
+                .flat_map(|data_row| {
+                    let screen_row = screen_row_of(data_row, view_end, height, self.top_down);
+                    let rows_back = view_end - 1 - data_row;
+                    (0..width).map(move |col| (data_row * width + col, col, screen_row, rows_back))
+                })
+                // Skip if before our buffer or after our data
+                .filter(|&(seq, ..)| seq >= self.result_base_seq && seq < total_results)
+                .collect()
+        };
+        // Rows longer than the area are cut off
+        cells.retain(|&(_, col, ..)| col < shown);
 
-            if screen_row >= height {
-                break;
+        // Faded samples blend into the graph background (the terminal's is taken as black)
+        let fade_target = match self.background {
+            Some(Color::Rgb(r, g, b)) => (r, g, b),
+            _ => (0, 0, 0),
+        };
+
+        // Lowest RTT on screen, to tint the samples at the floor
+        let floor_ms = if self.floor {
+            cells
+                .iter()
+                .filter_map(|&(seq, ..)| self.results[seq - self.result_base_seq].rtt_ms_f64())
+                .min_by(f64::total_cmp)
+        } else {
+            None
+        };
+
+        // Highest RTT on screen and where it was drawn: (rtt, x, y, color)
+        let mut max_cell: Option<(f64, u16, u16, Color)> = None;
+
+        // Dot density cells are drawn per group instead of per sample, and aggregated
+        // cells as their group reduced to one sample (at the column of the group's first)
+        let sample_cells: Vec<(Cow<PingResult>, usize, usize, usize, usize)> = if dots {
+            self.render_dot_cells(&cells, area, buf, is_live, fade_target);
+            Vec::new()
+        } else if group > 1 {
+            let mut groups: Vec<(usize, usize, usize, usize, Vec<PingResult>)> = Vec::new();
+            for &(seq, col, screen_row, rows_back) in &cells {
+                let result = self.results[seq - self.result_base_seq].clone();
+                match groups.last_mut() {
+                    Some((newest, first_col, row, back, samples))
+                        if *row == screen_row && *first_col == col / group * group =>
+                    {
+                        *newest = seq;
+                        *back = (*back).min(rows_back);
+                        samples.push(result);
+                    }
+                    _ => groups.push((
+                        seq,
+                        col / group * group,
+                        screen_row,
+                        rows_back,
+                        vec![result],
+                    )),
+                }
             }
+            groups
+                .into_iter()
+                .filter_map(|(seq, col, screen_row, rows_back, samples)| {
+                    let result = self.aggregate.1.reduce(&samples)?;
+                    Some((Cow::Owned(result), seq, col, screen_row, rows_back))
+                })
+                .collect()
+        } else {
+            cells
+                .iter()
+                .map(|&(seq, col, screen_row, rows_back)| {
+                    let result = &self.results[seq - self.result_base_seq];
+                    (Cow::Borrowed(result), seq, col, screen_row, rows_back)
+                })
+                .collect()
+        };
 
-            // Skip rows that are before our buffer
-            if data_row < first_buffered_row {
-                continue;
+        // Complete rows of the scrolling view drawn in earlier frames are copied as they
+        // were, unless something that changes how every sample looks has changed
+        let fading = is_live && self.fade_rows > 0;
+        let mut cache = self.cache.take().filter(|_| !sweeping && !dots);
+        if let Some(cache) = cache.as_deref_mut() {
+            let style = RowStyle {
+                x: area.x,
+                width,
+                cell_width,
+                numeric: self.numeric,
+                aggregate: self.aggregate,
+                color_scale: self.color_scale.clone(),
+                highlight_range: self.highlight_range,
+                baseline_ms: self.baseline_ms,
+                floor_ms,
+                bands: self.bands,
+                fade: fading,
+                background: self.background,
+                loss_gaps: self.loss_gaps,
+                selection: self.selection,
+            };
+            if cache.style.as_ref() != Some(&style) {
+                cache.rows.clear();
+                cache.style = Some(style);
             }
+        }
+        let row_columns = (shown * cell_width) as u16;
+        let mut copied_row = None;
+        // Highest RTT in the row being drawn: (rtt, x, color)
+        let mut row_max: Option<(f64, u16, Color)> = None;
 
-            for col in 0..width {
-                // Calculate stable sequence index
-                let seq_idx = data_row * width + col;
+        // Render results (aligned to the newest end, or around the write head in sweep mode)
+        for (result, seq_idx, col, screen_row, rows_back) in sample_cells {
+            let x = area.x + (col / group * cell_width) as u16;
+            let y = area.y + screen_row as u16;
+            let data_row = seq_idx / width;
+            let complete =
+                data_row * width >= self.result_base_seq && (data_row + 1) * width <= total_results;
 
-                // Skip if before our buffer or after our data
-                if seq_idx < self.result_base_seq || seq_idx >= total_results {
+            if let Some(cache) = cache.as_deref_mut()
+                && complete
+            {
+                if copied_row == Some(data_row) {
+                    continue;
+                }
+                if col == 0
+                    && let Some(row) = cache.rows.get(&data_row)
+                    && row.first == self.results[data_row * width - self.result_base_seq].timestamp
+                    && (!fading || row.rows_back == rows_back)
+                {
+                    for (dx, cell) in row.cells.iter().enumerate() {
+                        buf[(area.x + dx as u16, y)] = cell.clone();
+                    }
+                    if let Some((rtt, x, color)) = row.max
+                        && max_cell.is_none_or(|(max, ..)| rtt >= max)
+                    {
+                        max_cell = Some((rtt, x, y, color));
+                    }
+                    copied_row = Some(data_row);
                     continue;
                 }
+            }
 
-                // Convert to VecDeque index
-                let vec_idx = seq_idx - self.result_base_seq;
-                let result = &self.results[vec_idx];
-                let x = area.x + col as u16;
-                let y = area.y + screen_row as u16;
+            if col == 0 {
+                row_max = None;
+            }
+            if let Some((rtt, color)) = self.render_sample(
+                &result,
+                seq_idx,
+                x,
+                y,
+                rows_back,
+                is_live,
+                fade_target,
+                floor_ms,
+                cell_width,
+                buf,
+            ) {
+                if max_cell.is_none_or(|(max, ..)| rtt >= max) {
+                    max_cell = Some((rtt, x, y, color));
+                }
+                if row_max.is_none_or(|(max, ..)| rtt >= max) {
+                    row_max = Some((rtt, x, color));
+                }
+            }
+
+            if let Some(cache) = cache.as_deref_mut()
+                && complete
+                && col / group == (shown - 1) / group
+            {
+                let first = &self.results[data_row * width - self.result_base_seq];
+                let cells = (area.x..area.x + row_columns)
+                    .map(|x| buf[(x, y)].clone())
+                    .collect();
+                cache.rows.insert(
+                    data_row,
+                    CachedRow {
+                        first: first.timestamp,
+                        rows_back,
+                        cells,
+                        max: row_max,
+                    },
+                );
+            }
+        }
+        // Rows scrolled off screen are drawn again if they come back
+        if let Some(cache) = cache {
+            cache
+                .rows
+                .retain(|&row, _| (view_start_row..view_end).contains(&row));
+        }
 
-                // Check if this sample should be highlighted
-                let is_highlighted =
-                    if let Some((min_rtt, max_rtt, is_timeout_highlight)) = self.highlight_range {
-                        if is_timeout_highlight {
-                            // Highlight timeouts
-                            result.rtt_ms_f64().is_none()
-                        } else if let Some(rtt) = result.rtt_ms_f64() {
-                            // Highlight samples within the RTT range
-                            rtt >= min_rtt && rtt < max_rtt
-                        } else {
-                            false
-                        }
+        // Gridlines show through empty cells and darken the samples they cross
+        if let Some(every) = self.grid_every {
+            for col in (every..shown).step_by(every.max(1)) {
+                let x = area.x + (col / group * cell_width) as u16;
+                for y in area.top()..area.bottom() {
+                    let cell = &mut buf[(x, y)];
+                    if cell.symbol() == " " {
+                        cell.set_symbol(GRID_CHAR).set_fg(GRID_COLOR);
                     } else {
-                        false
-                    };
+                        let (fg, bg) = (cell.fg, cell.bg);
+                        cell.set_fg(tint(fg, fade_target, GRID_SHADE_AMOUNT))
+                            .set_bg(tint(bg, fade_target, GRID_SHADE_AMOUNT));
+                    }
+                }
+            }
+        }
 
-                // Highlight color: bright red for visibility
-                let highlight_color = Color::Rgb(255, 50, 50);
+        // Mark the worst sample on screen (the newest one if tied) so spikes are easy to find
+        if let Some((_, x, y, color)) = max_cell {
+            if self.numeric {
+                let cell = Rect::new(x, y, (NUMERIC_CELL_WIDTH - 1) as u16, 1);
+                buf.set_style(cell, Style::default().add_modifier(Modifier::REVERSED));
+            } else {
+                buf.set_string(
+                    x,
+                    y,
+                    MAX_MARKER_CHAR,
+                    Style::default().fg(contrast_fg(color)).bg(color),
+                );
+            }
+        }
 
-                if let Some(rtt) = result.rtt_ms_f64() {
-                    let color = if is_highlighted {
-                        highlight_color
-                    } else {
-                        self.color_scale.color_for_rtt_f64(Some(rtt))
-                    };
-                    buf.set_string(x, y, FILLED_SQUARE, Style::default().fg(color));
-                } else {
-                    let color = if is_highlighted {
-                        highlight_color
-                    } else {
-                        Color::Indexed(240)
-                    };
-                    buf.set_string(x, y, TIMEOUT_CHAR, Style::default().fg(color));
+        // Separate samples before and after an event with a thin bar in the event's color
+        for &(seq_idx, kind) in self.events {
+            if seq_idx < self.result_base_seq || seq_idx >= total_results {
+                continue;
+            }
+            let (col, screen_row) = if sweeping {
+                let slot = seq_idx % capacity;
+                if sweep_seq_at(slot, total_results, capacity) != Some(seq_idx) {
+                    continue;
+                }
+                (slot % width, slot / width)
+            } else {
+                let row = seq_idx / width;
+                if row < view_start_row || row >= view_end {
+                    continue;
+                }
+                (
+                    seq_idx % width,
+                    screen_row_of(row, view_end, height, self.top_down),
+                )
+            };
+            if col >= shown {
+                continue;
+            }
+            let x = area.x + (col / group * cell_width) as u16;
+            let y = area.y + screen_row as u16;
+            let color = Self::event_color(kind);
+            let cell = &mut buf[(x, y)];
+            match cell.symbol() {
+                // Keep the sample's color on the right half of the cell
+                FILLED_SQUARE => {
+                    cell.set_symbol(EVENT_MARKER_CHAR).set_bg(color);
+                }
+                " " => {
+                    cell.set_symbol(EVENT_MARKER_BLANK_CHAR).set_fg(color);
+                }
+                _ => {
+                    cell.set_bg(color);
                 }
             }
         }
 
-        // Draw cursor at current position (unless hidden)
-        if !self.hide_cursor && is_live {
+        // Draw cursor at current position (unless hidden or blinked off)
+        let show_cursor = !self.hide_cursor && !self.cursor_dark;
+        if show_cursor && sweeping {
+            // The write head: where the next sample goes (unless cut off)
+            let head = total_results % capacity;
+            if head % width < shown {
+                let x = area.x + ((head % width) / group * cell_width) as u16;
+                let y = area.y + (head / width) as u16;
+                buf.set_string(x, y, CURSOR_CHAR, Style::default().fg(self.cursor_color));
+            }
+        } else if show_cursor && is_live {
             // Calculate cursor position using stable indices
             let cursor_seq = total_results;
             let cursor_row = cursor_seq / width;
-            let cursor_col = cursor_seq % width;
-
-            // Only draw if cursor row is visible
-            if cursor_row >= view_start_row && cursor_row < view_end {
-                let screen_row = empty_rows_at_top + (cursor_row - view_start_row);
-                if screen_row < height {
-                    let x = area.x + cursor_col as u16;
-                    let y = area.y + screen_row as u16;
-                    buf.set_string(x, y, CURSOR_CHAR, Style::default().fg(Color::White));
-                }
-            } else if cursor_row == view_end && cursor_col == 0 {
-                // Cursor is at start of next row (just wrapped)
-                let screen_row = empty_rows_at_top + visible_rows;
-                if screen_row < height {
-                    let x = area.x;
-                    let y = area.y + screen_row as u16;
-                    buf.set_string(x, y, CURSOR_CHAR, Style::default().fg(Color::White));
-                }
+            // A dot density group still filling up is drawn, with the cursor after it
+            let cursor_cell = (cursor_seq % width).div_ceil(group);
+
+            // Only draw if cursor row is visible (a just-wrapped cursor is past the newest
+            // row, off screen either way)
+            if cursor_row >= view_start_row
+                && cursor_row < view_end
+                && cursor_cell < shown.div_ceil(group)
+            {
+                let screen_row = screen_row_of(cursor_row, view_end, height, self.top_down);
+                let x = area.x + (cursor_cell * cell_width) as u16;
+                let y = area.y + screen_row as u16;
+                buf.set_string(x, y, CURSOR_CHAR, Style::default().fg(self.cursor_color));
+            }
+        }
+
+        // Scrollbar in the rightmost column while browsing history
+        if !is_live
+            && let Some((thumb_start, thumb_len)) =
+                scrollbar_thumb(view_end, self.total_rows, height)
+        {
+            // Top-down the newest rows are at the top, so the thumb runs the other way
+            let thumb_start = if self.top_down {
+                height - thumb_start - thumb_len
+            } else {
+                thumb_start
+            };
+            let x = area.x + area.width - 1;
+            for row in 0..height {
+                let (symbol, color) = if (thumb_start..thumb_start + thumb_len).contains(&row) {
+                    (SCROLLBAR_THUMB, self.color_scale.indicator_color())
+                } else {
+                    (SCROLLBAR_TRACK, Color::DarkGray)
+                };
+                buf.set_string(x, area.y + row as u16, symbol, Style::default().fg(color));
+            }
+        }
+
+        // Indicators go on the edge with the oldest rows, away from new samples
+        let (indicator_y, next_indicator_y) = if self.top_down {
+            let bottom = area.y + area.height - 1;
+            (bottom, bottom.saturating_sub(1).max(area.y))
+        } else {
+            (area.y, area.y + 1.min(area.height - 1))
+        };
+
+        // Show baseline label in the top-left corner (bottom-left top-down)
+        let mut label_x = area.x;
+        if let Some(baseline) = self.baseline_ms {
+            let label = format!(" base {} ", self.unit.format(baseline, self.precision));
+            label_x += label.chars().count() as u16;
+            buf.set_string(
+                area.x,
+                indicator_y,
+                label,
+                Style::default()
+                    .fg(Color::Rgb(
+                        BASELINE_TINT.0,
+                        BASELINE_TINT.1,
+                        BASELINE_TINT.2,
+                    ))
+                    .bg(Color::DarkGray),
+            );
+        }
+
+        // Label the RTTs where the bands change, each in its color, next to the baseline
+        if self.bands && !self.numeric {
+            let max_rtt = self.color_scale.max_rtt as f64;
+            let label_style = Style::default().bg(Color::DarkGray);
+            let mut x = label_x;
+            for (fraction, name) in BANDS {
+                let rtt = max_rtt * fraction;
+                let label = format!(" {} {}ms", name, rtt);
+                let color = self.color_scale.color_for_rtt_f64(Some(rtt));
+                (x, _) = buf.set_stringn(
+                    x,
+                    indicator_y,
+                    label,
+                    (area.x + area.width).saturating_sub(x) as usize,
+                    label_style.fg(color),
+                );
             }
+            buf.set_stringn(
+                x,
+                indicator_y,
+                " ",
+                (area.x + area.width).saturating_sub(x) as usize,
+                label_style,
+            );
         }
 
         // Show indicator when paused or scrolled
-        if self.paused || !is_live {
-            let indicator = if !is_live {
+        let indicator = if self.paused || !is_live {
+            let text = if !is_live {
                 // Show "row X of Y" style
                 format!(" {}/{} ", view_end, self.total_rows)
             } else {
                 " PAUSED ".to_string()
             };
+            Some((
+                text,
+                Style::default()
+                    .fg(self.color_scale.indicator_color())
+                    .bg(Color::DarkGray),
+            ))
+        } else if self.coalesced > 0 {
+            // Pings arrive faster than frames: each redraw adds several samples at once
+            Some((
+                format!(" +{} merged ", self.coalesced),
+                Style::default().fg(Color::DarkGray),
+            ))
+        } else {
+            None
+        };
+        if let Some((text, style)) = indicator.filter(|_| !self.hide_indicator) {
+            let x = area.x + area.width.saturating_sub(text.len() as u16 + 1);
+            buf.set_string(x, indicator_y, &text, style);
+        }
+
+        // Probing stopped is shown next to the pause/scroll indicator so both stay visible
+        if self.sending_stopped {
+            let indicator = " NOT SENDING ";
             let x = area.x + area.width.saturating_sub(indicator.len() as u16 + 1);
-            let y = area.y;
             buf.set_string(
                 x,
-                y,
-                &indicator,
-                Style::default().fg(Color::Yellow).bg(Color::DarkGray),
+                next_indicator_y,
+                indicator,
+                Style::default().fg(Color::White).bg(Color::Red),
             );
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrollbar_thumb() {
+        // Everything fits: no scrollbar
+        assert_eq!(scrollbar_thumb(10, 10, 20), None);
+        // Scrolled to the oldest rows: thumb at the top
+        assert_eq!(scrollbar_thumb(10, 100, 10), Some((0, 1)));
+        // At the newest rows: thumb at the bottom
+        assert_eq!(scrollbar_thumb(100, 100, 10), Some((9, 1)));
+        // Halfway through, with a thumb proportional to the visible fraction
+        assert_eq!(scrollbar_thumb(25, 40, 10), Some((4, 3)));
+    }
+
+    #[test]
+    fn test_max_rtt_marker() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = [10, 80, 20, 80, 30]
+            .into_iter()
+            .enumerate()
+            .map(|(seq, ms)| PingResult::success(seq as u64, Duration::from_millis(ms), now, None))
+            .collect();
+
+        let area = Rect::new(0, 0, 10, 2);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 1, 0, false, true, None).render(area, &mut buf);
+
+        // The newest of the two 80ms samples is marked
+        assert_eq!(buf[(1, 1)].symbol(), FILLED_SQUARE);
+        assert_eq!(buf[(3, 1)].symbol(), MAX_MARKER_CHAR);
+    }
+
+    #[test]
+    fn test_event_marker() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = (0..4)
+            .map(|seq| PingResult::success(seq, Duration::from_millis(50), now, None))
+            .collect();
+        let events = [(1, EventKind::Restart), (9, EventKind::Split)];
+
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 1, 0, false, true, None)
+            .events(&events)
+            .render(area, &mut buf);
+
+        // The sample after the restart gets a white bar on its left half
+        assert_eq!(buf[(1, 0)].symbol(), EVENT_MARKER_CHAR);
+        assert_eq!(buf[(1, 0)].bg, Color::White);
+        assert_eq!(buf[(2, 0)].symbol(), FILLED_SQUARE);
+        // Events without a sample after them yet aren't drawn
+        assert_eq!(buf[(9, 0)].symbol(), " ");
+    }
+
+    #[test]
+    fn test_floor_tint() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = [100, 500, 103, 1000]
+            .into_iter()
+            .enumerate()
+            .map(|(seq, rtt)| {
+                PingResult::success(seq as u64, Duration::from_micros(rtt * 100), now, None)
+            })
+            .collect();
+        let plain = |ms| color_scale.color_for_rtt_f64(Some(ms));
+
+        let area = Rect::new(0, 0, 4, 1);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 1, 0, false, true, None)
+            .floor(true)
+            .render(area, &mut buf);
+
+        // 10ms is the floor and 10.3ms is within 5% of it; 50ms is drawn as usual
+        assert_ne!(buf[(0, 0)].fg, plain(10.0));
+        assert_ne!(buf[(2, 0)].fg, plain(10.3));
+        assert_eq!(buf[(1, 0)].fg, plain(50.0));
+    }
+
+    #[test]
+    fn test_grid() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = (0..6)
+            .map(|seq| PingResult::success(seq, Duration::from_millis(80), now, None))
+            .collect();
+        let plain = color_scale.color_for_rtt_f64(Some(80.0));
+
+        let area = Rect::new(0, 0, 8, 2);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 1, 0, false, true, None)
+            .grid(Some(4))
+            .render(area, &mut buf);
+
+        // The gridline darkens the sample it crosses and shows in the empty row above
+        assert_eq!(buf[(3, 1)].fg, plain);
+        assert_eq!(buf[(4, 1)].symbol(), FILLED_SQUARE);
+        assert_ne!(buf[(4, 1)].fg, plain);
+        assert_eq!(buf[(4, 0)].symbol(), GRID_CHAR);
+        assert_eq!(buf[(3, 0)].symbol(), " ");
+    }
+
+    #[test]
+    fn test_bands() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = [10, 30, 60, 80, 120]
+            .into_iter()
+            .enumerate()
+            .map(|(seq, rtt)| {
+                PingResult::success(seq as u64, Duration::from_millis(rtt), now, None)
+            })
+            .collect();
+        let plain = |ms| color_scale.color_for_rtt_f64(Some(ms));
+
+        let area = Rect::new(0, 0, 30, 2);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 1, 0, false, true, None)
+            .bands(true)
+            .render(area, &mut buf);
+
+        // Samples in the 25-50% and 75-100% quarters are shaded, the others drawn as usual
+        let row = 1;
+        assert_eq!(buf[(0, row)].fg, plain(10.0));
+        assert_ne!(buf[(1, row)].fg, plain(30.0));
+        assert_eq!(buf[(2, row)].fg, plain(60.0));
+        assert_ne!(buf[(3, row)].fg, plain(80.0));
+        // Over the scale isn't a band either (drawn as the max marker's background)
+        assert_eq!(buf[(4, row)].bg, plain(120.0));
+
+        let labels: String = (0..area.width).map(|x| buf[(x, 0)].symbol()).collect();
+        assert!(labels.starts_with(" ¼ 25ms ½ 50ms ¾ 75ms "), "{}", labels);
+        assert_eq!(buf[(3, 0)].fg, plain(25.0));
+    }
+
+    #[test]
+    fn test_fade() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = (0..4)
+            .map(|seq| PingResult::success(seq, Duration::from_millis(50), now, None))
+            .collect();
+        let plain = color_scale.color_for_rtt_f64(Some(50.0));
+
+        // One sample per row, fading over two rows
+        let area = Rect::new(0, 0, 1, 4);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 4, 0, false, true, None)
+            .fade(2)
+            .render(area, &mut buf);
+
+        assert_eq!(buf[(0, 2)].fg, tint(plain, (0, 0, 0), FADE_MAX / 2.0));
+        assert_eq!(buf[(0, 1)].fg, tint(plain, (0, 0, 0), FADE_MAX));
+        assert_eq!(buf[(0, 0)].fg, tint(plain, (0, 0, 0), FADE_MAX));
+    }
+
+    #[test]
+    fn test_cache() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let sample = |seq: u64| match seq % 7 {
+            3 => PingResult::timeout(seq, now),
+            _ => PingResult::success(seq, Duration::from_millis(seq * 37 % 120), now, None),
+        };
+        let area = Rect::new(2, 1, 5, 4);
+        let mut cache = GraphCache::default();
+        let mut results = VecDeque::new();
+        let mut base = 0;
+
+        // Each frame matches one drawn from scratch, as samples arrive and get evicted,
+        // with the history cleared, and while fading
+        for step in 0..60u64 {
+            if step == 45 {
+                results.clear();
+                base = 0;
+            }
+            let mut result = sample(step);
+            result.timestamp += chrono::Duration::seconds(step as i64);
+            results.push_back(result);
+            if results.len() > 12 {
+                results.pop_front();
+                base += 1;
+            }
+            let total_rows = (base + results.len()).div_ceil(5);
+            let graph = || {
+                Graph::new(
+                    &results,
+                    &color_scale,
+                    None,
+                    total_rows,
+                    base,
+                    false,
+                    false,
+                    None,
+                )
+                .loss_gaps(step % 10 < 5)
+                .fade(if step > 30 { 2 } else { 0 })
+            };
+            let mut cached = Buffer::empty(area);
+            graph().cache(&mut cache).render(area, &mut cached);
+            let mut fresh = Buffer::empty(area);
+            graph().render(area, &mut fresh);
+            assert_eq!(cached, fresh, "step {}", step);
+        }
+        assert!(!cache.rows.is_empty());
+    }
+
+    #[test]
+    fn test_top_down() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        // Two samples per row: rows [0, 1], [2, 3], [4]
+        let results: VecDeque<PingResult> = (0..5)
+            .map(|seq| PingResult::success(seq, Duration::from_millis(50), now, None))
+            .collect();
+
+        let area = Rect::new(0, 0, 2, 4);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 3, 0, false, false, None)
+            .top_down(true)
+            .render(area, &mut buf);
+
+        // Newest row first with the cursor after its last sample, oldest row last
+        assert_eq!(buf[(0, 0)].symbol(), MAX_MARKER_CHAR);
+        assert_eq!(buf[(1, 0)].symbol(), CURSOR_CHAR);
+        assert_eq!(buf[(1, 2)].symbol(), FILLED_SQUARE);
+        assert_eq!(buf[(0, 3)].symbol(), " ");
+
+        // Clicks map back the same way
+        let at = |row, col, top_down| {
+            Graph::result_at_position(5, 0, 2, 4, 3, row, col, top_down, false, 1)
+        };
+        assert_eq!(at(0, 0, true), Some(4));
+        assert_eq!(at(2, 1, true), Some(1));
+        assert_eq!(at(3, 0, true), None);
+        assert_eq!(at(0, 0, false), None);
+        assert_eq!(at(3, 0, false), Some(4));
+        assert_eq!(at(1, 1, false), Some(1));
+    }
+
+    #[test]
+    fn test_hide_indicator() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = (0..5)
+            .map(|seq| PingResult::success(seq, Duration::from_millis(50), now, None))
+            .collect();
+        let row =
+            |buf: &Buffer| -> String { (0..12).map(|x| buf[(x, 0)].symbol()).collect::<String>() };
+
+        // Paused: the indicator and the cursor are independent of each other
+        let area = Rect::new(0, 0, 12, 3);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 1, 0, true, true, None).render(area, &mut buf);
+        assert!(row(&buf).contains("PAUSED"));
+        assert_ne!(buf[(5, 2)].symbol(), CURSOR_CHAR);
+
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 1, 0, true, false, None)
+            .hide_indicator(true)
+            .render(area, &mut buf);
+        assert!(!row(&buf).contains("PAUSED"));
+        assert_eq!(buf[(5, 2)].symbol(), CURSOR_CHAR);
+    }
+
+    #[test]
+    fn test_cell_width() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        // Five columns fit two double-width samples per row: rows [0, 1], [2]
+        let results: VecDeque<PingResult> = [10, 50, 20]
+            .into_iter()
+            .enumerate()
+            .map(|(seq, ms)| PingResult::success(seq as u64, Duration::from_millis(ms), now, None))
+            .collect();
+
+        let area = Rect::new(0, 0, 5, 2);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 2, 0, false, false, None)
+            .cell_width(2)
+            .render(area, &mut buf);
+
+        assert_eq!(buf[(0, 0)].symbol(), FILLED_SQUARE);
+        assert_eq!(buf[(1, 0)].symbol(), FILLED_SQUARE);
+        assert_eq!(buf[(2, 0)].symbol(), MAX_MARKER_CHAR);
+        assert_eq!(buf[(3, 0)].symbol(), FILLED_SQUARE);
+        assert_eq!(buf[(4, 0)].symbol(), " ");
+        assert_eq!(buf[(1, 1)].symbol(), FILLED_SQUARE);
+        assert_eq!(buf[(2, 1)].symbol(), CURSOR_CHAR);
+    }
+
+    #[test]
+    fn test_fixed_row_len() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        // Five samples per row in three columns: rows [0, 1, 2 | 3, 4], [5, 6, 7 | -]
+        let results: VecDeque<PingResult> = [10, 10, 10, 10, 90, 10, 50]
+            .into_iter()
+            .enumerate()
+            .map(|(seq, ms)| PingResult::success(seq as u64, Duration::from_millis(ms), now, None))
+            .collect();
+
+        let area = Rect::new(0, 0, 3, 2);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 2, 0, false, false, None)
+            .row_len(Some(5))
+            .render(area, &mut buf);
+
+        assert_eq!(buf[(0, 0)].symbol(), FILLED_SQUARE);
+        assert_eq!(buf[(2, 0)].symbol(), FILLED_SQUARE);
+        assert_eq!(buf[(0, 1)].symbol(), FILLED_SQUARE);
+        // The 90ms sample is cut off, so the max marker goes to the highest one shown
+        assert_eq!(buf[(1, 1)].symbol(), MAX_MARKER_CHAR);
+        assert_eq!(buf[(2, 1)].symbol(), CURSOR_CHAR);
+    }
+
+    #[test]
+    fn test_dots() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        // Two columns hold 16 samples per row: the first group has two high samples and
+        // a loss, the second is all low
+        let results: VecDeque<PingResult> = [10, 80, 10, 0, 10, 60, 10, 10]
+            .into_iter()
+            .chain([10; 8])
+            .chain([20; 2])
+            .enumerate()
+            .map(|(seq, ms)| match ms {
+                0 => PingResult::timeout(seq as u64, now),
+                ms => PingResult::success(seq as u64, Duration::from_millis(ms), now, None),
+            })
+            .collect();
+
+        let area = Rect::new(0, 0, 2, 2);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 2, 0, false, false, None)
+            .dots(true)
+            .render(area, &mut buf);
+
+        assert_eq!(buf[(0, 0)].symbol(), density_char(3));
+        assert_eq!(buf[(0, 0)].symbol(), "⣄");
+        assert_eq!(buf[(0, 0)].bg, color_scale.color_for_rtt_f64(Some(80.0)));
+        assert_eq!(buf[(1, 0)].symbol(), "⠀");
+        // The group still filling up is drawn, with the cursor after it
+        assert_eq!(buf[(0, 1)].symbol(), "⠀");
+        assert_eq!(buf[(1, 1)].symbol(), CURSOR_CHAR);
+        assert_eq!(density_char(DOTS_PER_CELL), "⣿");
+
+        // A click maps to the first sample of the cell's group
+        let at = |row, col| Graph::result_at_position(18, 0, 16, 2, 2, row, col, false, false, 8);
+        assert_eq!(at(0, 1), Some(8));
+        assert_eq!(at(1, 0), Some(16));
+        assert_eq!(at(1, 1), None);
+        assert_eq!(at(0, 2), None);
+    }
+
+    #[test]
+    fn test_aggregate() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = [10, 0, 30, 40]
+            .into_iter()
+            .enumerate()
+            .map(|(seq, ms)| match ms {
+                0 => PingResult::timeout(seq as u64, now),
+                ms => PingResult::success(seq as u64, Duration::from_millis(ms), now, None),
+            })
+            .collect();
+        let render = |reducer| {
+            let area = Rect::new(0, 0, 3, 1);
+            let mut buf = Buffer::empty(area);
+            Graph::new(&results, &color_scale, None, 1, 0, false, false, None)
+                .aggregate(3, reducer)
+                .render(area, &mut buf);
+            buf
+        };
+
+        // Three samples to a cell, the group still filling drawn too, then the cursor
+        let buf = render(Reducer::Avg);
+        assert_eq!(buf[(0, 0)].symbol(), FILLED_SQUARE);
+        assert_eq!(buf[(0, 0)].fg, color_scale.color_for_rtt_f64(Some(20.0)));
+        assert_eq!(buf[(1, 0)].bg, color_scale.color_for_rtt_f64(Some(40.0)));
+        assert_eq!(buf[(2, 0)].symbol(), CURSOR_CHAR);
+
+        let buf = render(Reducer::WorstLoss);
+        assert_eq!(buf[(0, 0)].symbol(), TIMEOUT_CHAR);
+    }
+
+    #[test]
+    fn test_result_at_position() {
+        use std::time::{Duration, Instant};
+
+        // Results carry their stable sequence number, so a click can be checked against
+        // the sample it lands on
+        let now = Instant::now();
+        let buffer = |base: usize, len: usize| -> VecDeque<PingResult> {
+            (base..base + len)
+                .map(|seq| PingResult::success(seq as u64, Duration::from_millis(5), now, None))
+                .collect()
+        };
+        let seq_at = |results: &VecDeque<PingResult>, base, view_end, row, col| {
+            Graph::result_at_position(
+                results.len(),
+                base,
+                4,
+                3,
+                view_end,
+                row,
+                col,
+                false,
+                false,
+                1,
+            )
+            .map(|idx| results[idx].seq)
+        };
+
+        // Empty buffer, or a zero sized graph
+        assert_eq!(
+            Graph::result_at_position(0, 0, 4, 3, 0, 2, 0, false, false, 1),
+            None
+        );
+        assert_eq!(
+            Graph::result_at_position(5, 0, 0, 3, 2, 2, 0, false, false, 1),
+            None
+        );
+
+        // Partial last row: samples up to 5, then the cursor cell and blanks; rows above
+        // the data are empty
+        let results = buffer(0, 6);
+        assert_eq!(seq_at(&results, 0, 2, 2, 1), Some(5));
+        assert_eq!(seq_at(&results, 0, 2, 2, 2), None);
+        assert_eq!(seq_at(&results, 0, 2, 1, 3), Some(3));
+        assert_eq!(seq_at(&results, 0, 2, 0, 0), None);
+        // Beyond the last full cell (numeric mode leftovers) doesn't spill into the next row
+        assert_eq!(seq_at(&results, 0, 2, 1, 4), None);
+
+        // A just-wrapped row: its cursor cell is off screen, the last row is full
+        let results = buffer(0, 8);
+        assert_eq!(seq_at(&results, 0, 2, 2, 3), Some(7));
+        assert_eq!(seq_at(&results, 0, 2, 2, 0), Some(4));
+
+        // Scrolled back: the bottom row is the view end, and a view end past the data is
+        // clamped to the newest row
+        let results = buffer(0, 30);
+        assert_eq!(seq_at(&results, 0, 4, 2, 0), Some(12));
+        assert_eq!(seq_at(&results, 0, 4, 0, 3), Some(7));
+        assert_eq!(seq_at(&results, 0, 100, 2, 1), Some(29));
+
+        // After the buffer wrapped (base_seq > 0): evicted samples are empty, including
+        // the part of a row that was evicted
+        let results = buffer(10, 20);
+        assert_eq!(seq_at(&results, 10, 8, 2, 1), Some(29));
+        assert_eq!(seq_at(&results, 10, 3, 2, 2), Some(10));
+        assert_eq!(seq_at(&results, 10, 3, 2, 1), None);
+        assert_eq!(seq_at(&results, 10, 3, 0, 0), None);
+    }
+
+    #[test]
+    fn test_sweep() {
+        use crate::color::ColorScheme;
+        use std::time::{Duration, Instant};
+
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = (0..11)
+            .map(|seq| PingResult::success(seq, Duration::from_millis(50), now, None))
+            .collect();
+
+        // 8 slots: the last three samples wrapped around to the start, followed by the
+        // write head and the gap, with the older samples 6 and 7 still after it
+        let area = Rect::new(0, 0, 4, 2);
+        let mut buf = Buffer::empty(area);
+        Graph::new(&results, &color_scale, None, 3, 0, false, false, None)
+            .sweep(true)
+            .render(area, &mut buf);
+        let row = |y| (0..4).map(|x| buf[(x, y)].symbol()).collect::<String>();
+        assert_eq!(row(0), format!("██{}{}", MAX_MARKER_CHAR, CURSOR_CHAR));
+        assert_eq!(row(1), "  ██");
+
+        let at = |row, col| Graph::result_at_position(11, 0, 4, 2, 3, row, col, false, true, 1);
+        assert_eq!(at(0, 2), Some(10));
+        assert_eq!(at(0, 3), None);
+        assert_eq!(at(1, 0), None);
+        assert_eq!(at(1, 2), Some(6));
+    }
+}
@@ -6,8 +6,9 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph, Widget},
 };
 
-use crate::config::Config;
+use crate::config::{Config, Mode};
 use crate::ui::app::HeaderEditField;
+use std::net::SocketAddr;
 
 /// Clickable regions in header (start_x, end_x, field_type)
 #[derive(Clone, Debug)]
@@ -20,6 +21,7 @@ pub struct HeaderClickRegion {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum HeaderField {
     Target,
+    Mode,
     Interval,
     Scale,
     Colors,
@@ -32,6 +34,10 @@ pub struct Header<'a> {
     resolved_ip: Option<&'a str>,
     terminal_width: u16,
     selected_field: Option<HeaderEditField>,
+    /// Accent of the summary target the graph follows (colors the target and border)
+    accent: Option<Color>,
+    /// Local address probes are sent from, once known (UDP client mode)
+    local_addr: Option<SocketAddr>,
 }
 
 impl<'a> Header<'a> {
@@ -46,9 +52,23 @@ impl<'a> Header<'a> {
             resolved_ip,
             terminal_width,
             selected_field,
+            accent: None,
+            local_addr: None,
         }
     }
 
+    /// Color the target and border with the accent of the summary target being graphed
+    pub fn accent(mut self, accent: Option<Color>) -> Self {
+        self.accent = accent;
+        self
+    }
+
+    /// Show the local address (and ephemeral port) the probes are sent from
+    pub fn local_addr(mut self, local_addr: Option<SocketAddr>) -> Self {
+        self.local_addr = local_addr;
+        self
+    }
+
     /// Calculate click regions for header fields
     /// Returns regions relative to content area (inside borders)
     pub fn calculate_click_regions(&self) -> Vec<HeaderClickRegion> {
@@ -67,7 +87,7 @@ impl<'a> Header<'a> {
             None => "not set".to_string(),
         };
 
-        let mode_str = format!("{}", self.config.mode);
+        let mode_str = self.config.mode_label();
         let interval_str = format!("{}ms", self.config.interval);
         let scale_str = format!("{}ms", self.config.scale);
         let colors_str = format!("{}", self.config.colors);
@@ -87,9 +107,15 @@ impl<'a> Header<'a> {
         });
         pos += 3; // " │ "
 
-        // Mode: "Mode: " + value (not clickable)
+        // Mode: "Mode: " + value (click cycles ICMP / UDP client)
         pos += "Mode: ".len() as u16;
+        let mode_start = pos;
         pos += mode_str.len() as u16;
+        regions.push(HeaderClickRegion {
+            start_x: mode_start,
+            end_x: pos,
+            field: HeaderField::Mode,
+        });
         pos += 3; // " │ "
 
         // Interval: "Interval: " + value
@@ -156,7 +182,7 @@ impl Widget for Header<'_> {
             None => "not set".to_string(),
         };
 
-        let mode_str = format!("{}", self.config.mode);
+        let mode_str = self.config.mode_label();
         let interval_str = format!("{}ms", self.config.interval);
         let scale_str = format!("{}ms", self.config.scale);
         let colors_str = format!("{}", self.config.colors);
@@ -180,14 +206,17 @@ impl Widget for Header<'_> {
                 &target,
                 highlight(
                     Style::default()
-                        .fg(Color::Cyan)
+                        .fg(self.accent.unwrap_or(Color::Cyan))
                         .add_modifier(Modifier::BOLD),
                     HeaderEditField::Target,
                 ),
             ),
             Span::raw(" │ "),
             Span::styled("Mode: ", Style::default().fg(Color::DarkGray)),
-            Span::styled(&mode_str, Style::default().fg(Color::Yellow)),
+            Span::styled(
+                &mode_str,
+                highlight(Style::default().fg(Color::Yellow), HeaderEditField::Mode),
+            ),
             Span::raw(" │ "),
             Span::styled("Interval: ", Style::default().fg(Color::DarkGray)),
             Span::styled(
@@ -232,7 +261,7 @@ impl Widget for Header<'_> {
 
         let line = Line::from(spans);
 
-        let block = Block::default()
+        let mut block = Block::default()
             .title(" rttui ")
             .title_style(
                 Style::default()
@@ -240,7 +269,30 @@ impl Widget for Header<'_> {
                     .add_modifier(Modifier::BOLD),
             )
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::DarkGray));
+            .border_style(Style::default().fg(self.accent.unwrap_or(Color::DarkGray)));
+        // RTTs through a proxy include the hop to it, so say so
+        if let Some(proxy) = &self.config.proxy
+            && self.config.mode == Mode::TlsHandshake
+        {
+            block = block.title(
+                Line::from(Span::styled(
+                    format!(" via {} ", proxy),
+                    Style::default().fg(Color::Yellow),
+                ))
+                .right_aligned(),
+            );
+        }
+
+        // The ephemeral port, for firewall rules and matching up packet captures
+        if let Some(addr) = self.local_addr {
+            block = block.title_bottom(
+                Line::from(Span::styled(
+                    format!(" from {} ", addr),
+                    Style::default().fg(Color::DarkGray),
+                ))
+                .right_aligned(),
+            );
+        }
 
         let paragraph = Paragraph::new(line).block(block);
         paragraph.render(area, buf);
@@ -0,0 +1,182 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+use crate::color::ColorScale;
+use crate::ping::{PingResult, format_time};
+use crate::ui::graph::{FILLED_SQUARE, TIMEOUT_CHAR};
+
+/// Columns per row: one per second of the minute
+const SECONDS: usize = 60;
+/// Width of the "HH:MM " row labels
+const LABEL_WIDTH: u16 = 6;
+
+/// Time-of-day heatmap shown in place of the graph: one row per minute and one cell per
+/// second, placed by the timestamp of the ping rather than its sequence number
+/// Seconds without a ping stay empty; a second with several pings shows the worst of them
+pub struct Heatmap<'a> {
+    results: &'a VecDeque<PingResult>,
+    color_scale: &'a ColorScale,
+    background: Option<Color>,
+    /// Show the row labels in UTC instead of local time
+    utc: bool,
+}
+
+impl<'a> Heatmap<'a> {
+    pub fn new(results: &'a VecDeque<PingResult>, color_scale: &'a ColorScale) -> Self {
+        Self {
+            results,
+            color_scale,
+            background: None,
+            utc: false,
+        }
+    }
+
+    /// Fill the heatmap area with this background color
+    pub fn background(mut self, background: Option<Color>) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Show the row labels in UTC instead of local time
+    pub fn utc(mut self, utc: bool) -> Self {
+        self.utc = utc;
+        self
+    }
+}
+
+impl Widget for Heatmap<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        if let Some(bg) = self.background {
+            buf.set_style(area, Style::default().bg(bg));
+        }
+        let Some(newest) = self.results.back() else {
+            return;
+        };
+
+        // Rows end at the minute of the newest ping, newest at the bottom like the graph
+        let last_minute = newest.timestamp.timestamp().div_euclid(60);
+        let first_minute = last_minute - area.height as i64 + 1;
+
+        // Worst ping of every second shown: a timeout beats any reply
+        let mut cells: HashMap<i64, Option<f64>> = HashMap::new();
+        for result in self.results.iter().rev() {
+            let secs = result.timestamp.timestamp();
+            if secs.div_euclid(60) < first_minute {
+                // Timestamps only run a little out of order, so nothing older follows
+                if secs.div_euclid(60) < first_minute - 1 {
+                    break;
+                }
+                continue;
+            }
+            let rtt = result.rtt_ms_f64();
+            cells
+                .entry(secs)
+                .and_modify(|worst| {
+                    *worst = match (*worst, rtt) {
+                        (Some(a), Some(b)) => Some(a.max(b)),
+                        _ => None,
+                    }
+                })
+                .or_insert(rtt);
+        }
+
+        let columns = (area.width.saturating_sub(LABEL_WIDTH) as usize).min(SECONDS);
+        for (row, minute) in (first_minute..=last_minute).enumerate() {
+            let y = area.y + row as u16;
+            if let Some(start) = DateTime::from_timestamp(minute * 60, 0) {
+                let label = format_time(start.with_timezone(&Local), "%H:%M", self.utc);
+                buf.set_stringn(
+                    area.x,
+                    y,
+                    label,
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+            }
+            for second in 0..columns {
+                let x = area.x + LABEL_WIDTH + second as u16;
+                match cells.get(&(minute * 60 + second as i64)) {
+                    Some(Some(rtt)) => {
+                        let color = self.color_scale.color_for_rtt_f64(Some(*rtt));
+                        buf.set_string(x, y, FILLED_SQUARE, Style::default().fg(color));
+                    }
+                    Some(None) => {
+                        buf.set_string(
+                            x,
+                            y,
+                            TIMEOUT_CHAR,
+                            Style::default().fg(Color::Indexed(240)),
+                        );
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorScheme;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_cells_by_timestamp() {
+        let now = Instant::now();
+        let minute = Local::now().timestamp().div_euclid(60) * 60;
+        let at = |mut result: PingResult, secs: i64| {
+            result.timestamp = DateTime::from_timestamp(secs, 0)
+                .unwrap()
+                .with_timezone(&Local);
+            result
+        };
+        let results: VecDeque<PingResult> = [
+            at(
+                PingResult::success(1, Duration::from_millis(10), now, None),
+                minute - 60,
+            ),
+            // Two pings in one second: the worst one is shown
+            at(
+                PingResult::success(2, Duration::from_millis(10), now, None),
+                minute + 5,
+            ),
+            at(
+                PingResult::success(3, Duration::from_millis(90), now, None),
+                minute + 5,
+            ),
+            at(PingResult::timeout(4, now), minute + 7),
+        ]
+        .into();
+        let scale = ColorScale::new(100, ColorScheme::Dark);
+
+        let area = Rect::new(0, 0, 66, 3);
+        let mut buf = Buffer::empty(area);
+        Heatmap::new(&results, &scale).render(area, &mut buf);
+
+        // Newest minute on the bottom row, the one before it above; nothing earlier
+        let start = DateTime::from_timestamp(minute, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        let label: String = (0..5).map(|x| buf[(x, 2)].symbol()).collect();
+        assert_eq!(label, format_time(start, "%H:%M", false));
+        assert!((6..66).all(|x| buf[(x, 0)].symbol() == " "));
+        assert_eq!(buf[(6, 1)].symbol(), FILLED_SQUARE);
+        assert_eq!(buf[(6, 1)].fg, scale.color_for_rtt_f64(Some(10.0)));
+        assert_eq!(buf[(11, 2)].fg, scale.color_for_rtt_f64(Some(90.0)));
+        assert_eq!(buf[(13, 2)].symbol(), TIMEOUT_CHAR);
+        // Seconds without a ping stay empty
+        assert_eq!(buf[(12, 2)].symbol(), " ");
+        assert_eq!(buf[(7, 1)].symbol(), " ");
+    }
+}
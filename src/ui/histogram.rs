@@ -0,0 +1,122 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+use crate::color::ColorScale;
+use crate::ping::PingResult;
+
+/// Number of RTT buckets (same ranges as the legend entries)
+const RTT_BUCKETS: usize = 11;
+
+/// Count results per bucket: RTT buckets split the color scale into tenths
+/// (the last one is open-ended), followed by one bucket for timeouts
+pub fn bucket_counts(
+    results: &VecDeque<PingResult>,
+    color_scale: &ColorScale,
+) -> [u64; RTT_BUCKETS + 1] {
+    let mut counts = [0u64; RTT_BUCKETS + 1];
+    let step = color_scale.max_rtt as f64 / (RTT_BUCKETS - 1) as f64;
+
+    for result in results {
+        let idx = match result.rtt_ms_f64() {
+            Some(ms) => ((ms / step) as usize).min(RTT_BUCKETS - 1),
+            None => RTT_BUCKETS,
+        };
+        counts[idx] += 1;
+    }
+
+    counts
+}
+
+/// Horizontal histogram of the RTT distribution across the history buffer
+pub struct Histogram<'a> {
+    results: &'a VecDeque<PingResult>,
+    color_scale: &'a ColorScale,
+}
+
+impl<'a> Histogram<'a> {
+    pub fn new(results: &'a VecDeque<PingResult>, color_scale: &'a ColorScale) -> Self {
+        Self {
+            results,
+            color_scale,
+        }
+    }
+}
+
+impl Widget for Histogram<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let counts = bucket_counts(self.results, self.color_scale);
+        let max_count = counts.iter().copied().max().unwrap_or(0).max(1);
+        let content_width = area.width.saturating_sub(2) as usize;
+        let step = self.color_scale.max_rtt as f64 / (RTT_BUCKETS - 1) as f64;
+
+        let lines: Vec<Line> = counts
+            .iter()
+            .enumerate()
+            .map(|(idx, &count)| {
+                let color = if idx == RTT_BUCKETS {
+                    Color::Indexed(240)
+                } else {
+                    // The open-ended last bucket has no midpoint, so use its lower edge
+                    let midpoint = if idx == RTT_BUCKETS - 1 {
+                        idx as f64 * step
+                    } else {
+                        (idx as f64 + 0.5) * step
+                    };
+                    self.color_scale.color_for_rtt_f64(Some(midpoint))
+                };
+
+                let label = format!(" {}", count);
+                let bar_width = content_width.saturating_sub(label.len());
+                let bar_len = (count as f64 / max_count as f64 * bar_width as f64).ceil() as usize;
+
+                Line::from(vec![
+                    Span::styled("█".repeat(bar_len), Style::default().fg(color)),
+                    Span::styled(label, Style::default().fg(Color::Gray)),
+                ])
+            })
+            .collect();
+
+        let block = Block::default()
+            .title(" Histogram ")
+            .title_style(Style::default().fg(Color::DarkGray))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray));
+
+        let paragraph = Paragraph::new(lines).block(block);
+        paragraph.render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorScheme;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_bucket_counts() {
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = [Some(5), Some(15), Some(19), Some(500), None]
+            .into_iter()
+            .enumerate()
+            .map(|(seq, rtt)| match rtt {
+                Some(ms) => PingResult::success(seq as u64, Duration::from_millis(ms), now, None),
+                None => PingResult::timeout(seq as u64, now),
+            })
+            .collect();
+
+        let counts = bucket_counts(&results, &color_scale);
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts[1], 2);
+        assert_eq!(counts[RTT_BUCKETS - 1], 1);
+        assert_eq!(counts[RTT_BUCKETS], 1);
+    }
+}
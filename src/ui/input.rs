@@ -0,0 +1,272 @@
+use ratatui::{
+    style::{Color, Style},
+    text::Span,
+};
+
+use crate::config::parse_duration_ms;
+
+/// What a text input accepts, and how its text is validated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    /// Free text (validated by the caller when applied)
+    Text,
+    /// A duration such as "500ms" or "1.5s", from 1ms up to `max` ms
+    Duration { max: u64 },
+    /// A whole number from 1 up to `max`
+    Number { max: u64 },
+    /// A time of day as HH:MM[:SS] (validated by the caller when applied)
+    TimeOfDay,
+}
+
+impl InputKind {
+    /// Whether `c` can be typed into this kind of input
+    pub fn accepts(self, c: char) -> bool {
+        match self {
+            InputKind::Text => !c.is_control(),
+            InputKind::Duration { .. } => c.is_ascii_digit() || matches!(c, '.' | 'm' | 's'),
+            InputKind::Number { .. } => c.is_ascii_digit(),
+            InputKind::TimeOfDay => c.is_ascii_digit() || c == ':',
+        }
+    }
+
+    /// Parse `text` as a value of this kind in `1..=max` (ms for durations)
+    pub fn parse(self, text: &str) -> Result<u64, String> {
+        match self {
+            InputKind::Duration { max } => {
+                let ms = parse_duration_ms(text)?;
+                if (1..=max).contains(&ms) {
+                    Ok(ms)
+                } else {
+                    Err(format!("Must be between 1ms and {}ms", max))
+                }
+            }
+            InputKind::Number { max } => {
+                if text.is_empty() {
+                    return Err("Enter a value".to_string());
+                }
+                match text.parse::<u64>() {
+                    Ok(value) if (1..=max).contains(&value) => Ok(value),
+                    _ => Err(format!("Must be between 1 and {}", max)),
+                }
+            }
+            InputKind::Text | InputKind::TimeOfDay => Err("Not a number".to_string()),
+        }
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(self, InputKind::Duration { .. } | InputKind::Number { .. })
+    }
+}
+
+/// Editable single line of text with a cursor, select-all and validation, shared by
+/// the settings menu and the header's inline editor
+///
+/// Numeric kinds are revalidated on every edit, so `error` always describes the
+/// current text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextInput {
+    pub kind: InputKind,
+    pub text: String,
+    /// Cursor position in characters
+    pub cursor: usize,
+    /// Whole text selected (the next key typed replaces it)
+    pub selected: bool,
+    /// Why the text isn't a valid value (None = valid)
+    pub error: Option<String>,
+}
+
+impl Default for TextInput {
+    fn default() -> Self {
+        Self::new(InputKind::Text, "")
+    }
+}
+
+impl TextInput {
+    /// Input holding `text` with the cursor at its end
+    pub fn new(kind: InputKind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.to_string(),
+            cursor: text.chars().count(),
+            selected: false,
+            error: None,
+        }
+    }
+
+    /// Replace the text, moving the cursor to its end and revalidating
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = text.chars().count();
+        self.selected = false;
+        self.validate();
+    }
+
+    /// Select the whole text, so typing replaces it
+    pub fn select_all(&mut self) {
+        self.selected = true;
+        self.cursor = self.text.chars().count();
+    }
+
+    /// Empty the text
+    pub fn clear(&mut self) {
+        self.set_text("");
+    }
+
+    /// Type a character at the cursor (replacing a selection)
+    /// Returns false, changing nothing, if the kind of input doesn't accept it
+    pub fn insert(&mut self, c: char) -> bool {
+        if !self.kind.accepts(c) {
+            return false;
+        }
+        if self.selected {
+            self.text.clear();
+            self.cursor = 0;
+            self.selected = false;
+        }
+        let at = self.byte_index(self.cursor);
+        self.text.insert(at, c);
+        self.cursor += 1;
+        self.validate();
+        true
+    }
+
+    /// Delete the character before the cursor (or the whole selection)
+    pub fn backspace(&mut self) {
+        if self.selected {
+            self.text.clear();
+            self.cursor = 0;
+            self.selected = false;
+        } else if self.cursor > 0 {
+            self.cursor -= 1;
+            let at = self.byte_index(self.cursor);
+            self.text.remove(at);
+        }
+        self.validate();
+    }
+
+    /// Move the cursor one character left, dropping the selection
+    pub fn left(&mut self) {
+        self.selected = false;
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// Move the cursor one character right, dropping the selection
+    pub fn right(&mut self) {
+        self.selected = false;
+        self.cursor = (self.cursor + 1).min(self.text.chars().count());
+    }
+
+    /// The typed value of a numeric input
+    pub fn value(&self) -> Result<u64, String> {
+        self.kind.parse(&self.text)
+    }
+
+    /// Recheck the text, setting or clearing `error`
+    /// Text and times of day are only checked when applied, so any edit clears the error
+    pub fn validate(&mut self) {
+        self.error = if self.kind.is_numeric() {
+            self.value().err()
+        } else {
+            None
+        };
+    }
+
+    /// Step a numeric value by `delta`, keeping it in range
+    /// Returns the new value, or None if the current text isn't a number
+    pub fn step(&mut self, delta: i64) -> Option<u64> {
+        let max = match self.kind {
+            InputKind::Duration { max } | InputKind::Number { max } => max,
+            InputKind::Text | InputKind::TimeOfDay => return None,
+        };
+        let current = match self.kind {
+            InputKind::Duration { .. } => parse_duration_ms(&self.text).ok()?,
+            _ => self.text.parse::<u64>().ok()?,
+        };
+        let value = current.saturating_add_signed(delta).clamp(1, max);
+        self.set_text(&value.to_string());
+        Some(value)
+    }
+
+    /// Spans drawing the text: highlighted while selected, otherwise in `style` with
+    /// a cursor bar
+    pub fn spans(&self, style: Style, selected_style: Style) -> Vec<Span<'static>> {
+        if self.selected {
+            return vec![Span::styled(self.text.clone(), selected_style)];
+        }
+        let at = self.byte_index(self.cursor);
+        vec![
+            Span::styled(self.text[..at].to_string(), style),
+            Span::styled("▏", Style::default().fg(Color::White)),
+            Span::styled(self.text[at..].to_string(), style),
+        ]
+    }
+
+    /// Byte offset of the character at `cursor`
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_input() {
+        let mut input = TextInput::new(InputKind::Number { max: 500 }, "100");
+        input.select_all();
+        // Typing replaces the selection, rejected characters change nothing
+        assert!(!input.insert('x'));
+        assert!(input.selected);
+        assert!(input.insert('9'));
+        assert_eq!(input.text, "9");
+        assert!(input.insert('0'));
+        assert!(input.insert('0'));
+        assert_eq!(input.error.as_deref(), Some("Must be between 1 and 500"));
+        input.backspace();
+        assert_eq!(input.value(), Ok(90));
+        assert_eq!(input.error, None);
+
+        // Cursor moves and edits by character
+        input.left();
+        input.left();
+        input.left();
+        assert_eq!(input.cursor, 0);
+        input.insert('1');
+        assert_eq!(input.text, "190");
+        input.right();
+        input.backspace();
+        assert_eq!((input.text.as_str(), input.cursor), ("10", 1));
+
+        // Stepping stays in range
+        input.set_text("500");
+        assert_eq!(input.step(1), Some(500));
+        assert_eq!(input.step(-1), Some(499));
+        input.set_text("");
+        assert_eq!(input.error.as_deref(), Some("Enter a value"));
+        assert_eq!(input.step(1), None);
+
+        let mut duration = TextInput::new(InputKind::Duration { max: 1000 }, "1s");
+        assert_eq!(duration.value(), Ok(1000));
+        duration.left();
+        duration.insert('0');
+        assert_eq!(
+            duration.error.as_deref(),
+            Some("Must be between 1ms and 1000ms")
+        );
+        assert_eq!(duration.step(-5), Some(1000));
+        assert_eq!(duration.step(-5), Some(995));
+
+        // Free text isn't validated while typing, and multi-byte text edits cleanly
+        let mut text = TextInput::new(InputKind::Text, "hüst");
+        text.left();
+        text.left();
+        text.backspace();
+        assert_eq!(text.text, "hst");
+        assert_eq!(text.error, None);
+        assert_eq!(text.spans(Style::default(), Style::default()).len(), 3);
+    }
+}
@@ -1,3 +1,4 @@
+use clap::ValueEnum;
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
@@ -7,11 +8,32 @@ use ratatui::{
 };
 
 use crate::color::ColorScale;
+use serde::{Deserialize, Serialize};
 
 /// Minimum terminal width to show legend
 pub const MIN_WIDTH_FOR_LEGEND: u16 = 100;
+/// Terminal width below which a visible legend is hidden again
+/// (the gap to MIN_WIDTH_FOR_LEGEND keeps the legend from flickering while resizing)
+pub const HIDE_WIDTH_FOR_LEGEND: u16 = 96;
+/// Minimum terminal width to show a legend turned on with `--legend on` or 'e'
+pub const MIN_WIDTH_FOR_FORCED_LEGEND: u16 = 40;
 /// Width of the legend panel
 pub const LEGEND_WIDTH: u16 = 16;
+/// Height of the legend panel when something is stacked below it (entries + borders)
+pub const LEGEND_HEIGHT: u16 = 14;
+
+/// When the legend is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LegendMode {
+    /// Shown on terminals at least MIN_WIDTH_FOR_LEGEND wide
+    #[default]
+    Auto,
+    /// Always shown, trading graph width for it (down to MIN_WIDTH_FOR_FORCED_LEGEND)
+    On,
+    /// Never shown
+    Off,
+}
 
 /// Legend widget showing color scale
 pub struct Legend<'a> {
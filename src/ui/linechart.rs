@@ -0,0 +1,183 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+use crate::color::ColorScale;
+use crate::ping::{PingResult, outlier_limit};
+
+/// Braille dot bits by (column, row) within a 2x4 cell
+const BRAILLE_BITS: [[u8; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+/// RTT over time as a braille line chart of the newest samples (two per column),
+/// alternative to the square grid
+/// The y-axis runs from 0 to the highest RTT on screen; timeouts leave gaps in the line
+pub struct LineChart<'a> {
+    results: &'a VecDeque<PingResult>,
+    color_scale: &'a ColorScale,
+    background: Option<Color>,
+    /// The y-axis tops out at this factor of the median RTT on screen (None = no clipping)
+    clip_outliers: Option<f64>,
+}
+
+impl<'a> LineChart<'a> {
+    pub fn new(results: &'a VecDeque<PingResult>, color_scale: &'a ColorScale) -> Self {
+        Self {
+            results,
+            color_scale,
+            background: None,
+            clip_outliers: None,
+        }
+    }
+
+    /// Cap the y-axis at `factor` × the median RTT on screen, drawing outliers at the top
+    pub fn clip_outliers(mut self, factor: Option<f64>) -> Self {
+        self.clip_outliers = factor;
+        self
+    }
+
+    /// Fill the chart area with this background color
+    pub fn background(mut self, background: Option<Color>) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+impl Widget for LineChart<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        if let Some(bg) = self.background {
+            buf.set_style(area, Style::default().bg(bg));
+        }
+
+        // Highest RTT among the samples that could fit (decided before the label width,
+        // so it may include a few samples hidden behind the labels)
+        let max_samples = area.width as usize * 2;
+        let skip = self.results.len().saturating_sub(max_samples);
+        let rtts = self
+            .results
+            .iter()
+            .skip(skip)
+            .filter_map(|r| r.rtt_ms_f64());
+        let limit = self
+            .clip_outliers
+            .and_then(|factor| outlier_limit(rtts.clone(), factor));
+        let y_max = rtts
+            .fold(0.0f64, f64::max)
+            .min(limit.unwrap_or(f64::INFINITY))
+            .ceil()
+            .max(1.0);
+
+        // Y-axis labels on the left
+        let top_label = format!("{}ms", y_max as u64);
+        let label_width = top_label.len() as u16 + 1;
+        if area.width <= label_width {
+            return;
+        }
+        let label_style = Style::default().fg(Color::DarkGray);
+        let mut labels = vec![(0, top_label)];
+        if area.height >= 2 {
+            labels.push((area.height - 1, "0ms".to_string()));
+        }
+        if area.height >= 5 {
+            labels.push((
+                area.height / 2,
+                format!("{}ms", (y_max / 2.0).round() as u64),
+            ));
+        }
+        for (row, label) in labels {
+            let x = area.x + label_width - 1 - label.len() as u16;
+            buf.set_string(x, area.y + row, label, label_style);
+        }
+
+        let plot_width = (area.width - label_width) as usize;
+        let plot_height = area.height as usize;
+        let dots_w = plot_width * 2;
+        let dots_h = plot_height * 4;
+
+        // Newest sample at the right edge
+        let skip = self.results.len().saturating_sub(dots_w);
+        let samples: Vec<Option<f64>> = self
+            .results
+            .iter()
+            .skip(skip)
+            .map(|r| r.rtt_ms_f64())
+            .collect();
+        let x_offset = dots_w - samples.len();
+
+        let mut bits = vec![0u8; plot_width * plot_height];
+        let mut cell_max = vec![None::<f64>; plot_width * plot_height];
+        let mut set_dot = |dx: usize, dy: usize, rtt: f64| {
+            let idx = (dy / 4) * plot_width + dx / 2;
+            bits[idx] |= BRAILLE_BITS[dx % 2][dy % 4];
+            cell_max[idx] = Some(cell_max[idx].map_or(rtt, |m: f64| m.max(rtt)));
+        };
+        let dot_y = |rtt: f64| ((1.0 - rtt / y_max) * (dots_h - 1) as f64).round() as usize;
+
+        let mut prev_y: Option<usize> = None;
+        for (i, sample) in samples.iter().enumerate() {
+            let Some(rtt) = *sample else {
+                // Timeouts break the line
+                prev_y = None;
+                continue;
+            };
+            let dx = x_offset + i;
+            let y = dot_y(rtt.min(y_max));
+            // Connect to the previous reply with a vertical run in this column
+            let (from, to) = match prev_y {
+                Some(prev) => (prev.min(y), prev.max(y)),
+                None => (y, y),
+            };
+            for dy in from..=to {
+                set_dot(dx, dy, rtt);
+            }
+            prev_y = Some(y);
+        }
+
+        for (idx, &cell_bits) in bits.iter().enumerate() {
+            if cell_bits == 0 {
+                continue;
+            }
+            let x = area.x + label_width + (idx % plot_width) as u16;
+            let y = area.y + (idx / plot_width) as u16;
+            let ch = char::from_u32(0x2800 + cell_bits as u32).unwrap_or(' ');
+            buf[(x, y)]
+                .set_char(ch)
+                .set_fg(self.color_scale.color_for_rtt_f64(cell_max[idx]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorScheme;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_line_chart_gaps_on_timeout() {
+        let now = Instant::now();
+        let results: VecDeque<PingResult> = (1..=4)
+            .map(|seq| match seq {
+                3 => PingResult::timeout(seq, now),
+                _ => PingResult::success(seq, Duration::from_millis(10), now, None),
+            })
+            .collect();
+        let scale = ColorScale::new(100, ColorScheme::Dark);
+
+        // "10ms " labels leave two columns (four samples) for the plot
+        let area = Rect::new(0, 0, 7, 1);
+        let mut buf = Buffer::empty(area);
+        LineChart::new(&results, &scale).render(area, &mut buf);
+
+        // All replies sit on the top dot row; the timeout leaves the third dot empty
+        assert_eq!(buf[(5, 0)].symbol(), "⠉");
+        assert_eq!(buf[(6, 0)].symbol(), "⠈");
+    }
+}
@@ -0,0 +1,208 @@
+use chrono::{DateTime, Local};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+use crate::ping::{PingResult, format_time};
+
+/// A run of consecutive timeouts
+#[derive(Debug, Clone, PartialEq)]
+pub struct LossEvent {
+    pub first_seq: u64,
+    pub last_seq: u64,
+    pub count: u64,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+/// The timeouts among the buffered results grouped into outages, oldest first, kept
+/// up to date as results are added and evicted
+#[derive(Debug, Default)]
+pub struct LossEvents {
+    events: Vec<LossEvent>,
+    /// Whether the newest result was lost, so the next loss extends its outage
+    in_outage: bool,
+}
+
+impl LossEvents {
+    /// Add the newest result
+    pub fn record(&mut self, result: &PingResult) {
+        if result.rtt.is_some() {
+            self.in_outage = false;
+            return;
+        }
+        match self.events.last_mut() {
+            Some(event) if self.in_outage => {
+                event.last_seq = result.seq;
+                event.count += 1;
+                event.end = result.timestamp;
+            }
+            _ => self.events.push(LossEvent {
+                first_seq: result.seq,
+                last_seq: result.seq,
+                count: 1,
+                start: result.timestamp,
+                end: result.timestamp,
+            }),
+        }
+        self.in_outage = true;
+    }
+
+    /// Take out the oldest result, `evicted`, leaving `next` as the oldest
+    pub fn evict(&mut self, evicted: &PingResult, next: Option<&PingResult>) {
+        if evicted.rtt.is_some() {
+            return;
+        }
+        let Some(event) = self.events.first_mut() else {
+            return;
+        };
+        event.count -= 1;
+        match next {
+            Some(next) if event.count > 0 => {
+                event.first_seq = next.seq;
+                event.start = next.timestamp;
+            }
+            _ => {
+                self.events.remove(0);
+                self.in_outage &= !self.events.is_empty();
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.in_outage = false;
+    }
+
+    pub fn as_slice(&self) -> &[LossEvent] {
+        &self.events
+    }
+}
+
+/// Timeline of loss events shown in place of the graph
+pub struct LossTimeline<'a> {
+    events: &'a [LossEvent],
+    /// Number of newest events scrolled past (0 = newest at the bottom)
+    scroll: usize,
+    /// Show times in UTC instead of local time
+    utc: bool,
+}
+
+impl<'a> LossTimeline<'a> {
+    pub fn new(events: &'a [LossEvent], scroll: usize) -> Self {
+        Self {
+            events,
+            scroll,
+            utc: false,
+        }
+    }
+
+    /// Show times in UTC instead of local time
+    pub fn utc(mut self, utc: bool) -> Self {
+        self.utc = utc;
+        self
+    }
+
+    /// Number of event lines that fit in an area of the given height
+    pub fn visible_rows(height: u16) -> usize {
+        height.saturating_sub(2) as usize
+    }
+}
+
+impl Widget for LossTimeline<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let total_lost: u64 = self.events.iter().map(|e| e.count).sum();
+        let block = Block::default()
+            .title(format!(
+                " Losses: {} event(s), {} lost [l: back to graph] ",
+                self.events.len(),
+                total_lost
+            ))
+            .title_style(Style::default().fg(Color::Yellow))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray));
+
+        if self.events.is_empty() {
+            let paragraph = Paragraph::new(Line::from(Span::styled(
+                "No losses in the buffer",
+                Style::default().fg(Color::DarkGray),
+            )))
+            .block(block);
+            paragraph.render(area, buf);
+            return;
+        }
+
+        // Newest events at the bottom, like the graph
+        let visible = Self::visible_rows(area.height);
+        let end = self.events.len().saturating_sub(self.scroll);
+        let start = end.saturating_sub(visible);
+
+        let lines: Vec<Line> = self.events[start..end]
+            .iter()
+            .map(|event| {
+                let seqs = if event.count == 1 {
+                    format!("seq {}", event.first_seq)
+                } else {
+                    format!("seq {}-{}", event.first_seq, event.last_seq)
+                };
+                let time = |t| format_time(t, "%H:%M:%S%.3f", self.utc);
+                let time = if event.count == 1 {
+                    time(event.start)
+                } else {
+                    format!("{} - {}", time(event.start), time(event.end))
+                };
+                let color = if event.count > 1 {
+                    Color::Red
+                } else {
+                    Color::Yellow
+                };
+                Line::from(vec![
+                    Span::styled(format!(" {:<22}", seqs), Style::default().fg(Color::Gray)),
+                    Span::styled(format!("{:<28}", time), Style::default().fg(Color::White)),
+                    Span::styled(format!("{} lost", event.count), Style::default().fg(color)),
+                ])
+            })
+            .collect();
+
+        let paragraph = Paragraph::new(lines).block(block);
+        paragraph.render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_loss_events() {
+        let now = Instant::now();
+        let results: Vec<PingResult> = (1..=8)
+            .map(|seq| match seq {
+                2 | 5 | 6 | 7 => PingResult::timeout(seq, now),
+                _ => PingResult::success(seq, Duration::from_millis(10), now, None),
+            })
+            .collect();
+        let mut losses = LossEvents::default();
+        results.iter().for_each(|result| losses.record(result));
+
+        let events = losses.as_slice();
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].first_seq, events[0].count), (2, 1));
+        assert_eq!((events[1].first_seq, events[1].last_seq), (5, 7));
+        assert_eq!(events[1].count, 3);
+
+        // Evicting results shrinks and then drops the outages they were in
+        for idx in 0..5 {
+            losses.evict(&results[idx], results.get(idx + 1));
+        }
+        let events = losses.as_slice();
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].first_seq, events[0].last_seq), (6, 7));
+        assert_eq!(events[0].count, 2);
+    }
+}
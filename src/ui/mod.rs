@@ -2,5 +2,12 @@ pub mod app;
 pub mod footer;
 pub mod graph;
 pub mod header;
+pub mod heatmap;
+pub mod histogram;
+pub mod input;
 pub mod legend;
+pub mod linechart;
+pub mod losses;
 pub mod settings;
+pub mod snapshot;
+pub mod targets;
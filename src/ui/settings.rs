@@ -7,21 +7,36 @@ use ratatui::{
 };
 
 use super::app::SettingsField;
+use super::input::TextInput;
 use crate::color::ColorScheme;
+use crate::ping::{Reducer, RttUnit};
+
+/// Size of the settings box, shrunk to fit small terminals
+pub const MENU_WIDTH: u16 = 65;
+pub const MENU_HEIGHT: u16 = 31;
+
+/// Line of the "Aggregate By" field in the menu, left out when not aggregating
+pub const AGGREGATE_BY_LINE: usize = 13;
 
 /// Settings menu widget
 pub struct SettingsMenu {
     pub selected_field: SettingsField,
     pub target: String,
     pub interval: u64,
+    pub timeout: u64,
     pub scale: u64,
     pub colors: ColorScheme,
+    pub unit: RttUnit,
+    pub aggregate_by: Reducer,
+    /// Whether --aggregate groups pings, so the reducer is worth showing
+    pub aggregating: bool,
     pub hide_cursor: bool,
+    pub hide_indicator: bool,
+    pub top_down: bool,
+    pub confirm_quit: bool,
     pub buffer_mb: u64,
     pub input_active: bool,
-    pub input_buffer: String,
-    pub input_cursor: usize,
-    pub input_selected: bool,
+    pub input: TextInput,
 }
 
 impl SettingsMenu {
@@ -30,27 +45,37 @@ impl SettingsMenu {
         selected_field: SettingsField,
         target: String,
         interval: u64,
+        timeout: u64,
         scale: u64,
         colors: ColorScheme,
+        unit: RttUnit,
+        aggregate_by: Reducer,
+        aggregating: bool,
         hide_cursor: bool,
+        hide_indicator: bool,
+        top_down: bool,
+        confirm_quit: bool,
         buffer_mb: u64,
         input_active: bool,
-        input_buffer: String,
-        input_cursor: usize,
-        input_selected: bool,
+        input: TextInput,
     ) -> Self {
         Self {
             selected_field,
             target,
             interval,
+            timeout,
             scale,
             colors,
+            unit,
+            aggregate_by,
+            aggregating,
             hide_cursor,
+            hide_indicator,
+            top_down,
+            confirm_quit,
             buffer_mb,
             input_active,
-            input_buffer,
-            input_cursor,
-            input_selected,
+            input,
         }
     }
 }
@@ -58,8 +83,8 @@ impl SettingsMenu {
 impl Widget for SettingsMenu {
     fn render(self, area: Rect, buf: &mut Buffer) {
         // Calculate centered position for the settings box (wider now)
-        let width = 65u16.min(area.width.saturating_sub(4));
-        let height = 19u16.min(area.height.saturating_sub(4)); // Increased height for buffer size
+        let width = MENU_WIDTH.min(area.width.saturating_sub(4));
+        let height = MENU_HEIGHT.min(area.height.saturating_sub(4));
         let x = area.x + (area.width.saturating_sub(width)) / 2;
         let y = area.y + (area.height.saturating_sub(height)) / 2;
 
@@ -85,7 +110,11 @@ impl Widget for SettingsMenu {
         let label_style = Style::default().fg(Color::Gray);
         let value_style = Style::default().fg(Color::Cyan);
         let hint_style = Style::default().fg(Color::DarkGray);
-        let input_style = Style::default().fg(Color::White).bg(Color::Rgb(60, 60, 80));
+        // Text being typed turns red (and Confirm is greyed out) while it's invalid
+        let invalid = self.input_active && self.input.error.is_some();
+        let input_style = Style::default()
+            .fg(if invalid { Color::Red } else { Color::White })
+            .bg(Color::Rgb(60, 60, 80));
         let selected_text_style = Style::default()
             .fg(Color::Black)
             .bg(Color::Rgb(150, 180, 255));
@@ -96,24 +125,12 @@ impl Widget for SettingsMenu {
         let cancel_selected_style = Style::default()
             .fg(Color::Black)
             .bg(Color::Rgb(200, 100, 100));
+        let error_style = Style::default().fg(Color::Red);
 
         // Helper to show value or input buffer with cursor
         let show_value = |field: SettingsField, value: &str| -> Vec<Span> {
             if self.input_active && self.selected_field == field {
-                if self.input_selected {
-                    // Show entire text as selected
-                    vec![Span::styled(self.input_buffer.clone(), selected_text_style)]
-                } else {
-                    // Show with cursor at position
-                    let before: String =
-                        self.input_buffer.chars().take(self.input_cursor).collect();
-                    let after: String = self.input_buffer.chars().skip(self.input_cursor).collect();
-                    vec![
-                        Span::styled(before, input_style),
-                        Span::styled("▏", Style::default().fg(Color::White)),
-                        Span::styled(after, input_style),
-                    ]
-                }
+                self.input.spans(input_style, selected_text_style)
             } else {
                 vec![Span::styled(
                     value.to_string(),
@@ -128,6 +145,7 @@ impl Widget for SettingsMenu {
 
         let target_spans = show_value(SettingsField::Target, &self.target);
         let interval_spans = show_value(SettingsField::Interval, &format!("{}", self.interval));
+        let timeout_spans = show_value(SettingsField::Timeout, &format!("{}", self.timeout));
         let scale_spans = show_value(SettingsField::Scale, &format!("{}", self.scale));
         let buffer_spans = show_value(SettingsField::BufferSize, &format!("{}", self.buffer_mb));
 
@@ -166,7 +184,34 @@ impl Widget for SettingsMenu {
             Span::styled("Interval:     ", label_style),
         ];
         interval_line.extend(interval_spans);
-        interval_line.push(Span::styled(" ms", label_style));
+        // Typed durations carry their own unit
+        if !(self.input_active && self.selected_field == SettingsField::Interval) {
+            interval_line.push(Span::styled(" ms (e.g. 500ms, 2s)", label_style));
+        }
+
+        // Build timeout line
+        let mut timeout_line = vec![
+            Span::styled(
+                if self.selected_field == SettingsField::Timeout {
+                    "► "
+                } else {
+                    "  "
+                },
+                if self.selected_field == SettingsField::Timeout {
+                    selected_style
+                } else {
+                    normal_style
+                },
+            ),
+            Span::styled("Timeout:      ", label_style),
+        ];
+        timeout_line.extend(timeout_spans);
+        if !(self.input_active && self.selected_field == SettingsField::Timeout) {
+            timeout_line.push(Span::styled(
+                " ms (later replies count as lost)",
+                label_style,
+            ));
+        }
 
         // Build scale line
         let mut scale_line = vec![
@@ -209,7 +254,7 @@ impl Widget for SettingsMenu {
         buffer_line.extend(buffer_spans);
         buffer_line.push(Span::styled(" MB (history scrollback)", label_style));
 
-        let lines = vec![
+        let mut lines = vec![
             Line::from(""),
             // Target
             Line::from(target_line),
@@ -217,6 +262,9 @@ impl Widget for SettingsMenu {
             // Interval
             Line::from(interval_line),
             Line::from(""),
+            // Timeout
+            Line::from(timeout_line),
+            Line::from(""),
             // Scale
             Line::from(scale_line),
             Line::from(""),
@@ -243,6 +291,64 @@ impl Widget for SettingsMenu {
                         value_style
                     },
                 ),
+                Span::styled(
+                    if self.selected_field == SettingsField::ColorScheme {
+                        "  [p: compare]"
+                    } else {
+                        ""
+                    },
+                    hint_style,
+                ),
+            ]),
+            Line::from(""),
+            // RTT unit
+            Line::from(vec![
+                Span::styled(
+                    if self.selected_field == SettingsField::Unit {
+                        "► "
+                    } else {
+                        "  "
+                    },
+                    if self.selected_field == SettingsField::Unit {
+                        selected_style
+                    } else {
+                        normal_style
+                    },
+                ),
+                Span::styled("RTT Unit:     ", label_style),
+                Span::styled(
+                    format!("{}", self.unit),
+                    if self.selected_field == SettingsField::Unit {
+                        selected_style
+                    } else {
+                        value_style
+                    },
+                ),
+            ]),
+            Line::from(""),
+            // Reducer for --aggregate
+            Line::from(vec![
+                Span::styled(
+                    if self.selected_field == SettingsField::AggregateBy {
+                        "► "
+                    } else {
+                        "  "
+                    },
+                    if self.selected_field == SettingsField::AggregateBy {
+                        selected_style
+                    } else {
+                        normal_style
+                    },
+                ),
+                Span::styled("Aggregate By: ", label_style),
+                Span::styled(
+                    format!("{}", self.aggregate_by),
+                    if self.selected_field == SettingsField::AggregateBy {
+                        selected_style
+                    } else {
+                        value_style
+                    },
+                ),
             ]),
             Line::from(""),
             // Hide cursor
@@ -270,6 +376,81 @@ impl Widget for SettingsMenu {
                 ),
             ]),
             Line::from(""),
+            // Hide the corner status indicator
+            Line::from(vec![
+                Span::styled(
+                    if self.selected_field == SettingsField::HideIndicator {
+                        "► "
+                    } else {
+                        "  "
+                    },
+                    if self.selected_field == SettingsField::HideIndicator {
+                        selected_style
+                    } else {
+                        normal_style
+                    },
+                ),
+                Span::styled("Hide Status:  ", label_style),
+                Span::styled(
+                    if self.hide_indicator { "Yes" } else { "No" },
+                    if self.selected_field == SettingsField::HideIndicator {
+                        selected_style
+                    } else {
+                        value_style
+                    },
+                ),
+            ]),
+            Line::from(""),
+            // Graph direction
+            Line::from(vec![
+                Span::styled(
+                    if self.selected_field == SettingsField::TopDown {
+                        "► "
+                    } else {
+                        "  "
+                    },
+                    if self.selected_field == SettingsField::TopDown {
+                        selected_style
+                    } else {
+                        normal_style
+                    },
+                ),
+                Span::styled("Newest Row:   ", label_style),
+                Span::styled(
+                    if self.top_down { "Top" } else { "Bottom" },
+                    if self.selected_field == SettingsField::TopDown {
+                        selected_style
+                    } else {
+                        value_style
+                    },
+                ),
+            ]),
+            Line::from(""),
+            // Quit confirmation
+            Line::from(vec![
+                Span::styled(
+                    if self.selected_field == SettingsField::ConfirmQuit {
+                        "► "
+                    } else {
+                        "  "
+                    },
+                    if self.selected_field == SettingsField::ConfirmQuit {
+                        selected_style
+                    } else {
+                        normal_style
+                    },
+                ),
+                Span::styled("Confirm Quit: ", label_style),
+                Span::styled(
+                    if self.confirm_quit { "Yes" } else { "No" },
+                    if self.selected_field == SettingsField::ConfirmQuit {
+                        selected_style
+                    } else {
+                        value_style
+                    },
+                ),
+            ]),
+            Line::from(""),
             // Buffer size
             Line::from(buffer_line),
             Line::from(""),
@@ -278,7 +459,11 @@ impl Widget for SettingsMenu {
                 Span::raw("                    "),
                 Span::styled(
                     " Confirm ",
-                    if self.selected_field == SettingsField::Confirm {
+                    if invalid {
+                        Style::default()
+                            .fg(Color::DarkGray)
+                            .bg(Color::Rgb(45, 45, 55))
+                    } else if self.selected_field == SettingsField::Confirm {
                         button_selected_style
                     } else {
                         button_style
@@ -294,7 +479,11 @@ impl Widget for SettingsMenu {
                     },
                 ),
             ]),
-            Line::from(""),
+            // Validation error for the field being edited
+            match self.input.error.as_deref().filter(|_| self.input_active) {
+                Some(error) => Line::from(Span::styled(format!("  {}", error), error_style)),
+                None => Line::from(""),
+            },
             Line::from(vec![Span::styled(
                 "  ↑/↓ navigate │ ←/→ adjust │ type to edit",
                 hint_style,
@@ -305,6 +494,12 @@ impl Widget for SettingsMenu {
             )]),
         ];
 
+        // Without --aggregate the reducer has nothing to do: leave out its line and the
+        // blank line after it
+        if !self.aggregating {
+            lines.drain(AGGREGATE_BY_LINE..AGGREGATE_BY_LINE + 2);
+        }
+
         let paragraph = Paragraph::new(lines).alignment(Alignment::Left);
         paragraph.render(inner_area, buf);
     }
@@ -0,0 +1,208 @@
+use chrono::{DateTime, Local};
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use std::fmt::Write;
+
+/// Size of a terminal cell in the SVG, in pixels
+const CELL_WIDTH: usize = 9;
+const CELL_HEIGHT: usize = 18;
+const FONT_SIZE: usize = 15;
+/// Colors the terminal's defaults (`Color::Reset`) are drawn in
+const DEFAULT_FG: (u8, u8, u8) = (208, 208, 208);
+const DEFAULT_BG: (u8, u8, u8) = (0, 0, 0);
+
+/// File name for a snapshot taken at `time`, e.g. "rttui-20240501-143000.svg"
+pub fn snapshot_file_name(time: DateTime<Local>) -> String {
+    format!("rttui-{}.svg", time.format("%Y%m%d-%H%M%S"))
+}
+
+/// Render a drawn frame as an SVG image, keeping the exact colors and layout
+///
+/// Runs of cells sharing a background become one rectangle and runs sharing a text
+/// style one `<text>` element, stretched to the cells it covers
+pub fn buffer_to_svg(buf: &Buffer) -> String {
+    let area = buf.area;
+    let (width, height) = (area.width as usize, area.height as usize);
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="{}">"#,
+        width * CELL_WIDTH,
+        height * CELL_HEIGHT,
+        FONT_SIZE
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        hex(DEFAULT_BG)
+    );
+
+    for row in 0..height {
+        let y = area.y + row as u16;
+        let cells: Vec<_> = (0..width)
+            .map(|col| {
+                let cell = &buf[(area.x + col as u16, y)];
+                let (mut fg, mut bg) = (rgb(cell.fg, DEFAULT_FG), rgb(cell.bg, DEFAULT_BG));
+                if cell.modifier.contains(Modifier::REVERSED) {
+                    std::mem::swap(&mut fg, &mut bg);
+                }
+                (
+                    cell.symbol(),
+                    fg,
+                    bg,
+                    cell.modifier.contains(Modifier::BOLD),
+                )
+            })
+            .collect();
+
+        // Backgrounds, leaving out the default one already filled in
+        let mut col = 0;
+        while col < width {
+            let bg = cells[col].2;
+            let len = cells[col..].iter().take_while(|cell| cell.2 == bg).count();
+            if bg != DEFAULT_BG {
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                    col * CELL_WIDTH,
+                    row * CELL_HEIGHT,
+                    len * CELL_WIDTH,
+                    CELL_HEIGHT,
+                    hex(bg)
+                );
+            }
+            col += len;
+        }
+
+        // Text, skipping blanks (and the empty cells trailing wide characters)
+        let mut col = 0;
+        while col < width {
+            let (_, fg, _, bold) = cells[col];
+            let len = cells[col..]
+                .iter()
+                .take_while(|cell| cell.1 == fg && cell.3 == bold)
+                .count();
+            let text: String = cells[col..col + len]
+                .iter()
+                .map(|cell| cell.0)
+                .collect::<String>();
+            let trimmed = text.trim_end();
+            if !trimmed.is_empty() {
+                let cols = trimmed.chars().count().min(len);
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" fill="{}"{} textLength="{}" lengthAdjust="spacingAndGlyphs" xml:space="preserve">{}</text>"#,
+                    col * CELL_WIDTH,
+                    row * CELL_HEIGHT + CELL_HEIGHT * 3 / 4,
+                    hex(fg),
+                    if bold { r#" font-weight="bold""# } else { "" },
+                    cols * CELL_WIDTH,
+                    escape(trimmed)
+                );
+            }
+            col += len;
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// RGB value of a terminal color (xterm palette for the named and indexed ones)
+fn rgb(color: Color, default: (u8, u8, u8)) -> (u8, u8, u8) {
+    match color {
+        Color::Reset => default,
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Black => indexed(0),
+        Color::Red => indexed(1),
+        Color::Green => indexed(2),
+        Color::Yellow => indexed(3),
+        Color::Blue => indexed(4),
+        Color::Magenta => indexed(5),
+        Color::Cyan => indexed(6),
+        Color::Gray => indexed(7),
+        Color::DarkGray => indexed(8),
+        Color::LightRed => indexed(9),
+        Color::LightGreen => indexed(10),
+        Color::LightYellow => indexed(11),
+        Color::LightBlue => indexed(12),
+        Color::LightMagenta => indexed(13),
+        Color::LightCyan => indexed(14),
+        Color::White => indexed(15),
+        Color::Indexed(i) => indexed(i),
+    }
+}
+
+/// Color of an entry in the xterm 256-color palette
+fn indexed(i: u8) -> (u8, u8, u8) {
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    match i {
+        0..16 => BASIC[i as usize],
+        // 6x6x6 color cube
+        16..232 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let n = i - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        // Grayscale ramp
+        _ => {
+            let level = 8 + (i - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, style::Style};
+
+    #[test]
+    fn test_buffer_to_svg() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buf.set_string(0, 0, "a<b", Style::default().fg(Color::Rgb(255, 0, 0)));
+        buf.set_string(2, 1, "  ", Style::default().bg(Color::Indexed(240)));
+        let svg = buffer_to_svg(&buf);
+
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="54" height="36""#)
+        );
+        assert!(svg.contains(r##"fill="#ff0000" textLength="27" lengthAdjust="spacingAndGlyphs" xml:space="preserve">a&lt;b</text>"##));
+        assert!(svg.contains(r##"<rect x="18" y="18" width="18" height="18" fill="#585858"/>"##));
+        // Blank cells on the default background add nothing
+        assert_eq!(svg.matches("<text").count(), 1);
+        assert_eq!(svg.matches("<rect").count(), 2);
+
+        assert_eq!(indexed(16), (0, 0, 0));
+        assert_eq!(indexed(196), (255, 0, 0));
+        assert_eq!(indexed(255), (238, 238, 238));
+    }
+}
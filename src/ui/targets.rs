@@ -0,0 +1,207 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+use crate::color::{ColorScale, loss_color, target_accent};
+use crate::ping::{DEFAULT_PRECISION, RttUnit};
+use crate::ui::app::TargetState;
+
+/// Maximum number of target rows before the panel stops growing (further targets are
+/// counted on a "+N more" line)
+pub const MAX_TARGET_ROWS: u16 = 10;
+
+/// Summary panel listing each monitored target with loss%, avg RTT and recent samples
+pub struct TargetList<'a> {
+    targets: &'a [TargetState],
+    color_scale: &'a ColorScale,
+    /// Loss percentages at which the loss figure turns yellow / red
+    loss_thresholds: (f64, f64),
+    /// Unit average RTTs are shown in
+    unit: RttUnit,
+    /// Decimals average RTTs in ms are shown with
+    precision: usize,
+}
+
+impl<'a> TargetList<'a> {
+    pub fn new(targets: &'a [TargetState], color_scale: &'a ColorScale) -> Self {
+        Self {
+            targets,
+            color_scale,
+            loss_thresholds: (1.0, 5.0),
+            unit: RttUnit::Ms,
+            precision: DEFAULT_PRECISION,
+        }
+    }
+
+    /// Show average RTTs in this unit
+    pub fn unit(mut self, unit: RttUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Show average RTTs in ms with this many decimals
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Loss percentages above which loss is colored yellow (`warn`) and red (`crit`)
+    pub fn loss_thresholds(mut self, warn: f64, crit: f64) -> Self {
+        self.loss_thresholds = (warn, crit);
+        self
+    }
+
+    /// Height of the panel including borders
+    pub fn height(targets: usize) -> u16 {
+        (targets as u16).min(MAX_TARGET_ROWS) + 2
+    }
+
+    /// Build one summary line: accent bar and host, loss%, avg RTT, then recent samples
+    /// filling the width
+    fn target_line(
+        &self,
+        idx: usize,
+        target: &TargetState,
+        host_width: usize,
+        content_width: usize,
+    ) -> Line<'static> {
+        let host: String = target.host.chars().take(host_width).collect();
+        let accent = target_accent(idx);
+        let mut spans = vec![
+            Span::styled("▌", Style::default().fg(accent)),
+            Span::styled(
+                format!("{:<width$} ", host, width = host_width),
+                Style::default().fg(accent),
+            ),
+        ];
+
+        if target.ip.is_none() {
+            spans.push(Span::styled("unresolved", Style::default().fg(Color::Red)));
+            return Line::from(spans);
+        }
+
+        let loss = target.stats.loss_percent();
+        let (loss_warn, loss_crit) = self.loss_thresholds;
+        let loss_color = loss_color(loss, loss_warn, loss_crit);
+        let avg = target
+            .stats
+            .avg_rtt()
+            .map(|d| self.unit.format_duration(d, self.precision))
+            .unwrap_or_else(|| "-".to_string());
+
+        spans.push(Span::styled("loss ", Style::default().fg(Color::DarkGray)));
+        spans.push(Span::styled(
+            format!("{:>5.1}% ", loss),
+            Style::default().fg(loss_color),
+        ));
+        spans.push(Span::styled("avg ", Style::default().fg(Color::DarkGray)));
+        spans.push(Span::styled(
+            format!("{:>10} ", avg),
+            Style::default().fg(Color::White),
+        ));
+
+        // Fill the rest of the line with the most recent samples
+        let used = Line::from(spans.clone()).width();
+        let strip_width = content_width.saturating_sub(used);
+        let start = target.recent_rtts.len().saturating_sub(strip_width);
+        for rtt in target.recent_rtts.iter().skip(start) {
+            let (symbol, color) = match rtt {
+                Some(ms) => ("█", self.color_scale.color_for_rtt_f64(Some(*ms))),
+                None => ("X", Color::Indexed(240)),
+            };
+            spans.push(Span::styled(symbol, Style::default().fg(color)));
+        }
+
+        Line::from(spans)
+    }
+}
+
+impl Widget for TargetList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let content_width = area.width.saturating_sub(2) as usize;
+        let host_width = self
+            .targets
+            .iter()
+            .map(|t| t.host.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(30);
+
+        // Without room for every target, the last row counts the ones left out
+        let rows = area.height.saturating_sub(2) as usize;
+        let shown = if self.targets.len() > rows {
+            rows.saturating_sub(1)
+        } else {
+            self.targets.len()
+        };
+        let mut lines: Vec<Line> = self
+            .targets
+            .iter()
+            .take(shown)
+            .enumerate()
+            .map(|(idx, target)| self.target_line(idx, target, host_width, content_width))
+            .collect();
+        if shown < self.targets.len() && rows > 0 {
+            lines.push(Line::from(Span::styled(
+                format!(" +{} more", self.targets.len() - shown),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        let block = Block::default()
+            .title(" Targets ")
+            .title_style(Style::default().fg(Color::DarkGray))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray));
+
+        let paragraph = Paragraph::new(lines).block(block);
+        paragraph.render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorScheme;
+
+    #[test]
+    fn test_target_accents() {
+        let targets: Vec<TargetState> = ["a.example", "b.example"]
+            .into_iter()
+            .map(|host| TargetState::new(host.to_string(), None))
+            .collect();
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let area = Rect::new(0, 0, 40, TargetList::height(targets.len()));
+        let mut buf = Buffer::empty(area);
+        TargetList::new(&targets, &color_scale).render(area, &mut buf);
+
+        // Each row starts with a bar and host name in that target's accent
+        for (idx, y) in [(0, 1), (1, 2)] {
+            assert_eq!(buf[(1, y)].symbol(), "▌");
+            assert_eq!(buf[(1, y)].fg, target_accent(idx));
+            assert_eq!(buf[(2, y)].fg, target_accent(idx));
+        }
+        assert_ne!(target_accent(0), target_accent(1));
+        assert_eq!(target_accent(0), target_accent(8));
+    }
+
+    #[test]
+    fn test_more_targets() {
+        let targets: Vec<TargetState> = (0..12)
+            .map(|idx| TargetState::new(format!("{}.example", idx), None))
+            .collect();
+        let color_scale = ColorScale::new(100, ColorScheme::default());
+        let area = Rect::new(0, 0, 40, TargetList::height(targets.len()));
+        let mut buf = Buffer::empty(area);
+        TargetList::new(&targets, &color_scale).render(area, &mut buf);
+
+        // Nine targets, then a line counting the three left out
+        assert_eq!(buf[(2, 9)].symbol(), "8");
+        let more: String = (1..9).map(|x| buf[(x, 10)].symbol()).collect();
+        assert_eq!(more, " +3 more");
+    }
+}